@@ -0,0 +1,9 @@
+DROP TABLE  IF EXISTS   items;
+DROP TYPE   IF EXISTS   inventory_type;
+
+ALTER TABLE characters
+    DROP COLUMN cash_slots,
+    DROP COLUMN etc_slots,
+    DROP COLUMN setup_slots,
+    DROP COLUMN use_slots,
+    DROP COLUMN equip_slots;
@@ -0,0 +1,51 @@
+ALTER TABLE characters
+    ADD COLUMN equip_slots  SMALLINT    NOT NULL DEFAULT 24,
+    ADD COLUMN use_slots    SMALLINT    NOT NULL DEFAULT 24,
+    ADD COLUMN setup_slots  SMALLINT    NOT NULL DEFAULT 24,
+    ADD COLUMN etc_slots    SMALLINT    NOT NULL DEFAULT 24,
+    ADD COLUMN cash_slots   SMALLINT    NOT NULL DEFAULT 96;
+
+CREATE TYPE inventory_type AS ENUM (
+    'equip',
+    'use',
+    'setup',
+    'etc',
+    'cash'
+);
+
+-- Equipped items live in the equip tab at negative positions.
+-- Equip stat columns stay zero for every other kind of item.
+CREATE TABLE items (
+    id              SERIAL          PRIMARY KEY,
+    character_id    INTEGER         NOT NULL,
+    inventory_type  INVENTORY_TYPE  NOT NULL,
+    position        SMALLINT        NOT NULL,
+    item_id         INTEGER         NOT NULL,
+    quantity        SMALLINT        NOT NULL DEFAULT 1,
+    owner           VARCHAR(13)     NOT NULL DEFAULT '',
+    flag            SMALLINT        NOT NULL DEFAULT 0,
+
+    upgrade_slots   SMALLINT        NOT NULL DEFAULT 0,
+    level           SMALLINT        NOT NULL DEFAULT 0,
+    stre            SMALLINT        NOT NULL DEFAULT 0,
+    dex             SMALLINT        NOT NULL DEFAULT 0,
+    int             SMALLINT        NOT NULL DEFAULT 0,
+    luk             SMALLINT        NOT NULL DEFAULT 0,
+    hp              SMALLINT        NOT NULL DEFAULT 0,
+    mp              SMALLINT        NOT NULL DEFAULT 0,
+    watk            SMALLINT        NOT NULL DEFAULT 0,
+    matk            SMALLINT        NOT NULL DEFAULT 0,
+    wdef            SMALLINT        NOT NULL DEFAULT 0,
+    mdef            SMALLINT        NOT NULL DEFAULT 0,
+    acc             SMALLINT        NOT NULL DEFAULT 0,
+    avoid           SMALLINT        NOT NULL DEFAULT 0,
+    hands           SMALLINT        NOT NULL DEFAULT 0,
+    speed           SMALLINT        NOT NULL DEFAULT 0,
+    jump            SMALLINT        NOT NULL DEFAULT 0,
+
+    CONSTRAINT fk_character
+        FOREIGN KEY(character_id)
+            REFERENCES characters(id) ON DELETE CASCADE,
+
+    CONSTRAINT position_is_unique_per_inventory UNIQUE(character_id, inventory_type, position)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE characters
+    ADD COLUMN sp   SMALLINT    NOT NULL DEFAULT 0;
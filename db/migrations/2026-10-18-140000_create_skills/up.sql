@@ -0,0 +1,14 @@
+CREATE TABLE skills (
+    id              SERIAL      PRIMARY KEY,
+    character_id    INTEGER     NOT NULL,
+    skill_id        INTEGER     NOT NULL,
+    level           SMALLINT    NOT NULL,
+    -- Only fourth job skills have a master level; it caps their level.
+    master_level    SMALLINT    NOT NULL DEFAULT 0,
+
+    CONSTRAINT fk_character
+        FOREIGN KEY(character_id)
+            REFERENCES characters(id) ON DELETE CASCADE,
+
+    CONSTRAINT skill_is_unique_per_character UNIQUE(character_id, skill_id)
+);
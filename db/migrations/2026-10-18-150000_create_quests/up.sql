@@ -0,0 +1,21 @@
+CREATE TYPE quest_status AS ENUM (
+    'started',
+    'completed'
+);
+
+-- Quests a character has not started, or has forfeited, have no row.
+CREATE TABLE quests (
+    id              SERIAL          PRIMARY KEY,
+    character_id    INTEGER         NOT NULL,
+    quest_id        INTEGER         NOT NULL,
+    status          QUEST_STATUS    NOT NULL,
+    -- What the client shows of a started quest, such as its mob kill counts.
+    progress        VARCHAR         NOT NULL DEFAULT '',
+    completed_at    TIMESTAMP,
+
+    CONSTRAINT fk_character
+        FOREIGN KEY(character_id)
+            REFERENCES characters(id) ON DELETE CASCADE,
+
+    CONSTRAINT quest_is_unique_per_character UNIQUE(character_id, quest_id)
+);
@@ -0,0 +1,2 @@
+DROP TABLE  IF EXISTS   storage_items;
+DROP TABLE  IF EXISTS   storages;
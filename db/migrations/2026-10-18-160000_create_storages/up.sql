@@ -0,0 +1,51 @@
+-- Each account has one storage per world, shared by all of its characters
+-- there. The row is created the first time the storage is opened.
+CREATE TABLE storages (
+    id              SERIAL          PRIMARY KEY,
+    account_id      INTEGER         NOT NULL,
+    world           SMALLINT        NOT NULL,
+    slots           SMALLINT        NOT NULL DEFAULT 4,
+    meso            INTEGER         NOT NULL DEFAULT 0,
+
+    CONSTRAINT fk_account
+        FOREIGN KEY(account_id)
+            REFERENCES accounts(id) ON DELETE CASCADE,
+
+    CONSTRAINT storage_is_unique_per_world UNIQUE(account_id, world)
+);
+
+-- Stored items are kept in the order they were put in. Equip stat columns
+-- stay zero for every other kind of item.
+CREATE TABLE storage_items (
+    id              SERIAL          PRIMARY KEY,
+    storage_id      INTEGER         NOT NULL,
+    position        SMALLINT        NOT NULL,
+    item_id         INTEGER         NOT NULL,
+    quantity        SMALLINT        NOT NULL DEFAULT 1,
+    owner           VARCHAR(13)     NOT NULL DEFAULT '',
+    flag            SMALLINT        NOT NULL DEFAULT 0,
+
+    upgrade_slots   SMALLINT        NOT NULL DEFAULT 0,
+    level           SMALLINT        NOT NULL DEFAULT 0,
+    stre            SMALLINT        NOT NULL DEFAULT 0,
+    dex             SMALLINT        NOT NULL DEFAULT 0,
+    int             SMALLINT        NOT NULL DEFAULT 0,
+    luk             SMALLINT        NOT NULL DEFAULT 0,
+    hp              SMALLINT        NOT NULL DEFAULT 0,
+    mp              SMALLINT        NOT NULL DEFAULT 0,
+    watk            SMALLINT        NOT NULL DEFAULT 0,
+    matk            SMALLINT        NOT NULL DEFAULT 0,
+    wdef            SMALLINT        NOT NULL DEFAULT 0,
+    mdef            SMALLINT        NOT NULL DEFAULT 0,
+    acc             SMALLINT        NOT NULL DEFAULT 0,
+    avoid           SMALLINT        NOT NULL DEFAULT 0,
+    hands           SMALLINT        NOT NULL DEFAULT 0,
+    speed           SMALLINT        NOT NULL DEFAULT 0,
+    jump            SMALLINT        NOT NULL DEFAULT 0,
+
+    CONSTRAINT fk_storage
+        FOREIGN KEY(storage_id)
+            REFERENCES storages(id) ON DELETE CASCADE,
+
+    CONSTRAINT position_is_unique_per_storage UNIQUE(storage_id, position)
+);
@@ -1,5 +1,12 @@
-use crate::{keybinding::KeybindSet, schema::characters};
-use diesel::QueryResult;
+use crate::{
+    establish_connection,
+    inventory::{Inventory, InventoryItem},
+    keybinding::KeybindSet,
+    quest::QuestLog,
+    schema::characters,
+    skill::SkillBook,
+};
+use diesel::{Connection, QueryResult};
 use std::time::SystemTime;
 
 pub mod repository;
@@ -7,7 +14,7 @@ pub mod repository;
 pub use repository::*;
 
 /// Character database entity.
-#[derive(Identifiable, Queryable, AsChangeset)]
+#[derive(Clone, Identifiable, Queryable, AsChangeset)]
 pub struct Character {
     pub id: i32,
     pub accountid: i32,
@@ -41,12 +48,32 @@ pub struct Character {
     pub created_at: SystemTime,
 
     pub map_id: i32,
+
+    pub equip_slots: i16,
+    pub use_slots: i16,
+    pub setup_slots: i16,
+    pub etc_slots: i16,
+    pub cash_slots: i16,
+
+    pub sp: i16,
 }
 
 impl Character {
     pub fn save(&self) -> QueryResult<Character> {
         repository::update_character(self)
     }
+
+    /// Slot counts of the equip, use, setup, etc and cash tabs, in that order.
+    pub fn inventory_slot_limits(&self) -> [u8; 5] {
+        [
+            self.equip_slots,
+            self.use_slots,
+            self.setup_slots,
+            self.etc_slots,
+            self.cash_slots,
+        ]
+        .map(|slots| slots.clamp(0, u8::MAX as i16) as u8)
+    }
 }
 
 /// Character creation projection.
@@ -65,15 +92,34 @@ pub struct NewCharacter<'a> {
 }
 
 impl NewCharacter<'_> {
-    /// Save the new character and return the saved Character's
-    /// wrapper.
-    pub fn create(self) -> QueryResult<CharacterWrapper> {
-        let character = repository::create_character(self)?;
-        let key_binds = KeybindSet::set_defaults(&character)?;
-
-        Ok(CharacterWrapper {
-            character,
-            key_binds,
+    /// Save the new character wearing the given starter equips and return
+    /// the saved Character's wrapper. The character is saved whole or not at
+    /// all.
+    pub fn create(self, starter_equips: Vec<InventoryItem>) -> QueryResult<CharacterWrapper> {
+        let mut connection = establish_connection();
+
+        connection.transaction(|connection| {
+            let character = repository::create_character_in(connection, self)?;
+            let key_binds = KeybindSet::set_defaults_in(connection, &character)?;
+            let mut inventory = Inventory::from_item_vec(
+                character.id,
+                character.inventory_slot_limits(),
+                Vec::new(),
+            );
+            for item in starter_equips {
+                inventory.wear(item);
+            }
+            inventory.save_in(connection)?;
+            let skills = SkillBook::from_skill_vec(character.id, Vec::new());
+            let quests = QuestLog::from_quest_vec(character.id, Vec::new());
+
+            Ok(CharacterWrapper {
+                character,
+                key_binds,
+                inventory,
+                skills,
+                quests,
+            })
         })
     }
 }
@@ -83,6 +129,9 @@ impl NewCharacter<'_> {
 pub struct CharacterWrapper {
     pub character: Character,
     pub key_binds: KeybindSet,
+    pub inventory: Inventory,
+    pub skills: SkillBook,
+    pub quests: QuestLog,
 }
 
 impl CharacterWrapper {
@@ -95,10 +144,16 @@ impl CharacterWrapper {
     /// Wrap a character entity struct along with any additional information.
     pub fn from_character(character: Character) -> QueryResult<Self> {
         let key_binds = KeybindSet::from_character(&character)?;
+        let inventory = Inventory::from_character(&character)?;
+        let skills = SkillBook::from_character(&character)?;
+        let quests = QuestLog::from_character(&character)?;
 
         let dto = Self {
             character,
             key_binds,
+            inventory,
+            skills,
+            quests,
         };
         Ok(dto)
     }
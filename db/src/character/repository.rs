@@ -1,9 +1,12 @@
 use super::{Character, NewCharacter};
 use crate::establish_connection;
+use crate::inventory::Inventory;
+use crate::quest::QuestLog;
 use crate::schema;
 use crate::schema::characters;
 use diesel::expression_methods::*;
-use diesel::{QueryDsl, QueryResult, RunQueryDsl, SaveChangesDsl};
+use diesel::pg::PgConnection;
+use diesel::{Connection, QueryDsl, QueryResult, RunQueryDsl, SaveChangesDsl};
 use schema::characters::dsl::*;
 
 pub fn get_characters_by_accountid(account_id: i32) -> QueryResult<Vec<Character>> {
@@ -30,12 +33,14 @@ pub fn get_character_by_id(cid: i32) -> QueryResult<Character> {
         .first::<Character>(&mut connection)
 }
 
-pub fn create_character<'a>(char: NewCharacter) -> QueryResult<Character> {
-    let mut connection = establish_connection();
-
+/// Insert a new character row as part of a larger transaction.
+pub(crate) fn create_character_in(
+    connection: &mut PgConnection,
+    char: NewCharacter,
+) -> QueryResult<Character> {
     diesel::insert_into(characters::table)
         .values(&char)
-        .get_result::<Character>(&mut connection)
+        .get_result::<Character>(connection)
 }
 
 pub fn update_character(character: &Character) -> QueryResult<Character> {
@@ -44,6 +49,36 @@ pub fn update_character(character: &Character) -> QueryResult<Character> {
     character.save_changes(&mut connection)
 }
 
+/// Save several characters with their inventories in one transaction, so an
+/// exchange between them is either saved whole or not at all.
+pub fn update_characters_with_inventories(saves: &[(&Character, &Inventory)]) -> QueryResult<()> {
+    let mut connection = establish_connection();
+
+    connection.transaction(|connection| {
+        for (character, inventory) in saves {
+            character.save_changes::<Character>(connection)?;
+            inventory.save_in(connection)?;
+        }
+        Ok(())
+    })
+}
+
+/// Save a character with its inventory and quest log in one transaction, so
+/// a quest's state and its rewards are either saved together or not at all.
+pub fn update_character_with_quests(
+    character: &Character,
+    inventory: &Inventory,
+    quest_log: &QuestLog,
+) -> QueryResult<()> {
+    let mut connection = establish_connection();
+
+    connection.transaction(|connection| {
+        character.save_changes::<Character>(connection)?;
+        inventory.save_in(connection)?;
+        quest_log.save_in(connection)
+    })
+}
+
 pub fn delete_character(character_id: i32, account_id: i32) -> QueryResult<usize> {
     let mut connection = establish_connection();
 
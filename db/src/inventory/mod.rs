@@ -0,0 +1,666 @@
+use crate::{character::Character, schema::items};
+use diesel::pg::PgConnection;
+use diesel::QueryResult;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+mod repository;
+pub use repository::*;
+
+pub const EQUIP_SLOT_TOP: i16 = -5;
+pub const EQUIP_SLOT_BOTTOM: i16 = -6;
+pub const EQUIP_SLOT_SHOES: i16 = -7;
+pub const EQUIP_SLOT_WEAPON: i16 = -11;
+
+const RING_SLOTS: [i16; 4] = [-12, -13, -15, -16];
+
+/// Equip positions an item may be worn in, derived from its id.
+pub fn equip_slots(item_id: i32) -> &'static [i16] {
+    match item_id / 10000 {
+        100 => &[-1],
+        101 => &[-2],
+        102 => &[-3],
+        103 => &[-4],
+        104 | 105 => &[EQUIP_SLOT_TOP],
+        106 => &[EQUIP_SLOT_BOTTOM],
+        107 => &[EQUIP_SLOT_SHOES],
+        108 => &[-8],
+        109 => &[-10],
+        110 => &[-9],
+        111 => &RING_SLOTS,
+        112 => &[-17],
+        130..=170 => &[EQUIP_SLOT_WEAPON],
+        _ => &[],
+    }
+}
+
+/// Overalls cover both the top and bottom equip slots.
+pub fn is_overall(item_id: i32) -> bool {
+    item_id / 10000 == 105
+}
+
+/// Throwing stars and bullets are used up stack by stack, and shops refill
+/// a stack rather than selling more of them.
+pub fn is_rechargeable(item_id: i32) -> bool {
+    matches!(item_id / 10000, 207 | 233)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::InventoryType"]
+pub enum InventoryType {
+    Equip,
+    Use,
+    Setup,
+    Etc,
+    Cash,
+}
+
+impl InventoryType {
+    pub const ALL: [InventoryType; 5] = [
+        InventoryType::Equip,
+        InventoryType::Use,
+        InventoryType::Setup,
+        InventoryType::Etc,
+        InventoryType::Cash,
+    ];
+
+    /// The tab an item belongs in, derived from the leading digit of its id.
+    pub fn of_item(item_id: i32) -> Option<Self> {
+        match item_id / 1_000_000 {
+            1 => Some(InventoryType::Equip),
+            2 => Some(InventoryType::Use),
+            3 => Some(InventoryType::Setup),
+            4 => Some(InventoryType::Etc),
+            5 => Some(InventoryType::Cash),
+            _ => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        u8::from(self) as usize - 1
+    }
+}
+
+impl TryFrom<u8> for InventoryType {
+    type Error = u8;
+
+    fn try_from(kind: u8) -> Result<Self, Self::Error> {
+        match kind {
+            1 => Ok(InventoryType::Equip),
+            2 => Ok(InventoryType::Use),
+            3 => Ok(InventoryType::Setup),
+            4 => Ok(InventoryType::Etc),
+            5 => Ok(InventoryType::Cash),
+            _ => Err(kind),
+        }
+    }
+}
+
+impl From<InventoryType> for u8 {
+    fn from(kind: InventoryType) -> Self {
+        match kind {
+            InventoryType::Equip => 1,
+            InventoryType::Use => 2,
+            InventoryType::Setup => 3,
+            InventoryType::Etc => 4,
+            InventoryType::Cash => 5,
+        }
+    }
+}
+
+/// Item database entity.
+#[derive(Identifiable, Queryable)]
+#[diesel(table_name = items)]
+pub struct Item {
+    pub id: i32,
+    pub character_id: i32,
+    pub inventory_type: InventoryType,
+    pub position: i16,
+    pub item_id: i32,
+    pub quantity: i16,
+    pub owner: String,
+    pub flag: i16,
+
+    pub upgrade_slots: i16,
+    pub level: i16,
+    pub stre: i16,
+    pub dex: i16,
+    pub int: i16,
+    pub luk: i16,
+    pub hp: i16,
+    pub mp: i16,
+    pub watk: i16,
+    pub matk: i16,
+    pub wdef: i16,
+    pub mdef: i16,
+    pub acc: i16,
+    pub avoid: i16,
+    pub hands: i16,
+    pub speed: i16,
+    pub jump: i16,
+}
+
+/// Item creation projection.
+#[derive(Insertable)]
+#[diesel(table_name = items)]
+pub struct NewItem<'a> {
+    pub character_id: i32,
+    pub inventory_type: InventoryType,
+    pub position: i16,
+    pub item_id: i32,
+    pub quantity: i16,
+    pub owner: &'a str,
+    pub flag: i16,
+
+    pub upgrade_slots: i16,
+    pub level: i16,
+    pub stre: i16,
+    pub dex: i16,
+    pub int: i16,
+    pub luk: i16,
+    pub hp: i16,
+    pub mp: i16,
+    pub watk: i16,
+    pub matk: i16,
+    pub wdef: i16,
+    pub mdef: i16,
+    pub acc: i16,
+    pub avoid: i16,
+    pub hands: i16,
+    pub speed: i16,
+    pub jump: i16,
+}
+
+impl<'a> NewItem<'a> {
+    fn from(
+        character_id: i32,
+        inventory_type: InventoryType,
+        position: i16,
+        item: &'a InventoryItem,
+    ) -> Self {
+        let stats = item.stats.unwrap_or_default();
+
+        NewItem {
+            character_id,
+            inventory_type,
+            position,
+            item_id: item.item_id,
+            quantity: item.quantity,
+            owner: &item.owner,
+            flag: item.flag,
+            upgrade_slots: stats.upgrade_slots,
+            level: stats.level,
+            stre: stats.stre,
+            dex: stats.dex,
+            int: stats.int,
+            luk: stats.luk,
+            hp: stats.hp,
+            mp: stats.mp,
+            watk: stats.watk,
+            matk: stats.matk,
+            wdef: stats.wdef,
+            mdef: stats.mdef,
+            acc: stats.acc,
+            avoid: stats.avoid,
+            hands: stats.hands,
+            speed: stats.speed,
+            jump: stats.jump,
+        }
+    }
+}
+
+/// Stats carried by a single equip.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EquipStats {
+    pub upgrade_slots: i16,
+    pub level: i16,
+    pub stre: i16,
+    pub dex: i16,
+    pub int: i16,
+    pub luk: i16,
+    pub hp: i16,
+    pub mp: i16,
+    pub watk: i16,
+    pub matk: i16,
+    pub wdef: i16,
+    pub mdef: i16,
+    pub acc: i16,
+    pub avoid: i16,
+    pub hands: i16,
+    pub speed: i16,
+    pub jump: i16,
+}
+
+/// An item as held in an inventory slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryItem {
+    pub item_id: i32,
+    pub quantity: i16,
+    pub owner: String,
+    pub flag: i16,
+    /// Present for equips only.
+    pub stats: Option<EquipStats>,
+}
+
+impl InventoryItem {
+    /// Create a plain item, or an equip with zeroed stats for equip ids.
+    pub fn new(item_id: i32, quantity: i16) -> Self {
+        let is_equip = InventoryType::of_item(item_id) == Some(InventoryType::Equip);
+
+        InventoryItem {
+            item_id,
+            quantity: if is_equip { 1 } else { quantity },
+            owner: String::new(),
+            flag: 0,
+            stats: if is_equip {
+                Some(EquipStats::default())
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn is_equip(&self) -> bool {
+        self.stats.is_some()
+    }
+}
+
+impl From<Item> for InventoryItem {
+    fn from(item: Item) -> Self {
+        let stats = (item.inventory_type == InventoryType::Equip).then_some(EquipStats {
+            upgrade_slots: item.upgrade_slots,
+            level: item.level,
+            stre: item.stre,
+            dex: item.dex,
+            int: item.int,
+            luk: item.luk,
+            hp: item.hp,
+            mp: item.mp,
+            watk: item.watk,
+            matk: item.matk,
+            wdef: item.wdef,
+            mdef: item.mdef,
+            acc: item.acc,
+            avoid: item.avoid,
+            hands: item.hands,
+            speed: item.speed,
+            jump: item.jump,
+        });
+
+        InventoryItem {
+            item_id: item.item_id,
+            quantity: item.quantity,
+            owner: item.owner,
+            flag: item.flag,
+            stats,
+        }
+    }
+}
+
+/// A character's five inventory tabs. Positions start at 1; equipped items are
+/// kept in the equip tab at negative positions and do not use up slots.
+#[derive(Clone)]
+pub struct Inventory {
+    character_id: i32,
+    slot_limits: [u8; 5],
+    tabs: [BTreeMap<i16, InventoryItem>; 5],
+}
+
+impl Inventory {
+    /// Get the inventory of the given character.
+    pub fn from_character(character: &Character) -> QueryResult<Self> {
+        Ok(Self::from_item_vec(
+            character.id,
+            character.inventory_slot_limits(),
+            repository::get_items_by_characterid(character.id)?,
+        ))
+    }
+
+    /// Build an inventory out of a vector of item rows.
+    pub fn from_item_vec(character_id: i32, slot_limits: [u8; 5], item_vec: Vec<Item>) -> Self {
+        let mut inventory = Self {
+            character_id,
+            slot_limits,
+            tabs: Default::default(),
+        };
+
+        for item in item_vec {
+            let inventory_type = item.inventory_type;
+            let position = item.position;
+            inventory.tabs[inventory_type.index()].insert(position, item.into());
+        }
+
+        inventory
+    }
+
+    pub fn slot_limit(&self, inventory_type: InventoryType) -> u8 {
+        self.slot_limits[inventory_type.index()]
+    }
+
+    pub fn get(&self, inventory_type: InventoryType, position: i16) -> Option<&InventoryItem> {
+        self.tabs[inventory_type.index()].get(&position)
+    }
+
+    /// Items stored in the given tab, by position. Equipped items are not
+    /// included.
+    pub fn items(
+        &self,
+        inventory_type: InventoryType,
+    ) -> impl Iterator<Item = (i16, &InventoryItem)> + '_ {
+        self.tabs[inventory_type.index()]
+            .range(1..)
+            .map(|(&position, item)| (position, item))
+    }
+
+    /// Equipped items, by their (negative) equip slot.
+    pub fn equipped(&self) -> impl Iterator<Item = (i16, &InventoryItem)> + '_ {
+        self.tabs[InventoryType::Equip.index()]
+            .range(..0)
+            .rev()
+            .map(|(&position, item)| (position, item))
+    }
+
+    /// Item ids of everything equipped, by equip position.
+    pub fn equipment(&self) -> Vec<(i16, i32)> {
+        self.equipped()
+            .map(|(position, item)| (position, item.item_id))
+            .collect()
+    }
+
+    /// How many of an item the character carries, not counting equipped items.
+    pub fn count(&self, item_id: i32) -> i32 {
+        InventoryType::of_item(item_id).map_or(0, |inventory_type| {
+            self.items(inventory_type)
+                .filter(|(_, item)| item.item_id == item_id)
+                .map(|(_, item)| i32::from(item.quantity))
+                .sum()
+        })
+    }
+
+    pub fn free_slots(&self, inventory_type: InventoryType) -> u8 {
+        let used = self.items(inventory_type).count();
+        (self.slot_limit(inventory_type) as usize).saturating_sub(used) as u8
+    }
+
+    pub fn first_free_position(&self, inventory_type: InventoryType) -> Option<i16> {
+        let tab = &self.tabs[inventory_type.index()];
+        (1..=i16::from(self.slot_limit(inventory_type)))
+            .find(|position| !tab.contains_key(position))
+    }
+
+    /// Add an item, topping up an existing stack of the same item if the whole
+    /// quantity fits under `slot_max`, and otherwise taking the first free
+    /// slot. Returns the tab and position now holding the item, or `None` if
+    /// there is no room.
+    pub fn add(&mut self, item: InventoryItem, slot_max: i16) -> Option<(InventoryType, i16)> {
+        let inventory_type = InventoryType::of_item(item.item_id)?;
+
+        if !item.is_equip() {
+            let stack = self.tabs[inventory_type.index()]
+                .range_mut(1..)
+                .find(|(_, stack)| {
+                    stack.item_id == item.item_id
+                        && stack.quantity.saturating_add(item.quantity) <= slot_max
+                });
+            if let Some((&position, stack)) = stack {
+                stack.quantity += item.quantity;
+                return Some((inventory_type, position));
+            }
+        }
+
+        let position = self.first_free_position(inventory_type)?;
+        self.tabs[inventory_type.index()].insert(position, item);
+        Some((inventory_type, position))
+    }
+
+    /// Take `quantity` of an item out of the inventory, starting with the
+    /// first stack. Returns each position it was taken from with what is left
+    /// there, or `None`, changing nothing, if there are not enough.
+    pub fn take(&mut self, item_id: i32, quantity: i16) -> Option<Vec<(InventoryType, i16, i16)>> {
+        let inventory_type = InventoryType::of_item(item_id)?;
+        if quantity <= 0 || self.count(item_id) < i32::from(quantity) {
+            return None;
+        }
+
+        let tab = &mut self.tabs[inventory_type.index()];
+        let mut left = quantity;
+        let mut taken = Vec::new();
+        for (&position, stack) in tab.range_mut(1..) {
+            if left == 0 {
+                break;
+            }
+            if stack.item_id != item_id {
+                continue;
+            }
+            let amount = stack.quantity.min(left);
+            stack.quantity -= amount;
+            left -= amount;
+            taken.push((inventory_type, position, stack.quantity));
+        }
+        tab.retain(|&position, stack| position < 1 || stack.quantity > 0);
+        Some(taken)
+    }
+
+    /// Take `quantity` out of the stack at `position`. Returns what is left
+    /// there, or `None`, changing nothing, if the stack is smaller. An emptied
+    /// position is cleared.
+    pub fn take_at(
+        &mut self,
+        inventory_type: InventoryType,
+        position: i16,
+        quantity: i16,
+    ) -> Option<i16> {
+        let tab = &mut self.tabs[inventory_type.index()];
+        let stack = tab.get_mut(&position).filter(|_| position > 0)?;
+        if quantity <= 0 || stack.quantity < quantity {
+            return None;
+        }
+        stack.quantity -= quantity;
+        let remaining = stack.quantity;
+        if remaining == 0 {
+            tab.remove(&position);
+        }
+        Some(remaining)
+    }
+
+    /// Change the size of the stack at `position`, as recharging does.
+    /// Returns `false` if there is no stack there.
+    pub fn set_quantity(
+        &mut self,
+        inventory_type: InventoryType,
+        position: i16,
+        quantity: i16,
+    ) -> bool {
+        match self.tabs[inventory_type.index()].get_mut(&position) {
+            Some(stack) if position > 0 && quantity > 0 => {
+                stack.quantity = quantity;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Put an equip straight into its first equip slot, as is done for the
+    /// equips a new character starts with. Returns the slot, or `None` if the
+    /// item is not an equip or the slot is taken.
+    pub fn wear(&mut self, item: InventoryItem) -> Option<i16> {
+        let position = *equip_slots(item.item_id).first()?;
+        let tab = &mut self.tabs[InventoryType::Equip.index()];
+        if !item.is_equip() || tab.contains_key(&position) {
+            return None;
+        }
+        tab.insert(position, item);
+        Some(position)
+    }
+
+    /// Move the item at `from` to `to` within a tab, swapping it with
+    /// whatever is already there. Negative positions in the equip tab are
+    /// equip slots. Callers are responsible for checking that the move is
+    /// allowed; this only checks that `from` holds an item and `to` is a
+    /// slot that exists.
+    pub fn swap(&mut self, inventory_type: InventoryType, from: i16, to: i16) -> bool {
+        let in_range = |position: i16| {
+            position != 0
+                && position <= i16::from(self.slot_limit(inventory_type))
+                && (position > 0 || inventory_type == InventoryType::Equip)
+        };
+        if from == to || !in_range(from) || !in_range(to) {
+            return false;
+        }
+
+        let tab = &mut self.tabs[inventory_type.index()];
+        let Some(item) = tab.remove(&from) else {
+            return false;
+        };
+        if let Some(displaced) = tab.remove(&to) {
+            tab.insert(from, displaced);
+        }
+        tab.insert(to, item);
+        true
+    }
+
+    /// Save the current state of the inventory.
+    pub fn save(&self) -> QueryResult<()> {
+        repository::replace_items(self.character_id, self.new_items())
+    }
+
+    /// Save the current state of the inventory as part of a larger
+    /// transaction.
+    pub(crate) fn save_in(&self, connection: &mut PgConnection) -> QueryResult<()> {
+        repository::replace_items_in(connection, self.character_id, self.new_items())
+    }
+
+    fn new_items(&self) -> Vec<NewItem<'_>> {
+        InventoryType::ALL
+            .iter()
+            .flat_map(|&inventory_type| {
+                self.tabs[inventory_type.index()]
+                    .iter()
+                    .map(move |(&position, item)| {
+                        NewItem::from(self.character_id, inventory_type, position, item)
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inventory(slot_limit: u8) -> Inventory {
+        Inventory::from_item_vec(1, [slot_limit; 5], Vec::new())
+    }
+
+    #[test]
+    fn take_empties_stacks_in_order() {
+        let mut inventory = inventory(4);
+        inventory.add(InventoryItem::new(4000019, 100), 100);
+        inventory.add(InventoryItem::new(4000000, 5), 100);
+        inventory.add(InventoryItem::new(4000019, 30), 100);
+
+        assert_eq!(inventory.take(4000019, 200), None);
+        assert_eq!(inventory.count(4000019), 130);
+        assert_eq!(
+            inventory.take(4000019, 110),
+            Some(vec![
+                (InventoryType::Etc, 1, 0),
+                (InventoryType::Etc, 3, 20)
+            ])
+        );
+        assert!(inventory.get(InventoryType::Etc, 1).is_none());
+        assert_eq!(inventory.count(4000019), 20);
+        assert_eq!(inventory.count(4000000), 5);
+        assert_eq!(inventory.take(4000000, 0), None);
+    }
+
+    #[test]
+    fn take_at_only_takes_from_the_given_stack() {
+        let mut inventory = inventory(4);
+        inventory.add(InventoryItem::new(2000000, 50), 100);
+        inventory.add(InventoryItem::new(2070000, 500), 500);
+
+        assert_eq!(inventory.take_at(InventoryType::Use, 1, 51), None);
+        assert_eq!(inventory.take_at(InventoryType::Use, 1, 20), Some(30));
+        assert_eq!(inventory.take_at(InventoryType::Use, 2, 500), Some(0));
+        assert!(inventory.get(InventoryType::Use, 2).is_none());
+        assert_eq!(inventory.take_at(InventoryType::Use, 2, 1), None);
+
+        assert!(inventory.set_quantity(InventoryType::Use, 1, 100));
+        assert_eq!(inventory.count(2000000), 100);
+        assert!(!inventory.set_quantity(InventoryType::Use, 2, 100));
+    }
+
+    #[test]
+    fn add_stacks_items_and_takes_free_slots() {
+        let mut inventory = inventory(2);
+
+        assert_eq!(
+            inventory.add(InventoryItem::new(4000019, 60), 100),
+            Some((InventoryType::Etc, 1))
+        );
+        assert_eq!(
+            inventory.add(InventoryItem::new(4000019, 30), 100),
+            Some((InventoryType::Etc, 1))
+        );
+        assert_eq!(inventory.get(InventoryType::Etc, 1).unwrap().quantity, 90);
+
+        // Does not fit on the existing stack, so it starts a new one.
+        assert_eq!(
+            inventory.add(InventoryItem::new(4000019, 30), 100),
+            Some((InventoryType::Etc, 2))
+        );
+        assert_eq!(inventory.free_slots(InventoryType::Etc), 0);
+        assert_eq!(inventory.add(InventoryItem::new(4000000, 1), 100), None);
+        assert_eq!(inventory.free_slots(InventoryType::Use), 2);
+        assert_eq!(inventory.count(4000019), 120);
+        assert_eq!(inventory.count(4000000), 0);
+    }
+
+    #[test]
+    fn equips_never_stack_and_equipped_items_use_no_slots() {
+        let mut inventory = inventory(2);
+        let mut worn = InventoryItem::new(1302000, 1);
+        worn.stats.as_mut().unwrap().watk = 17;
+        inventory.tabs[InventoryType::Equip.index()].insert(-11, worn);
+
+        assert_eq!(
+            inventory.add(InventoryItem::new(1302000, 1), 1),
+            Some((InventoryType::Equip, 1))
+        );
+        assert_eq!(
+            inventory.add(InventoryItem::new(1302000, 1), 1),
+            Some((InventoryType::Equip, 2))
+        );
+        assert_eq!(inventory.free_slots(InventoryType::Equip), 0);
+
+        let equipped: Vec<_> = inventory.equipped().collect();
+        assert_eq!(equipped.len(), 1);
+        assert_eq!(equipped[0].0, -11);
+        assert_eq!(equipped[0].1.stats.unwrap().watk, 17);
+    }
+
+    #[test]
+    fn swap_moves_items_between_slots_and_equip_positions() {
+        let mut inventory = inventory(4);
+        assert_eq!(
+            inventory.wear(InventoryItem::new(1302000, 1)),
+            Some(EQUIP_SLOT_WEAPON)
+        );
+        assert_eq!(inventory.wear(InventoryItem::new(1322005, 1)), None);
+        inventory.add(InventoryItem::new(1322005, 1), 1);
+
+        // Swap the weapon in slot 1 with the equipped one.
+        assert!(inventory.swap(InventoryType::Equip, 1, EQUIP_SLOT_WEAPON));
+        assert_eq!(inventory.equipment(), vec![(EQUIP_SLOT_WEAPON, 1322005)]);
+        assert_eq!(
+            inventory.get(InventoryType::Equip, 1).unwrap().item_id,
+            1302000
+        );
+
+        assert!(inventory.swap(InventoryType::Equip, 1, 4));
+        assert!(!inventory.swap(InventoryType::Equip, 1, 2));
+        assert!(!inventory.swap(InventoryType::Equip, 4, 5));
+        assert!(!inventory.swap(InventoryType::Etc, 4, -1));
+    }
+}
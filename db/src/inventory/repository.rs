@@ -0,0 +1,34 @@
+use super::{Item, NewItem};
+use crate::establish_connection;
+use crate::schema::items::dsl::*;
+use diesel::expression_methods::*;
+use diesel::pg::PgConnection;
+use diesel::{Connection, QueryDsl, QueryResult, RunQueryDsl};
+
+pub fn get_items_by_characterid(c_id: i32) -> QueryResult<Vec<Item>> {
+    let mut connection = establish_connection();
+
+    items
+        .filter(character_id.eq(c_id))
+        .load::<Item>(&mut connection)
+}
+
+/// Replace every item row of a character with the given items.
+pub fn replace_items(c_id: i32, new_items: Vec<NewItem>) -> QueryResult<()> {
+    let mut connection = establish_connection();
+
+    connection.transaction(|connection| replace_items_in(connection, c_id, new_items))
+}
+
+/// Replace every item row of a character as part of a larger transaction.
+pub(crate) fn replace_items_in(
+    connection: &mut PgConnection,
+    c_id: i32,
+    new_items: Vec<NewItem>,
+) -> QueryResult<()> {
+    diesel::delete(items.filter(character_id.eq(c_id))).execute(connection)?;
+    diesel::insert_into(items)
+        .values(&new_items)
+        .execute(connection)?;
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::{character::Character, schema::keybindings};
-use diesel::QueryResult;
+use diesel::{pg::PgConnection, QueryResult};
 use itertools::izip;
 use std::collections::HashMap;
 
@@ -136,8 +136,12 @@ impl KeybindSet {
         ))
     }
 
-    /// Create, set, and return a default keybind set for the given character.
-    pub fn set_defaults(character: &Character) -> QueryResult<Self> {
+    /// Create, set, and return a default keybind set for the given character
+    /// as part of a larger transaction.
+    pub(crate) fn set_defaults_in(
+        connection: &mut PgConnection,
+        character: &Character,
+    ) -> QueryResult<Self> {
         let character_id = character.id;
         let mut bind_set = Self {
             character_id,
@@ -158,7 +162,7 @@ impl KeybindSet {
             ))
         });
 
-        bind_set.save()?;
+        repository::upsert_keybindings_in(connection, bind_set.binds.values().cloned().collect())?;
 
         Ok(bind_set)
     }
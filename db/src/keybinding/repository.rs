@@ -3,6 +3,7 @@ use crate::establish_connection;
 use crate::schema::keybindings::dsl::*;
 use diesel::expression_methods::*;
 use diesel::pg::upsert::*;
+use diesel::pg::PgConnection;
 use diesel::{QueryDsl, QueryResult, RunQueryDsl};
 
 pub fn get_keybindings_by_characterid(c_id: i32) -> QueryResult<Vec<Keybinding>> {
@@ -16,10 +17,18 @@ pub fn get_keybindings_by_characterid(c_id: i32) -> QueryResult<Vec<Keybinding>>
 pub fn upsert_keybindings(bindings: Vec<KeybindDTO>) -> QueryResult<Vec<Keybinding>> {
     let mut connection = establish_connection();
 
+    upsert_keybindings_in(&mut connection, bindings)
+}
+
+/// Insert or update keybindings as part of a larger transaction.
+pub(crate) fn upsert_keybindings_in(
+    connection: &mut PgConnection,
+    bindings: Vec<KeybindDTO>,
+) -> QueryResult<Vec<Keybinding>> {
     diesel::insert_into(keybindings)
         .values(bindings)
         .on_conflict(on_constraint("key_is_unique_per_character"))
         .do_update()
         .set(key.eq(excluded(key)))
-        .get_results(&mut connection)
+        .get_results(connection)
 }
@@ -16,8 +16,12 @@ mod sql_types;
 
 pub mod account;
 pub mod character;
+pub mod inventory;
 pub mod keybinding;
+pub mod quest;
 pub mod session;
+pub mod skill;
+pub mod storage;
 
 pub use diesel::result::Error;
 
@@ -0,0 +1,221 @@
+use crate::{character::Character, schema::quests};
+use diesel::pg::PgConnection;
+use diesel::QueryResult;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+mod repository;
+pub use repository::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::QuestStatus"]
+pub enum QuestStatus {
+    Started,
+    Completed,
+}
+
+/// Quest database entity.
+#[derive(Identifiable, Queryable)]
+#[diesel(table_name = quests)]
+pub struct Quest {
+    pub id: i32,
+    pub character_id: i32,
+    pub quest_id: i32,
+    pub status: QuestStatus,
+    pub progress: String,
+    pub completed_at: Option<SystemTime>,
+}
+
+/// Quest creation projection.
+#[derive(Insertable)]
+#[diesel(table_name = quests)]
+pub struct NewQuest<'a> {
+    pub character_id: i32,
+    pub quest_id: i32,
+    pub status: QuestStatus,
+    pub progress: &'a str,
+    pub completed_at: Option<SystemTime>,
+}
+
+/// A quest the character has started or completed, independent of its
+/// database row.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuestEntry {
+    pub status: QuestStatus,
+    /// What the client shows of a started quest, such as its mob kill counts.
+    pub progress: String,
+    pub completed_at: Option<SystemTime>,
+}
+
+/// The quests a character has started or completed, keyed by quest id.
+#[derive(Clone)]
+pub struct QuestLog {
+    character_id: i32,
+    quests: BTreeMap<i32, QuestEntry>,
+}
+
+impl QuestLog {
+    /// Get the quest log of the given character.
+    pub fn from_character(character: &Character) -> QueryResult<Self> {
+        Ok(Self::from_quest_vec(
+            character.id,
+            repository::get_quests_by_characterid(character.id)?,
+        ))
+    }
+
+    /// Build a quest log out of a vector of quest rows.
+    pub fn from_quest_vec(character_id: i32, quest_vec: Vec<Quest>) -> Self {
+        Self {
+            character_id,
+            quests: quest_vec
+                .into_iter()
+                .map(|quest| {
+                    (
+                        quest.quest_id,
+                        QuestEntry {
+                            status: quest.status,
+                            progress: quest.progress,
+                            completed_at: quest.completed_at,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, quest_id: i32) -> Option<&QuestEntry> {
+        self.quests.get(&quest_id)
+    }
+
+    /// The status of the given quest, or `None` if it has not been started.
+    pub fn status(&self, quest_id: i32) -> Option<QuestStatus> {
+        self.get(quest_id).map(|entry| entry.status)
+    }
+
+    /// Started quests and their progress, in quest id order.
+    pub fn started(&self) -> impl Iterator<Item = (i32, &str)> + '_ {
+        self.quests
+            .iter()
+            .filter(|(_, entry)| entry.status == QuestStatus::Started)
+            .map(|(&quest_id, entry)| (quest_id, entry.progress.as_str()))
+    }
+
+    /// Completed quests and when they were completed, in quest id order.
+    pub fn completed(&self) -> impl Iterator<Item = (i32, SystemTime)> + '_ {
+        self.quests
+            .iter()
+            .filter(|(_, entry)| entry.status == QuestStatus::Completed)
+            .map(|(&quest_id, entry)| {
+                (
+                    quest_id,
+                    entry.completed_at.unwrap_or(SystemTime::UNIX_EPOCH),
+                )
+            })
+    }
+
+    /// Start a quest that is not in the log yet. Returns false if it is.
+    pub fn start(&mut self, quest_id: i32, progress: String) -> bool {
+        if self.quests.contains_key(&quest_id) {
+            return false;
+        }
+        self.quests.insert(
+            quest_id,
+            QuestEntry {
+                status: QuestStatus::Started,
+                progress,
+                completed_at: None,
+            },
+        );
+        true
+    }
+
+    /// Replace the progress of a started quest. Returns false if the quest
+    /// is not started.
+    pub fn set_progress(&mut self, quest_id: i32, progress: String) -> bool {
+        match self.quests.get_mut(&quest_id) {
+            Some(entry) if entry.status == QuestStatus::Started => {
+                entry.progress = progress;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Complete a started quest. Returns false if the quest is not started.
+    pub fn complete(&mut self, quest_id: i32, completed_at: SystemTime) -> bool {
+        match self.quests.get_mut(&quest_id) {
+            Some(entry) if entry.status == QuestStatus::Started => {
+                entry.status = QuestStatus::Completed;
+                entry.progress.clear();
+                entry.completed_at = Some(completed_at);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Drop a started quest, as if it had never been started. Returns false
+    /// if the quest is not started.
+    pub fn forfeit(&mut self, quest_id: i32) -> bool {
+        if self.status(quest_id) != Some(QuestStatus::Started) {
+            return false;
+        }
+        self.quests.remove(&quest_id);
+        true
+    }
+
+    /// Save the current state of the quest log.
+    pub fn save(&self) -> QueryResult<()> {
+        repository::replace_quests(self.character_id, self.new_quests())
+    }
+
+    /// Save the current state of the quest log as part of a larger
+    /// transaction.
+    pub(crate) fn save_in(&self, connection: &mut PgConnection) -> QueryResult<()> {
+        repository::replace_quests_in(connection, self.character_id, self.new_quests())
+    }
+
+    fn new_quests(&self) -> Vec<NewQuest<'_>> {
+        self.quests
+            .iter()
+            .map(|(&quest_id, entry)| NewQuest {
+                character_id: self.character_id,
+                quest_id,
+                status: entry.status,
+                progress: &entry.progress,
+                completed_at: entry.completed_at,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quests_move_from_started_to_completed_or_forfeited() {
+        let mut log = QuestLog::from_quest_vec(1, Vec::new());
+        assert!(log.start(1000, "000".to_string()));
+        assert!(log.start(1001, String::new()));
+        assert!(!log.start(1000, String::new()));
+
+        assert!(log.set_progress(1000, "003".to_string()));
+        assert_eq!(
+            log.started().collect::<Vec<_>>(),
+            vec![(1000, "003"), (1001, "")]
+        );
+
+        let now = SystemTime::now();
+        assert!(log.complete(1000, now));
+        assert!(!log.complete(1000, now));
+        assert!(!log.set_progress(1000, "004".to_string()));
+        assert!(!log.forfeit(1000));
+        assert_eq!(log.completed().collect::<Vec<_>>(), vec![(1000, now)]);
+
+        assert!(log.forfeit(1001));
+        assert_eq!(log.status(1001), None);
+        assert_eq!(log.started().count(), 0);
+        assert!(log.start(1001, String::new()));
+    }
+}
@@ -0,0 +1,34 @@
+use super::{NewQuest, Quest};
+use crate::establish_connection;
+use crate::schema::quests::dsl::*;
+use diesel::expression_methods::*;
+use diesel::pg::PgConnection;
+use diesel::{Connection, QueryDsl, QueryResult, RunQueryDsl};
+
+pub fn get_quests_by_characterid(c_id: i32) -> QueryResult<Vec<Quest>> {
+    let mut connection = establish_connection();
+
+    quests
+        .filter(character_id.eq(c_id))
+        .load::<Quest>(&mut connection)
+}
+
+/// Replace every quest row of a character with the given quests.
+pub fn replace_quests(c_id: i32, new_quests: Vec<NewQuest>) -> QueryResult<()> {
+    let mut connection = establish_connection();
+
+    connection.transaction(|connection| replace_quests_in(connection, c_id, new_quests))
+}
+
+/// Replace every quest row of a character as part of a larger transaction.
+pub(crate) fn replace_quests_in(
+    connection: &mut PgConnection,
+    c_id: i32,
+    new_quests: Vec<NewQuest>,
+) -> QueryResult<()> {
+    diesel::delete(quests.filter(character_id.eq(c_id))).execute(connection)?;
+    diesel::insert_into(quests)
+        .values(&new_quests)
+        .execute(connection)?;
+    Ok(())
+}
@@ -1,10 +1,18 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "inventory_type"))]
+    pub struct InventoryType;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "keybind_type"))]
     pub struct KeybindType;
 
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "quest_status"))]
+    pub struct QuestStatus;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "session_state"))]
     pub struct SessionState;
@@ -76,6 +84,46 @@ diesel::table! {
         gender -> Int2,
         created_at -> Timestamp,
         map_id -> Int4,
+        equip_slots -> Int2,
+        use_slots -> Int2,
+        setup_slots -> Int2,
+        etc_slots -> Int2,
+        cash_slots -> Int2,
+        sp -> Int2,
+    }
+}
+
+diesel::table! {
+    use crate::sql_types::*;
+    use super::sql_types::InventoryType;
+
+    items (id) {
+        id -> Int4,
+        character_id -> Int4,
+        inventory_type -> InventoryType,
+        position -> Int2,
+        item_id -> Int4,
+        quantity -> Int2,
+        #[max_length = 13]
+        owner -> Varchar,
+        flag -> Int2,
+        upgrade_slots -> Int2,
+        level -> Int2,
+        stre -> Int2,
+        dex -> Int2,
+        int -> Int2,
+        luk -> Int2,
+        hp -> Int2,
+        mp -> Int2,
+        watk -> Int2,
+        matk -> Int2,
+        wdef -> Int2,
+        mdef -> Int2,
+        acc -> Int2,
+        avoid -> Int2,
+        hands -> Int2,
+        speed -> Int2,
+        jump -> Int2,
     }
 }
 
@@ -92,6 +140,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use crate::sql_types::*;
+    use super::sql_types::QuestStatus;
+
+    quests (id) {
+        id -> Int4,
+        character_id -> Int4,
+        quest_id -> Int4,
+        status -> QuestStatus,
+        progress -> Varchar,
+        completed_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     use crate::sql_types::*;
     use super::sql_types::SessionState;
@@ -112,8 +174,80 @@ diesel::table! {
 }
 
 diesel::joinable!(characters -> accounts (accountid));
+diesel::table! {
+    use crate::sql_types::*;
+
+    skills (id) {
+        id -> Int4,
+        character_id -> Int4,
+        skill_id -> Int4,
+        level -> Int2,
+        master_level -> Int2,
+    }
+}
+
+diesel::table! {
+    use crate::sql_types::*;
+
+    storage_items (id) {
+        id -> Int4,
+        storage_id -> Int4,
+        position -> Int2,
+        item_id -> Int4,
+        quantity -> Int2,
+        #[max_length = 13]
+        owner -> Varchar,
+        flag -> Int2,
+        upgrade_slots -> Int2,
+        level -> Int2,
+        stre -> Int2,
+        dex -> Int2,
+        int -> Int2,
+        luk -> Int2,
+        hp -> Int2,
+        mp -> Int2,
+        watk -> Int2,
+        matk -> Int2,
+        wdef -> Int2,
+        mdef -> Int2,
+        acc -> Int2,
+        avoid -> Int2,
+        hands -> Int2,
+        speed -> Int2,
+        jump -> Int2,
+    }
+}
+
+diesel::table! {
+    use crate::sql_types::*;
+
+    storages (id) {
+        id -> Int4,
+        account_id -> Int4,
+        world -> Int2,
+        slots -> Int2,
+        meso -> Int4,
+    }
+}
+
+diesel::joinable!(items -> characters (character_id));
 diesel::joinable!(keybindings -> characters (character_id));
+diesel::joinable!(quests -> characters (character_id));
 diesel::joinable!(sessions -> accounts (account_id));
 diesel::joinable!(sessions -> characters (character_id));
+diesel::joinable!(skills -> characters (character_id));
+diesel::joinable!(storage_items -> storages (storage_id));
+diesel::joinable!(storages -> accounts (account_id));
 
-diesel::allow_tables_to_appear_in_same_query!(accounts, buddies, characters, keybindings, sessions,);
+diesel::allow_tables_to_appear_in_same_query!(
+    accounts,
+    buddies,
+    characters,
+    items,
+    keybindings,
+    quests,
+    sessions,
+    skills,
+    storage_items,
+    storages,
+);
@@ -0,0 +1,108 @@
+use crate::{character::Character, schema::skills};
+use diesel::QueryResult;
+use std::collections::BTreeMap;
+
+mod repository;
+pub use repository::*;
+
+/// Skill database entity.
+#[derive(Identifiable, Queryable)]
+#[diesel(table_name = skills)]
+pub struct Skill {
+    pub id: i32,
+    pub character_id: i32,
+    pub skill_id: i32,
+    pub level: i16,
+    pub master_level: i16,
+}
+
+/// Skill creation and update projection.
+#[derive(Insertable)]
+#[diesel(table_name = skills)]
+pub struct NewSkill {
+    pub character_id: i32,
+    pub skill_id: i32,
+    pub level: i16,
+    pub master_level: i16,
+}
+
+/// A learned skill's level, independent of its database row.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SkillEntry {
+    pub level: i16,
+    /// Cap on the level of fourth job skills; zero for every other skill.
+    pub master_level: i16,
+}
+
+/// The skills a character has put SP into, keyed by skill id.
+pub struct SkillBook {
+    character_id: i32,
+    skills: BTreeMap<i32, SkillEntry>,
+}
+
+impl SkillBook {
+    /// Get the skill book of the given character.
+    pub fn from_character(character: &Character) -> QueryResult<Self> {
+        Ok(Self::from_skill_vec(
+            character.id,
+            repository::get_skills_by_characterid(character.id)?,
+        ))
+    }
+
+    /// Build a skill book out of a vector of skill rows.
+    pub fn from_skill_vec(character_id: i32, skill_vec: Vec<Skill>) -> Self {
+        Self {
+            character_id,
+            skills: skill_vec
+                .into_iter()
+                .map(|skill| {
+                    (
+                        skill.skill_id,
+                        SkillEntry {
+                            level: skill.level,
+                            master_level: skill.master_level,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, skill_id: i32) -> Option<SkillEntry> {
+        self.skills.get(&skill_id).copied()
+    }
+
+    /// The level of the given skill, or 0 if it has not been learned.
+    pub fn level(&self, skill_id: i32) -> i16 {
+        self.get(skill_id).map_or(0, |entry| entry.level)
+    }
+
+    /// Every learned skill, in skill id order.
+    pub fn skills(&self) -> impl Iterator<Item = (i32, SkillEntry)> + '_ {
+        self.skills
+            .iter()
+            .map(|(&skill_id, &entry)| (skill_id, entry))
+    }
+
+    /// Set/replace the given skill in the skill book.
+    pub fn set(&mut self, skill_id: i32, entry: SkillEntry) {
+        self.skills.insert(skill_id, entry);
+    }
+
+    /// Save the current state of the skill book.
+    pub fn save(&self) -> QueryResult<()> {
+        let new_skills = self
+            .skills
+            .iter()
+            .map(|(&skill_id, entry)| NewSkill {
+                character_id: self.character_id,
+                skill_id,
+                level: entry.level,
+                master_level: entry.master_level,
+            })
+            .collect();
+
+        repository::upsert_skills(new_skills)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use super::{NewSkill, Skill};
+use crate::establish_connection;
+use crate::schema::skills::dsl::*;
+use diesel::expression_methods::*;
+use diesel::pg::upsert::*;
+use diesel::{QueryDsl, QueryResult, RunQueryDsl};
+
+pub fn get_skills_by_characterid(c_id: i32) -> QueryResult<Vec<Skill>> {
+    let mut connection = establish_connection();
+
+    skills
+        .filter(character_id.eq(c_id))
+        .load::<Skill>(&mut connection)
+}
+
+pub fn upsert_skills(new_skills: Vec<NewSkill>) -> QueryResult<Vec<Skill>> {
+    let mut connection = establish_connection();
+
+    diesel::insert_into(skills)
+        .values(new_skills)
+        .on_conflict(on_constraint("skill_is_unique_per_character"))
+        .do_update()
+        .set((
+            level.eq(excluded(level)),
+            master_level.eq(excluded(master_level)),
+        ))
+        .get_results(&mut connection)
+}
@@ -0,0 +1,353 @@
+use crate::{
+    character::CharacterWrapper,
+    inventory::{EquipStats, InventoryItem, InventoryType},
+    schema::{storage_items, storages},
+};
+use diesel::QueryResult;
+use std::convert::TryFrom;
+
+mod repository;
+pub use repository::*;
+
+/// The most slots a storage can be expanded to.
+pub const MAX_STORAGE_SLOTS: i16 = 48;
+
+/// Storage database entity. Each account has one per world.
+#[derive(Identifiable, Queryable, AsChangeset)]
+#[diesel(table_name = storages)]
+pub struct Storage {
+    pub id: i32,
+    pub account_id: i32,
+    pub world: i16,
+    pub slots: i16,
+    pub meso: i32,
+}
+
+/// Stored item database entity.
+#[derive(Identifiable, Queryable)]
+#[diesel(table_name = storage_items)]
+pub struct StorageItem {
+    pub id: i32,
+    pub storage_id: i32,
+    pub position: i16,
+    pub item_id: i32,
+    pub quantity: i16,
+    pub owner: String,
+    pub flag: i16,
+
+    pub upgrade_slots: i16,
+    pub level: i16,
+    pub stre: i16,
+    pub dex: i16,
+    pub int: i16,
+    pub luk: i16,
+    pub hp: i16,
+    pub mp: i16,
+    pub watk: i16,
+    pub matk: i16,
+    pub wdef: i16,
+    pub mdef: i16,
+    pub acc: i16,
+    pub avoid: i16,
+    pub hands: i16,
+    pub speed: i16,
+    pub jump: i16,
+}
+
+/// Stored item creation projection.
+#[derive(Insertable)]
+#[diesel(table_name = storage_items)]
+pub struct NewStorageItem<'a> {
+    pub storage_id: i32,
+    pub position: i16,
+    pub item_id: i32,
+    pub quantity: i16,
+    pub owner: &'a str,
+    pub flag: i16,
+
+    pub upgrade_slots: i16,
+    pub level: i16,
+    pub stre: i16,
+    pub dex: i16,
+    pub int: i16,
+    pub luk: i16,
+    pub hp: i16,
+    pub mp: i16,
+    pub watk: i16,
+    pub matk: i16,
+    pub wdef: i16,
+    pub mdef: i16,
+    pub acc: i16,
+    pub avoid: i16,
+    pub hands: i16,
+    pub speed: i16,
+    pub jump: i16,
+}
+
+impl<'a> NewStorageItem<'a> {
+    fn from(storage_id: i32, position: i16, item: &'a InventoryItem) -> Self {
+        let stats = item.stats.unwrap_or_default();
+
+        NewStorageItem {
+            storage_id,
+            position,
+            item_id: item.item_id,
+            quantity: item.quantity,
+            owner: &item.owner,
+            flag: item.flag,
+            upgrade_slots: stats.upgrade_slots,
+            level: stats.level,
+            stre: stats.stre,
+            dex: stats.dex,
+            int: stats.int,
+            luk: stats.luk,
+            hp: stats.hp,
+            mp: stats.mp,
+            watk: stats.watk,
+            matk: stats.matk,
+            wdef: stats.wdef,
+            mdef: stats.mdef,
+            acc: stats.acc,
+            avoid: stats.avoid,
+            hands: stats.hands,
+            speed: stats.speed,
+            jump: stats.jump,
+        }
+    }
+}
+
+impl From<StorageItem> for InventoryItem {
+    fn from(item: StorageItem) -> Self {
+        let is_equip = InventoryType::of_item(item.item_id) == Some(InventoryType::Equip);
+        let stats = is_equip.then_some(EquipStats {
+            upgrade_slots: item.upgrade_slots,
+            level: item.level,
+            stre: item.stre,
+            dex: item.dex,
+            int: item.int,
+            luk: item.luk,
+            hp: item.hp,
+            mp: item.mp,
+            watk: item.watk,
+            matk: item.matk,
+            wdef: item.wdef,
+            mdef: item.mdef,
+            acc: item.acc,
+            avoid: item.avoid,
+            hands: item.hands,
+            speed: item.speed,
+            jump: item.jump,
+        });
+
+        InventoryItem {
+            item_id: item.item_id,
+            quantity: item.quantity,
+            owner: item.owner,
+            flag: item.flag,
+            stats,
+        }
+    }
+}
+
+/// The storage an account shares between its characters in one world, with
+/// the items and mesos kept in it. Items are not stacked, and keep the order
+/// they were put in until the storage is arranged.
+pub struct AccountStorage {
+    storage: Storage,
+    items: Vec<InventoryItem>,
+}
+
+impl AccountStorage {
+    /// Get the storage of the given account in the given world, creating an
+    /// empty one the first time.
+    pub fn from_account(account_id: i32, world: i16) -> QueryResult<Self> {
+        let storage = repository::get_or_create_storage(account_id, world)?;
+        let items = repository::get_storage_items(storage.id)?;
+        Ok(Self::from_item_vec(storage, items))
+    }
+
+    /// Build a storage out of its row and a vector of stored item rows.
+    pub fn from_item_vec(storage: Storage, mut item_vec: Vec<StorageItem>) -> Self {
+        item_vec.sort_by_key(|item| item.position);
+
+        Self {
+            storage,
+            items: item_vec.into_iter().map(InventoryItem::from).collect(),
+        }
+    }
+
+    pub fn slots(&self) -> u8 {
+        self.storage.slots.clamp(0, MAX_STORAGE_SLOTS) as u8
+    }
+
+    pub fn meso(&self) -> i32 {
+        self.storage.meso
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= usize::from(self.slots())
+    }
+
+    /// Everything stored, in order.
+    pub fn items(&self) -> &[InventoryItem] {
+        &self.items
+    }
+
+    /// The stored items that belong in the given tab, in order. The client
+    /// picks items to take out by their index in here.
+    pub fn items_of(&self, inventory_type: InventoryType) -> Vec<&InventoryItem> {
+        self.items
+            .iter()
+            .filter(|item| InventoryType::of_item(item.item_id) == Some(inventory_type))
+            .collect()
+    }
+
+    /// Store an item after everything else. Returns `false`, changing
+    /// nothing, if the storage is full.
+    pub fn put(&mut self, item: InventoryItem) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.items.push(item);
+        true
+    }
+
+    /// Take out the item at `index` among the items of the given tab.
+    pub fn take(&mut self, inventory_type: InventoryType, index: usize) -> Option<InventoryItem> {
+        let position = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| InventoryType::of_item(item.item_id) == Some(inventory_type))
+            .nth(index)
+            .map(|(position, _)| position)?;
+        Some(self.items.remove(position))
+    }
+
+    /// Sort the stored items by tab, then by item id.
+    pub fn arrange(&mut self) {
+        self.items.sort_by_key(|item| {
+            (
+                InventoryType::of_item(item.item_id).map(u8::from),
+                item.item_id,
+            )
+        });
+    }
+
+    /// Add mesos to the storage. Returns `false`, changing nothing, if the
+    /// storage can't hold that many.
+    pub fn deposit_meso(&mut self, meso: i32) -> bool {
+        match self.storage.meso.checked_add(meso) {
+            Some(total) if meso > 0 => {
+                self.storage.meso = total;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Take mesos out of the storage. Returns `false`, changing nothing, if
+    /// there are not that many stored.
+    pub fn withdraw_meso(&mut self, meso: i32) -> bool {
+        if meso <= 0 || meso > self.storage.meso {
+            return false;
+        }
+        self.storage.meso -= meso;
+        true
+    }
+
+    /// Add `slots` slots to the storage. Returns `false`, changing nothing,
+    /// if that would take it past `MAX_STORAGE_SLOTS`.
+    pub fn expand(&mut self, slots: i16) -> bool {
+        match self.storage.slots.checked_add(slots) {
+            Some(total) if slots > 0 && total <= MAX_STORAGE_SLOTS => {
+                self.storage.slots = total;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Save the storage on its own, as after an expansion.
+    pub fn save(&self) -> QueryResult<()> {
+        repository::save_storage(&self.storage, self.new_items())
+    }
+
+    /// Save the storage together with the character that put items or mesos
+    /// in or took them out, so that either both changes are saved or
+    /// neither is.
+    pub fn save_with(&self, chr: &CharacterWrapper) -> QueryResult<()> {
+        repository::save_storage_with(
+            &self.storage,
+            self.new_items(),
+            &chr.character,
+            &chr.inventory,
+        )
+    }
+
+    fn new_items(&self) -> Vec<NewStorageItem<'_>> {
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(position, item)| {
+                let position = i16::try_from(position).unwrap_or(i16::MAX);
+                NewStorageItem::from(self.storage.id, position, item)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage(slots: i16) -> AccountStorage {
+        let storage = Storage {
+            id: 1,
+            account_id: 1,
+            world: 0,
+            slots,
+            meso: 0,
+        };
+        AccountStorage::from_item_vec(storage, Vec::new())
+    }
+
+    #[test]
+    fn items_are_taken_out_by_their_index_in_the_tab() {
+        let mut storage = storage(3);
+        assert!(storage.put(InventoryItem::new(2000000, 50)));
+        assert!(storage.put(InventoryItem::new(4000019, 10)));
+        assert!(storage.put(InventoryItem::new(2000001, 20)));
+        assert!(storage.is_full());
+        assert!(!storage.put(InventoryItem::new(2000002, 1)));
+
+        assert_eq!(storage.items_of(InventoryType::Use).len(), 2);
+        assert_eq!(storage.take(InventoryType::Use, 2), None);
+        let taken = storage
+            .take(InventoryType::Use, 1)
+            .expect("second use item");
+        assert_eq!((taken.item_id, taken.quantity), (2000001, 20));
+        assert_eq!(storage.items().len(), 2);
+
+        assert!(storage.put(InventoryItem::new(1302000, 1)));
+        storage.arrange();
+        let item_ids: Vec<i32> = storage.items().iter().map(|item| item.item_id).collect();
+        assert_eq!(item_ids, vec![1302000, 2000000, 4000019]);
+    }
+
+    #[test]
+    fn mesos_and_slots_stay_in_bounds() {
+        let mut storage = storage(4);
+        assert!(storage.deposit_meso(100));
+        assert!(!storage.deposit_meso(i32::MAX));
+        assert!(!storage.deposit_meso(-1));
+        assert!(!storage.withdraw_meso(101));
+        assert!(storage.withdraw_meso(100));
+        assert_eq!(storage.meso(), 0);
+
+        assert!(storage.expand(4));
+        assert_eq!(storage.slots(), 8);
+        assert!(!storage.expand(MAX_STORAGE_SLOTS));
+        assert!(!storage.expand(0));
+    }
+}
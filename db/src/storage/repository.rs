@@ -0,0 +1,70 @@
+use super::{NewStorageItem, Storage, StorageItem};
+use crate::character::Character;
+use crate::establish_connection;
+use crate::inventory::Inventory;
+use crate::schema::{storage_items, storages};
+use diesel::expression_methods::*;
+use diesel::pg::PgConnection;
+use diesel::{Connection, QueryDsl, QueryResult, RunQueryDsl, SaveChangesDsl};
+
+/// Get the storage of an account in a world, creating an empty one if it has
+/// none yet.
+pub fn get_or_create_storage(a_id: i32, w: i16) -> QueryResult<Storage> {
+    let mut connection = establish_connection();
+
+    diesel::insert_into(storages::table)
+        .values((storages::account_id.eq(a_id), storages::world.eq(w)))
+        .on_conflict_do_nothing()
+        .execute(&mut connection)?;
+    storages::table
+        .filter(storages::account_id.eq(a_id))
+        .filter(storages::world.eq(w))
+        .first::<Storage>(&mut connection)
+}
+
+pub fn get_storage_items(s_id: i32) -> QueryResult<Vec<StorageItem>> {
+    let mut connection = establish_connection();
+
+    storage_items::table
+        .filter(storage_items::storage_id.eq(s_id))
+        .order(storage_items::position)
+        .load::<StorageItem>(&mut connection)
+}
+
+/// Save a storage and replace every item stored in it.
+pub fn save_storage(storage: &Storage, new_items: Vec<NewStorageItem>) -> QueryResult<()> {
+    let mut connection = establish_connection();
+
+    connection.transaction(|connection| save_storage_in(connection, storage, new_items))
+}
+
+/// Save a storage, its items, and the character and inventory that took
+/// from it or put into it, in one transaction.
+pub fn save_storage_with(
+    storage: &Storage,
+    new_items: Vec<NewStorageItem>,
+    character: &Character,
+    inventory: &Inventory,
+) -> QueryResult<()> {
+    let mut connection = establish_connection();
+
+    connection.transaction(|connection| {
+        save_storage_in(connection, storage, new_items)?;
+        character.save_changes::<Character>(connection)?;
+        inventory.save_in(connection)
+    })
+}
+
+fn save_storage_in(
+    connection: &mut PgConnection,
+    storage: &Storage,
+    new_items: Vec<NewStorageItem>,
+) -> QueryResult<()> {
+    storage.save_changes::<Storage>(connection)?;
+    diesel::delete(storage_items::table.filter(storage_items::storage_id.eq(storage.id)))
+        .execute(connection)?;
+    diesel::insert_into(storage_items::table)
+        .values(&new_items)
+        .execute(connection)?;
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use crate::{GameDataError, NxMapFile};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Job types the character creation screen sends: Cygnus Knights, Explorers
+/// and Arans.
+pub const CREATION_KNIGHT: i16 = 0;
+pub const CREATION_EXPLORER: i16 = 1;
+pub const CREATION_ARAN: i16 = 2;
+
+/// MakeCharInfo.img nodes listing each job type's choices, as the male and
+/// female node. Explorers' sit under `Info`.
+const CHOICE_NODES: [(i16, &[&str], &[&str]); 3] = [
+    (
+        CREATION_KNIGHT,
+        &["PremiumCharMale"],
+        &["PremiumCharFemale"],
+    ),
+    (
+        CREATION_EXPLORER,
+        &["Info", "CharMale"],
+        &["Info", "CharFemale"],
+    ),
+    (CREATION_ARAN, &["OrientCharMale"], &["OrientCharFemale"]),
+];
+
+/// What a new character may start with, from one MakeCharInfo.img node.
+/// Each list is a numbered child of that node.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CreationChoices {
+    pub faces: Vec<i32>,
+    pub hairs: Vec<i32>,
+    pub hair_colors: Vec<i32>,
+    pub skins: Vec<i32>,
+    pub tops: Vec<i32>,
+    pub bottoms: Vec<i32>,
+    pub shoes: Vec<i32>,
+    pub weapons: Vec<i32>,
+}
+
+/// The character creation whitelist from Etc.nx, by job type and gender.
+#[derive(Debug, Default)]
+pub struct CreationData {
+    choices: HashMap<(i16, bool), CreationChoices>,
+}
+
+impl CreationData {
+    pub fn load_from_nx(path: impl AsRef<Path>) -> Result<Self, GameDataError> {
+        let nx = NxMapFile::open(path.as_ref())?;
+        let mut creation = Self::default();
+
+        let Some(info_idx) = nx.child_by_name(0, "MakeCharInfo.img")? else {
+            return Err(GameDataError::InvalidData(
+                "MakeCharInfo.img not found".to_string(),
+            ));
+        };
+        for (job_type, male, female) in CHOICE_NODES {
+            for (is_female, node_path) in [(false, male), (true, female)] {
+                if let Some(choices) = load_choices(&nx, info_idx, node_path)? {
+                    creation.choices.insert((job_type, is_female), choices);
+                }
+            }
+        }
+
+        Ok(creation)
+    }
+
+    /// What a new character of `job_type` may start with. Job types without
+    /// their own node fall back to the Explorer choices, like the client does.
+    pub fn choices(&self, job_type: i16, female: bool) -> Option<&CreationChoices> {
+        self.choices
+            .get(&(job_type, female))
+            .or_else(|| self.choices.get(&(CREATION_EXPLORER, female)))
+    }
+}
+
+fn load_choices(
+    nx: &NxMapFile,
+    info_idx: u32,
+    node_path: &[&str],
+) -> Result<Option<CreationChoices>, GameDataError> {
+    let mut node_idx = info_idx;
+    for child in node_path {
+        let Some(child_idx) = nx.child_by_name(node_idx, child)? else {
+            return Ok(None);
+        };
+        node_idx = child_idx;
+    }
+
+    let mut choices = CreationChoices::default();
+    for list_idx in nx.child_indices(node_idx)? {
+        let list = match nx.node_name(list_idx)?.as_str() {
+            "0" => &mut choices.faces,
+            "1" => &mut choices.hairs,
+            "2" => &mut choices.hair_colors,
+            "3" => &mut choices.skins,
+            "4" => &mut choices.tops,
+            "5" => &mut choices.bottoms,
+            "6" => &mut choices.shoes,
+            "7" => &mut choices.weapons,
+            _ => continue,
+        };
+        for id_idx in nx.child_indices(list_idx)? {
+            if let Some(id) = nx.int_value(id_idx)? {
+                list.push(id);
+            }
+        }
+    }
+    Ok(Some(choices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices_fall_back_to_the_explorer_ones() {
+        let explorer = CreationChoices {
+            weapons: vec![1302000],
+            ..CreationChoices::default()
+        };
+        let aran = CreationChoices {
+            weapons: vec![1442079],
+            ..CreationChoices::default()
+        };
+        let creation = CreationData {
+            choices: HashMap::from([
+                ((CREATION_EXPLORER, false), explorer.clone()),
+                ((CREATION_ARAN, false), aran.clone()),
+            ]),
+        };
+
+        assert_eq!(creation.choices(CREATION_ARAN, false), Some(&aran));
+        assert_eq!(creation.choices(CREATION_KNIGHT, false), Some(&explorer));
+        assert_eq!(creation.choices(CREATION_KNIGHT, true), None);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::GameDataError;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Drop chances are expressed out of this many rolls.
+pub const DROP_CHANCE_SCALE: u32 = 1_000_000;
+/// Item id used in drop tables for meso drops.
+pub const MESO_DROP_ITEM_ID: i32 = 0;
+
+const BUNDLED_MOB_DROPS: &str = include_str!("../data/mob_drops.tsv");
+
+/// One possible drop from a mob.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MobDrop {
+    pub item_id: i32,
+    pub min_quantity: i32,
+    pub max_quantity: i32,
+    /// Chance out of `DROP_CHANCE_SCALE`.
+    pub chance: u32,
+}
+
+impl MobDrop {
+    pub fn is_meso(&self) -> bool {
+        self.item_id == MESO_DROP_ITEM_ID
+    }
+}
+
+/// Mob drop tables. These are server data rather than client data, so they
+/// are kept as a tab-separated file instead of being read from an NX archive.
+#[derive(Debug, Default)]
+pub struct DropData {
+    mob_drops: HashMap<i32, Vec<MobDrop>>,
+}
+
+impl DropData {
+    /// Drop tables shipped with the server in `game-data/data/mob_drops.tsv`.
+    pub fn bundled() -> Result<Self, GameDataError> {
+        Self::parse(BUNDLED_MOB_DROPS)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GameDataError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, GameDataError> {
+        let mut mob_drops: HashMap<i32, Vec<MobDrop>> = HashMap::new();
+
+        for (line_idx, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let [mob_id, item_id, min_quantity, max_quantity, chance] = columns[..] else {
+                return Err(invalid_line(line_idx, "expected 5 columns"));
+            };
+
+            let drop = MobDrop {
+                item_id: parse_column(line_idx, item_id)?,
+                min_quantity: parse_column(line_idx, min_quantity)?,
+                max_quantity: parse_column(line_idx, max_quantity)?,
+                chance: parse_column(line_idx, chance)?,
+            };
+            if drop.min_quantity < 1 || drop.max_quantity < drop.min_quantity {
+                return Err(invalid_line(line_idx, "invalid quantity range"));
+            }
+            if drop.chance > DROP_CHANCE_SCALE {
+                return Err(invalid_line(line_idx, "chance out of range"));
+            }
+
+            mob_drops
+                .entry(parse_column(line_idx, mob_id)?)
+                .or_default()
+                .push(drop);
+        }
+
+        Ok(Self { mob_drops })
+    }
+
+    pub fn mob_drops(&self, mob_id: i32) -> &[MobDrop] {
+        self.mob_drops
+            .get(&mob_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+fn parse_column<T: std::str::FromStr>(line_idx: usize, value: &str) -> Result<T, GameDataError> {
+    value
+        .parse()
+        .map_err(|_| invalid_line(line_idx, &format!("invalid number '{value}'")))
+}
+
+fn invalid_line(line_idx: usize, reason: &str) -> GameDataError {
+    GameDataError::InvalidData(format!("drop table line {}: {reason}", line_idx + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_drop_rows_by_mob() {
+        let data = DropData::parse(
+            "# comment\n100100\t0\t1\t6\t700000\n100100\t4000019\t1\t1\t600000\n\n210100\t4000004\t1\t2\t1000000\n",
+        )
+        .expect("parse drops");
+
+        let snail = data.mob_drops(100100);
+        assert_eq!(snail.len(), 2);
+        assert!(snail[0].is_meso());
+        assert_eq!(snail[1].item_id, 4000019);
+        assert_eq!(data.mob_drops(210100)[0].max_quantity, 2);
+        assert!(data.mob_drops(999).is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_rows() {
+        assert!(DropData::parse("100100\t0\t1\t6\n").is_err());
+        assert!(DropData::parse("100100\t0\t6\t1\t700000\n").is_err());
+        assert!(DropData::parse("100100\t0\t1\t6\t2000000\n").is_err());
+    }
+
+    #[test]
+    fn bundled_drop_tables_parse() {
+        let data = DropData::bundled().expect("bundled drops");
+        assert!(!data.mob_drops(100100).is_empty());
+    }
+}
@@ -0,0 +1,51 @@
+/// Highest level a character can reach.
+pub const MAX_LEVEL: i16 = 200;
+
+/// EXP needed to advance from each level to the next, indexed by level.
+/// Level 0 does not exist and is only there to keep the indexing simple.
+#[rustfmt::skip]
+const EXP_TABLE: [i32; MAX_LEVEL as usize] = [
+    1, 15, 34, 57, 92, 135, 372, 560, 840, 1242,
+    1144, 1573, 2144, 2800, 3640, 4700, 5893, 7360, 9144, 11_120,
+    13_477, 16_268, 19_320, 22_880, 27_008, 31_477, 36_600, 42_444, 48_720, 55_813,
+    63_800, 86_784, 98_208, 110_932, 124_432, 139_372, 155_865, 173_280, 192_400, 213_345,
+    235_372, 259_392, 285_532, 312_928, 342_624, 374_760, 408_336, 445_544, 483_532, 524_160,
+    567_772, 598_886, 631_704, 666_321, 702_836, 741_351, 781_976, 824_828, 870_028, 917_625,
+    967_995, 1_021_041, 1_076_994, 1_136_013, 1_198_266, 1_263_930, 1_333_194, 1_406_252, 1_483_314, 1_564_600,
+    1_650_340, 1_740_778, 1_836_173, 1_936_794, 2_042_930, 2_154_882, 2_272_970, 2_397_528, 2_528_912, 2_667_496,
+    2_813_674, 2_967_863, 3_130_502, 3_302_053, 3_483_005, 3_673_873, 3_875_201, 4_087_562, 4_311_559, 4_547_832,
+    4_797_053, 5_059_931, 5_337_215, 5_629_694, 5_938_202, 6_263_614, 6_606_860, 6_968_915, 7_350_811, 7_753_635,
+    8_178_534, 8_626_718, 9_099_462, 9_598_112, 10_124_088, 10_678_888, 11_264_090, 11_881_362, 12_532_461, 13_219_239,
+    13_943_653, 14_707_765, 15_513_750, 16_363_902, 17_260_644, 18_206_527, 19_204_245, 20_256_637, 21_366_700, 22_537_594,
+    23_772_654, 25_075_395, 26_449_526, 27_898_960, 29_427_822, 31_040_466, 32_741_483, 34_535_716, 36_428_273, 38_424_542,
+    40_530_206, 42_751_262, 45_094_030, 47_565_183, 50_171_755, 52_921_167, 55_821_246, 58_880_250, 62_106_888, 65_510_344,
+    69_100_311, 72_887_008, 76_881_216, 81_094_306, 85_594_273, 90_225_770, 95_170_142, 100_385_466, 105_886_589, 111_689_174,
+    117_809_740, 124_265_714, 131_075_474, 138_258_410, 145_834_970, 153_826_726, 162_256_430, 171_148_082, 180_526_997, 190_419_876,
+    200_854_885, 211_861_732, 223_471_711, 223_471_711, 248_635_353, 262_260_570, 276_632_449, 291_791_906, 307_782_102, 324_648_562,
+    342_439_302, 361_204_976, 380_999_008, 401_877_754, 423_900_654, 447_130_410, 471_633_156, 497_478_653, 524_740_482, 553_496_261,
+    583_827_855, 615_821_622, 649_568_646, 685_165_008, 722_712_050, 762_316_670, 804_091_623, 848_155_844, 894_634_784, 943_660_770,
+    995_373_379, 1_049_919_840, 1_107_455_447, 1_168_144_006, 1_232_158_297, 1_299_680_571, 1_370_903_066, 1_446_028_554, 1_525_246_918, 1_608_855_764,
+];
+
+/// EXP needed to advance from `level` to the next level, or `None` at the
+/// level cap.
+pub fn exp_to_next_level(level: i16) -> Option<i32> {
+    if level < 1 {
+        return None;
+    }
+    EXP_TABLE.get(usize::try_from(level).ok()?).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exp_to_next_level_covers_levels_below_the_cap() {
+        assert_eq!(exp_to_next_level(1), Some(15));
+        assert_eq!(exp_to_next_level(10), Some(1144));
+        assert_eq!(exp_to_next_level(199), Some(1_608_855_764));
+        assert_eq!(exp_to_next_level(MAX_LEVEL), None);
+        assert_eq!(exp_to_next_level(0), None);
+    }
+}
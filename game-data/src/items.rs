@@ -0,0 +1,228 @@
+use crate::{GameDataError, NxMapFile};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Stack size used when an item's `info` has no `slotMax`.
+pub const DEFAULT_SLOT_MAX: i16 = 100;
+
+/// Item.nx groups that hold item templates. Pets and specials are left out.
+const ITEM_GROUPS: [&str; 4] = ["Consume", "Install", "Etc", "Cash"];
+
+/// Base stats and requirements of an equip, from `Character.nx`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EquipTemplate {
+    pub req_level: i16,
+    /// Bit mask of job branches that may wear the equip; 0 means anyone.
+    pub req_job: i16,
+    pub req_str: i16,
+    pub req_dex: i16,
+    pub req_int: i16,
+    pub req_luk: i16,
+    pub upgrade_slots: i16,
+    pub stre: i16,
+    pub dex: i16,
+    pub int: i16,
+    pub luk: i16,
+    pub hp: i16,
+    pub mp: i16,
+    pub watk: i16,
+    pub matk: i16,
+    pub wdef: i16,
+    pub mdef: i16,
+    pub acc: i16,
+    pub avoid: i16,
+    pub hands: i16,
+    pub speed: i16,
+    pub jump: i16,
+}
+
+impl EquipTemplate {
+    /// Whether a character with `job` may wear the equip. Each bit of
+    /// `req_job` is a job branch: 1 warrior, 2 magician, 4 bowman, 8 thief
+    /// and 16 pirate.
+    pub fn allows_job(&self, job: i16) -> bool {
+        if self.req_job == 0 {
+            return true;
+        }
+
+        match job / 100 % 10 {
+            0 => false,
+            branch => self.req_job & (1 << (branch - 1)) != 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemTemplate {
+    pub item_id: i32,
+    pub price: i32,
+    /// Price of each throwing star or bullet when a shop recharges them.
+    pub unit_price: f64,
+    pub slot_max: i16,
+    pub cash: bool,
+    /// Present for equips only.
+    pub equip: Option<EquipTemplate>,
+}
+
+/// Item and equip templates, keyed by item id.
+#[derive(Debug, Default)]
+pub struct ItemData {
+    items: HashMap<i32, ItemTemplate>,
+}
+
+impl ItemData {
+    /// Load item templates from Item.nx and equip templates from
+    /// Character.nx.
+    pub fn load_from_nx(
+        item_path: impl AsRef<Path>,
+        character_path: impl AsRef<Path>,
+    ) -> Result<Self, GameDataError> {
+        let mut items = HashMap::new();
+
+        let nx = NxMapFile::open(item_path.as_ref())?;
+        for group in ITEM_GROUPS {
+            let Some(group_idx) = nx.child_by_name(0, group)? else {
+                continue;
+            };
+
+            for img_idx in nx.child_indices(group_idx)? {
+                for item_idx in nx.child_indices(img_idx)? {
+                    let Ok(item_id) = nx.node_name(item_idx)?.parse::<i32>() else {
+                        continue;
+                    };
+                    let Some(info_idx) = nx.child_by_name(item_idx, "info")? else {
+                        continue;
+                    };
+
+                    items.insert(item_id, build_item_template(&nx, info_idx, item_id, None)?);
+                }
+            }
+        }
+
+        let nx = NxMapFile::open(character_path.as_ref())?;
+        for category_idx in nx.child_indices(0)? {
+            for img_idx in nx.child_indices(category_idx)? {
+                let Some(item_id) = parse_equip_id(nx.node_name(img_idx)?.as_str()) else {
+                    continue;
+                };
+                let Some(info_idx) = nx.child_by_name(img_idx, "info")? else {
+                    continue;
+                };
+
+                let equip = build_equip_template(&nx, info_idx)?;
+                items.insert(
+                    item_id,
+                    build_item_template(&nx, info_idx, item_id, Some(equip))?,
+                );
+            }
+        }
+
+        Ok(Self { items })
+    }
+
+    pub fn from_templates(templates: impl IntoIterator<Item = ItemTemplate>) -> Self {
+        Self {
+            items: templates
+                .into_iter()
+                .map(|template| (template.item_id, template))
+                .collect(),
+        }
+    }
+
+    pub fn item(&self, item_id: i32) -> Option<&ItemTemplate> {
+        self.items.get(&item_id)
+    }
+
+    pub fn item_exists(&self, item_id: i32) -> bool {
+        self.items.contains_key(&item_id)
+    }
+}
+
+fn build_item_template(
+    nx: &NxMapFile,
+    info_idx: u32,
+    item_id: i32,
+    equip: Option<EquipTemplate>,
+) -> Result<ItemTemplate, GameDataError> {
+    let slot_max = if equip.is_some() {
+        1
+    } else {
+        crate::read_i16_child(nx, info_idx, "slotMax", i32::from(DEFAULT_SLOT_MAX))?
+    };
+
+    Ok(ItemTemplate {
+        item_id,
+        price: nx.int_child(info_idx, "price")?.unwrap_or(0),
+        unit_price: nx.real_child(info_idx, "unitPrice")?.unwrap_or(0.0),
+        slot_max,
+        cash: nx.int_child(info_idx, "cash")?.unwrap_or(0) != 0,
+        equip,
+    })
+}
+
+fn build_equip_template(nx: &NxMapFile, info_idx: u32) -> Result<EquipTemplate, GameDataError> {
+    let read = |key: &str| crate::read_i16_child(nx, info_idx, key, 0);
+
+    Ok(EquipTemplate {
+        req_level: read("reqLevel")?,
+        req_job: read("reqJob")?,
+        req_str: read("reqSTR")?,
+        req_dex: read("reqDEX")?,
+        req_int: read("reqINT")?,
+        req_luk: read("reqLUK")?,
+        upgrade_slots: read("tuc")?,
+        stre: read("incSTR")?,
+        dex: read("incDEX")?,
+        int: read("incINT")?,
+        luk: read("incLUK")?,
+        hp: read("incMHP")?,
+        mp: read("incMMP")?,
+        watk: read("incPAD")?,
+        matk: read("incMAD")?,
+        wdef: read("incPDD")?,
+        mdef: read("incMDD")?,
+        acc: read("incACC")?,
+        avoid: read("incEVA")?,
+        hands: read("incHANDS")?,
+        speed: read("incSpeed")?,
+        jump: read("incJump")?,
+    })
+}
+
+/// Character.nx also holds faces, hair and body parts; only ids in the equip
+/// range are items.
+fn parse_equip_id(img_name: &str) -> Option<i32> {
+    let item_id = crate::parse_img_id(img_name)?;
+    (item_id / 1_000_000 == 1).then_some(item_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_equip_id_skips_faces_hair_and_bodies() {
+        assert_eq!(parse_equip_id("01302000.img"), Some(1302000));
+        assert_eq!(parse_equip_id("00020000.img"), None);
+        assert_eq!(parse_equip_id("00030000.img"), None);
+        assert_eq!(parse_equip_id("00002000.img"), None);
+        assert_eq!(parse_equip_id("bow.img"), None);
+    }
+
+    #[test]
+    fn allows_job_checks_the_job_branch_bit() {
+        let anyone = EquipTemplate::default();
+        assert!(anyone.allows_job(0));
+        assert!(anyone.allows_job(412));
+
+        let warrior_or_thief = EquipTemplate {
+            req_job: 1 | 8,
+            ..EquipTemplate::default()
+        };
+        assert!(warrior_or_thief.allows_job(100));
+        assert!(warrior_or_thief.allows_job(412));
+        assert!(!warrior_or_thief.allows_job(0));
+        assert!(!warrior_or_thief.allows_job(230));
+        assert!(!warrior_or_thief.allows_job(500));
+    }
+}
@@ -6,6 +6,28 @@ use std::fs::File;
 use std::path::Path;
 use thiserror::Error;
 
+mod creation;
+mod drops;
+mod exp;
+mod items;
+mod life;
+mod quests;
+mod shops;
+mod skills;
+mod strings;
+
+pub use creation::{
+    CreationChoices, CreationData, CREATION_ARAN, CREATION_EXPLORER, CREATION_KNIGHT,
+};
+pub use drops::{DropData, MobDrop, DROP_CHANCE_SCALE, MESO_DROP_ITEM_ID};
+pub use exp::{exp_to_next_level, MAX_LEVEL};
+pub use items::{EquipTemplate, ItemData, ItemTemplate, DEFAULT_SLOT_MAX};
+pub use life::{MobData, MobSkill, MobTemplate, NpcData, NpcTemplate};
+pub use quests::{QuestData, QuestItem, QuestRequirements, QuestRewards, QuestTemplate};
+pub use shops::ShopData;
+pub use skills::{is_fourth_job_skill, SkillData, SkillLevel, SkillTemplate};
+pub use strings::{MapName, StringData};
+
 const NODE_SIZE_BYTES: u64 = 20;
 pub const NO_DESTINATION_MAP: i32 = 999_999_999;
 pub const PORTAL_TYPE_START_POINT: i32 = 0;
@@ -36,6 +58,83 @@ pub struct PortalTemplate {
     pub script: Option<String>,
 }
 
+impl PortalTemplate {
+    pub fn kind(&self) -> Option<PortalType> {
+        PortalType::from_id(self.portal_type)
+    }
+}
+
+/// What a portal does, from its `pt` in Map.nx. Hidden and collision portals
+/// only differ in when the client triggers them; the server treats them like
+/// the visible portal or script portal they otherwise are.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PortalType {
+    StartPoint,
+    Invisible,
+    Visible,
+    Collision,
+    Changeable,
+    ChangeableInvisible,
+    TownPortalPoint,
+    Script,
+    ScriptInvisible,
+    CollisionScript,
+    Hidden,
+    ScriptHidden,
+    CollisionVerticalJump,
+    CollisionCustomImpact,
+    CollisionCustomImpactOnKey,
+}
+
+impl PortalType {
+    pub fn from_id(id: i32) -> Option<Self> {
+        let kind = match id {
+            PORTAL_TYPE_START_POINT => PortalType::StartPoint,
+            1 => PortalType::Invisible,
+            2 => PortalType::Visible,
+            3 => PortalType::Collision,
+            4 => PortalType::Changeable,
+            5 => PortalType::ChangeableInvisible,
+            6 => PortalType::TownPortalPoint,
+            7 => PortalType::Script,
+            8 => PortalType::ScriptInvisible,
+            9 => PortalType::CollisionScript,
+            10 => PortalType::Hidden,
+            11 => PortalType::ScriptHidden,
+            12 => PortalType::CollisionVerticalJump,
+            13 => PortalType::CollisionCustomImpact,
+            14 => PortalType::CollisionCustomImpactOnKey,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Characters appear at these portals but can't leave through them.
+    pub fn is_spawn_point(self) -> bool {
+        matches!(self, PortalType::StartPoint | PortalType::TownPortalPoint)
+    }
+
+    /// Entering runs the portal's script instead of following `to_map`.
+    pub fn is_scripted(self) -> bool {
+        matches!(
+            self,
+            PortalType::Script
+                | PortalType::ScriptInvisible
+                | PortalType::CollisionScript
+                | PortalType::ScriptHidden
+        )
+    }
+
+    /// The server may close these portals or send them somewhere else while
+    /// the game runs.
+    pub fn is_changeable(self) -> bool {
+        matches!(
+            self,
+            PortalType::Changeable | PortalType::ChangeableInvisible
+        )
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct MapNpcTemplate {
     pub npc_id: i32,
@@ -47,12 +146,26 @@ pub struct MapNpcTemplate {
     pub rx1: i16,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MapMobTemplate {
+    pub mob_id: i32,
+    pub x: i16,
+    pub y: i16,
+    pub foothold: i16,
+    pub flip: bool,
+    pub rx0: i16,
+    pub rx1: i16,
+    /// Extra respawn delay in seconds, as authored in the map's `mobTime`.
+    pub mob_time: i32,
+}
+
 #[derive(Clone, Debug)]
 pub struct FieldTemplate {
     pub map_id: i32,
     pub return_map: Option<i32>,
     pub forced_return: Option<i32>,
     pub map_npcs: Vec<MapNpcTemplate>,
+    pub map_mobs: Vec<MapMobTemplate>,
     portals_by_id: BTreeMap<u32, PortalTemplate>,
     portal_ids_by_name: HashMap<String, Vec<u32>>,
 }
@@ -72,6 +185,12 @@ impl FieldTemplate {
         self.portals_by_id.get(id)
     }
 
+    /// Map a character who dies here respawns in. Maps without a return map
+    /// respawn characters in place.
+    pub fn respawn_map_id(&self) -> i32 {
+        self.return_map.unwrap_or(self.map_id)
+    }
+
     pub fn resolve_spawn_portal(&self, preferred_name: &str) -> Option<&PortalTemplate> {
         if !preferred_name.trim().is_empty() {
             if let Some(portal) = self.portal_by_name(preferred_name) {
@@ -115,7 +234,7 @@ impl GameData {
 
             for map_node_idx in nx.child_indices(map_group_idx)? {
                 let map_node_name = nx.node_name(map_node_idx)?;
-                let Some(map_id) = parse_map_id(map_node_name.as_str()) else {
+                let Some(map_id) = parse_img_id(map_node_name.as_str()) else {
                     continue;
                 };
 
@@ -151,7 +270,7 @@ fn build_field_template(
             (None, None)
         };
 
-    let map_npcs = build_map_npcs_from_life(nx, map_node_idx)?;
+    let (map_npcs, map_mobs) = build_map_life(nx, map_node_idx)?;
 
     let mut portals_by_id = BTreeMap::new();
 
@@ -205,31 +324,33 @@ fn build_field_template(
         return_map,
         forced_return,
         map_npcs,
+        map_mobs,
         portals_by_id,
         portal_ids_by_name,
     })
 }
 
-fn build_map_npcs_from_life(
+fn build_map_life(
     nx: &NxMapFile,
     map_node_idx: u32,
-) -> Result<Vec<MapNpcTemplate>, GameDataError> {
+) -> Result<(Vec<MapNpcTemplate>, Vec<MapMobTemplate>), GameDataError> {
     let Some(life_root_idx) = nx.child_by_name(map_node_idx, "life")? else {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), Vec::new()));
     };
 
     let mut map_npcs = Vec::new();
+    let mut map_mobs = Vec::new();
 
     for life_node_idx in nx.child_indices(life_root_idx)? {
         let Some(life_type) = nx.string_child(life_node_idx, "type")? else {
             continue;
         };
 
-        if life_type != "n" {
+        if life_type != "n" && life_type != "m" {
             continue;
         }
 
-        let npc_id = parse_string_id(nx.string_child(life_node_idx, "id")?, "npc id")?;
+        let life_id = parse_string_id(nx.string_child(life_node_idx, "id")?, "life id")?;
         let x = read_i16_child(nx, life_node_idx, "x", 0)?;
         let y = read_i16_child(nx, life_node_idx, "y", 0)?;
         let foothold = read_i16_child(nx, life_node_idx, "fh", 0)?;
@@ -237,22 +358,36 @@ fn build_map_npcs_from_life(
         let rx0 = read_i16_child(nx, life_node_idx, "rx0", x as i32)?;
         let rx1 = read_i16_child(nx, life_node_idx, "rx1", x as i32)?;
 
-        map_npcs.push(MapNpcTemplate {
-            npc_id,
-            x,
-            y,
-            foothold,
-            flip: !face_left,
-            rx0,
-            rx1,
-        });
+        if life_type == "n" {
+            map_npcs.push(MapNpcTemplate {
+                npc_id: life_id,
+                x,
+                y,
+                foothold,
+                flip: !face_left,
+                rx0,
+                rx1,
+            });
+        } else {
+            map_mobs.push(MapMobTemplate {
+                mob_id: life_id,
+                x,
+                y,
+                foothold,
+                flip: !face_left,
+                rx0,
+                rx1,
+                mob_time: nx.int_child(life_node_idx, "mobTime")?.unwrap_or(0),
+            });
+        }
     }
 
-    Ok(map_npcs)
+    Ok((map_npcs, map_mobs))
 }
 
-fn parse_map_id(map_node_name: &str) -> Option<i32> {
-    map_node_name
+/// Maps, NPCs, mobs and equips are each stored as an `<id>.img` node.
+fn parse_img_id(node_name: &str) -> Option<i32> {
+    node_name
         .strip_suffix(".img")
         .and_then(|id| id.parse::<i32>().ok())
 }
@@ -424,17 +559,38 @@ impl NxMapFile {
             return Ok(None);
         };
 
-        let node = self.node_at(child_index)?;
+        self.int_value(child_index)
+    }
+
+    fn int_value(&self, index: u32) -> Result<Option<i32>, GameDataError> {
+        let node = self.node_at(index)?;
         if node.data_type != 1 {
             return Ok(None);
         }
 
         let signed = i64::from_le_bytes(node.data.to_le_bytes());
         i32::try_from(signed).map(Some).map_err(|_| {
-            GameDataError::InvalidData(format!("integer '{key}' out of range: {signed}"))
+            GameDataError::InvalidData(format!(
+                "integer '{}' out of range: {signed}",
+                self.node_name(index).unwrap_or_default()
+            ))
         })
     }
 
+    /// A real child, or an integer one read as real.
+    fn real_child(&self, parent_index: u32, key: &str) -> Result<Option<f64>, GameDataError> {
+        let Some(child_index) = self.child_by_name(parent_index, key)? else {
+            return Ok(None);
+        };
+
+        let node = self.node_at(child_index)?;
+        match node.data_type {
+            1 => Ok(Some(i64::from_le_bytes(node.data.to_le_bytes()) as f64)),
+            2 => Ok(Some(f64::from_bits(node.data))),
+            _ => Ok(None),
+        }
+    }
+
     fn string_child(&self, parent_index: u32, key: &str) -> Result<Option<String>, GameDataError> {
         let Some(child_index) = self.child_by_name(parent_index, key)? else {
             return Ok(None);
@@ -487,6 +643,7 @@ mod tests {
             return_map: None,
             forced_return: None,
             map_npcs: Vec::new(),
+            map_mobs: Vec::new(),
             portals_by_id: BTreeMap::from([
                 (
                     0,
@@ -534,6 +691,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn portal_types_follow_their_map_nx_ids() {
+        assert_eq!(PortalType::from_id(0), Some(PortalType::StartPoint));
+        assert_eq!(PortalType::from_id(9), Some(PortalType::CollisionScript));
+        assert_eq!(PortalType::from_id(15), None);
+
+        let scripted: Vec<i32> = (0..15)
+            .filter(|&id| PortalType::from_id(id).unwrap().is_scripted())
+            .collect();
+        assert_eq!(scripted, [7, 8, 9, 11]);
+        let changeable: Vec<i32> = (0..15)
+            .filter(|&id| PortalType::from_id(id).unwrap().is_changeable())
+            .collect();
+        assert_eq!(changeable, [4, 5]);
+        assert!(PortalType::TownPortalPoint.is_spawn_point());
+        assert!(!PortalType::Hidden.is_spawn_point());
+    }
+
+    #[test]
+    fn respawn_map_id_falls_back_to_the_field_itself() {
+        let mut field = FieldTemplate {
+            map_id: 104000100,
+            return_map: Some(104000000),
+            forced_return: None,
+            map_npcs: Vec::new(),
+            map_mobs: Vec::new(),
+            portals_by_id: BTreeMap::new(),
+            portal_ids_by_name: HashMap::new(),
+        };
+        assert_eq!(field.respawn_map_id(), 104000000);
+
+        field.return_map = None;
+        assert_eq!(field.respawn_map_id(), 104000100);
+    }
+
     #[test]
     fn loads_map_npcs_from_assets_map_nx() {
         let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/game-data/Map.nx");
@@ -549,4 +741,83 @@ mod tests {
                 && npc.rx1 >= npc.x
         }));
     }
+
+    #[test]
+    fn loads_map_mobs_from_assets_map_nx() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/game-data/Map.nx");
+        let game_data = GameData::load_from_nx_map(&path).expect("load map nx");
+        let field = game_data.field(100_000_000).expect("field 100000000");
+        assert!(field.map_mobs.is_empty());
+
+        let field = game_data.field(104_040_000).expect("field 104040000");
+        assert!(!field.map_mobs.is_empty());
+    }
+
+    #[test]
+    fn loads_npc_and_mob_templates_from_assets_nx() {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/game-data");
+        let npcs = NpcData::load_from_nx(assets.join("Npc.nx")).expect("load npc nx");
+        let mobs = MobData::load_from_nx(assets.join("Mob.nx")).expect("load mob nx");
+
+        assert!(npcs.npc(2101).is_some());
+
+        let snail = mobs.mob(100_100).expect("snail");
+        assert_eq!(snail.level, 1);
+        assert!(snail.max_hp > 0 && snail.exp > 0);
+        assert!(!snail.boss);
+    }
+
+    #[test]
+    fn loads_names_from_assets_string_nx() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/game-data/String.nx");
+        let strings = StringData::load_from_nx(&path).expect("load string nx");
+
+        assert_eq!(strings.map_name(100_000_000), Some("Henesys"));
+        assert!(strings.find_maps("henesys").contains(&100_000_000));
+        assert_eq!(strings.mob_name(100_100), Some("Snail"));
+        assert!(strings.item_name(2_000_000).is_some());
+        assert!(strings.npc_name(2101).is_some());
+    }
+
+    #[test]
+    fn loads_item_templates_from_assets_nx() {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/game-data");
+        let item_data = ItemData::load_from_nx(assets.join("Item.nx"), assets.join("Character.nx"))
+            .expect("load item nx");
+
+        let potion = item_data.item(2_000_000).expect("red potion");
+        assert!(potion.equip.is_none());
+        assert!(potion.slot_max > 1);
+
+        let sword = item_data.item(1_302_000).expect("sword");
+        assert_eq!(sword.slot_max, 1);
+        assert!(sword.equip.as_ref().expect("equip template").watk > 0);
+        assert!(!item_data.item_exists(20_000));
+    }
+
+    #[test]
+    fn loads_skill_templates_from_assets_nx() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/game-data/Skill.nx");
+        let skill_data = SkillData::load_from_nx(&path).expect("load skill nx");
+
+        let power_strike = skill_data.skill(1_001_004).expect("power strike");
+        assert_eq!(power_strike.max_level(), 20);
+        assert!(power_strike.level(1).expect("level 1").mp_cost > 0);
+        assert!(!power_strike.is_fourth_job());
+        assert!(skill_data
+            .skill(1_121_008)
+            .expect("brandish")
+            .is_fourth_job());
+    }
+
+    #[test]
+    fn loads_quest_templates_from_assets_nx() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/game-data/Quest.nx");
+        let quest_data = QuestData::load_from_nx(&path).expect("load quest nx");
+
+        // Roger's Apple, the first quest on Maple Island.
+        let apple = quest_data.quest(1021).expect("roger's apple");
+        assert_eq!(apple.start.npc, Some(2000));
+        assert!(apple.complete_rewards.exp > 0);
+    }
 }
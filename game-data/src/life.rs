@@ -0,0 +1,191 @@
+use crate::{GameDataError, NxMapFile};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NpcTemplate {
+    pub npc_id: i32,
+    /// Conversation script named in the NPC's `info/script`.
+    pub script: Option<String>,
+    /// NPCs flagged with `info/shop` open a shop instead of a conversation.
+    pub shop: bool,
+    /// Storage keepers charge `info/trunkPut` mesos to store an item and
+    /// `info/trunkGet` to take one out. Other NPCs have neither.
+    pub trunk_put: Option<i32>,
+    pub trunk_get: Option<i32>,
+}
+
+impl NpcTemplate {
+    pub fn is_storage_keeper(&self) -> bool {
+        self.trunk_put.is_some() || self.trunk_get.is_some()
+    }
+}
+
+/// A skill a mob can use, as listed under its `info/skill`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MobSkill {
+    pub skill_id: i32,
+    pub level: i32,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MobTemplate {
+    pub mob_id: i32,
+    pub level: i32,
+    pub max_hp: i32,
+    pub max_mp: i32,
+    pub exp: i32,
+    pub watk: i32,
+    pub matk: i32,
+    pub wdef: i32,
+    pub mdef: i32,
+    pub acc: i32,
+    pub avoid: i32,
+    pub speed: i32,
+    pub boss: bool,
+    pub undead: bool,
+    /// Whether touching the mob hurts.
+    pub body_attack: bool,
+    pub skills: Vec<MobSkill>,
+    /// Mobs spawned in this one's place when it dies.
+    pub revives: Vec<i32>,
+}
+
+/// NPC templates from Npc.nx, keyed by NPC id.
+#[derive(Debug, Default)]
+pub struct NpcData {
+    npcs: BTreeMap<i32, NpcTemplate>,
+}
+
+impl NpcData {
+    pub fn load_from_nx(path: impl AsRef<Path>) -> Result<Self, GameDataError> {
+        Ok(Self {
+            npcs: load_npc_templates(path.as_ref())?,
+        })
+    }
+
+    pub fn npc(&self, npc_id: i32) -> Option<&NpcTemplate> {
+        self.npcs.get(&npc_id)
+    }
+}
+
+/// Mob templates from Mob.nx, keyed by mob id.
+#[derive(Debug, Default)]
+pub struct MobData {
+    mobs: BTreeMap<i32, MobTemplate>,
+}
+
+impl MobData {
+    pub fn load_from_nx(path: impl AsRef<Path>) -> Result<Self, GameDataError> {
+        Ok(Self {
+            mobs: load_mob_templates(path.as_ref())?,
+        })
+    }
+
+    pub fn mob(&self, mob_id: i32) -> Option<&MobTemplate> {
+        self.mobs.get(&mob_id)
+    }
+}
+
+fn load_npc_templates(path: &Path) -> Result<BTreeMap<i32, NpcTemplate>, GameDataError> {
+    let nx = NxMapFile::open(path)?;
+    let mut npcs = BTreeMap::new();
+
+    for img_idx in nx.child_indices(0)? {
+        let Some(npc_id) = crate::parse_img_id(nx.node_name(img_idx)?.as_str()) else {
+            continue;
+        };
+
+        let mut npc = NpcTemplate {
+            npc_id,
+            ..NpcTemplate::default()
+        };
+        if let Some(info_idx) = nx.child_by_name(img_idx, "info")? {
+            npc.shop = nx.int_child(info_idx, "shop")?.unwrap_or(0) != 0;
+            npc.trunk_put = nx.int_child(info_idx, "trunkPut")?;
+            npc.trunk_get = nx.int_child(info_idx, "trunkGet")?;
+            if let Some(script_idx) = nx.child_by_name(info_idx, "script")? {
+                for entry_idx in nx.child_indices(script_idx)? {
+                    npc.script =
+                        crate::normalize_optional_string(nx.string_child(entry_idx, "script")?);
+                    if npc.script.is_some() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        npcs.insert(npc_id, npc);
+    }
+
+    Ok(npcs)
+}
+
+fn load_mob_templates(path: &Path) -> Result<BTreeMap<i32, MobTemplate>, GameDataError> {
+    let nx = NxMapFile::open(path)?;
+    let mut mobs = BTreeMap::new();
+
+    for img_idx in nx.child_indices(0)? {
+        let Some(mob_id) = crate::parse_img_id(nx.node_name(img_idx)?.as_str()) else {
+            continue;
+        };
+        let Some(info_idx) = nx.child_by_name(img_idx, "info")? else {
+            continue;
+        };
+
+        mobs.insert(mob_id, build_mob_template(&nx, info_idx, mob_id)?);
+    }
+
+    Ok(mobs)
+}
+
+fn build_mob_template(
+    nx: &NxMapFile,
+    info_idx: u32,
+    mob_id: i32,
+) -> Result<MobTemplate, GameDataError> {
+    let read =
+        |key: &str| -> Result<i32, GameDataError> { Ok(nx.int_child(info_idx, key)?.unwrap_or(0)) };
+
+    let mut skills = Vec::new();
+    if let Some(skill_root_idx) = nx.child_by_name(info_idx, "skill")? {
+        for skill_idx in nx.child_indices(skill_root_idx)? {
+            let Some(skill_id) = nx.int_child(skill_idx, "skill")? else {
+                continue;
+            };
+            skills.push(MobSkill {
+                skill_id,
+                level: nx.int_child(skill_idx, "level")?.unwrap_or(1),
+            });
+        }
+    }
+
+    let mut revives = Vec::new();
+    if let Some(revive_root_idx) = nx.child_by_name(info_idx, "revive")? {
+        for revive_idx in nx.child_indices(revive_root_idx)? {
+            if let Some(revive_id) = nx.int_value(revive_idx)? {
+                revives.push(revive_id);
+            }
+        }
+    }
+
+    Ok(MobTemplate {
+        mob_id,
+        level: read("level")?,
+        max_hp: read("maxHP")?,
+        max_mp: read("maxMP")?,
+        exp: read("exp")?,
+        watk: read("PADamage")?,
+        matk: read("MADamage")?,
+        wdef: read("PDDamage")?,
+        mdef: read("MDDamage")?,
+        acc: read("acc")?,
+        avoid: read("eva")?,
+        speed: read("speed")?,
+        boss: read("boss")? != 0,
+        undead: read("undead")? != 0,
+        body_attack: read("bodyAttack")? != 0,
+        skills,
+        revives,
+    })
+}
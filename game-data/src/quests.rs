@@ -0,0 +1,266 @@
+use crate::{GameDataError, NxMapFile};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// What a character needs before it can start or complete a quest, from
+/// `Check.img/<quest id>/0` and `/1`. Empty lists and `None` ask for nothing.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct QuestRequirements {
+    /// The NPC the character has to talk to.
+    pub npc: Option<i32>,
+    pub min_level: Option<i16>,
+    pub max_level: Option<i16>,
+    pub jobs: Vec<i16>,
+    /// Other quests and the state they must be in: 0 for not started, 1 for
+    /// started and 2 for completed.
+    pub quests: Vec<(i32, u8)>,
+    /// Items and how many of each the character must carry.
+    pub items: Vec<(i32, i32)>,
+    /// Mobs and how many of each the character must kill while the quest is
+    /// started, in the order the client counts them.
+    pub mobs: Vec<(i32, i32)>,
+    /// Mesos the quest costs, from `endmeso`.
+    pub meso: i32,
+}
+
+/// An item a quest gives, or takes for a negative count.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct QuestItem {
+    pub item_id: i32,
+    pub count: i32,
+    /// Weight among the quest's random items. Zero for items that are always
+    /// given, and negative for items the player picks from a list.
+    pub prop: i32,
+    /// 0 for male characters only, 1 for female characters only, and 2 for
+    /// both.
+    pub gender: i16,
+}
+
+/// What starting or completing a quest does, from `Act.img/<quest id>/0` and
+/// `/1`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct QuestRewards {
+    pub exp: i32,
+    /// Mesos given, or taken for a negative amount.
+    pub meso: i32,
+    pub fame: i32,
+    pub items: Vec<QuestItem>,
+    /// The quest the client moves on to once this one is completed.
+    pub next_quest: Option<i32>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct QuestTemplate {
+    pub quest_id: i32,
+    pub name: String,
+    pub start: QuestRequirements,
+    pub complete: QuestRequirements,
+    pub start_rewards: QuestRewards,
+    pub complete_rewards: QuestRewards,
+}
+
+/// Quest templates, keyed by quest id.
+#[derive(Debug, Default)]
+pub struct QuestData {
+    quests: HashMap<i32, QuestTemplate>,
+}
+
+impl QuestData {
+    /// Load quest templates from QuestInfo.img, Check.img and Act.img in
+    /// Quest.nx.
+    pub fn load_from_nx(path: impl AsRef<Path>) -> Result<Self, GameDataError> {
+        let nx = NxMapFile::open(path.as_ref())?;
+        let mut quests: HashMap<i32, QuestTemplate> = HashMap::new();
+
+        if let Some(info_idx) = nx.child_by_name(0, "QuestInfo.img")? {
+            for (quest_id, quest_idx) in numbered_children(&nx, info_idx)? {
+                quest_entry(&mut quests, quest_id).name =
+                    nx.string_child(quest_idx, "name")?.unwrap_or_default();
+            }
+        }
+
+        if let Some(check_idx) = nx.child_by_name(0, "Check.img")? {
+            for (quest_id, quest_idx) in numbered_children(&nx, check_idx)? {
+                let template = quest_entry(&mut quests, quest_id);
+                if let Some(start_idx) = nx.child_by_name(quest_idx, "0")? {
+                    template.start = build_requirements(&nx, start_idx)?;
+                }
+                if let Some(complete_idx) = nx.child_by_name(quest_idx, "1")? {
+                    template.complete = build_requirements(&nx, complete_idx)?;
+                }
+            }
+        }
+
+        if let Some(act_idx) = nx.child_by_name(0, "Act.img")? {
+            for (quest_id, quest_idx) in numbered_children(&nx, act_idx)? {
+                let template = quest_entry(&mut quests, quest_id);
+                if let Some(start_idx) = nx.child_by_name(quest_idx, "0")? {
+                    template.start_rewards = build_rewards(&nx, start_idx)?;
+                }
+                if let Some(complete_idx) = nx.child_by_name(quest_idx, "1")? {
+                    template.complete_rewards = build_rewards(&nx, complete_idx)?;
+                }
+            }
+        }
+
+        Ok(Self { quests })
+    }
+
+    pub fn from_templates(templates: impl IntoIterator<Item = QuestTemplate>) -> Self {
+        Self {
+            quests: templates
+                .into_iter()
+                .map(|template| (template.quest_id, template))
+                .collect(),
+        }
+    }
+
+    pub fn quest(&self, quest_id: i32) -> Option<&QuestTemplate> {
+        self.quests.get(&quest_id)
+    }
+
+    /// Quests that need kills of `mob_id` to be completed, in no particular
+    /// order.
+    pub fn quests_killing(&self, mob_id: i32) -> impl Iterator<Item = &QuestTemplate> {
+        self.quests.values().filter(move |template| {
+            template
+                .complete
+                .mobs
+                .iter()
+                .any(|&(required, _)| required == mob_id)
+        })
+    }
+}
+
+fn quest_entry(quests: &mut HashMap<i32, QuestTemplate>, quest_id: i32) -> &mut QuestTemplate {
+    quests.entry(quest_id).or_insert_with(|| QuestTemplate {
+        quest_id,
+        ..QuestTemplate::default()
+    })
+}
+
+/// Children named by number, in numeric order. Quest.nx lists requirements
+/// and rewards as children `0`, `1`, ..., which the archive sorts as text.
+fn numbered_children(nx: &NxMapFile, parent_idx: u32) -> Result<Vec<(i32, u32)>, GameDataError> {
+    let mut numbered = Vec::new();
+    for child_idx in nx.child_indices(parent_idx)? {
+        if let Ok(number) = nx.node_name(child_idx)?.parse::<i32>() {
+            numbered.push((number, child_idx));
+        }
+    }
+    numbered.sort_by_key(|(number, _)| *number);
+    Ok(numbered)
+}
+
+/// The `id` and `count` children of each numbered child of `key`.
+fn id_counts(nx: &NxMapFile, parent_idx: u32, key: &str) -> Result<Vec<(i32, i32)>, GameDataError> {
+    let Some(list_idx) = nx.child_by_name(parent_idx, key)? else {
+        return Ok(Vec::new());
+    };
+    let mut entries = Vec::new();
+    for (_, entry_idx) in numbered_children(nx, list_idx)? {
+        let Some(id) = nx.int_child(entry_idx, "id")? else {
+            continue;
+        };
+        entries.push((id, nx.int_child(entry_idx, "count")?.unwrap_or(0)));
+    }
+    Ok(entries)
+}
+
+fn build_requirements(nx: &NxMapFile, idx: u32) -> Result<QuestRequirements, GameDataError> {
+    let level = |key: &str| -> Result<Option<i16>, GameDataError> {
+        Ok(nx
+            .int_child(idx, key)?
+            .map(|level| i16::try_from(level).unwrap_or(i16::MAX)))
+    };
+
+    let mut jobs = Vec::new();
+    if let Some(job_idx) = nx.child_by_name(idx, "job")? {
+        for (_, entry_idx) in numbered_children(nx, job_idx)? {
+            if let Some(job) = nx.int_value(entry_idx)? {
+                jobs.push(i16::try_from(job).unwrap_or(-1));
+            }
+        }
+    }
+
+    let mut quests = Vec::new();
+    if let Some(quest_idx) = nx.child_by_name(idx, "quest")? {
+        for (_, entry_idx) in numbered_children(nx, quest_idx)? {
+            let Some(quest_id) = nx.int_child(entry_idx, "id")? else {
+                continue;
+            };
+            let state = nx.int_child(entry_idx, "state")?.unwrap_or(0);
+            quests.push((quest_id, u8::try_from(state).unwrap_or(0)));
+        }
+    }
+
+    Ok(QuestRequirements {
+        npc: nx.int_child(idx, "npc")?,
+        min_level: level("lvmin")?,
+        max_level: level("lvmax")?,
+        jobs,
+        quests,
+        items: id_counts(nx, idx, "item")?,
+        mobs: id_counts(nx, idx, "mob")?,
+        meso: nx.int_child(idx, "endmeso")?.unwrap_or(0),
+    })
+}
+
+fn build_rewards(nx: &NxMapFile, idx: u32) -> Result<QuestRewards, GameDataError> {
+    let mut items = Vec::new();
+    if let Some(item_idx) = nx.child_by_name(idx, "item")? {
+        for (_, entry_idx) in numbered_children(nx, item_idx)? {
+            let Some(item_id) = nx.int_child(entry_idx, "id")? else {
+                continue;
+            };
+            items.push(QuestItem {
+                item_id,
+                count: nx.int_child(entry_idx, "count")?.unwrap_or(1),
+                prop: nx.int_child(entry_idx, "prop")?.unwrap_or(0),
+                gender: i16::try_from(nx.int_child(entry_idx, "gender")?.unwrap_or(2)).unwrap_or(2),
+            });
+        }
+    }
+
+    Ok(QuestRewards {
+        exp: nx.int_child(idx, "exp")?.unwrap_or(0),
+        meso: nx.int_child(idx, "money")?.unwrap_or(0),
+        fame: nx.int_child(idx, "pop")?.unwrap_or(0),
+        items,
+        next_quest: nx.int_child(idx, "nextQuest")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quests_killing_only_lists_quests_counting_the_mob() {
+        let snail_quest = QuestTemplate {
+            quest_id: 1000,
+            complete: QuestRequirements {
+                mobs: vec![(100_100, 10), (100_101, 5)],
+                ..QuestRequirements::default()
+            },
+            ..QuestTemplate::default()
+        };
+        let delivery = QuestTemplate {
+            quest_id: 1001,
+            start: QuestRequirements {
+                mobs: vec![(100_100, 1)],
+                ..QuestRequirements::default()
+            },
+            ..QuestTemplate::default()
+        };
+        let data = QuestData::from_templates([snail_quest, delivery]);
+
+        let quest_ids: Vec<_> = data
+            .quests_killing(100_101)
+            .map(|template| template.quest_id)
+            .collect();
+        assert_eq!(quest_ids, vec![1000]);
+        assert_eq!(data.quests_killing(100_100).count(), 1);
+        assert_eq!(data.quests_killing(9_300_000).count(), 0);
+    }
+}
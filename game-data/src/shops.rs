@@ -0,0 +1,84 @@
+use crate::GameDataError;
+use std::collections::HashMap;
+use std::path::Path;
+
+const BUNDLED_NPC_SHOPS: &str = include_str!("../data/npc_shops.tsv");
+
+/// What each NPC shop sells. Like drop tables, shops are server data, so they
+/// are kept as a tab-separated file. Prices come from the item templates.
+#[derive(Debug, Default)]
+pub struct ShopData {
+    shops: HashMap<i32, Vec<i32>>,
+}
+
+impl ShopData {
+    /// Shops shipped with the server in `game-data/data/npc_shops.tsv`.
+    pub fn bundled() -> Result<Self, GameDataError> {
+        Self::parse(BUNDLED_NPC_SHOPS)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GameDataError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(source: &str) -> Result<Self, GameDataError> {
+        let mut shops: HashMap<i32, Vec<i32>> = HashMap::new();
+
+        for (line_idx, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let [npc_id, item_id] = columns[..] else {
+                return Err(invalid_line(line_idx, "expected 2 columns"));
+            };
+
+            shops
+                .entry(parse_column(line_idx, npc_id)?)
+                .or_default()
+                .push(parse_column(line_idx, item_id)?);
+        }
+
+        Ok(Self { shops })
+    }
+
+    /// The item ids `npc_id` sells, in shop order, or `None` if it has no
+    /// shop.
+    pub fn shop(&self, npc_id: i32) -> Option<&[i32]> {
+        self.shops.get(&npc_id).map(Vec::as_slice)
+    }
+}
+
+fn parse_column(line_idx: usize, value: &str) -> Result<i32, GameDataError> {
+    value
+        .parse()
+        .map_err(|_| invalid_line(line_idx, &format!("invalid number '{value}'")))
+}
+
+fn invalid_line(line_idx: usize, reason: &str) -> GameDataError {
+    GameDataError::InvalidData(format!("shop line {}: {reason}", line_idx + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_shop_items_in_order() {
+        let data = ShopData::parse("# comment\n1011100\t2000001\n1011100\t2000000\n\n")
+            .expect("parse shops");
+
+        assert_eq!(data.shop(1011100), Some(&[2000001, 2000000][..]));
+        assert_eq!(data.shop(1011000), None);
+        assert!(ShopData::parse("1011100\n").is_err());
+        assert!(ShopData::parse("1011100\tpotion\n").is_err());
+    }
+
+    #[test]
+    fn bundled_shops_parse() {
+        let data = ShopData::bundled().expect("bundled shops");
+        assert!(data.shop(1011100).is_some());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{GameDataError, NxMapFile};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// What a skill does at one of its levels, from `skill/<id>/level/<n>`.
+/// Values a level does not set are zero.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SkillLevel {
+    pub mp_cost: i32,
+    pub hp_cost: i32,
+    /// Seconds before the skill can be used again.
+    pub cooldown: i32,
+    /// Seconds a buff from the skill lasts.
+    pub duration: i32,
+    pub damage: i32,
+    pub attack_count: i32,
+    pub mob_count: i32,
+    /// Chance of the skill's effect, in percent.
+    pub prop: i32,
+    pub x: i32,
+    pub y: i32,
+    pub watk: i32,
+    pub wdef: i32,
+    pub matk: i32,
+    pub mdef: i32,
+    pub acc: i32,
+    pub avoid: i32,
+    pub speed: i32,
+    pub jump: i32,
+    pub hp: i32,
+    pub mp: i32,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SkillTemplate {
+    pub skill_id: i32,
+    /// Level 1 first.
+    pub levels: Vec<SkillLevel>,
+    /// Skills and the levels they must be at before this one can be learned.
+    pub requirements: Vec<(i32, i16)>,
+    /// Skills the client hides from the skill window.
+    pub invisible: bool,
+    /// Master level a fourth job skill starts with on advancement, from
+    /// `masterLevel`. Zero for skills that need a mastery book.
+    pub master_level: i16,
+}
+
+impl SkillTemplate {
+    /// The job whose skill tree the skill is in.
+    pub fn job(&self) -> i16 {
+        (self.skill_id / 10_000) as i16
+    }
+
+    /// Fourth job skills can only be raised up to a master level, which
+    /// mastery books unlock.
+    pub fn is_fourth_job(&self) -> bool {
+        is_fourth_job_skill(self.skill_id)
+    }
+
+    pub fn max_level(&self) -> i16 {
+        self.levels.len() as i16
+    }
+
+    /// The skill at `level`, starting from 1.
+    pub fn level(&self, level: i16) -> Option<&SkillLevel> {
+        self.levels
+            .get(usize::try_from(level).ok()?.checked_sub(1)?)
+    }
+}
+
+/// Whether the skill belongs to a fourth job, which the client tells by the
+/// last digit of the job.
+pub fn is_fourth_job_skill(skill_id: i32) -> bool {
+    skill_id / 10_000 % 10 == 2
+}
+
+/// Skill templates, keyed by skill id.
+#[derive(Debug, Default)]
+pub struct SkillData {
+    skills: HashMap<i32, SkillTemplate>,
+}
+
+impl SkillData {
+    /// Load skill templates from every job image in Skill.nx.
+    pub fn load_from_nx(path: impl AsRef<Path>) -> Result<Self, GameDataError> {
+        let nx = NxMapFile::open(path.as_ref())?;
+        let mut skills = HashMap::new();
+
+        for img_idx in nx.child_indices(0)? {
+            // Job images are named after the job; MobSkill.img and the like
+            // are not player skills.
+            if crate::parse_img_id(nx.node_name(img_idx)?.as_str()).is_none() {
+                continue;
+            }
+            let Some(skill_root_idx) = nx.child_by_name(img_idx, "skill")? else {
+                continue;
+            };
+
+            for skill_idx in nx.child_indices(skill_root_idx)? {
+                let Ok(skill_id) = nx.node_name(skill_idx)?.parse::<i32>() else {
+                    continue;
+                };
+                skills.insert(skill_id, build_skill_template(&nx, skill_idx, skill_id)?);
+            }
+        }
+
+        Ok(Self { skills })
+    }
+
+    pub fn from_templates(templates: impl IntoIterator<Item = SkillTemplate>) -> Self {
+        Self {
+            skills: templates
+                .into_iter()
+                .map(|template| (template.skill_id, template))
+                .collect(),
+        }
+    }
+
+    pub fn skill(&self, skill_id: i32) -> Option<&SkillTemplate> {
+        self.skills.get(&skill_id)
+    }
+
+    /// Every skill in `job`'s skill tree, in no particular order.
+    pub fn job_skills(&self, job: i16) -> impl Iterator<Item = &SkillTemplate> {
+        self.skills
+            .values()
+            .filter(move |template| template.job() == job)
+    }
+}
+
+fn build_skill_template(
+    nx: &NxMapFile,
+    skill_idx: u32,
+    skill_id: i32,
+) -> Result<SkillTemplate, GameDataError> {
+    let mut levels = Vec::new();
+    if let Some(level_root_idx) = nx.child_by_name(skill_idx, "level")? {
+        let mut numbered = Vec::new();
+        for level_idx in nx.child_indices(level_root_idx)? {
+            if let Ok(level) = nx.node_name(level_idx)?.parse::<i32>() {
+                numbered.push((level, build_skill_level(nx, level_idx)?));
+            }
+        }
+        numbered.sort_by_key(|(level, _)| *level);
+        levels = numbered.into_iter().map(|(_, level)| level).collect();
+    }
+
+    let mut requirements = Vec::new();
+    if let Some(req_root_idx) = nx.child_by_name(skill_idx, "req")? {
+        for req_idx in nx.child_indices(req_root_idx)? {
+            let Ok(required_id) = nx.node_name(req_idx)?.parse::<i32>() else {
+                continue;
+            };
+            let level = nx.int_value(req_idx)?.unwrap_or(0);
+            requirements.push((required_id, i16::try_from(level).unwrap_or(i16::MAX)));
+        }
+    }
+
+    Ok(SkillTemplate {
+        skill_id,
+        levels,
+        requirements,
+        invisible: nx.int_child(skill_idx, "invisible")?.unwrap_or(0) != 0,
+        master_level: i16::try_from(nx.int_child(skill_idx, "masterLevel")?.unwrap_or(0))
+            .unwrap_or(0),
+    })
+}
+
+fn build_skill_level(nx: &NxMapFile, level_idx: u32) -> Result<SkillLevel, GameDataError> {
+    let read = |key: &str| -> Result<i32, GameDataError> {
+        Ok(nx.int_child(level_idx, key)?.unwrap_or(0))
+    };
+
+    Ok(SkillLevel {
+        mp_cost: read("mpCon")?,
+        hp_cost: read("hpCon")?,
+        cooldown: read("cooltime")?,
+        duration: read("time")?,
+        damage: read("damage")?,
+        attack_count: read("attackCount")?,
+        mob_count: read("mobCount")?,
+        prop: read("prop")?,
+        x: read("x")?,
+        y: read("y")?,
+        watk: read("pad")?,
+        wdef: read("pdd")?,
+        matk: read("mad")?,
+        mdef: read("mdd")?,
+        acc: read("acc")?,
+        avoid: read("eva")?,
+        speed: read("speed")?,
+        jump: read("jump")?,
+        hp: read("hp")?,
+        mp: read("mp")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_is_one_based() {
+        let template = SkillTemplate {
+            skill_id: 1001004,
+            levels: vec![
+                SkillLevel {
+                    mp_cost: 8,
+                    ..SkillLevel::default()
+                },
+                SkillLevel {
+                    mp_cost: 10,
+                    ..SkillLevel::default()
+                },
+            ],
+            ..SkillTemplate::default()
+        };
+
+        assert_eq!(template.job(), 100);
+        assert_eq!(template.max_level(), 2);
+        assert_eq!(template.level(1).map(|level| level.mp_cost), Some(8));
+        assert_eq!(template.level(2).map(|level| level.mp_cost), Some(10));
+        assert!(template.level(0).is_none());
+        assert!(template.level(3).is_none());
+        assert!(!template.is_fourth_job());
+    }
+
+    #[test]
+    fn fourth_job_skills_are_told_by_job() {
+        assert!(is_fourth_job_skill(1121008));
+        assert!(is_fourth_job_skill(4221001));
+        assert!(!is_fourth_job_skill(1111002));
+        assert!(!is_fourth_job_skill(1000));
+    }
+
+    #[test]
+    fn job_skills_only_lists_the_job_tree() {
+        let data =
+            SkillData::from_templates([1001004, 1101006, 1121008].map(|skill_id| SkillTemplate {
+                skill_id,
+                ..SkillTemplate::default()
+            }));
+
+        let skill_ids: Vec<_> = data
+            .job_skills(112)
+            .map(|template| template.skill_id)
+            .collect();
+        assert_eq!(skill_ids, vec![1121008]);
+        assert_eq!(data.job_skills(300).count(), 0);
+    }
+}
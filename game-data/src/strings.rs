@@ -0,0 +1,186 @@
+use crate::{GameDataError, NxMapFile};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// String.nx images holding item names, with the path from the image to the
+/// per-item nodes.
+const ITEM_STRING_IMAGES: [(&str, &[&str]); 6] = [
+    ("Eqp.img", &["Eqp"]),
+    ("Consume.img", &[]),
+    ("Ins.img", &[]),
+    ("Etc.img", &["Etc"]),
+    ("Cash.img", &[]),
+    ("Pet.img", &[]),
+];
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MapName {
+    pub street_name: String,
+    pub map_name: String,
+}
+
+/// Display names from String.nx, keyed by id.
+#[derive(Debug, Default)]
+pub struct StringData {
+    maps: BTreeMap<i32, MapName>,
+    items: HashMap<i32, String>,
+    npcs: HashMap<i32, String>,
+    mobs: HashMap<i32, String>,
+}
+
+impl StringData {
+    pub fn load_from_nx(path: impl AsRef<Path>) -> Result<Self, GameDataError> {
+        let nx = NxMapFile::open(path.as_ref())?;
+        let mut strings = Self::default();
+
+        if let Some(map_img_idx) = nx.child_by_name(0, "Map.img")? {
+            for region_idx in nx.child_indices(map_img_idx)? {
+                for map_idx in nx.child_indices(region_idx)? {
+                    let Ok(map_id) = nx.node_name(map_idx)?.parse::<i32>() else {
+                        continue;
+                    };
+                    strings.maps.insert(
+                        map_id,
+                        MapName {
+                            street_name: nx
+                                .string_child(map_idx, "streetName")?
+                                .unwrap_or_default(),
+                            map_name: nx.string_child(map_idx, "mapName")?.unwrap_or_default(),
+                        },
+                    );
+                }
+            }
+        }
+
+        'images: for (image, path) in ITEM_STRING_IMAGES {
+            let Some(mut parent_idx) = nx.child_by_name(0, image)? else {
+                continue;
+            };
+            for child in path {
+                let Some(child_idx) = nx.child_by_name(parent_idx, child)? else {
+                    continue 'images;
+                };
+                parent_idx = child_idx;
+            }
+
+            if image == "Eqp.img" {
+                // Equip names are further split by category.
+                for category_idx in nx.child_indices(parent_idx)? {
+                    read_names(&nx, category_idx, &mut strings.items)?;
+                }
+            } else {
+                read_names(&nx, parent_idx, &mut strings.items)?;
+            }
+        }
+
+        if let Some(npc_img_idx) = nx.child_by_name(0, "Npc.img")? {
+            read_names(&nx, npc_img_idx, &mut strings.npcs)?;
+        }
+        if let Some(mob_img_idx) = nx.child_by_name(0, "Mob.img")? {
+            read_names(&nx, mob_img_idx, &mut strings.mobs)?;
+        }
+
+        Ok(strings)
+    }
+
+    pub fn map(&self, map_id: i32) -> Option<&MapName> {
+        self.maps.get(&map_id)
+    }
+
+    /// The map's name, e.g. "Henesys".
+    pub fn map_name(&self, map_id: i32) -> Option<&str> {
+        self.maps.get(&map_id).map(|name| name.map_name.as_str())
+    }
+
+    pub fn item_name(&self, item_id: i32) -> Option<&str> {
+        self.items.get(&item_id).map(String::as_str)
+    }
+
+    pub fn npc_name(&self, npc_id: i32) -> Option<&str> {
+        self.npcs.get(&npc_id).map(String::as_str)
+    }
+
+    pub fn mob_name(&self, mob_id: i32) -> Option<&str> {
+        self.mobs.get(&mob_id).map(String::as_str)
+    }
+
+    /// Maps whose name matches `query`, ignoring case. Exact matches are
+    /// returned if there are any, otherwise maps whose name contains the
+    /// query. Ids are in ascending order.
+    pub fn find_maps(&self, query: &str) -> Vec<i32> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let names = || {
+            self.maps
+                .iter()
+                .map(|(&map_id, name)| (map_id, name.map_name.to_lowercase()))
+        };
+        let exact: Vec<i32> = names()
+            .filter(|(_, name)| *name == query)
+            .map(|(map_id, _)| map_id)
+            .collect();
+        if !exact.is_empty() {
+            return exact;
+        }
+
+        names()
+            .filter(|(_, name)| name.contains(&query))
+            .map(|(map_id, _)| map_id)
+            .collect()
+    }
+}
+
+/// Read the `name` of every id-named child of `parent_idx`.
+fn read_names(
+    nx: &NxMapFile,
+    parent_idx: u32,
+    names: &mut HashMap<i32, String>,
+) -> Result<(), GameDataError> {
+    for child_idx in nx.child_indices(parent_idx)? {
+        let Ok(id) = nx.node_name(child_idx)?.parse::<i32>() else {
+            continue;
+        };
+        if let Some(name) = nx.string_child(child_idx, "name")? {
+            names.insert(id, name);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(street_name: &str, map_name: &str) -> MapName {
+        MapName {
+            street_name: street_name.to_string(),
+            map_name: map_name.to_string(),
+        }
+    }
+
+    #[test]
+    fn find_maps_prefers_exact_names() {
+        let strings = StringData {
+            maps: BTreeMap::from([
+                (100000000, map("Victoria Road", "Henesys")),
+                (100000001, map("Henesys", "Henesys Townstreet")),
+                (100000002, map("Henesys", "Henesys Hunting Ground I")),
+            ]),
+            ..StringData::default()
+        };
+
+        assert_eq!(strings.find_maps("henesys"), vec![100000000]);
+        assert_eq!(strings.find_maps("HUNTING"), vec![100000002]);
+        assert_eq!(strings.find_maps("henesys "), vec![100000000]);
+        assert_eq!(
+            strings.find_maps("hen"),
+            vec![100000000, 100000001, 100000002]
+        );
+        assert!(strings.find_maps("ellinia").is_empty());
+        assert!(strings.find_maps("").is_empty());
+    }
+}
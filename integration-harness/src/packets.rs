@@ -490,7 +490,8 @@ pub fn decode_char_list(packet: &Packet) -> Result<CharListPacket, String> {
     let mut characters = Vec::with_capacity(count as usize);
     for _ in 0..count {
         let meta = decode_character_meta(&mut cursor)?;
-        skip(&mut cursor, 41)?;
+        skip_look(&mut cursor)?;
+        skip(&mut cursor, 2)?;
         characters.push(CharacterSummary {
             id: meta.id,
             name: meta.name,
@@ -528,7 +529,8 @@ pub fn decode_new_character(packet: &Packet) -> Result<NewCharacterPacket, Strin
         .read_byte()
         .map_err(|e| format!("failed to read new-character status: {e}"))?;
     let meta = decode_character_meta(&mut cursor)?;
-    skip(&mut cursor, 41)?;
+    skip_look(&mut cursor)?;
+    skip(&mut cursor, 2)?;
 
     Ok(NewCharacterPacket {
         status,
@@ -686,10 +688,8 @@ pub fn decode_spawn_player(packet: &Packet) -> Result<SpawnPlayerPacket, String>
         &mut cursor,
         2 + 2 + 1 + 2 + 1 + 8 + 4 + 4 + 4 + 43 + 4 + 61 + 2,
     )?;
-    skip(
-        &mut cursor,
-        1 + 1 + 4 + 1 + 4 + 1 + 4 + 1 + 4 + 1 + 1 + 4 + 4 + 4 + 4 + 4 + 4 + 4,
-    )?;
+    skip_look(&mut cursor)?;
+    skip(&mut cursor, 4 + 4 + 4)?;
     let x = cursor
         .read_short()
         .map_err(|e| format!("failed to read spawn-player x: {e}"))?;
@@ -883,6 +883,24 @@ fn decode_character_meta(cursor: &mut Cursor<&[u8]>) -> Result<CharacterMeta, St
     Ok(CharacterMeta { id, name, map_id })
 }
 
+/// Skip a character look: appearance, the equip lists and pet ids.
+fn skip_look(cursor: &mut Cursor<&[u8]>) -> Result<(), String> {
+    skip(cursor, 1 + 1 + 4 + 1 + 4)?;
+    // Visible equips, then masked equips, each ended by 0xFF.
+    for _ in 0..2 {
+        loop {
+            let slot = cursor
+                .read_byte()
+                .map_err(|e| format!("failed to read equip slot: {e}"))?;
+            if slot == 0xFF {
+                break;
+            }
+            skip(cursor, 4)?;
+        }
+    }
+    skip(cursor, 4 + 4 * 3)
+}
+
 fn skip(cursor: &mut Cursor<&[u8]>, length: usize) -> Result<(), String> {
     cursor
         .read_bytes(length)
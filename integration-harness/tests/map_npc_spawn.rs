@@ -5,12 +5,12 @@ use integration_harness::packets::{
     build_change_map, decode_set_field_warp, decode_spawn_npc, opcode_name, SetFieldWarpPacket,
 };
 use integration_harness::preconditions::load_harness_config_or_fail;
+use net::packet::build::world::npc::MAP_NPC_OBJECT_ID_BASE;
 use net::packet::op::SendOpcode;
 use std::path::PathBuf;
 use tokio::time::{timeout, Duration};
 
 const NPC_TEST_MAP_ID: i32 = 10_000;
-const MAP_NPC_OBJECT_ID_BASE: i32 = 1_000_000_000;
 
 #[tokio::test]
 async fn direct_warp_replays_static_map_npcs() {
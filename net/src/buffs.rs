@@ -0,0 +1,183 @@
+use crate::projectiles;
+use game_data::SkillTemplate;
+
+/// Temporary stats a buff can raise, by their bit in the second half of the
+/// client's 128-bit buff mask. `ALL` is in mask order, which is also the order
+/// the client reads buff values in.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum BuffStat {
+    /// Shadow Stars. Like `SoulArrow`, it only carries a flag.
+    ShadowClaw,
+    Watk,
+    Wdef,
+    Matk,
+    Mdef,
+    Acc,
+    Avoid,
+    Speed,
+    Jump,
+    SoulArrow,
+}
+
+impl BuffStat {
+    pub const ALL: [BuffStat; 10] = [
+        BuffStat::ShadowClaw,
+        BuffStat::Watk,
+        BuffStat::Wdef,
+        BuffStat::Matk,
+        BuffStat::Mdef,
+        BuffStat::Acc,
+        BuffStat::Avoid,
+        BuffStat::Speed,
+        BuffStat::Jump,
+        BuffStat::SoulArrow,
+    ];
+
+    pub fn mask(self) -> u64 {
+        match self {
+            BuffStat::ShadowClaw => 0x100,
+            BuffStat::Watk => 0x1_0000_0000,
+            BuffStat::Wdef => 0x2_0000_0000,
+            BuffStat::Matk => 0x4_0000_0000,
+            BuffStat::Mdef => 0x8_0000_0000,
+            BuffStat::Acc => 0x10_0000_0000,
+            BuffStat::Avoid => 0x20_0000_0000,
+            BuffStat::Speed => 0x80_0000_0000,
+            BuffStat::Jump => 0x100_0000_0000,
+            BuffStat::SoulArrow => 0x1_0000_0000_0000,
+        }
+    }
+
+    /// Whether other players in the field are told about the stat. They only
+    /// see stats that change how the character looks or moves.
+    pub fn is_foreign(self) -> bool {
+        self == BuffStat::Speed
+    }
+
+    fn value(self, skill_id: i32, level: &game_data::SkillLevel) -> i32 {
+        match self {
+            BuffStat::ShadowClaw => i32::from(skill_id == projectiles::SKILL_SHADOW_STARS),
+            BuffStat::SoulArrow => i32::from(projectiles::SOUL_ARROW_SKILLS.contains(&skill_id)),
+            BuffStat::Watk => level.watk,
+            BuffStat::Wdef => level.wdef,
+            BuffStat::Matk => level.matk,
+            BuffStat::Mdef => level.mdef,
+            BuffStat::Acc => level.acc,
+            BuffStat::Avoid => level.avoid,
+            BuffStat::Speed => level.speed,
+            BuffStat::Jump => level.jump,
+        }
+    }
+}
+
+/// A timed set of stat changes from one skill.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Buff {
+    pub skill_id: i32,
+    pub level: i16,
+    pub duration_ms: i32,
+    /// In `BuffStat::ALL` order.
+    pub stats: Vec<(BuffStat, i16)>,
+}
+
+impl Buff {
+    /// The stats other players in the field see.
+    pub fn foreign_stats(&self) -> Vec<(BuffStat, i16)> {
+        self.stats
+            .iter()
+            .copied()
+            .filter(|(stat, _)| stat.is_foreign())
+            .collect()
+    }
+}
+
+/// The buff a skill gives at `level`, or None for skills that don't last or
+/// change none of the stats a buff can carry.
+pub fn skill_buff(template: &SkillTemplate, level: i16) -> Option<Buff> {
+    let data = template.level(level)?;
+    if data.duration <= 0 {
+        return None;
+    }
+
+    let stats: Vec<_> = BuffStat::ALL
+        .iter()
+        .copied()
+        .filter_map(|stat| match stat.value(template.skill_id, data) {
+            0 => None,
+            value => Some((stat, value as i16)),
+        })
+        .collect();
+    if stats.is_empty() {
+        return None;
+    }
+
+    Some(Buff {
+        skill_id: template.skill_id,
+        level,
+        duration_ms: data.duration.saturating_mul(1_000),
+        stats,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_data::SkillLevel;
+
+    #[test]
+    fn skill_buff_takes_the_stats_and_duration_of_the_level() {
+        let template = SkillTemplate {
+            skill_id: 2001002,
+            levels: vec![
+                SkillLevel {
+                    duration: 10,
+                    ..SkillLevel::default()
+                },
+                SkillLevel {
+                    duration: 20,
+                    speed: 10,
+                    wdef: 5,
+                    ..SkillLevel::default()
+                },
+            ],
+            ..SkillTemplate::default()
+        };
+
+        assert_eq!(skill_buff(&template, 1), None);
+        assert_eq!(skill_buff(&template, 3), None);
+
+        let buff = skill_buff(&template, 2).expect("buff");
+        assert_eq!(buff.duration_ms, 20_000);
+        assert_eq!(buff.stats, vec![(BuffStat::Wdef, 5), (BuffStat::Speed, 10)]);
+        assert_eq!(buff.foreign_stats(), vec![(BuffStat::Speed, 10)]);
+    }
+
+    #[test]
+    fn skills_without_a_duration_are_not_buffs() {
+        let template = SkillTemplate {
+            skill_id: 1001004,
+            levels: vec![SkillLevel {
+                watk: 10,
+                ..SkillLevel::default()
+            }],
+            ..SkillTemplate::default()
+        };
+
+        assert_eq!(skill_buff(&template, 1), None);
+    }
+
+    #[test]
+    fn soul_arrow_is_a_flag_buff() {
+        let template = SkillTemplate {
+            skill_id: 3101004,
+            levels: vec![SkillLevel {
+                duration: 60,
+                ..SkillLevel::default()
+            }],
+            ..SkillTemplate::default()
+        };
+
+        let buff = skill_buff(&template, 1).expect("buff");
+        assert_eq!(buff.stats, vec![(BuffStat::SoulArrow, 1)]);
+    }
+}
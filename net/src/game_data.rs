@@ -1,30 +1,265 @@
 use crate::error::NetworkError;
-use game_data::GameData;
-use std::path::PathBuf;
+use db::inventory::{EquipStats, InventoryItem};
+use game_data::{
+    CreationData, DropData, GameData, GameDataError, ItemData, ItemTemplate, MobData, NpcData,
+    QuestData, ShopData, SkillData, StringData,
+};
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 static GAME_DATA: OnceLock<Result<GameData, String>> = OnceLock::new();
+static NPC_DATA: OnceLock<Result<NpcData, String>> = OnceLock::new();
+static MOB_DATA: OnceLock<Result<MobData, String>> = OnceLock::new();
+static CREATION_DATA: OnceLock<Result<CreationData, String>> = OnceLock::new();
+static DROP_DATA: OnceLock<Result<DropData, String>> = OnceLock::new();
+static ITEM_DATA: OnceLock<Result<ItemData, String>> = OnceLock::new();
+static STRING_DATA: OnceLock<Result<StringData, String>> = OnceLock::new();
+static SKILL_DATA: OnceLock<Result<SkillData, String>> = OnceLock::new();
+static QUEST_DATA: OnceLock<Result<QuestData, String>> = OnceLock::new();
+static SHOP_DATA: OnceLock<Result<ShopData, String>> = OnceLock::new();
 
+/// Load `data` the first time it is asked for and keep it, or the reason it
+/// failed to load. `load` fails with where it loaded from and why.
+fn load_once<T>(
+    data: &'static OnceLock<Result<T, String>>,
+    error: &'static str,
+    load: impl FnOnce() -> Result<T, String>,
+) -> Result<&'static T, NetworkError> {
+    match data.get_or_init(load) {
+        Ok(data) => Ok(data),
+        Err(message) => {
+            eprintln!("{error} {message}");
+            Err(NetworkError::PacketHandlerError(error))
+        }
+    }
+}
+
+/// Where to read an NX archive from: `env_var` if it is set, or `file_name`
+/// in the bundled assets.
+fn nx_path(env_var: &str, file_name: &str) -> PathBuf {
+    std::env::var(env_var)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| Path::new("assets/game-data").join(file_name))
+}
+
+/// Load `data` from a single NX archive, see `load_once` and `nx_path`.
+fn load_nx_once<T>(
+    data: &'static OnceLock<Result<T, String>>,
+    error: &'static str,
+    env_var: &str,
+    file_name: &str,
+    load: impl FnOnce(PathBuf) -> Result<T, GameDataError>,
+) -> Result<&'static T, NetworkError> {
+    load_once(data, error, || {
+        let path = nx_path(env_var, file_name);
+        load(path.clone()).map_err(|error| format!("from '{}': {}", path.display(), error))
+    })
+}
+
+/// Map templates. `RUSTMS_MAP_NX_PATH` overrides where Map.nx is read from.
 pub fn get() -> Result<&'static GameData, NetworkError> {
-    let state = GAME_DATA.get_or_init(|| {
-        let path = std::env::var("RUSTMS_MAP_NX_PATH")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| PathBuf::from("assets/game-data/Map.nx"));
+    load_nx_once(
+        &GAME_DATA,
+        "Failed to load game data",
+        "RUSTMS_MAP_NX_PATH",
+        "Map.nx",
+        GameData::load_from_nx_map,
+    )
+}
+
+/// NPC templates. `RUSTMS_NPC_NX_PATH` overrides where Npc.nx is read from.
+pub fn npcs() -> Result<&'static NpcData, NetworkError> {
+    load_nx_once(
+        &NPC_DATA,
+        "Failed to load NPC data",
+        "RUSTMS_NPC_NX_PATH",
+        "Npc.nx",
+        NpcData::load_from_nx,
+    )
+}
+
+/// Mob templates. `RUSTMS_MOB_NX_PATH` overrides where Mob.nx is read from.
+pub fn mobs() -> Result<&'static MobData, NetworkError> {
+    load_nx_once(
+        &MOB_DATA,
+        "Failed to load mob data",
+        "RUSTMS_MOB_NX_PATH",
+        "Mob.nx",
+        MobData::load_from_nx,
+    )
+}
+
+/// Mob drop tables. `RUSTMS_DROP_DATA_PATH` overrides the tables bundled with
+/// the server.
+pub fn drops() -> Result<&'static DropData, NetworkError> {
+    load_once(
+        &DROP_DATA,
+        "Failed to load drop data",
+        || match std::env::var("RUSTMS_DROP_DATA_PATH") {
+            Ok(path) => {
+                DropData::load(&path).map_err(|error| format!("from '{}': {}", path, error))
+            }
+            Err(_) => {
+                DropData::bundled().map_err(|error| format!("from the bundled tables: {}", error))
+            }
+        },
+    )
+}
+
+/// NPC shops. `RUSTMS_SHOP_DATA_PATH` overrides the shops bundled with the
+/// server.
+pub fn shops() -> Result<&'static ShopData, NetworkError> {
+    load_once(
+        &SHOP_DATA,
+        "Failed to load shop data",
+        || match std::env::var("RUSTMS_SHOP_DATA_PATH") {
+            Ok(path) => {
+                ShopData::load(&path).map_err(|error| format!("from '{}': {}", path, error))
+            }
+            Err(_) => {
+                ShopData::bundled().map_err(|error| format!("from the bundled tables: {}", error))
+            }
+        },
+    )
+}
+
+/// Item and equip templates. `RUSTMS_ITEM_NX_PATH` and
+/// `RUSTMS_CHARACTER_NX_PATH` override where Item.nx and Character.nx are
+/// read from.
+pub fn items() -> Result<&'static ItemData, NetworkError> {
+    load_once(&ITEM_DATA, "Failed to load item data", || {
+        let item_path = nx_path("RUSTMS_ITEM_NX_PATH", "Item.nx");
+        let character_path = nx_path("RUSTMS_CHARACTER_NX_PATH", "Character.nx");
 
-        GameData::load_from_nx_map(&path).map_err(|error| {
+        ItemData::load_from_nx(&item_path, &character_path).map_err(|error| {
             format!(
-                "failed to load game data from '{}': {}",
-                path.display(),
+                "from '{}' and '{}': {}",
+                item_path.display(),
+                character_path.display(),
                 error
             )
         })
-    });
+    })
+}
 
-    match state {
-        Ok(data) => Ok(data),
-        Err(message) => {
-            eprintln!("{message}");
-            Err(NetworkError::PacketHandlerError("Failed to load game data"))
-        }
+/// Display names for maps, items, NPCs and mobs. `RUSTMS_STRING_NX_PATH`
+/// overrides where String.nx is read from.
+pub fn strings() -> Result<&'static StringData, NetworkError> {
+    load_nx_once(
+        &STRING_DATA,
+        "Failed to load string data",
+        "RUSTMS_STRING_NX_PATH",
+        "String.nx",
+        StringData::load_from_nx,
+    )
+}
+
+/// Skill templates. `RUSTMS_SKILL_NX_PATH` overrides where Skill.nx is read
+/// from.
+pub fn skills() -> Result<&'static SkillData, NetworkError> {
+    load_nx_once(
+        &SKILL_DATA,
+        "Failed to load skill data",
+        "RUSTMS_SKILL_NX_PATH",
+        "Skill.nx",
+        SkillData::load_from_nx,
+    )
+}
+
+/// Quest templates. `RUSTMS_QUEST_NX_PATH` overrides where Quest.nx is read
+/// from.
+pub fn quests() -> Result<&'static QuestData, NetworkError> {
+    load_nx_once(
+        &QUEST_DATA,
+        "Failed to load quest data",
+        "RUSTMS_QUEST_NX_PATH",
+        "Quest.nx",
+        QuestData::load_from_nx,
+    )
+}
+
+/// What new characters may start with. `RUSTMS_ETC_NX_PATH` overrides where
+/// Etc.nx is read from.
+pub fn creation() -> Result<&'static CreationData, NetworkError> {
+    load_nx_once(
+        &CREATION_DATA,
+        "Failed to load character creation data",
+        "RUSTMS_ETC_NX_PATH",
+        "Etc.nx",
+        CreationData::load_from_nx,
+    )
+}
+
+/// A new inventory item for `item_id`, with the template's base stats if it
+/// is an equip. Fails for ids that have no template.
+pub fn create_item(item_id: i32, quantity: i16) -> Result<InventoryItem, NetworkError> {
+    let template = items()?
+        .item(item_id)
+        .ok_or(NetworkError::PacketHandlerError("Unknown item id"))?;
+
+    Ok(item_from_template(template, quantity))
+}
+
+/// A new inventory item from `template`, holding at most a full stack.
+pub fn item_from_template(template: &ItemTemplate, quantity: i16) -> InventoryItem {
+    let mut item = InventoryItem::new(template.item_id, quantity.min(template.slot_max));
+    if let (Some(stats), Some(equip)) = (item.stats.as_mut(), template.equip.as_ref()) {
+        *stats = EquipStats {
+            upgrade_slots: equip.upgrade_slots,
+            stre: equip.stre,
+            dex: equip.dex,
+            int: equip.int,
+            luk: equip.luk,
+            hp: equip.hp,
+            mp: equip.mp,
+            watk: equip.watk,
+            matk: equip.matk,
+            wdef: equip.wdef,
+            mdef: equip.mdef,
+            acc: equip.acc,
+            avoid: equip.avoid,
+            hands: equip.hands,
+            speed: equip.speed,
+            jump: equip.jump,
+            ..EquipStats::default()
+        };
+    }
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_data::EquipTemplate;
+
+    #[test]
+    fn item_from_template_copies_equip_stats_and_caps_stacks() {
+        let sword = ItemTemplate {
+            item_id: 1302000,
+            price: 1,
+            unit_price: 0.0,
+            slot_max: 1,
+            cash: false,
+            equip: Some(EquipTemplate {
+                upgrade_slots: 7,
+                watk: 17,
+                ..EquipTemplate::default()
+            }),
+        };
+        let item = item_from_template(&sword, 1);
+        let stats = item.stats.expect("equip stats");
+        assert_eq!((stats.upgrade_slots, stats.watk), (7, 17));
+
+        let potion = ItemTemplate {
+            item_id: 2000000,
+            price: 50,
+            unit_price: 0.0,
+            slot_max: 100,
+            cash: false,
+            equip: None,
+        };
+        let item = item_from_template(&potion, 250);
+        assert_eq!(item.quantity, 100);
+        assert!(item.stats.is_none());
     }
 }
@@ -1,7 +1,16 @@
+use crate::buffs::Buff;
 use crate::error::NetworkError;
 use crate::helpers::to_hex_string;
+use crate::npc::Conversation;
+use crate::packet::build::world::attack::DamageLine;
+use crate::party::PartyAction;
+use crate::skills::Cooldowns;
+use crate::stats::RegenClock;
+use crate::storage::OpenStorage;
+use crate::trade::TradeAction;
 use db::session::SessionWrapper;
 use packet::Packet;
+use std::collections::HashMap;
 
 /// Unique identifier for a connected client.
 /// Uses character_id for world server clients.
@@ -78,14 +87,37 @@ fn get_world_handler(op: i16) -> Box<dyn PacketHandler> {
 
     match num::FromPrimitive::from_i16(op) {
         Some(RecvOpcode::PlayerMove) => Box::new(world::PlayerMoveHandler::new()),
+        Some(RecvOpcode::CloseRangeAttack) => Box::new(world::CloseRangeAttackHandler::new()),
+        Some(RecvOpcode::RangedAttack) => Box::new(world::RangedAttackHandler::new()),
+        Some(RecvOpcode::MagicAttack) => Box::new(world::MagicAttackHandler::new()),
+        Some(RecvOpcode::TakeDamage) => Box::new(world::TakeDamageHandler::new()),
+        Some(RecvOpcode::HealOverTime) => Box::new(world::HealOverTimeHandler::new()),
+        Some(RecvOpcode::DistributeSp) => Box::new(world::DistributeSpHandler::new()),
+        Some(RecvOpcode::UseSkill) => Box::new(world::UseSkillHandler::new()),
+        Some(RecvOpcode::CancelBuff) => Box::new(world::CancelBuffHandler::new()),
+        Some(RecvOpcode::MobMove) => Box::new(world::MobMoveHandler::new()),
         Some(RecvOpcode::PlayerLoggedIn) => Box::new(world::PlayerLoggedInHandler::new()),
         Some(RecvOpcode::ChangeChannel) => Box::new(world::ChangeChannelHandler::new()),
+        Some(RecvOpcode::PickupItem) => Box::new(world::PickupItemHandler::new()),
+        Some(RecvOpcode::MoveItem) => Box::new(world::MoveItemHandler::new()),
+        Some(RecvOpcode::DistributeAp) => Box::new(world::DistributeApHandler::new()),
+        Some(RecvOpcode::AutoDistributeAp) => Box::new(world::AutoDistributeApHandler::new()),
         Some(RecvOpcode::PlayerMapTransfer) => Box::new(world::PlayerMapTransferHandler::new()),
         Some(RecvOpcode::ChangeMap) => Box::new(world::ChangeMapHandler::new()),
+        Some(RecvOpcode::EnterScriptedPortal) => Box::new(world::EnterScriptedPortalHandler::new()),
         Some(RecvOpcode::PartySearch) => Box::new(world::PartySearchHandler::new()),
         Some(RecvOpcode::ChangeKeybinds) => Box::new(world::ChangeKeybindsHandler::new()),
         Some(RecvOpcode::AllChat) => Box::new(world::AllChatHandler::new()),
+        Some(RecvOpcode::TalkToNpc) => Box::new(world::NpcTalkHandler::new()),
+        Some(RecvOpcode::NpcTalkMore) => Box::new(world::NpcTalkMoreHandler::new()),
+        Some(RecvOpcode::NpcShopAction) => Box::new(world::NpcShopHandler::new()),
+        Some(RecvOpcode::StorageAction) => Box::new(world::StorageHandler::new()),
+        Some(RecvOpcode::QuestAction) => Box::new(world::QuestActionHandler::new()),
         Some(RecvOpcode::Whisper) => Box::new(world::WhisperHandler::new()),
+        Some(RecvOpcode::PlayerInteraction) => Box::new(world::PlayerInteractionHandler::new()),
+        Some(RecvOpcode::PartyOperation) => Box::new(world::PartyOperationHandler::new()),
+        Some(RecvOpcode::DenyPartyRequest) => Box::new(world::DenyPartyRequestHandler::new()),
+        Some(RecvOpcode::MultiChat) => Box::new(world::MultiChatHandler::new()),
         None | Some(_) => Box::new(DefaultHandler),
     }
 }
@@ -101,7 +133,34 @@ pub enum BroadcastScope {
     World,
     /// All players in the world except the sender
     WorldExcludeSelf,
-    // Future: Party(i32), Guild(i32), Nearby(i32, i16, i16), etc.
+    /// All online members of a party, on any channel
+    Party(i32),
+    /// All online members of a party except the sender
+    PartyExcludeSelf(i32),
+    // Future: Guild(i32), Nearby(i32, i16, i16), etc.
+}
+
+/// Room in the inventory, snapshotted when a pickup is requested so the field
+/// can turn away drops that would not fit.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InventorySpace {
+    /// Indexed by tab: equip, use, setup, etc, cash.
+    pub free_slots: [u8; 5],
+    /// The most of each carried item that one of its stacks can still take.
+    pub stack_room: HashMap<i32, i16>,
+}
+
+impl InventorySpace {
+    /// Whether `quantity` of an item fits in a free slot or on top of one of
+    /// its stacks.
+    pub fn has_room_for(&self, item_id: i32, quantity: i16) -> bool {
+        let tab = item_id / 1_000_000;
+        ((1..=5).contains(&tab) && self.free_slots[(tab - 1) as usize] > 0)
+            || self
+                .stack_room
+                .get(&item_id)
+                .is_some_and(|&room| room >= quantity)
+    }
 }
 
 /// Context available to packet handlers.
@@ -111,6 +170,20 @@ pub struct HandlerContext<'a> {
     pub client_id: ClientId,
     /// Session and character data
     pub session: &'a mut SessionWrapper,
+    /// The NPC conversation waiting for the client's answer, if any
+    pub conversation: &'a mut Option<Conversation>,
+    /// The NPC whose shop the client has open, if any
+    pub shop: &'a mut Option<i32>,
+    /// The account storage the client has open, if any
+    pub storage: &'a mut Option<OpenStorage>,
+    /// The party the client is in, if any
+    pub party_id: Option<i32>,
+    /// Skills whose buffs are running on the client's character
+    pub buff_skills: Vec<i32>,
+    /// When the client's character last regenerated HP and MP
+    pub regen: &'a mut RegenClock,
+    /// When the skills the client's character used can be used again
+    pub cooldowns: &'a mut Cooldowns,
 }
 
 use db::session::SessionState;
@@ -156,6 +229,40 @@ pub enum HandlerAction {
         packet: Packet,
         movement_bytes: Vec<u8>,
     },
+    /// Relay movement of a mob this client controls to the client's current field.
+    FieldMobMove {
+        object_id: i32,
+        packet: Packet,
+        response_packet: Packet,
+        movement_bytes: Vec<u8>,
+    },
+    /// Broadcast an attack to the client's current field and apply its damage.
+    FieldAttack {
+        packet: Packet,
+        damage_lines: Vec<DamageLine>,
+    },
+    /// Pick up a drop in the client's current field.
+    FieldPickup {
+        object_id: i32,
+        space: InventorySpace,
+    },
+    /// Show the client's new equipment to the rest of its current field.
+    FieldUpdateLook { equipment: Vec<(i16, i32)> },
+    /// Show the rest of the client's current field that it took damage.
+    FieldDamage { packet: Packet },
+    /// Show the client's level up to the rest of its current field.
+    FieldLevelUp { level: i16 },
+    /// Show the client's job advancement to the rest of its current field.
+    FieldJobChange { job: i16 },
+    /// Start a buff on the client. `effect_packet` shows the skill to the rest
+    /// of its current field.
+    GiveBuff { buff: Buff, effect_packet: Packet },
+    /// End the client's buff from a skill, if it has one.
+    CancelBuff { skill_id: i32 },
+    /// Pass a trade window action on to the client's current field.
+    Trade(TradeAction),
+    /// Pass a party action on to the world server.
+    Party(PartyAction),
     /// Notify runtime that this client changed maps.
     MapChanged {
         old_map_id: i32,
@@ -293,6 +400,91 @@ impl HandlerResult {
         self
     }
 
+    /// Add a local field mob-move action.
+    pub fn with_field_mob_move(
+        mut self,
+        object_id: i32,
+        packet: Packet,
+        response_packet: Packet,
+        movement_bytes: Vec<u8>,
+    ) -> Self {
+        self.actions.push(HandlerAction::FieldMobMove {
+            object_id,
+            packet,
+            response_packet,
+            movement_bytes,
+        });
+        self
+    }
+
+    /// Add a local field attack action.
+    pub fn with_field_attack(mut self, packet: Packet, damage_lines: Vec<DamageLine>) -> Self {
+        self.actions.push(HandlerAction::FieldAttack {
+            packet,
+            damage_lines,
+        });
+        self
+    }
+
+    /// Add a local field pickup action.
+    pub fn with_field_pickup(mut self, object_id: i32, space: InventorySpace) -> Self {
+        self.actions
+            .push(HandlerAction::FieldPickup { object_id, space });
+        self
+    }
+
+    /// Add a local field look update action.
+    pub fn with_field_update_look(mut self, equipment: Vec<(i16, i32)>) -> Self {
+        self.actions
+            .push(HandlerAction::FieldUpdateLook { equipment });
+        self
+    }
+
+    /// Add a local field damage action.
+    pub fn with_field_damage(mut self, packet: Packet) -> Self {
+        self.actions.push(HandlerAction::FieldDamage { packet });
+        self
+    }
+
+    /// Add a local field level up action.
+    pub fn with_field_level_up(mut self, level: i16) -> Self {
+        self.actions.push(HandlerAction::FieldLevelUp { level });
+        self
+    }
+
+    /// Add a local field job change action.
+    pub fn with_field_job_change(mut self, job: i16) -> Self {
+        self.actions.push(HandlerAction::FieldJobChange { job });
+        self
+    }
+
+    /// Add a give buff action.
+    pub fn with_give_buff(mut self, buff: Buff, effect_packet: Packet) -> Self {
+        self.actions.push(HandlerAction::GiveBuff {
+            buff,
+            effect_packet,
+        });
+        self
+    }
+
+    /// Add a cancel buff action.
+    pub fn with_cancel_buff(mut self, skill_id: i32) -> Self {
+        self.actions.push(HandlerAction::CancelBuff { skill_id });
+        self
+    }
+
+    /// Add a trade window action.
+    pub fn with_trade(mut self, action: TradeAction) -> Self {
+        self.actions.push(HandlerAction::Trade(action));
+        self
+    }
+
+    /// Add a party action.
+    pub fn with_party(mut self, action: PartyAction) -> Self {
+        self.actions.push(HandlerAction::Party(action));
+        self
+    }
+
     /// Notify runtime that this client changed maps.
     pub fn with_map_changed(
         mut self,
@@ -314,3 +506,24 @@ impl HandlerResult {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_fit_in_a_free_slot_or_on_a_stack() {
+        let mut space = InventorySpace::default();
+        assert!(!space.has_room_for(2000000, 1));
+
+        space.stack_room.insert(2000000, 5);
+        assert!(space.has_room_for(2000000, 5));
+        assert!(!space.has_room_for(2000000, 6));
+        assert!(!space.has_room_for(2000001, 1));
+
+        space.free_slots[1] = 1;
+        assert!(space.has_room_for(2000000, 6));
+        assert!(space.has_room_for(2000001, 1));
+        assert!(!space.has_room_for(4000000, 1));
+    }
+}
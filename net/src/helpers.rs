@@ -8,6 +8,18 @@ pub fn to_hex_string(bytes: &Vec<u8>) -> String {
     strs.join(" ")
 }
 
+/// FILETIME the client treats as "never expires".
+pub const NO_EXPIRATION: i64 = 150_842_304_000_000_000;
+
 pub fn current_time_i64() -> Result<i64, NetworkError> {
     Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64)
 }
+
+/// FILETIME, in 100ns ticks since 1601, of a point in time.
+pub fn to_filetime(time: SystemTime) -> i64 {
+    const UNIX_EPOCH_FILETIME: i64 = 116_444_736_000_000_000;
+    let millis = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as i64);
+    UNIX_EPOCH_FILETIME + millis * 10_000
+}
@@ -0,0 +1,274 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::HandlerResult;
+use crate::packet::build::world::effect::{build_show_self_effect, EFFECT_JOB_CHANGE};
+use crate::packet::build::world::skill::build_update_skill;
+use crate::packet::build::world::stat::{build_stat_changes, Stat};
+use db::character::{Character, CharacterWrapper};
+use db::skill::{SkillBook, SkillEntry};
+use std::fmt;
+
+pub const BEGINNER: i16 = 0;
+
+/// Every Explorer job: beginner, the five first jobs, and each of their
+/// branches from second to fourth job.
+#[rustfmt::skip]
+pub const EXPLORER_JOBS: [i16; 42] = [
+    0,
+    100, 110, 111, 112, 120, 121, 122, 130, 131, 132,
+    200, 210, 211, 212, 220, 221, 222, 230, 231, 232,
+    300, 310, 311, 312, 320, 321, 322,
+    400, 410, 411, 412, 420, 421, 422,
+    500, 510, 511, 512, 520, 521, 522,
+];
+
+/// SP granted on every advancement.
+pub const ADVANCEMENT_SP: i16 = 1;
+
+/// How many advancements `job` is from beginner, up to 4 for fourth job.
+pub fn advancement(job: i16) -> u8 {
+    if job == BEGINNER {
+        0
+    } else if job % 100 == 0 {
+        1
+    } else {
+        2 + (job % 10) as u8
+    }
+}
+
+/// The job a character has to have to advance to `job`, or None for
+/// beginner and jobs outside the Explorer tree.
+pub fn previous_job(job: i16) -> Option<i16> {
+    if !EXPLORER_JOBS.contains(&job) {
+        return None;
+    }
+    match advancement(job) {
+        0 => None,
+        1 => Some(BEGINNER),
+        2 => Some(job / 100 * 100),
+        _ => Some(job - 1),
+    }
+}
+
+/// Level a character needs to advance to `job`.
+pub fn required_level(job: i16) -> i16 {
+    match advancement(job) {
+        0 => 1,
+        1 if job == 200 => 8,
+        1 => 10,
+        2 => 30,
+        3 => 70,
+        _ => 120,
+    }
+}
+
+/// Primary stat a first job asks for.
+fn required_stat(job: i16) -> Option<(Stat, i16)> {
+    match job {
+        100 => Some((Stat::Str, 35)),
+        200 => Some((Stat::Int, 20)),
+        300 | 400 => Some((Stat::Dex, 25)),
+        500 => Some((Stat::Dex, 20)),
+        _ => None,
+    }
+}
+
+/// Why a character can't advance to a job.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdvancementError {
+    UnknownJob,
+    /// The job does not follow on from the character's current job.
+    NotNextJob,
+    LevelTooLow(i16),
+    StatTooLow(Stat, i16),
+}
+
+impl AdvancementError {
+    pub fn message(self) -> &'static str {
+        match self {
+            AdvancementError::UnknownJob => "Unknown job",
+            AdvancementError::NotNextJob => "Job does not follow the current job",
+            AdvancementError::LevelTooLow(_) => "Level too low for job",
+            AdvancementError::StatTooLow(..) => "Stat too low for job",
+        }
+    }
+}
+
+impl fmt::Display for AdvancementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdvancementError::LevelTooLow(level) => write!(f, "Requires level {}", level),
+            AdvancementError::StatTooLow(stat, value) => {
+                write!(f, "Requires {:?} of at least {}", stat, value)
+            }
+            error => write!(f, "{}", error.message()),
+        }
+    }
+}
+
+pub fn check_advancement(character: &Character, job: i16) -> Result<(), AdvancementError> {
+    if !EXPLORER_JOBS.contains(&job) {
+        return Err(AdvancementError::UnknownJob);
+    }
+    if previous_job(job) != Some(character.job) {
+        return Err(AdvancementError::NotNextJob);
+    }
+    let level = required_level(job);
+    if character.level < level {
+        return Err(AdvancementError::LevelTooLow(level));
+    }
+    if let Some((stat, value)) = required_stat(job) {
+        if stat.value(character) < i32::from(value) {
+            return Err(AdvancementError::StatTooLow(stat, value));
+        }
+    }
+    Ok(())
+}
+
+/// Advance the character to `job`, granting SP and the master levels the
+/// new job's fourth job skills start with. Returns the skills that changed.
+pub fn advance_job(
+    character: &mut Character,
+    skills: &mut SkillBook,
+    skill_data: &::game_data::SkillData,
+    job: i16,
+) -> Result<Vec<(i32, SkillEntry)>, AdvancementError> {
+    check_advancement(character, job)?;
+    character.job = job;
+    character.sp = character.sp.saturating_add(ADVANCEMENT_SP);
+
+    let mut granted = Vec::new();
+    for template in skill_data
+        .job_skills(job)
+        .filter(|template| template.is_fourth_job())
+    {
+        let mut entry = skills.get(template.skill_id).unwrap_or_default();
+        if template.master_level > entry.master_level {
+            entry.master_level = template.master_level;
+            skills.set(template.skill_id, entry);
+            granted.push((template.skill_id, entry));
+        }
+    }
+    granted.sort_by_key(|&(skill_id, _)| skill_id);
+    Ok(granted)
+}
+
+/// Advance the session character to `job` and save it. This is what NPCs and
+/// scripts call once `check_advancement` passes. The client is shown its new
+/// job, SP and skills, and the field sees the job-change effect.
+pub fn change_job(chr: &mut CharacterWrapper, job: i16) -> Result<HandlerResult, NetworkError> {
+    let before = chr.character.clone();
+    let granted = advance_job(
+        &mut chr.character,
+        &mut chr.skills,
+        game_data::skills()?,
+        job,
+    )
+    .map_err(|error| NetworkError::PacketHandlerError(error.message()))?;
+    chr.character.save()?;
+    chr.skills.save()?;
+
+    let mut result = HandlerResult::reply(build_stat_changes(&before, &chr.character, false)?);
+    for (skill_id, entry) in granted {
+        result = result.with_reply(build_update_skill(skill_id, entry)?);
+    }
+    Ok(result
+        .with_reply(build_show_self_effect(EFFECT_JOB_CHANGE)?)
+        .with_field_job_change(job))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::game_data::{SkillData, SkillTemplate};
+
+    #[test]
+    fn job_tree_follows_explorer_advancements() {
+        assert_eq!(previous_job(100), Some(BEGINNER));
+        assert_eq!(previous_job(230), Some(200));
+        assert_eq!(previous_job(312), Some(311));
+        assert_eq!(previous_job(0), None);
+        assert_eq!(previous_job(140), None);
+        assert_eq!(previous_job(900), None);
+        assert_eq!([0, 500, 520, 521, 522].map(advancement), [0, 1, 2, 3, 4]);
+        assert_eq!(
+            [100, 200, 210, 211, 212].map(required_level),
+            [10, 8, 30, 70, 120]
+        );
+    }
+
+    #[test]
+    fn check_advancement_checks_job_level_and_stat() {
+        let mut character = crate::stats::test_character();
+        character.level = 9;
+        character.stre = 35;
+
+        assert_eq!(
+            check_advancement(&character, 100),
+            Err(AdvancementError::LevelTooLow(10))
+        );
+        character.level = 10;
+        assert_eq!(check_advancement(&character, 100), Ok(()));
+        assert_eq!(
+            check_advancement(&character, 300),
+            Err(AdvancementError::StatTooLow(Stat::Dex, 25))
+        );
+        assert_eq!(
+            check_advancement(&character, 110),
+            Err(AdvancementError::NotNextJob)
+        );
+        assert_eq!(
+            check_advancement(&character, 999),
+            Err(AdvancementError::UnknownJob)
+        );
+    }
+
+    #[test]
+    fn advance_job_grants_sp_and_fourth_job_master_levels() {
+        let mut character = crate::stats::test_character();
+        character.job = 111;
+        character.level = 120;
+        character.sp = 0;
+        let mut skills = SkillBook::from_skill_vec(character.id, Vec::new());
+        let skill_data = SkillData::from_templates(vec![
+            SkillTemplate {
+                skill_id: 1121000,
+                master_level: 10,
+                ..SkillTemplate::default()
+            },
+            SkillTemplate {
+                skill_id: 1121008,
+                ..SkillTemplate::default()
+            },
+            SkillTemplate {
+                skill_id: 1221000,
+                master_level: 10,
+                ..SkillTemplate::default()
+            },
+        ]);
+
+        let granted = advance_job(&mut character, &mut skills, &skill_data, 112).expect("advance");
+        assert_eq!(character.job, 112);
+        assert_eq!(character.sp, ADVANCEMENT_SP);
+        assert_eq!(
+            granted,
+            vec![(
+                1121000,
+                SkillEntry {
+                    level: 0,
+                    master_level: 10
+                }
+            )]
+        );
+        assert_eq!(
+            skills.get(1121000).map(|entry| entry.master_level),
+            Some(10)
+        );
+        assert_eq!(skills.get(1221000), None);
+
+        assert_eq!(
+            advance_job(&mut character, &mut skills, &skill_data, 112),
+            Err(AdvancementError::NotNextJob)
+        );
+    }
+}
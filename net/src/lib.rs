@@ -4,18 +4,41 @@ extern crate num_derive;
 extern crate serde_derive;
 extern crate serde;
 
+pub mod buffs;
 mod game_data;
 pub mod handler;
 mod helpers;
 mod io;
+pub mod jobs;
 pub mod login_world;
+pub mod npc;
 pub mod packet;
+pub mod party;
+pub mod portal;
+pub mod projectiles;
+pub mod quests;
+pub mod rewards;
+pub mod script;
 pub mod settings;
+pub mod shops;
+pub mod skills;
+pub mod stats;
+pub mod storage;
+pub mod trade;
 
+pub use self::game_data::drops as get_drop_data;
 pub use self::game_data::get as get_game_data;
+pub use self::game_data::items as get_item_data;
+pub use self::game_data::mobs as get_mob_data;
+pub use self::game_data::npcs as get_npc_data;
+pub use self::game_data::quests as get_quest_data;
+pub use self::game_data::shops as get_shop_data;
+pub use self::game_data::skills as get_skill_data;
+pub use self::game_data::strings as get_string_data;
+pub use self::game_data::{create_item, item_from_template};
 pub use self::handler::{
     get_handler, BroadcastScope, ClientId, DefaultHandler, HandlerAction, HandlerContext,
-    HandlerResult, PacketHandler,
+    HandlerResult, InventorySpace, PacketHandler,
 };
 pub use self::io::error;
 pub use self::io::listener;
@@ -0,0 +1,184 @@
+use crate::error::NetworkError;
+use packet::io::read::PktRead;
+use std::io::Cursor;
+
+/// One dialog box of an NPC conversation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Dialog {
+    /// Plain text, with optional back and next buttons. Without a next
+    /// button the client shows OK.
+    Say {
+        text: String,
+        prev: bool,
+        next: bool,
+    },
+    YesNo {
+        text: String,
+    },
+    AcceptDecline {
+        text: String,
+    },
+    /// Options are written into the text as `#L<n>#...#l`; the answer is the
+    /// chosen `n`.
+    Menu {
+        text: String,
+    },
+    GetNumber {
+        text: String,
+        default: i32,
+        min: i32,
+        max: i32,
+    },
+    GetText {
+        text: String,
+        default: String,
+    },
+    /// Hair, face or skin ids to preview; the answer is an index into them.
+    Style {
+        text: String,
+        styles: Vec<i32>,
+    },
+}
+
+impl Dialog {
+    pub fn say(text: impl Into<String>) -> Self {
+        Dialog::Say {
+            text: text.into(),
+            prev: false,
+            next: false,
+        }
+    }
+
+    pub fn say_next(text: impl Into<String>) -> Self {
+        Dialog::Say {
+            text: text.into(),
+            prev: false,
+            next: true,
+        }
+    }
+
+    /// The dialog type the client tags its answer with.
+    pub fn message_type(&self) -> u8 {
+        match self {
+            Dialog::Say { .. } => 0,
+            Dialog::YesNo { .. } => 1,
+            Dialog::GetText { .. } => 2,
+            Dialog::GetNumber { .. } => 3,
+            Dialog::Menu { .. } => 4,
+            Dialog::Style { .. } => 7,
+            Dialog::AcceptDecline { .. } => 12,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            Dialog::Say { text, .. }
+            | Dialog::YesNo { text }
+            | Dialog::AcceptDecline { text }
+            | Dialog::Menu { text }
+            | Dialog::GetNumber { text, .. }
+            | Dialog::GetText { text, .. }
+            | Dialog::Style { text, .. } => text,
+        }
+    }
+}
+
+/// The player's answer to a dialog.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Answer {
+    /// The player closed the dialog.
+    End,
+    Prev,
+    Next,
+    Yes,
+    No,
+    /// The chosen menu option or style index.
+    Selection(i32),
+    Number(i32),
+    Text(String),
+}
+
+impl Answer {
+    /// Read the client's answer to `dialog` from the rest of an `NpcTalkMore`
+    /// packet. Answers to a different dialog type end the conversation.
+    pub fn read(dialog: &Dialog, reader: &mut Cursor<&[u8]>) -> Result<Self, NetworkError> {
+        let message_type = reader.read_byte()?;
+        let action = reader.read_byte()? as i8;
+        if message_type != dialog.message_type() || action == -1 {
+            return Ok(Answer::End);
+        }
+
+        let answer = match (dialog, action) {
+            (Dialog::Say { .. }, 0) => Answer::Prev,
+            (Dialog::Say { .. }, _) => Answer::Next,
+            (Dialog::YesNo { .. }, 0) | (Dialog::AcceptDecline { .. }, 0) => Answer::No,
+            (Dialog::YesNo { .. }, _) | (Dialog::AcceptDecline { .. }, _) => Answer::Yes,
+            (_, 0) => Answer::End,
+            (Dialog::Menu { .. }, _) => Answer::Selection(reader.read_int()?),
+            (Dialog::GetNumber { .. }, _) => Answer::Number(reader.read_int()?),
+            (Dialog::GetText { .. }, _) => Answer::Text(reader.read_str_with_length()?),
+            (Dialog::Style { .. }, _) => {
+                // Older clients send the style index as a byte.
+                let remaining = reader.get_ref().len() as u64 - reader.position();
+                if remaining >= 4 {
+                    Answer::Selection(reader.read_int()?)
+                } else {
+                    Answer::Selection(i32::from(reader.read_byte()?))
+                }
+            }
+        };
+        Ok(answer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(dialog: &Dialog, bytes: &[u8]) -> Answer {
+        Answer::read(dialog, &mut Cursor::new(bytes)).expect("answer")
+    }
+
+    #[test]
+    fn answers_are_read_for_the_dialog_they_answer() {
+        let say = Dialog::say_next("Hello");
+        assert_eq!(read(&say, &[0, 1]), Answer::Next);
+        assert_eq!(read(&say, &[0, 0]), Answer::Prev);
+        assert_eq!(read(&say, &[0, 0xFF]), Answer::End);
+
+        let yes_no = Dialog::YesNo {
+            text: "Sure?".to_string(),
+        };
+        assert_eq!(read(&yes_no, &[1, 1]), Answer::Yes);
+        assert_eq!(read(&yes_no, &[1, 0]), Answer::No);
+
+        let menu = Dialog::Menu {
+            text: "#L0#One#l".to_string(),
+        };
+        assert_eq!(read(&menu, &[4, 1, 2, 0, 0, 0]), Answer::Selection(2));
+        assert_eq!(read(&menu, &[4, 0]), Answer::End);
+
+        let text = Dialog::GetText {
+            text: "Name?".to_string(),
+            default: String::new(),
+        };
+        assert_eq!(
+            read(&text, &[2, 1, 3, 0, b'B', b'o', b'b']),
+            Answer::Text("Bob".to_string())
+        );
+
+        let style = Dialog::Style {
+            text: "Pick one".to_string(),
+            styles: vec![30000, 30010],
+        };
+        assert_eq!(read(&style, &[7, 1, 1]), Answer::Selection(1));
+    }
+
+    #[test]
+    fn answers_to_another_dialog_type_end_the_conversation() {
+        let menu = Dialog::Menu {
+            text: "#L0#One#l".to_string(),
+        };
+        assert_eq!(read(&menu, &[1, 1]), Answer::End);
+    }
+}
@@ -0,0 +1,120 @@
+use super::dialog::{Answer, Dialog};
+use super::registry::NpcRegistry;
+use super::script::{NpcContext, NpcScript, Step};
+use crate::error::NetworkError;
+use crate::jobs;
+
+/// The first job instructors of Victoria Island: NPC id, job and job name.
+const INSTRUCTORS: [(i32, i16, &str); 5] = [
+    (1022000, 100, "Warrior"),
+    (1032001, 200, "Magician"),
+    (1012100, 300, "Bowman"),
+    (1052001, 400, "Thief"),
+    (1090000, 500, "Pirate"),
+];
+
+pub(super) fn register(registry: &mut NpcRegistry) {
+    for (npc_id, job, name) in INSTRUCTORS.iter().copied() {
+        registry.register(
+            npc_id,
+            Box::new(move || Box::new(Instructor::new(job, name))),
+        );
+    }
+}
+
+/// Offers the instructor's first job to beginners who qualify for it.
+struct Instructor {
+    job: i16,
+    name: &'static str,
+    offered: bool,
+}
+
+impl Instructor {
+    fn new(job: i16, name: &'static str) -> Self {
+        Instructor {
+            job,
+            name,
+            offered: false,
+        }
+    }
+}
+
+impl NpcScript for Instructor {
+    fn start(&mut self, ctx: &mut NpcContext) -> Result<Step, NetworkError> {
+        let chr = ctx.character.lock().unwrap();
+        let character = &chr.character;
+        let text = if character.job == self.job {
+            format!("Keep training, young #b{}#k.", self.name)
+        } else if character.job != jobs::BEGINNER {
+            "You have already chosen your path.".to_string()
+        } else {
+            match jobs::check_advancement(character, self.job) {
+                Ok(()) => {
+                    self.offered = true;
+                    return Ok(Step::Show(Dialog::YesNo {
+                        text: format!("Do you want to become a #b{}#k?", self.name),
+                    }));
+                }
+                Err(error) => format!(
+                    "You are not ready to become a #b{}#k yet. {}.",
+                    self.name, error
+                ),
+            }
+        };
+        Ok(Step::Show(Dialog::say(text)))
+    }
+
+    fn answer(&mut self, ctx: &mut NpcContext, answer: Answer) -> Result<Step, NetworkError> {
+        if !self.offered || answer != Answer::Yes {
+            return Ok(Step::End);
+        }
+        self.offered = false;
+        let result = jobs::change_job(&mut ctx.character.lock().unwrap(), self.job)?;
+        ctx.extend(result);
+        Ok(Step::Show(Dialog::say(format!(
+            "You are now a #b{}#k. Use your new SP in the skill window.",
+            self.name
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handler::HandlerResult;
+    use crate::stats::test_character_wrapper;
+    use std::sync::{Arc, Mutex};
+
+    fn context() -> NpcContext {
+        NpcContext {
+            npc_id: 1022000,
+            character: Arc::new(Mutex::new(test_character_wrapper())),
+            channel_id: 0,
+            result: HandlerResult::empty(),
+        }
+    }
+
+    #[test]
+    fn instructor_offers_the_job_only_to_qualified_beginners() {
+        let mut ctx = context();
+        let mut instructor = Instructor::new(100, "Warrior");
+        match instructor.start(&mut ctx).expect("start") {
+            Step::Show(Dialog::Say { text, .. }) => assert!(text.contains("Requires level 10")),
+            _ => panic!("expected the instructor to turn the beginner away"),
+        }
+
+        {
+            let mut chr = ctx.character.lock().unwrap();
+            chr.character.level = 10;
+            chr.character.stre = 35;
+        }
+        let mut instructor = Instructor::new(100, "Warrior");
+        let step = instructor.start(&mut ctx).expect("start");
+        assert!(matches!(step, Step::Show(Dialog::YesNo { .. })));
+
+        let step = instructor.answer(&mut ctx, Answer::No).expect("answer");
+        assert!(matches!(step, Step::End));
+        assert!(ctx.result.actions.is_empty());
+        assert_eq!(ctx.character.lock().unwrap().character.job, jobs::BEGINNER);
+    }
+}
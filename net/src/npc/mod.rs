@@ -0,0 +1,8 @@
+mod dialog;
+mod instructor;
+mod registry;
+mod script;
+
+pub use self::dialog::{Answer, Dialog};
+pub use self::registry::{npc_script, register_npc, NpcFactory, NpcRegistry};
+pub use self::script::{Conversation, NpcContext, NpcScript, Step};
@@ -0,0 +1,73 @@
+use super::dialog::{Answer, Dialog};
+use super::instructor;
+use super::script::{NpcContext, NpcScript, Step};
+use crate::error::NetworkError;
+use crate::script::NpcFileScript;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// Creates the script for one conversation with an NPC.
+pub type NpcFactory = Box<dyn Fn() -> Box<dyn NpcScript> + Send + Sync>;
+
+/// Which script each NPC runs. NPCs without one get a line saying they have
+/// nothing to say.
+#[derive(Default)]
+pub struct NpcRegistry {
+    scripts: HashMap<i32, NpcFactory>,
+}
+
+impl NpcRegistry {
+    /// A registry with the NPCs this server ships scripts for.
+    pub fn with_builtin() -> Self {
+        let mut registry = NpcRegistry::default();
+        instructor::register(&mut registry);
+        registry
+    }
+
+    /// Run `factory`'s script for `npc_id`, replacing any script it had.
+    pub fn register(&mut self, npc_id: i32, factory: NpcFactory) {
+        self.scripts.insert(npc_id, factory);
+    }
+
+    pub fn script(&self, npc_id: i32) -> Box<dyn NpcScript> {
+        match self.scripts.get(&npc_id) {
+            Some(factory) => factory(),
+            None => Box::new(Silent),
+        }
+    }
+}
+
+static NPC_REGISTRY: OnceLock<RwLock<NpcRegistry>> = OnceLock::new();
+
+fn registry() -> &'static RwLock<NpcRegistry> {
+    NPC_REGISTRY.get_or_init(|| RwLock::new(NpcRegistry::with_builtin()))
+}
+
+/// Register the script `npc_id` runs from its next conversation on.
+pub fn register_npc(npc_id: i32, factory: NpcFactory) {
+    registry().write().unwrap().register(npc_id, factory);
+}
+
+/// Create the script for a new conversation with `npc_id`. A script file for
+/// the NPC takes precedence over a registered script.
+pub fn npc_script(npc_id: i32) -> Box<dyn NpcScript> {
+    if let Some(script) = NpcFileScript::load(npc_id) {
+        return Box::new(script);
+    }
+    registry().read().unwrap().script(npc_id)
+}
+
+struct Silent;
+
+impl NpcScript for Silent {
+    fn start(&mut self, ctx: &mut NpcContext) -> Result<Step, NetworkError> {
+        Ok(Step::Show(Dialog::say(format!(
+            "I'm #p{}#. I don't have anything to say to you yet.",
+            ctx.npc_id
+        ))))
+    }
+
+    fn answer(&mut self, _ctx: &mut NpcContext, _answer: Answer) -> Result<Step, NetworkError> {
+        Ok(Step::End)
+    }
+}
@@ -0,0 +1,47 @@
+use super::dialog::{Answer, Dialog};
+use crate::error::NetworkError;
+use crate::handler::HandlerResult;
+use db::character::CharacterWrapper;
+use std::sync::{Arc, Mutex};
+
+/// What an NPC does after the player clicks it or answers it.
+pub enum Step {
+    Show(Dialog),
+    End,
+}
+
+/// What a script can reach while it runs: the NPC, the player's character,
+/// and anything else it wants done besides showing the next dialog.
+pub struct NpcContext {
+    pub npc_id: i32,
+    /// The session character. Scripts lock it only while they use it.
+    pub character: Arc<Mutex<CharacterWrapper>>,
+    /// The channel the player is on, for warps.
+    pub channel_id: u8,
+    pub result: HandlerResult,
+}
+
+impl NpcContext {
+    /// Add actions, such as the result of `jobs::change_job`, to what the
+    /// conversation sends back.
+    pub fn extend(&mut self, result: HandlerResult) {
+        self.result.actions.extend(result.actions);
+    }
+}
+
+/// The behavior of an NPC. A new script is created for every conversation, so
+/// it can keep its own state between answers.
+pub trait NpcScript: Send + Sync {
+    fn start(&mut self, ctx: &mut NpcContext) -> Result<Step, NetworkError>;
+
+    /// Answers that close the dialog end the conversation without reaching
+    /// the script.
+    fn answer(&mut self, ctx: &mut NpcContext, answer: Answer) -> Result<Step, NetworkError>;
+}
+
+/// A conversation waiting for the player to answer `dialog`.
+pub struct Conversation {
+    pub npc_id: i32,
+    pub dialog: Dialog,
+    pub script: Box<dyn NpcScript>,
+}
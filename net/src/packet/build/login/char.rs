@@ -1,8 +1,10 @@
+use crate::packet::build::world::inventory::write_equipment;
 use crate::{error::NetworkError, packet::op::SendOpcode};
-use db::character::Character;
+use db::character::{Character, CharacterWrapper};
+use db::inventory::Inventory;
 use packet::{io::write::PktWrite, Packet};
 
-pub fn build_char_list(chars: Vec<Character>) -> Result<Packet, NetworkError> {
+pub fn build_char_list(chars: Vec<(Character, Inventory)>) -> Result<Packet, NetworkError> {
     let mut packet = Packet::new_empty();
     let op = SendOpcode::CharList as i16;
 
@@ -10,8 +12,8 @@ pub fn build_char_list(chars: Vec<Character>) -> Result<Packet, NetworkError> {
     packet.write_byte(0)?; // account status?
 
     packet.write_byte(chars.len() as u8)?; // number of chars
-    for character in chars {
-        write_char(&mut packet, &character)?;
+    for (character, inventory) in chars {
+        write_char(&mut packet, &character, &inventory)?;
     }
 
     packet.write_byte(2)?; // use pic?
@@ -43,21 +45,25 @@ pub fn build_char_delete(character_id: i32, status: u8) -> Result<Packet, Networ
     Ok(packet)
 }
 
-pub fn build_char_packet(character: Character) -> Result<Packet, NetworkError> {
+pub fn build_char_packet(character: &CharacterWrapper) -> Result<Packet, NetworkError> {
     let mut packet = Packet::new_empty();
     let op = SendOpcode::NewCharacter as i16;
 
     packet.write_short(op)?;
     packet.write_byte(0)?;
 
-    write_char(&mut packet, &character)?;
+    write_char(&mut packet, &character.character, &character.inventory)?;
 
     Ok(packet)
 }
 
-fn write_char(packet: &mut Packet, character: &Character) -> Result<(), NetworkError> {
+fn write_char(
+    packet: &mut Packet,
+    character: &Character,
+    inventory: &Inventory,
+) -> Result<(), NetworkError> {
     write_char_meta(packet, &character)?;
-    write_char_look(packet, &character)?;
+    write_char_look(packet, &character, inventory)?;
 
     packet.write_byte(0)?;
 
@@ -93,9 +99,7 @@ fn write_char_meta(packet: &mut Packet, character: &Character) -> Result<(), Net
     packet.write_short(character.mp)?;
     packet.write_short(character.maxmp)?;
     packet.write_short(character.ap)?;
-
-    // SP
-    packet.write_short(0)?;
+    packet.write_short(character.sp)?;
 
     packet.write_int(character.exp)?;
     packet.write_short(character.fame)?;
@@ -111,42 +115,18 @@ fn write_char_meta(packet: &mut Packet, character: &Character) -> Result<(), Net
     Ok(())
 }
 
-fn write_char_look(packet: &mut Packet, character: &Character) -> Result<(), NetworkError> {
+fn write_char_look(
+    packet: &mut Packet,
+    character: &Character,
+    inventory: &Inventory,
+) -> Result<(), NetworkError> {
     packet.write_byte(character.gender as u8)?;
     packet.write_byte(character.skin as u8)?;
     packet.write_int(character.face)?;
     packet.write_byte(0)?;
     packet.write_int(character.hair)?;
 
-    write_char_equips(packet, character)?;
-
-    Ok(())
-}
-
-fn write_char_equips(packet: &mut Packet, _character: &Character) -> Result<(), NetworkError> {
-    // Regular equips
-
-    // Overall (Top slot)
-    packet.write_byte(5)?;
-    packet.write_int(1052122)?;
-
-    // Shoes
-    packet.write_byte(7)?;
-    packet.write_int(1072318)?;
-
-    packet.write_byte(0xFF)?;
-
-    // Cash shop equips
-
-    packet.write_byte(0xFF)?;
-
-    // Weapon
-    packet.write_int(1302000)?;
-
-    // Pet stuff...
-    packet.write_int(0)?;
-    packet.write_int(0)?;
-    packet.write_int(0)?;
+    write_equipment(packet, &inventory.equipment())?;
 
     Ok(())
 }
@@ -0,0 +1,266 @@
+use crate::{error::NetworkError, packet::op::SendOpcode};
+use packet::{io::write::PktWrite, Packet};
+
+/// Meso Explosion hits a variable number of times per mob, so its damage
+/// lines are prefixed with their length.
+pub const SKILL_MESO_EXPLOSION: i32 = 4211006;
+
+const ATTACK_UNKNOWN: u8 = 0x5B;
+const ATTACK_SPEED_TRAILER: u8 = 0x0A;
+
+/// Damage dealt to a single mob by one attack.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DamageLine {
+    pub object_id: i32,
+    pub damage: Vec<i32>,
+}
+
+impl DamageLine {
+    pub fn total(&self) -> i64 {
+        self.damage
+            .iter()
+            .map(|&damage| i64::from(damage.max(0)))
+            .sum()
+    }
+}
+
+/// An attack as reported by the attacking client, replayed to the rest of the
+/// field.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttackInfo {
+    pub hit_count: u8,
+    pub skill_id: i32,
+    pub skill_level: u8,
+    pub display: u8,
+    pub direction: u8,
+    pub stance: u8,
+    pub speed: u8,
+    /// Item id of the arrow, bolt or star fired by a ranged attack.
+    pub projectile: i32,
+    /// How long a charged spell such as Big Bang was held, or 0.
+    pub charge: i32,
+    pub damage_lines: Vec<DamageLine>,
+}
+
+impl AttackInfo {
+    /// Target and hit counts packed the way the client sends them.
+    pub fn targets_and_hits(&self) -> u8 {
+        ((self.damage_lines.len() as u8) << 4) | (self.hit_count & 0x0F)
+    }
+}
+
+pub fn build_close_range_attack(
+    character_id: i32,
+    attack: &AttackInfo,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::CloseRangeAttack as i16)?;
+    write_attack_body(&mut packet, character_id, attack)?;
+    Ok(packet)
+}
+
+pub fn build_ranged_attack(character_id: i32, attack: &AttackInfo) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::RangedAttack as i16)?;
+    write_attack_body(&mut packet, character_id, attack)?;
+    Ok(packet)
+}
+
+pub fn build_magic_attack(character_id: i32, attack: &AttackInfo) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::MagicAttack as i16)?;
+    write_attack_body(&mut packet, character_id, attack)?;
+    if attack.charge > 0 {
+        packet.write_int(attack.charge)?;
+    }
+    Ok(packet)
+}
+
+fn write_attack_body(
+    packet: &mut Packet,
+    character_id: i32,
+    attack: &AttackInfo,
+) -> Result<(), NetworkError> {
+    packet.write_int(character_id)?;
+    packet.write_byte(attack.targets_and_hits())?;
+    packet.write_byte(ATTACK_UNKNOWN)?;
+    packet.write_byte(attack.skill_level)?;
+    if attack.skill_level > 0 {
+        packet.write_int(attack.skill_id)?;
+    }
+    packet.write_byte(attack.display)?;
+    packet.write_byte(attack.direction)?;
+    packet.write_byte(attack.stance)?;
+    packet.write_byte(attack.speed)?;
+    packet.write_byte(ATTACK_SPEED_TRAILER)?;
+    packet.write_int(attack.projectile)?;
+
+    for line in &attack.damage_lines {
+        packet.write_int(line.object_id)?;
+        packet.write_byte(0)?;
+        if attack.skill_id == SKILL_MESO_EXPLOSION {
+            packet.write_byte(line.damage.len() as u8)?;
+        }
+        for &damage in &line.damage {
+            packet.write_int(damage)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    #[test]
+    fn build_close_range_attack_matches_expected_payload() {
+        let attack = AttackInfo {
+            hit_count: 2,
+            skill_id: 1001004,
+            skill_level: 1,
+            display: 0x12,
+            direction: 0x80,
+            stance: 3,
+            speed: 4,
+            projectile: 0,
+            charge: 0,
+            damage_lines: vec![
+                DamageLine {
+                    object_id: 7,
+                    damage: vec![120, 98],
+                },
+                DamageLine {
+                    object_id: 9,
+                    damage: vec![33, 0],
+                },
+            ],
+        };
+        let packet = build_close_range_attack(42, &attack).expect("build attack");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::CloseRangeAttack as i16
+        );
+        assert_eq!(cursor.read_int().expect("character id"), 42);
+        assert_eq!(cursor.read_byte().expect("targets and hits"), 0x22);
+        cursor.read_byte().expect("unknown");
+        assert_eq!(cursor.read_byte().expect("skill level"), 1);
+        assert_eq!(cursor.read_int().expect("skill id"), 1001004);
+        assert_eq!(cursor.read_byte().expect("display"), 0x12);
+        assert_eq!(cursor.read_byte().expect("direction"), 0x80);
+        assert_eq!(cursor.read_byte().expect("stance"), 3);
+        assert_eq!(cursor.read_byte().expect("speed"), 4);
+        cursor.read_byte().expect("trailer");
+        assert_eq!(cursor.read_int().expect("projectile"), 0);
+        assert_eq!(cursor.read_int().expect("first target"), 7);
+        cursor.read_byte().expect("padding");
+        assert_eq!(cursor.read_int().expect("first hit"), 120);
+        assert_eq!(cursor.read_int().expect("second hit"), 98);
+        assert_eq!(cursor.read_int().expect("second target"), 9);
+        cursor.read_byte().expect("padding");
+        assert_eq!(cursor.read_int().expect("first hit"), 33);
+        assert_eq!(cursor.read_int().expect("second hit"), 0);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn build_close_range_attack_omits_skill_for_basic_attacks() {
+        let attack = AttackInfo {
+            hit_count: 1,
+            skill_id: 0,
+            skill_level: 0,
+            display: 0,
+            direction: 0,
+            stance: 5,
+            speed: 6,
+            projectile: 0,
+            charge: 0,
+            damage_lines: vec![DamageLine {
+                object_id: 3,
+                damage: vec![15],
+            }],
+        };
+        let packet = build_close_range_attack(1, &attack).expect("build attack");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        cursor.read_short().expect("opcode");
+        cursor.read_int().expect("character id");
+        assert_eq!(cursor.read_byte().expect("targets and hits"), 0x11);
+        cursor.read_byte().expect("unknown");
+        assert_eq!(cursor.read_byte().expect("skill level"), 0);
+        assert_eq!(cursor.read_byte().expect("display"), 0);
+    }
+
+    #[test]
+    fn build_ranged_attack_carries_projectile() {
+        let attack = AttackInfo {
+            hit_count: 1,
+            skill_id: 0,
+            skill_level: 0,
+            display: 0,
+            direction: 0,
+            stance: 7,
+            speed: 6,
+            projectile: 2060000,
+            charge: 0,
+            damage_lines: vec![DamageLine {
+                object_id: 3,
+                damage: vec![25],
+            }],
+        };
+        let packet = build_ranged_attack(1, &attack).expect("build attack");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::RangedAttack as i16
+        );
+        cursor.read_int().expect("character id");
+        cursor.read_bytes(3).expect("counts and skill level");
+        cursor.read_bytes(5).expect("display through trailer");
+        assert_eq!(cursor.read_int().expect("projectile"), 2060000);
+        assert_eq!(cursor.read_int().expect("target"), 3);
+        cursor.read_byte().expect("padding");
+        assert_eq!(cursor.read_int().expect("hit"), 25);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn build_magic_attack_appends_charge() {
+        let attack = AttackInfo {
+            hit_count: 1,
+            skill_id: 2121001,
+            skill_level: 1,
+            display: 0,
+            direction: 0,
+            stance: 0,
+            speed: 4,
+            projectile: 0,
+            charge: 850,
+            damage_lines: vec![DamageLine {
+                object_id: 3,
+                damage: vec![900],
+            }],
+        };
+        let packet = build_magic_attack(1, &attack).expect("build attack");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::MagicAttack as i16
+        );
+        cursor.read_int().expect("character id");
+        cursor.read_bytes(3).expect("counts and skill level");
+        assert_eq!(cursor.read_int().expect("skill id"), 2121001);
+        cursor.read_bytes(5).expect("display through trailer");
+        assert_eq!(cursor.read_int().expect("projectile"), 0);
+        assert_eq!(cursor.read_int().expect("target"), 3);
+        cursor.read_byte().expect("padding");
+        assert_eq!(cursor.read_int().expect("hit"), 900);
+        assert_eq!(cursor.read_int().expect("charge"), 850);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+}
@@ -0,0 +1,116 @@
+use crate::buffs::{Buff, BuffStat};
+use crate::{error::NetworkError, packet::op::SendOpcode};
+use packet::{io::write::PktWrite, Packet};
+
+/// Write the 128-bit buff mask. Every stat a buff can carry is in the second
+/// half.
+fn write_buff_mask(
+    packet: &mut Packet,
+    stats: impl IntoIterator<Item = BuffStat>,
+) -> Result<(), NetworkError> {
+    let mask = stats.into_iter().fold(0, |mask, stat| mask | stat.mask());
+    packet.write_long(0)?;
+    packet.write_long(mask as i64)?;
+    Ok(())
+}
+
+/// Start a buff on the player, running for `remaining_ms`.
+pub fn build_give_buff(buff: &Buff, remaining_ms: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::GiveBuff as i16)?;
+    write_buff_mask(&mut packet, buff.stats.iter().map(|&(stat, _)| stat))?;
+    for &(_, value) in &buff.stats {
+        packet.write_short(value)?;
+        packet.write_int(buff.skill_id)?;
+        packet.write_int(remaining_ms)?;
+    }
+    packet.write_short(0)?; // Defense state
+    packet.write_short(0)?; // Delay
+    packet.write_byte(0)?; // Movement stat count
+    Ok(packet)
+}
+
+/// End the player's buffs on `stats`.
+pub fn build_cancel_buff(stats: &[BuffStat]) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::CancelBuff as i16)?;
+    write_buff_mask(&mut packet, stats.iter().copied())?;
+    packet.write_byte(1)?;
+    Ok(packet)
+}
+
+/// Show another character's buff to the field.
+pub fn build_give_foreign_buff(
+    character_id: i32,
+    stats: &[(BuffStat, i16)],
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::GiveForeignBuff as i16)?;
+    packet.write_int(character_id)?;
+    write_buff_mask(&mut packet, stats.iter().map(|&(stat, _)| stat))?;
+    for &(_, value) in stats {
+        packet.write_short(value)?;
+    }
+    packet.write_int(0)?;
+    packet.write_short(0)?;
+    Ok(packet)
+}
+
+/// Remove another character's buff from the field.
+pub fn build_cancel_foreign_buff(
+    character_id: i32,
+    stats: &[BuffStat],
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::CancelForeignBuff as i16)?;
+    packet.write_int(character_id)?;
+    write_buff_mask(&mut packet, stats.iter().copied())?;
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    #[test]
+    fn build_give_buff_writes_each_stat_in_mask_order() {
+        let buff = Buff {
+            skill_id: 2001002,
+            level: 1,
+            duration_ms: 20_000,
+            stats: vec![(BuffStat::Wdef, 5), (BuffStat::Speed, 10)],
+        };
+
+        let packet = build_give_buff(&buff, 15_000).expect("give buff");
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::GiveBuff as i16
+        );
+        assert_eq!(cursor.read_long().expect("first mask"), 0);
+        assert_eq!(cursor.read_long().expect("second mask"), 0x82_0000_0000);
+        for value in [5, 10] {
+            assert_eq!(cursor.read_short().expect("value"), value);
+            assert_eq!(cursor.read_int().expect("skill id"), 2001002);
+            assert_eq!(cursor.read_int().expect("duration"), 15_000);
+        }
+    }
+
+    #[test]
+    fn build_cancel_foreign_buff_names_the_character() {
+        let packet = build_cancel_foreign_buff(7, &[BuffStat::Speed]).expect("cancel foreign buff");
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::CancelForeignBuff as i16
+        );
+        assert_eq!(cursor.read_int().expect("character id"), 7);
+        assert_eq!(cursor.read_long().expect("first mask"), 0);
+        assert_eq!(
+            cursor.read_long().expect("second mask"),
+            BuffStat::Speed.mask() as i64
+        );
+    }
+}
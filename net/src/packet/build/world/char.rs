@@ -1,9 +1,16 @@
 use crate::{error::NetworkError, packet::op::SendOpcode};
-use db::character::Character;
+use db::character::{Character, CharacterWrapper};
+use db::inventory::{Inventory, InventoryType};
+use db::quest::QuestLog;
+use db::skill::SkillBook;
 use packet::{io::write::PktWrite, Packet};
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::inventory::write_item;
+use super::quest::write_quest_log;
+use super::skill::write_skill_book;
+
 // TODO: This is just a barebones implementation.
 pub fn _build_update_buddy_list() -> Result<Packet, NetworkError> {
     let mut packet = Packet::new_empty();
@@ -49,7 +56,10 @@ pub fn _build_family_info() -> Result<Packet, NetworkError> {
     Ok(packet)
 }
 
-pub fn build_char_info(character: &Character, channel_id: u8) -> Result<Packet, NetworkError> {
+pub fn build_char_info(
+    character: &CharacterWrapper,
+    channel_id: u8,
+) -> Result<Packet, NetworkError> {
     let mut packet = Packet::new_empty();
 
     let op = SendOpcode::SetField as i16;
@@ -66,7 +76,7 @@ pub fn build_char_info(character: &Character, channel_id: u8) -> Result<Packet,
     packet.write_int(2)?;
     packet.write_int(3)?;
 
-    write_char(&mut packet, &character)?;
+    write_char(&mut packet, character)?;
 
     let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
     packet.write_long(time)?;
@@ -74,7 +84,9 @@ pub fn build_char_info(character: &Character, channel_id: u8) -> Result<Packet,
     Ok(packet)
 }
 
-fn write_char(packet: &mut Packet, character: &Character) -> Result<(), NetworkError> {
+fn write_char(packet: &mut Packet, wrapper: &CharacterWrapper) -> Result<(), NetworkError> {
+    let character = &wrapper.character;
+
     packet.write_long(-1)?;
     packet.write_byte(0)?;
 
@@ -87,9 +99,9 @@ fn write_char(packet: &mut Packet, character: &Character) -> Result<(), NetworkE
 
     packet.write_int(character.meso)?;
 
-    write_inventory(packet, character)?;
-    write_skills(packet, character)?;
-    write_quests(packet, character)?;
+    write_inventory(packet, &wrapper.inventory)?;
+    write_skills(packet, &wrapper.skills)?;
+    write_quests(packet, &wrapper.quests)?;
     write_minigames(packet, character)?;
     write_rings(packet, character)?;
     write_teleport(packet, character)?;
@@ -103,42 +115,78 @@ fn write_char(packet: &mut Packet, character: &Character) -> Result<(), NetworkE
 }
 
 /// Write the equiped items and the inventory of the player
-fn write_inventory(packet: &mut Packet, _character: &Character) -> Result<(), NetworkError> {
+fn write_inventory(packet: &mut Packet, inventory: &Inventory) -> Result<(), NetworkError> {
     // Inventory slot Capacities
-    packet.write_bytes(&vec![0u8; 5])?;
+    for inventory_type in InventoryType::ALL.iter() {
+        packet.write_byte(inventory.slot_limit(*inventory_type))?;
+    }
 
     // Time?
     packet.write_long(0)?;
 
-    // Equiped items go here
+    // Equiped items go here. Cash equips sit 100 slots below the regular ones.
+    for (position, item) in inventory
+        .equipped()
+        .filter(|(position, _)| *position > -100)
+    {
+        packet.write_short(-position)?;
+        write_item(packet, item)?;
+    }
 
     // Start of equiped cash items
     packet.write_short(0)?;
+    for (position, item) in inventory
+        .equipped()
+        .filter(|(position, _)| *position <= -100)
+    {
+        packet.write_short(-position - 100)?;
+        write_item(packet, item)?;
+    }
 
     // Start of equiped inventory
     packet.write_short(0)?;
+    for (position, item) in inventory.items(InventoryType::Equip) {
+        packet.write_short(position)?;
+        write_item(packet, item)?;
+    }
 
     // Start of USE
     packet.write_int(0)?;
+    write_tab(packet, inventory, InventoryType::Use)?;
 
     // Start of SETUP
     packet.write_byte(0)?;
+    write_tab(packet, inventory, InventoryType::Setup)?;
 
     // Start of ETC
     packet.write_byte(0)?;
+    write_tab(packet, inventory, InventoryType::Etc)?;
 
     // Start of CASH
     packet.write_byte(0)?;
+    // The cash tab's terminator is written as the start of the skills.
+    write_tab(packet, inventory, InventoryType::Cash)?;
+
+    Ok(())
+}
 
+fn write_tab(
+    packet: &mut Packet,
+    inventory: &Inventory,
+    inventory_type: InventoryType,
+) -> Result<(), NetworkError> {
+    for (position, item) in inventory.items(inventory_type) {
+        packet.write_byte(position as u8)?;
+        write_item(packet, item)?;
+    }
     Ok(())
 }
 
-fn write_skills(packet: &mut Packet, _character: &Character) -> Result<(), NetworkError> {
+fn write_skills(packet: &mut Packet, skills: &SkillBook) -> Result<(), NetworkError> {
     // Start of skills
     packet.write_byte(0)?;
 
-    // No skills!
-    packet.write_short(0)?;
+    write_skill_book(packet, skills)?;
 
     // No no cooldowns!
     packet.write_short(0)?;
@@ -146,14 +194,8 @@ fn write_skills(packet: &mut Packet, _character: &Character) -> Result<(), Netwo
     Ok(())
 }
 
-fn write_quests(packet: &mut Packet, _character: &Character) -> Result<(), NetworkError> {
-    let started_quests = 0;
-    packet.write_short(started_quests)?;
-
-    let completed_quests = 0;
-    packet.write_short(completed_quests)?;
-
-    Ok(())
+fn write_quests(packet: &mut Packet, quests: &QuestLog) -> Result<(), NetworkError> {
+    write_quest_log(packet, quests)
 }
 
 fn write_minigames(packet: &mut Packet, _character: &Character) -> Result<(), NetworkError> {
@@ -0,0 +1,165 @@
+use crate::{error::NetworkError, helpers::NO_EXPIRATION, packet::op::SendOpcode};
+use packet::{io::write::PktWrite, Packet};
+
+const DROP_MODE_ANIMATED: u8 = 1;
+const DROP_MODE_EXISTING: u8 = 2;
+/// Delay before the drop animation starts, in milliseconds.
+const DROP_ANIMATION_DELAY: i16 = 0;
+
+pub const DROP_PICKUP_OWNER: u8 = 0;
+pub const DROP_PICKUP_FREE_FOR_ALL: u8 = 2;
+
+pub const REMOVE_DROP_EXPIRED: u8 = 0;
+const REMOVE_DROP_PICKED_UP: u8 = 2;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DropContent {
+    Meso(i32),
+    Item { item_id: i32, quantity: i16 },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForeignDrop {
+    pub object_id: i32,
+    pub content: DropContent,
+    pub owner: i32,
+    pub pickup_type: u8,
+    pub x: i16,
+    pub y: i16,
+    /// Object id of the mob that dropped it.
+    pub source_object_id: i32,
+}
+
+/// Build a drop packet. With `source`, the drop is animated falling out of the
+/// mob at that position; without it the drop is shown already on the ground,
+/// which is what joining occupants should see.
+pub fn build_drop_item_from_map_object(
+    drop: &ForeignDrop,
+    source: Option<(i16, i16)>,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::DropItemFromMapObject as i16)?;
+    packet.write_byte(if source.is_some() {
+        DROP_MODE_ANIMATED
+    } else {
+        DROP_MODE_EXISTING
+    })?;
+    packet.write_int(drop.object_id)?;
+
+    let (is_meso, id_or_amount) = match drop.content {
+        DropContent::Meso(amount) => (true, amount),
+        DropContent::Item { item_id, .. } => (false, item_id),
+    };
+    packet.write_byte(is_meso as u8)?;
+    packet.write_int(id_or_amount)?;
+    packet.write_int(drop.owner)?;
+    packet.write_byte(drop.pickup_type)?;
+    packet.write_short(drop.x)?;
+    packet.write_short(drop.y)?;
+    packet.write_int(drop.source_object_id)?;
+
+    if let Some((source_x, source_y)) = source {
+        packet.write_short(source_x)?;
+        packet.write_short(source_y)?;
+        packet.write_short(DROP_ANIMATION_DELAY)?;
+    }
+    if !is_meso {
+        packet.write_long(NO_EXPIRATION)?;
+    }
+    // Mob drops, not player drops.
+    packet.write_byte(1)?;
+    Ok(packet)
+}
+
+pub fn build_remove_drop(object_id: i32, mode: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::RemoveItemFromMap as i16)?;
+    packet.write_byte(mode)?;
+    packet.write_int(object_id)?;
+    Ok(packet)
+}
+
+/// Remove a drop by showing it fly to the character who picked it up.
+pub fn build_pick_up_drop(object_id: i32, character_id: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::RemoveItemFromMap as i16)?;
+    packet.write_byte(REMOVE_DROP_PICKED_UP)?;
+    packet.write_int(object_id)?;
+    packet.write_int(character_id)?;
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    #[test]
+    fn build_animated_item_drop_matches_expected_payload() {
+        let packet = build_drop_item_from_map_object(
+            &ForeignDrop {
+                object_id: 12,
+                content: DropContent::Item {
+                    item_id: 4000019,
+                    quantity: 1,
+                },
+                owner: 42,
+                pickup_type: DROP_PICKUP_OWNER,
+                x: -100,
+                y: 275,
+                source_object_id: 7,
+            },
+            Some((-120, 275)),
+        )
+        .expect("build drop");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::DropItemFromMapObject as i16
+        );
+        assert_eq!(cursor.read_byte().expect("mode"), DROP_MODE_ANIMATED);
+        assert_eq!(cursor.read_int().expect("object id"), 12);
+        assert_eq!(cursor.read_byte().expect("meso"), 0);
+        assert_eq!(cursor.read_int().expect("item id"), 4000019);
+        assert_eq!(cursor.read_int().expect("owner"), 42);
+        assert_eq!(cursor.read_byte().expect("pickup type"), DROP_PICKUP_OWNER);
+        assert_eq!(cursor.read_short().expect("x"), -100);
+        assert_eq!(cursor.read_short().expect("y"), 275);
+        assert_eq!(cursor.read_int().expect("source"), 7);
+        assert_eq!(cursor.read_short().expect("source x"), -120);
+        assert_eq!(cursor.read_short().expect("source y"), 275);
+        assert_eq!(cursor.read_short().expect("delay"), DROP_ANIMATION_DELAY);
+        assert_eq!(cursor.read_long().expect("expiration"), NO_EXPIRATION);
+        assert_eq!(cursor.read_byte().expect("mob drop"), 1);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn build_existing_meso_drop_skips_source_and_expiration() {
+        let packet = build_drop_item_from_map_object(
+            &ForeignDrop {
+                object_id: 13,
+                content: DropContent::Meso(25),
+                owner: 42,
+                pickup_type: DROP_PICKUP_FREE_FOR_ALL,
+                x: 10,
+                y: 20,
+                source_object_id: 7,
+            },
+            None,
+        )
+        .expect("build drop");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        cursor.read_short().expect("opcode");
+        assert_eq!(cursor.read_byte().expect("mode"), DROP_MODE_EXISTING);
+        assert_eq!(cursor.read_int().expect("object id"), 13);
+        assert_eq!(cursor.read_byte().expect("meso"), 1);
+        assert_eq!(cursor.read_int().expect("amount"), 25);
+        cursor.read_bytes(13).expect("owner through source");
+        assert_eq!(cursor.read_byte().expect("mob drop"), 1);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+}
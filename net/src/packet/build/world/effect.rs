@@ -0,0 +1,42 @@
+use crate::{error::NetworkError, packet::op::SendOpcode};
+use packet::{io::write::PktWrite, Packet};
+
+/// Character effect shown when a character levels up.
+pub const EFFECT_LEVEL_UP: u8 = 0;
+/// Character effect shown when a character uses a skill.
+pub const EFFECT_SKILL_USE: u8 = 1;
+/// Character effect shown when a character advances to a new job.
+pub const EFFECT_JOB_CHANGE: u8 = 8;
+/// Character effect shown when a character completes a quest.
+pub const EFFECT_QUEST_COMPLETE: u8 = 9;
+
+/// Play a character effect on the player's own character.
+pub fn build_show_self_effect(effect: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowSelfEffect as i16)?;
+    packet.write_byte(effect)?;
+    Ok(packet)
+}
+
+/// Play a character effect on another character in the field.
+pub fn build_show_foreign_effect(character_id: i32, effect: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowForeignEffect as i16)?;
+    packet.write_int(character_id)?;
+    packet.write_byte(effect)?;
+    Ok(packet)
+}
+
+/// Play another character's skill animation in the field.
+pub fn build_show_foreign_skill_effect(
+    character_id: i32,
+    skill_id: i32,
+    character_level: i16,
+    skill_level: i16,
+) -> Result<Packet, NetworkError> {
+    let mut packet = build_show_foreign_effect(character_id, EFFECT_SKILL_USE)?;
+    packet.write_int(skill_id)?;
+    packet.write_byte(character_level as u8)?;
+    packet.write_byte(skill_level as u8)?;
+    Ok(packet)
+}
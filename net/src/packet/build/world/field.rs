@@ -1,15 +1,19 @@
+use super::inventory::write_equipment;
 use crate::{error::NetworkError, packet::op::SendOpcode};
 use packet::io::read::PktRead;
 use packet::{io::write::PktWrite, Packet};
 use std::io::Cursor;
 
 const DEFAULT_STANCE: u8 = 2;
+/// `damage_from` of damage a mob dealt by touching the player. Zero and up
+/// are the index of the mob attack that hit.
+pub const DAMAGE_FROM_MOB_TOUCH: i8 = -1;
+/// `damage_from` values of damage from the map itself, such as obstacles and
+/// falls. These come without a mob.
+pub const DAMAGE_FROM_MAP: [i8; 2] = [-3, -4];
 const STARTING_MAP_ID: i32 = 1_000_000;
 const STARTING_MAP_SPAWN_X: i16 = 240;
 const STARTING_MAP_SPAWN_Y: i16 = 190;
-const DEFAULT_OVERALL: i32 = 1052122;
-const DEFAULT_SHOES: i32 = 1072318;
-const DEFAULT_WEAPON: i32 = 1302000;
 
 #[derive(Clone, Debug)]
 pub struct ForeignCharacter {
@@ -21,6 +25,8 @@ pub struct ForeignCharacter {
     pub hair: i32,
     pub skin: i32,
     pub gender: i16,
+    /// Worn item ids by equip position.
+    pub equipment: Vec<(i16, i32)>,
     pub map_id: i32,
     pub x: i16,
     pub y: i16,
@@ -66,6 +72,21 @@ pub fn build_player_enter_field(character: &ForeignCharacter) -> Result<Packet,
     Ok(packet)
 }
 
+/// Show other occupants a character's new look after an equip change.
+pub fn build_update_char_look(character: &ForeignCharacter) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::UpdateCharLook as i16)?;
+    packet.write_int(character.id)?;
+    packet.write_byte(1)?;
+    write_look(&mut packet, character)?;
+    // Crush ring, friendship ring and marriage ring
+    packet.write_byte(0)?;
+    packet.write_byte(0)?;
+    packet.write_byte(0)?;
+    packet.write_int(0)?;
+    Ok(packet)
+}
+
 pub fn build_player_leave_field(character_id: i32) -> Result<Packet, NetworkError> {
     let mut packet = Packet::new_empty();
     packet.write_short(SendOpcode::RemovePlayerFromMap as i16)?;
@@ -82,6 +103,30 @@ pub fn build_player_move(character_id: i32, movement_bytes: &[u8]) -> Result<Pac
     Ok(packet)
 }
 
+/// Show other players that `character_id` took `damage`. `damage_from` is
+/// passed on as the client sent it.
+pub fn build_damage_player(
+    character_id: i32,
+    damage_from: i8,
+    damage: i32,
+    mob_id: i32,
+    direction: u8,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::DamagePlayer as i16)?;
+    packet.write_int(character_id)?;
+    packet.write_byte(damage_from as u8)?;
+    packet.write_int(damage)?;
+    // The client reads a mob for every source but this one.
+    if damage_from != -4 {
+        packet.write_int(mob_id)?;
+        packet.write_byte(direction)?;
+        packet.write_short(0)?; // No power guard reflection
+    }
+    packet.write_int(damage)?;
+    Ok(packet)
+}
+
 pub fn build_local_chat(
     character_id: i32,
     message: &str,
@@ -97,22 +142,16 @@ pub fn build_local_chat(
     Ok(packet)
 }
 
-fn write_look(packet: &mut Packet, character: &ForeignCharacter) -> Result<(), NetworkError> {
+pub(crate) fn write_look(
+    packet: &mut Packet,
+    character: &ForeignCharacter,
+) -> Result<(), NetworkError> {
     packet.write_byte((character.gender != 0) as u8)?;
     packet.write_byte(character.skin as u8)?;
     packet.write_int(character.face)?;
     packet.write_byte(0)?;
     packet.write_int(character.hair)?;
-    packet.write_byte(5)?;
-    packet.write_int(DEFAULT_OVERALL)?;
-    packet.write_byte(7)?;
-    packet.write_int(DEFAULT_SHOES)?;
-    packet.write_byte(0xFF)?;
-    packet.write_byte(0xFF)?;
-    packet.write_int(DEFAULT_WEAPON)?;
-    packet.write_int(0)?;
-    packet.write_int(0)?;
-    packet.write_int(0)?;
+    write_equipment(packet, &character.equipment)?;
     Ok(())
 }
 
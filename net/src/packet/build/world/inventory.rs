@@ -0,0 +1,316 @@
+use crate::{error::NetworkError, helpers::NO_EXPIRATION, packet::op::SendOpcode};
+use db::inventory::{EquipStats, InventoryItem, InventoryType};
+use packet::{io::write::PktWrite, Packet};
+use std::collections::BTreeMap;
+
+const ITEM_TYPE_EQUIP: u8 = 1;
+const ITEM_TYPE_ITEM: u8 = 2;
+
+const MODIFY_INVENTORY_ADD: u8 = 0;
+const MODIFY_INVENTORY_QUANTITY: u8 = 1;
+const MODIFY_INVENTORY_SWAP: u8 = 2;
+const MODIFY_INVENTORY_REMOVE: u8 = 3;
+
+/// Cash weapons are shown over the regular weapon rather than listed with the
+/// other equips.
+const CASH_WEAPON_SLOT: i16 = -111;
+const CASH_EQUIP_OFFSET: i16 = 100;
+
+/// How the client should animate an item move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ItemMovement {
+    Internal = 0,
+    Unequip = 1,
+    Equip = 2,
+}
+
+/// Write a single item the way both the character info and inventory update
+/// packets expect it, without its position.
+pub fn write_item(packet: &mut Packet, item: &InventoryItem) -> Result<(), NetworkError> {
+    match &item.stats {
+        Some(stats) => {
+            packet.write_byte(ITEM_TYPE_EQUIP)?;
+            packet.write_int(item.item_id)?;
+            // Not a cash item
+            packet.write_byte(0)?;
+            packet.write_long(NO_EXPIRATION)?;
+            write_equip_stats(packet, stats)?;
+            packet.write_str_with_length(&item.owner)?;
+            packet.write_short(item.flag)?;
+
+            // Item level, item EXP and Vicious' Hammer uses
+            packet.write_byte(0)?;
+            packet.write_byte(0)?;
+            packet.write_short(0)?;
+            packet.write_short(0)?;
+            packet.write_int(0)?;
+            packet.write_long(0)?;
+
+            packet.write_long(0)?;
+            packet.write_int(-1)?;
+        }
+        None => {
+            packet.write_byte(ITEM_TYPE_ITEM)?;
+            packet.write_int(item.item_id)?;
+            // Not a cash item
+            packet.write_byte(0)?;
+            packet.write_long(NO_EXPIRATION)?;
+            packet.write_short(item.quantity)?;
+            packet.write_str_with_length(&item.owner)?;
+            packet.write_short(item.flag)?;
+
+            // Rechargeable stars and bullets carry an extra unique id.
+            if is_rechargeable(item.item_id) {
+                packet.write_long(0)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_equip_stats(packet: &mut Packet, stats: &EquipStats) -> Result<(), NetworkError> {
+    packet.write_byte(stats.upgrade_slots as u8)?;
+    packet.write_byte(stats.level as u8)?;
+    for stat in [
+        stats.stre,
+        stats.dex,
+        stats.int,
+        stats.luk,
+        stats.hp,
+        stats.mp,
+        stats.watk,
+        stats.matk,
+        stats.wdef,
+        stats.mdef,
+        stats.acc,
+        stats.avoid,
+        stats.hands,
+        stats.speed,
+        stats.jump,
+    ] {
+        packet.write_short(stat)?;
+    }
+    Ok(())
+}
+
+fn is_rechargeable(item_id: i32) -> bool {
+    matches!(item_id / 10000, 207 | 233)
+}
+
+/// Write a character's worn equipment as part of their look. `equipment` holds
+/// item ids by equip position; cash equips sit 100 below the regular slots
+/// and hide whatever regular equip shares their slot.
+pub fn write_equipment(packet: &mut Packet, equipment: &[(i16, i32)]) -> Result<(), NetworkError> {
+    let mut visible = BTreeMap::new();
+    let mut masked = BTreeMap::new();
+    for &(position, item_id) in equipment {
+        if position > -CASH_EQUIP_OFFSET && position < 0 {
+            visible.entry(-position).or_insert(item_id);
+        }
+    }
+    for &(position, item_id) in equipment {
+        if position <= -CASH_EQUIP_OFFSET && position != CASH_WEAPON_SLOT {
+            let slot = -position - CASH_EQUIP_OFFSET;
+            if let Some(hidden) = visible.insert(slot, item_id) {
+                masked.insert(slot, hidden);
+            }
+        }
+    }
+
+    for (slot, item_id) in visible {
+        packet.write_byte(slot as u8)?;
+        packet.write_int(item_id)?;
+    }
+    packet.write_byte(0xFF)?;
+    for (slot, item_id) in masked {
+        packet.write_byte(slot as u8)?;
+        packet.write_int(item_id)?;
+    }
+    packet.write_byte(0xFF)?;
+
+    let cash_weapon = equipment
+        .iter()
+        .find(|(position, _)| *position == CASH_WEAPON_SLOT)
+        .map_or(0, |&(_, item_id)| item_id);
+    packet.write_int(cash_weapon)?;
+
+    // Pets
+    packet.write_int(0)?;
+    packet.write_int(0)?;
+    packet.write_int(0)?;
+    Ok(())
+}
+
+/// Tell the client an item moved from one position to another, swapping with
+/// anything that was already there.
+pub fn build_inventory_move(
+    inventory_type: InventoryType,
+    from: i16,
+    to: i16,
+    movement: ItemMovement,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ModifyInventory as i16)?;
+    // Re-enable the client's actions
+    packet.write_byte(1)?;
+    // Number of modifications
+    packet.write_byte(1)?;
+    packet.write_byte(MODIFY_INVENTORY_SWAP)?;
+    packet.write_byte(inventory_type.into())?;
+    packet.write_short(from)?;
+    packet.write_short(to)?;
+    packet.write_byte(movement as u8)?;
+    Ok(packet)
+}
+
+/// Tell the client an item now sits at the given position. Used both for new
+/// items and for stacks whose quantity changed.
+pub fn build_inventory_add(
+    inventory_type: InventoryType,
+    position: i16,
+    item: &InventoryItem,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ModifyInventory as i16)?;
+    // Re-enable the client's actions
+    packet.write_byte(1)?;
+    // Number of modifications
+    packet.write_byte(1)?;
+    packet.write_byte(MODIFY_INVENTORY_ADD)?;
+    packet.write_byte(inventory_type.into())?;
+    packet.write_short(position)?;
+    write_item(&mut packet, item)?;
+    Ok(packet)
+}
+
+/// Tell the client items were taken from a position, leaving `remaining`
+/// there. An empty position is cleared.
+pub fn build_inventory_take(
+    inventory_type: InventoryType,
+    position: i16,
+    remaining: i16,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ModifyInventory as i16)?;
+    // Re-enable the client's actions
+    packet.write_byte(1)?;
+    // Number of modifications
+    packet.write_byte(1)?;
+    if remaining > 0 {
+        packet.write_byte(MODIFY_INVENTORY_QUANTITY)?;
+        packet.write_byte(inventory_type.into())?;
+        packet.write_short(position)?;
+        packet.write_short(remaining)?;
+    } else {
+        packet.write_byte(MODIFY_INVENTORY_REMOVE)?;
+        packet.write_byte(inventory_type.into())?;
+        packet.write_short(position)?;
+    }
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    #[test]
+    fn build_inventory_add_writes_stackable_item() {
+        let packet = build_inventory_add(InventoryType::Etc, 3, &InventoryItem::new(4000019, 7))
+            .expect("build inventory add");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::ModifyInventory as i16
+        );
+        assert_eq!(cursor.read_byte().expect("update tick"), 1);
+        assert_eq!(cursor.read_byte().expect("count"), 1);
+        assert_eq!(cursor.read_byte().expect("mode"), MODIFY_INVENTORY_ADD);
+        assert_eq!(cursor.read_byte().expect("tab"), 4);
+        assert_eq!(cursor.read_short().expect("position"), 3);
+        assert_eq!(cursor.read_byte().expect("item type"), ITEM_TYPE_ITEM);
+        assert_eq!(cursor.read_int().expect("item id"), 4000019);
+        assert_eq!(cursor.read_byte().expect("cash"), 0);
+        assert_eq!(cursor.read_long().expect("expiration"), NO_EXPIRATION);
+        assert_eq!(cursor.read_short().expect("quantity"), 7);
+        assert_eq!(cursor.read_short().expect("owner length"), 0);
+        assert_eq!(cursor.read_short().expect("flag"), 0);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn build_inventory_take_updates_or_removes_the_stack() {
+        let packet = build_inventory_take(InventoryType::Etc, 3, 20).expect("build take");
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        cursor.read_bytes(4).expect("header");
+        assert_eq!(cursor.read_byte().expect("mode"), MODIFY_INVENTORY_QUANTITY);
+        assert_eq!(cursor.read_byte().expect("tab"), 4);
+        assert_eq!(cursor.read_short().expect("position"), 3);
+        assert_eq!(cursor.read_short().expect("quantity"), 20);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+
+        let packet = build_inventory_take(InventoryType::Etc, 3, 0).expect("build take");
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        cursor.read_bytes(4).expect("header");
+        assert_eq!(cursor.read_byte().expect("mode"), MODIFY_INVENTORY_REMOVE);
+        assert_eq!(cursor.read_byte().expect("tab"), 4);
+        assert_eq!(cursor.read_short().expect("position"), 3);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn write_equipment_lists_regular_and_masked_equips() {
+        let mut packet = Packet::new_empty();
+        write_equipment(
+            &mut packet,
+            &[
+                (-5, 1040002),
+                (-11, 1302000),
+                (-105, 1042003),
+                (-111, 1702000),
+            ],
+        )
+        .expect("write equipment");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(cursor.read_byte().expect("slot"), 5);
+        assert_eq!(cursor.read_int().expect("cash top"), 1042003);
+        assert_eq!(cursor.read_byte().expect("slot"), 11);
+        assert_eq!(cursor.read_int().expect("weapon"), 1302000);
+        assert_eq!(cursor.read_byte().expect("end"), 0xFF);
+        assert_eq!(cursor.read_byte().expect("slot"), 5);
+        assert_eq!(cursor.read_int().expect("masked top"), 1040002);
+        assert_eq!(cursor.read_byte().expect("end"), 0xFF);
+        assert_eq!(cursor.read_int().expect("cash weapon"), 1702000);
+        cursor.read_bytes(12).expect("pets");
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn write_item_writes_equip_stats() {
+        let mut item = InventoryItem::new(1302000, 1);
+        if let Some(stats) = item.stats.as_mut() {
+            stats.upgrade_slots = 7;
+            stats.watk = 17;
+        }
+        let mut packet = Packet::new_empty();
+        write_item(&mut packet, &item).expect("write equip");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(cursor.read_byte().expect("item type"), ITEM_TYPE_EQUIP);
+        assert_eq!(cursor.read_int().expect("item id"), 1302000);
+        cursor.read_byte().expect("cash");
+        cursor.read_long().expect("expiration");
+        assert_eq!(cursor.read_byte().expect("upgrade slots"), 7);
+        assert_eq!(cursor.read_byte().expect("level"), 0);
+        cursor.read_bytes(12).expect("str through mp");
+        assert_eq!(cursor.read_short().expect("watk"), 17);
+        cursor.read_bytes(16).expect("matk through jump");
+        assert_eq!(cursor.read_short().expect("owner length"), 0);
+        assert_eq!(cursor.read_short().expect("flag"), 0);
+        cursor.read_bytes(30).expect("item level through trailer");
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+}
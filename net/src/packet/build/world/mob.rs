@@ -0,0 +1,188 @@
+use crate::{error::NetworkError, packet::op::SendOpcode};
+use packet::{io::write::PktWrite, Packet};
+
+const MOB_SPAWN_UNCONTROLLED: u8 = 5;
+const MOB_SPAWN_EFFECT_NEW: u8 = 0xFE;
+const MOB_SPAWN_EFFECT_EXISTING: u8 = 0xFF;
+const MOB_TEAM_NONE: u8 = 0xFF;
+const MOB_CONTROL_ACTIVE: u8 = 1;
+
+pub const MOB_DEATH_ANIMATION_NORMAL: u8 = 1;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForeignMob {
+    pub object_id: i32,
+    pub mob_id: i32,
+    pub x: i16,
+    pub y: i16,
+    pub stance: u8,
+    pub foothold: i16,
+}
+
+/// Build a SpawnMonster packet. `new_spawn` plays the fade-in effect, which is
+/// what occupants should see when a mob (re)spawns while they are watching.
+pub fn build_spawn_mob(mob: &ForeignMob, new_spawn: bool) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::SpawnMonster as i16)?;
+    packet.write_int(mob.object_id)?;
+    packet.write_byte(MOB_SPAWN_UNCONTROLLED)?;
+    write_mob_body(&mut packet, mob, new_spawn)?;
+    Ok(packet)
+}
+
+pub fn build_kill_mob(object_id: i32, animation: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::KillMonster as i16)?;
+    packet.write_int(object_id)?;
+    packet.write_byte(animation)?;
+    Ok(packet)
+}
+
+/// Update the HP bar shown above a mob, as a percentage of its max HP.
+pub fn build_show_mob_hp(object_id: i32, hp_percent: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowMonsterHp as i16)?;
+    packet.write_int(object_id)?;
+    packet.write_byte(hp_percent)?;
+    Ok(packet)
+}
+
+/// Hand control of a mob to the receiving client, which will then simulate and
+/// report its movement.
+pub fn build_mob_control(mob: &ForeignMob) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::SpawnMonsterControl as i16)?;
+    packet.write_byte(MOB_CONTROL_ACTIVE)?;
+    packet.write_int(mob.object_id)?;
+    packet.write_byte(MOB_SPAWN_UNCONTROLLED)?;
+    write_mob_body(&mut packet, mob, false)?;
+    Ok(packet)
+}
+
+pub fn build_move_mob(
+    object_id: i32,
+    skill_possible: u8,
+    skill: &[u8],
+    start_x: i16,
+    start_y: i16,
+    movement_bytes: &[u8],
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::MoveMonster as i16)?;
+    packet.write_int(object_id)?;
+    packet.write_byte(0)?;
+    packet.write_byte(skill_possible)?;
+    packet.write_bytes(skill)?;
+    packet.write_short(start_x)?;
+    packet.write_short(start_y)?;
+    packet.write_bytes(movement_bytes)?;
+    Ok(packet)
+}
+
+/// Acknowledge a controller's movement report. Mob skills are not simulated
+/// yet, so the controller is never told to use one.
+pub fn build_move_mob_response(
+    object_id: i32,
+    move_id: i16,
+    mp: i16,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::MoveMonsterResponse as i16)?;
+    packet.write_int(object_id)?;
+    packet.write_short(move_id)?;
+    packet.write_byte(0)?;
+    packet.write_short(mp)?;
+    packet.write_byte(0)?;
+    packet.write_byte(0)?;
+    Ok(packet)
+}
+
+fn write_mob_body(
+    packet: &mut Packet,
+    mob: &ForeignMob,
+    new_spawn: bool,
+) -> Result<(), NetworkError> {
+    packet.write_int(mob.mob_id)?;
+
+    // Temporary status mask; no statuses are applied yet.
+    packet.write_bytes(&[0; 15])?;
+    packet.write_byte(0x88)?;
+    packet.write_bytes(&[0; 6])?;
+
+    packet.write_short(mob.x)?;
+    packet.write_short(mob.y)?;
+    packet.write_byte(mob.stance)?;
+    packet.write_short(0)?;
+    packet.write_short(mob.foothold)?;
+    packet.write_byte(if new_spawn {
+        MOB_SPAWN_EFFECT_NEW
+    } else {
+        MOB_SPAWN_EFFECT_EXISTING
+    })?;
+    packet.write_byte(MOB_TEAM_NONE)?;
+    packet.write_int(0)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    #[test]
+    fn build_spawn_mob_matches_expected_payload() {
+        let packet = build_spawn_mob(
+            &ForeignMob {
+                object_id: 7,
+                mob_id: 100100,
+                x: -120,
+                y: 275,
+                stance: 5,
+                foothold: 12,
+            },
+            true,
+        )
+        .expect("build spawn mob");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::SpawnMonster as i16
+        );
+        assert_eq!(cursor.read_int().expect("object id"), 7);
+        assert_eq!(cursor.read_byte().expect("control"), MOB_SPAWN_UNCONTROLLED);
+        assert_eq!(cursor.read_int().expect("mob id"), 100100);
+        cursor.read_bytes(22).expect("status mask");
+        assert_eq!(cursor.read_short().expect("x"), -120);
+        assert_eq!(cursor.read_short().expect("y"), 275);
+        assert_eq!(cursor.read_byte().expect("stance"), 5);
+        assert_eq!(cursor.read_short().expect("origin foothold"), 0);
+        assert_eq!(cursor.read_short().expect("foothold"), 12);
+        assert_eq!(cursor.read_byte().expect("effect"), MOB_SPAWN_EFFECT_NEW);
+        assert_eq!(cursor.read_byte().expect("team"), MOB_TEAM_NONE);
+        assert_eq!(cursor.read_int().expect("trailer"), 0);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn build_move_mob_forwards_movement_fragment() {
+        let packet = build_move_mob(7, 1, &[2, 3, 4, 5, 6], 10, -20, &[1, 0, 10, 0])
+            .expect("build move mob");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::MoveMonster as i16
+        );
+        assert_eq!(cursor.read_int().expect("object id"), 7);
+        assert_eq!(
+            cursor.read_bytes(7).expect("skill"),
+            vec![0, 1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(cursor.read_short().expect("start x"), 10);
+        assert_eq!(cursor.read_short().expect("start y"), -20);
+        assert_eq!(cursor.read_bytes(4).expect("movement"), vec![1, 0, 10, 0]);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+}
@@ -1,7 +1,20 @@
+pub mod attack;
+pub mod buff;
 pub mod channel;
 pub mod char;
+pub mod drop;
+pub mod effect;
 pub mod field;
+pub mod inventory;
 pub mod keymap;
 pub mod map;
 pub mod messaging;
+pub mod mob;
 pub mod npc;
+pub mod party;
+pub mod quest;
+pub mod shop;
+pub mod skill;
+pub mod stat;
+pub mod storage;
+pub mod trade;
@@ -1,6 +1,10 @@
-use crate::{error::NetworkError, packet::op::SendOpcode};
+use crate::{error::NetworkError, npc::Dialog, packet::op::SendOpcode};
 use packet::{io::write::PktWrite, Packet};
 
+/// Object id of a map's first NPC; the rest follow in the order the map lists
+/// them.
+pub const MAP_NPC_OBJECT_ID_BASE: i32 = 1_000_000_000;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ForeignNpc {
     pub object_id: i32,
@@ -27,6 +31,43 @@ pub fn build_spawn_npc(npc: &ForeignNpc) -> Result<Packet, NetworkError> {
     Ok(packet)
 }
 
+pub fn build_npc_talk(npc_id: i32, dialog: &Dialog) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::NpcTalk as i16)?;
+    packet.write_byte(4)?;
+    packet.write_int(npc_id)?;
+    packet.write_byte(dialog.message_type())?;
+    // The NPC speaks, not the player.
+    packet.write_byte(0)?;
+    packet.write_str_with_length(dialog.text())?;
+    match dialog {
+        Dialog::Say { prev, next, .. } => {
+            packet.write_byte(*prev as u8)?;
+            packet.write_byte(*next as u8)?;
+        }
+        Dialog::GetText { default, .. } => {
+            packet.write_str_with_length(default)?;
+            packet.write_int(0)?;
+        }
+        Dialog::GetNumber {
+            default, min, max, ..
+        } => {
+            packet.write_int(*default)?;
+            packet.write_int(*min)?;
+            packet.write_int(*max)?;
+            packet.write_int(0)?;
+        }
+        Dialog::Style { styles, .. } => {
+            packet.write_byte(styles.len() as u8)?;
+            for style in styles {
+                packet.write_int(*style)?;
+            }
+        }
+        Dialog::YesNo { .. } | Dialog::AcceptDecline { .. } | Dialog::Menu { .. } => {}
+    }
+    Ok(packet)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,4 +102,54 @@ mod tests {
         assert_eq!(cursor.read_short().expect("rx0"), 1150);
         assert_eq!(cursor.read_short().expect("rx1"), 1250);
     }
+
+    #[test]
+    fn build_npc_talk_writes_the_dialog_and_its_buttons() {
+        let packet = build_npc_talk(
+            1022000,
+            &Dialog::Say {
+                text: "Hi".to_string(),
+                prev: false,
+                next: true,
+            },
+        )
+        .expect("build npc talk");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::NpcTalk as i16
+        );
+        assert_eq!(cursor.read_byte().expect("talk type"), 4);
+        assert_eq!(cursor.read_int().expect("npc id"), 1022000);
+        assert_eq!(cursor.read_byte().expect("message type"), 0);
+        assert_eq!(cursor.read_byte().expect("speaker"), 0);
+        assert_eq!(cursor.read_str_with_length().expect("text"), "Hi");
+        assert_eq!(cursor.read_byte().expect("prev"), 0);
+        assert_eq!(cursor.read_byte().expect("next"), 1);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+
+        let packet = build_npc_talk(
+            9000000,
+            &Dialog::GetNumber {
+                text: "How many?".to_string(),
+                default: 1,
+                min: 1,
+                max: 100,
+            },
+        )
+        .expect("build npc talk");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        // Skip the opcode, talk type and NPC id.
+        cursor.set_position(7);
+        assert_eq!(cursor.read_byte().expect("message type"), 3);
+        cursor.read_byte().expect("speaker");
+        assert_eq!(cursor.read_str_with_length().expect("text"), "How many?");
+        assert_eq!(cursor.read_int().expect("default"), 1);
+        assert_eq!(cursor.read_int().expect("min"), 1);
+        assert_eq!(cursor.read_int().expect("max"), 100);
+        assert_eq!(cursor.read_int().expect("padding"), 0);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
 }
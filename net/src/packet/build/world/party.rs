@@ -0,0 +1,265 @@
+use crate::party::{Party, MAX_PARTY_SIZE};
+use crate::{error::NetworkError, packet::op::SendOpcode};
+use packet::{io::write::PktWrite, Packet};
+
+const PARTY_INVITE: u8 = 0x04;
+const PARTY_UPDATE: u8 = 0x07;
+const PARTY_CREATED: u8 = 0x08;
+const PARTY_LEFT: u8 = 0x0C;
+const PARTY_JOINED: u8 = 0x0F;
+const PARTY_LEADER_CHANGED: u8 = 0x1B;
+
+/// Messages the client shows when a party request can't go through.
+pub const PARTY_MESSAGE_ALREADY_JOINED: u8 = 16;
+pub const PARTY_MESSAGE_FULL: u8 = 17;
+pub const PARTY_MESSAGE_NOT_FOUND: u8 = 19;
+/// Messages that name the character they are about.
+pub const PARTY_MESSAGE_BUSY: u8 = 22;
+pub const PARTY_MESSAGE_DENIED: u8 = 23;
+
+const MULTI_CHAT_PARTY: u8 = 1;
+const NAME_LENGTH: usize = 13;
+const OFFLINE_CHANNEL: i32 = -2;
+/// Where a member's mystic door leads when it has none.
+const NO_DOOR_MAP: i32 = 999_999_999;
+
+/// Show the creator its new party.
+pub fn build_party_created(party_id: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PartyOperation as i16)?;
+    packet.write_byte(PARTY_CREATED)?;
+    packet.write_int(party_id)?;
+    packet.write_int(NO_DOOR_MAP)?;
+    packet.write_int(NO_DOOR_MAP)?;
+    packet.write_short(0)?;
+    packet.write_short(0)?;
+    Ok(packet)
+}
+
+/// Ask a character to join `party_id`.
+pub fn build_party_invite(party_id: i32, inviter: &str) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PartyOperation as i16)?;
+    packet.write_byte(PARTY_INVITE)?;
+    packet.write_int(party_id)?;
+    packet.write_str_with_length(inviter)?;
+    packet.write_byte(0)?;
+    Ok(packet)
+}
+
+/// Show one of the `PARTY_MESSAGE_*` messages.
+pub fn build_party_message(message: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PartyOperation as i16)?;
+    packet.write_byte(message)?;
+    Ok(packet)
+}
+
+/// Show one of the `PARTY_MESSAGE_*` messages that name a character.
+pub fn build_party_message_about(message: u8, name: &str) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PartyOperation as i16)?;
+    packet.write_byte(message)?;
+    packet.write_str_with_length(name)?;
+    Ok(packet)
+}
+
+/// Show a member on `channel_id` that `name` joined the party.
+pub fn build_party_joined(
+    party: &Party,
+    name: &str,
+    channel_id: u8,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PartyOperation as i16)?;
+    packet.write_byte(PARTY_JOINED)?;
+    packet.write_int(party.id)?;
+    packet.write_str_with_length(name)?;
+    write_party_status(&mut packet, party, channel_id)?;
+    Ok(packet)
+}
+
+/// Show a member on `channel_id`, or the one that left, that a member left
+/// or was expelled.
+pub fn build_party_left(
+    party: &Party,
+    member_id: i32,
+    name: &str,
+    expelled: bool,
+    channel_id: u8,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PartyOperation as i16)?;
+    packet.write_byte(PARTY_LEFT)?;
+    packet.write_int(party.id)?;
+    packet.write_int(member_id)?;
+    packet.write_byte(1)?;
+    packet.write_byte(expelled as u8)?;
+    packet.write_str_with_length(name)?;
+    write_party_status(&mut packet, party, channel_id)?;
+    Ok(packet)
+}
+
+/// Show a member that its leader, `leader_id`, disbanded the party.
+pub fn build_party_disbanded(party_id: i32, leader_id: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PartyOperation as i16)?;
+    packet.write_byte(PARTY_LEFT)?;
+    packet.write_int(party_id)?;
+    packet.write_int(leader_id)?;
+    packet.write_byte(0)?;
+    packet.write_int(party_id)?;
+    Ok(packet)
+}
+
+/// Refresh the party window of a member on `channel_id`, as after a member
+/// logs in or out, changes maps or levels up.
+pub fn build_party_update(party: &Party, channel_id: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PartyOperation as i16)?;
+    packet.write_byte(PARTY_UPDATE)?;
+    packet.write_int(party.id)?;
+    write_party_status(&mut packet, party, channel_id)?;
+    Ok(packet)
+}
+
+pub fn build_party_leader_changed(leader_id: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PartyOperation as i16)?;
+    packet.write_byte(PARTY_LEADER_CHANGED)?;
+    packet.write_int(leader_id)?;
+    packet.write_byte(0)?;
+    Ok(packet)
+}
+
+/// Show a line of party chat. The sender's client shows its own line.
+pub fn build_party_chat(name: &str, message: &str) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::MultiChat as i16)?;
+    packet.write_byte(MULTI_CHAT_PARTY)?;
+    packet.write_str_with_length(name)?;
+    packet.write_str_with_length(message)?;
+    Ok(packet)
+}
+
+/// Update the HP bar of a party member.
+pub fn build_party_member_hp(
+    character_id: i32,
+    hp: i32,
+    max_hp: i32,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::UpdatePartyMemberHp as i16)?;
+    packet.write_int(character_id)?;
+    packet.write_int(hp)?;
+    packet.write_int(max_hp)?;
+    Ok(packet)
+}
+
+/// Write every member slot, column by column. Maps are only shown for
+/// members on the receiver's channel.
+fn write_party_status(
+    packet: &mut Packet,
+    party: &Party,
+    channel_id: u8,
+) -> Result<(), NetworkError> {
+    let slots = || (0..MAX_PARTY_SIZE).map(|index| party.members.get(index));
+
+    for member in slots() {
+        packet.write_int(member.map_or(0, |member| member.id))?;
+    }
+    for member in slots() {
+        let name = member.map_or("", |member| member.name.as_str());
+        let name = &name.as_bytes()[..name.len().min(NAME_LENGTH - 1)];
+        packet.write_bytes(name)?;
+        packet.write_bytes(&vec![0u8; NAME_LENGTH - name.len()])?;
+    }
+    for member in slots() {
+        packet.write_int(member.map_or(0, |member| i32::from(member.job)))?;
+    }
+    for member in slots() {
+        packet.write_int(member.map_or(0, |member| i32::from(member.level)))?;
+    }
+    for member in slots() {
+        let channel = member
+            .and_then(|member| member.channel_id)
+            .map_or(OFFLINE_CHANNEL, i32::from);
+        packet.write_int(channel)?;
+    }
+    packet.write_int(party.leader_id)?;
+    for member in slots() {
+        let map_id = member
+            .filter(|member| member.channel_id == Some(channel_id))
+            .map_or(0, |member| member.map_id);
+        packet.write_int(map_id)?;
+    }
+    // No mystic doors.
+    for member in slots() {
+        let on_channel = member.is_some_and(|member| member.channel_id == Some(channel_id));
+        let (door_map, door_position) = if on_channel {
+            (NO_DOOR_MAP, -1)
+        } else {
+            (0, 0)
+        };
+        packet.write_int(door_map)?;
+        packet.write_int(door_map)?;
+        packet.write_int(door_position)?;
+        packet.write_int(door_position)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::party::PartyMember;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    fn member(id: i32, name: &str, channel_id: Option<u8>) -> PartyMember {
+        PartyMember {
+            id,
+            name: name.to_string(),
+            job: 100,
+            level: 30,
+            channel_id,
+            map_id: 100000000,
+        }
+    }
+
+    #[test]
+    fn party_status_shows_maps_only_on_the_receivers_channel() {
+        let mut party = Party::new(7, member(1, "Leader", Some(0)));
+        party.add(member(2, "Elsewhere", Some(1)));
+        party.add(member(3, "Offline", None));
+
+        let packet = build_party_update(&party, 0).expect("build update");
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::PartyOperation as i16
+        );
+        assert_eq!(cursor.read_byte().expect("mode"), PARTY_UPDATE);
+        assert_eq!(cursor.read_int().expect("party id"), 7);
+        let ids: Vec<i32> = (0..MAX_PARTY_SIZE)
+            .map(|_| cursor.read_int().expect("id"))
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3, 0, 0, 0]);
+        assert_eq!(
+            cursor.read_str(NAME_LENGTH).expect("name"),
+            "Leader\0\0\0\0\0\0\0"
+        );
+        cursor.set_position(cursor.position() + (NAME_LENGTH * 5 + 4 * 12) as u64);
+        let channels: Vec<i32> = (0..MAX_PARTY_SIZE)
+            .map(|_| cursor.read_int().expect("channel"))
+            .collect();
+        assert_eq!(channels, vec![0, 1, -2, -2, -2, -2]);
+        assert_eq!(cursor.read_int().expect("leader"), 1);
+        let maps: Vec<i32> = (0..MAX_PARTY_SIZE)
+            .map(|_| cursor.read_int().expect("map"))
+            .collect();
+        assert_eq!(maps, vec![100000000, 0, 0, 0, 0, 0]);
+        cursor.set_position(cursor.position() + 16 * MAX_PARTY_SIZE as u64);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+}
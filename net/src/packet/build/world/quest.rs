@@ -0,0 +1,128 @@
+use crate::{error::NetworkError, helpers::to_filetime, packet::op::SendOpcode};
+use db::quest::QuestLog;
+use packet::{io::write::PktWrite, Packet};
+use std::time::SystemTime;
+
+const STATUS_INFO_QUEST: u8 = 1;
+
+const QUEST_NOT_STARTED: u8 = 0;
+const QUEST_STARTED: u8 = 1;
+const QUEST_COMPLETED: u8 = 2;
+
+/// `UpdateQuestInfo` result telling the client a quest action went through.
+const QUEST_RESULT_SUCCESS: u8 = 8;
+
+/// Show a started quest, or new progress on it, in the quest log.
+pub fn build_update_quest(quest_id: i32, progress: &str) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowStatusInfo as i16)?;
+    packet.write_byte(STATUS_INFO_QUEST)?;
+    packet.write_short(quest_id as i16)?;
+    packet.write_byte(QUEST_STARTED)?;
+    packet.write_str_with_length(progress)?;
+    Ok(packet)
+}
+
+pub fn build_complete_quest(
+    quest_id: i32,
+    completed_at: SystemTime,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowStatusInfo as i16)?;
+    packet.write_byte(STATUS_INFO_QUEST)?;
+    packet.write_short(quest_id as i16)?;
+    packet.write_byte(QUEST_COMPLETED)?;
+    packet.write_long(to_filetime(completed_at))?;
+    Ok(packet)
+}
+
+/// Drop a forfeited quest from the quest log.
+pub fn build_forfeit_quest(quest_id: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowStatusInfo as i16)?;
+    packet.write_byte(STATUS_INFO_QUEST)?;
+    packet.write_short(quest_id as i16)?;
+    packet.write_byte(QUEST_NOT_STARTED)?;
+    Ok(packet)
+}
+
+/// Tell the client the quest it asked `npc_id` to start or complete went
+/// through, and which quest follows it.
+pub fn build_quest_result(
+    quest_id: i32,
+    npc_id: i32,
+    next_quest: Option<i32>,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::UpdateQuestInfo as i16)?;
+    packet.write_byte(QUEST_RESULT_SUCCESS)?;
+    packet.write_short(quest_id as i16)?;
+    packet.write_int(npc_id)?;
+    packet.write_short(next_quest.unwrap_or(0) as i16)?;
+    Ok(packet)
+}
+
+/// Write the started and completed quests as they appear in character info.
+pub fn write_quest_log(packet: &mut Packet, quests: &QuestLog) -> Result<(), NetworkError> {
+    let started: Vec<_> = quests.started().collect();
+    packet.write_short(started.len() as i16)?;
+    for (quest_id, progress) in started {
+        packet.write_short(quest_id as i16)?;
+        packet.write_str_with_length(progress)?;
+    }
+
+    let completed: Vec<_> = quests.completed().collect();
+    packet.write_short(completed.len() as i16)?;
+    for (quest_id, completed_at) in completed {
+        packet.write_short(quest_id as i16)?;
+        packet.write_long(to_filetime(completed_at))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn write_quest_log_lists_started_then_completed_quests() {
+        let mut quests = QuestLog::from_quest_vec(1, Vec::new());
+        quests.start(1000, "003".to_string());
+        quests.start(1021, String::new());
+        quests.complete(1021, UNIX_EPOCH + Duration::from_millis(1));
+
+        let mut packet = Packet::new_empty();
+        write_quest_log(&mut packet, &quests).expect("write quest log");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(cursor.read_short().expect("started"), 1);
+        assert_eq!(cursor.read_short().expect("quest id"), 1000);
+        assert_eq!(cursor.read_str_with_length().expect("progress"), "003");
+        assert_eq!(cursor.read_short().expect("completed"), 1);
+        assert_eq!(cursor.read_short().expect("quest id"), 1021);
+        assert_eq!(
+            cursor.read_long().expect("completed at"),
+            116_444_736_000_010_000
+        );
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn build_update_quest_writes_progress() {
+        let packet = build_update_quest(1000, "007").expect("build update quest");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::ShowStatusInfo as i16
+        );
+        assert_eq!(cursor.read_byte().expect("mode"), STATUS_INFO_QUEST);
+        assert_eq!(cursor.read_short().expect("quest id"), 1000);
+        assert_eq!(cursor.read_byte().expect("status"), QUEST_STARTED);
+        assert_eq!(cursor.read_str_with_length().expect("progress"), "007");
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{error::NetworkError, packet::op::SendOpcode};
+use ::game_data::ItemTemplate;
+use db::inventory::is_rechargeable;
+use packet::{io::write::PktWrite, Packet};
+
+/// Results the client shows after a shop transaction.
+pub const SHOP_RESULT_BOUGHT: u8 = 0;
+pub const SHOP_RESULT_NOT_ENOUGH_MESO: u8 = 2;
+pub const SHOP_RESULT_INVENTORY_FULL: u8 = 3;
+pub const SHOP_RESULT_SOLD: u8 = 8;
+
+/// Open `npc_id`'s shop, listing each of `items` at its price.
+pub fn build_open_shop(
+    npc_id: i32,
+    items: &[(&ItemTemplate, i32)],
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::OpenNpcShop as i16)?;
+    packet.write_int(npc_id)?;
+    packet.write_short(items.len() as i16)?;
+    for &(item, price) in items {
+        packet.write_int(item.item_id)?;
+        packet.write_int(price)?;
+        // Perfect pitch price, minutes before the item can be used, and an
+        // unused int.
+        packet.write_int(0)?;
+        packet.write_int(0)?;
+        packet.write_int(0)?;
+        if is_rechargeable(item.item_id) {
+            packet.write_short(0)?;
+            packet.write_int(0)?;
+            // The client reads the unit price as the top 16 bits of a double.
+            packet.write_short((item.unit_price.to_bits() >> 48) as i16)?;
+            packet.write_short(item.slot_max)?;
+        } else {
+            packet.write_short(1)?;
+            // Most the player can buy at once.
+            packet.write_short(item.slot_max)?;
+        }
+    }
+    Ok(packet)
+}
+
+pub fn build_shop_result(result: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ConfirmShopTransaction as i16)?;
+    packet.write_byte(result)?;
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    fn template(item_id: i32, price: i32, unit_price: f64, slot_max: i16) -> ItemTemplate {
+        ItemTemplate {
+            item_id,
+            price,
+            unit_price,
+            slot_max,
+            cash: false,
+            equip: None,
+        }
+    }
+
+    #[test]
+    fn build_open_shop_lists_rechargeables_with_their_unit_price() {
+        let potion = template(2000000, 50, 0.0, 100);
+        let stars = template(2070000, 500, 1.0, 500);
+        let packet =
+            build_open_shop(1011100, &[(&potion, 50), (&stars, 1000)]).expect("build open shop");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::OpenNpcShop as i16
+        );
+        assert_eq!(cursor.read_int().expect("npc id"), 1011100);
+        assert_eq!(cursor.read_short().expect("count"), 2);
+
+        assert_eq!(cursor.read_int().expect("item id"), 2000000);
+        assert_eq!(cursor.read_int().expect("price"), 50);
+        cursor.set_position(cursor.position() + 12);
+        assert_eq!(cursor.read_short().expect("not rechargeable"), 1);
+        assert_eq!(cursor.read_short().expect("buyable"), 100);
+
+        assert_eq!(cursor.read_int().expect("item id"), 2070000);
+        assert_eq!(cursor.read_int().expect("price"), 1000);
+        cursor.set_position(cursor.position() + 12);
+        assert_eq!(cursor.read_short().expect("rechargeable"), 0);
+        assert_eq!(cursor.read_int().expect("unused"), 0);
+        assert_eq!(cursor.read_short().expect("unit price") as u16, 0x3FF0);
+        assert_eq!(cursor.read_short().expect("slot max"), 500);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+}
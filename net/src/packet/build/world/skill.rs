@@ -0,0 +1,83 @@
+use crate::{error::NetworkError, helpers::NO_EXPIRATION, packet::op::SendOpcode};
+use ::game_data::is_fourth_job_skill;
+use db::skill::{SkillBook, SkillEntry};
+use packet::{io::write::PktWrite, Packet};
+
+/// Update one skill in the client's skill window.
+pub fn build_update_skill(skill_id: i32, entry: SkillEntry) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::UpdateSkills as i16)?;
+    packet.write_byte(1)?;
+    packet.write_short(1)?; // Skill count
+    packet.write_int(skill_id)?;
+    packet.write_int(i32::from(entry.level))?;
+    packet.write_int(i32::from(entry.master_level))?;
+    packet.write_long(NO_EXPIRATION)?;
+    packet.write_byte(4)?;
+    Ok(packet)
+}
+
+/// Show a skill as cooling down for `seconds`. Zero clears the cooldown.
+pub fn build_cooldown(skill_id: i32, seconds: i16) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::Cooldown as i16)?;
+    packet.write_int(skill_id)?;
+    packet.write_short(seconds)?;
+    Ok(packet)
+}
+
+/// Write the skill book as it appears in character info. Only fourth job
+/// skills carry a master level.
+pub fn write_skill_book(packet: &mut Packet, skills: &SkillBook) -> Result<(), NetworkError> {
+    let learned: Vec<_> = skills.skills().collect();
+    packet.write_short(learned.len() as i16)?;
+    for (skill_id, entry) in learned {
+        packet.write_int(skill_id)?;
+        packet.write_int(i32::from(entry.level))?;
+        packet.write_long(NO_EXPIRATION)?;
+        if is_fourth_job_skill(skill_id) {
+            packet.write_int(i32::from(entry.master_level))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    #[test]
+    fn write_skill_book_adds_master_levels_for_fourth_job_skills() {
+        let mut skills = SkillBook::from_skill_vec(1, Vec::new());
+        skills.set(
+            1001004,
+            SkillEntry {
+                level: 3,
+                master_level: 0,
+            },
+        );
+        skills.set(
+            1121008,
+            SkillEntry {
+                level: 1,
+                master_level: 20,
+            },
+        );
+
+        let mut packet = Packet::new_empty();
+        write_skill_book(&mut packet, &skills).expect("write skill book");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(cursor.read_short().expect("count"), 2);
+        assert_eq!(cursor.read_int().expect("skill id"), 1001004);
+        assert_eq!(cursor.read_int().expect("level"), 3);
+        assert_eq!(cursor.read_long().expect("expiration"), NO_EXPIRATION);
+        assert_eq!(cursor.read_int().expect("skill id"), 1121008);
+        assert_eq!(cursor.read_int().expect("level"), 1);
+        assert_eq!(cursor.read_long().expect("expiration"), NO_EXPIRATION);
+        assert_eq!(cursor.read_int().expect("master level"), 20);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+}
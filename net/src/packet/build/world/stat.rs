@@ -0,0 +1,303 @@
+use crate::{error::NetworkError, packet::op::SendOpcode};
+use db::character::Character;
+use packet::{io::write::PktWrite, Packet};
+
+const STATUS_INFO_DROP_PICKUP: u8 = 0;
+const STATUS_INFO_EXP_GAIN: u8 = 3;
+const STATUS_INFO_FAME_GAIN: u8 = 4;
+const DROP_PICKUP_INVENTORY_FULL: u8 = 0xFF;
+const DROP_PICKUP_ITEM: u8 = 0;
+const DROP_PICKUP_MESO: u8 = 1;
+
+/// Character stats a `StatChange` can update, as their bit in the update
+/// mask. Values are written in mask order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stat {
+    Skin = 0x1,
+    Face = 0x2,
+    Hair = 0x4,
+    Level = 0x10,
+    Job = 0x20,
+    Str = 0x40,
+    Dex = 0x80,
+    Int = 0x100,
+    Luk = 0x200,
+    Hp = 0x400,
+    MaxHp = 0x800,
+    Mp = 0x1000,
+    MaxMp = 0x2000,
+    Ap = 0x4000,
+    Sp = 0x8000,
+    Exp = 0x10000,
+    Fame = 0x20000,
+    Meso = 0x40000,
+}
+
+impl Stat {
+    /// Every stat, in mask order.
+    pub const ALL: [Stat; 18] = [
+        Stat::Skin,
+        Stat::Face,
+        Stat::Hair,
+        Stat::Level,
+        Stat::Job,
+        Stat::Str,
+        Stat::Dex,
+        Stat::Int,
+        Stat::Luk,
+        Stat::Hp,
+        Stat::MaxHp,
+        Stat::Mp,
+        Stat::MaxMp,
+        Stat::Ap,
+        Stat::Sp,
+        Stat::Exp,
+        Stat::Fame,
+        Stat::Meso,
+    ];
+
+    /// The stat with exactly this mask bit.
+    pub fn from_mask(mask: i32) -> Option<Stat> {
+        Self::ALL.iter().copied().find(|&stat| stat as i32 == mask)
+    }
+
+    pub(crate) fn value(self, character: &Character) -> i32 {
+        match self {
+            Stat::Skin => character.skin,
+            Stat::Face => character.face,
+            Stat::Hair => character.hair,
+            Stat::Level => i32::from(character.level),
+            Stat::Job => i32::from(character.job),
+            Stat::Str => i32::from(character.stre),
+            Stat::Dex => i32::from(character.dex),
+            Stat::Int => i32::from(character.int),
+            Stat::Luk => i32::from(character.luk),
+            Stat::Hp => i32::from(character.hp),
+            Stat::MaxHp => i32::from(character.maxhp),
+            Stat::Mp => i32::from(character.mp),
+            Stat::MaxMp => i32::from(character.maxmp),
+            Stat::Ap => i32::from(character.ap),
+            Stat::Sp => i32::from(character.sp),
+            Stat::Exp => character.exp,
+            Stat::Fame => i32::from(character.fame),
+            Stat::Meso => character.meso,
+        }
+    }
+}
+
+/// Stats whose value differs between the two snapshots of a character.
+pub fn changed_stats(before: &Character, after: &Character) -> Vec<Stat> {
+    Stat::ALL
+        .iter()
+        .copied()
+        .filter(|stat| stat.value(before) != stat.value(after))
+        .collect()
+}
+
+/// Update `stats` on the client with the character's current values.
+/// `item_reaction` re-enables the client's actions.
+pub fn build_stat_update(
+    character: &Character,
+    stats: &[Stat],
+    item_reaction: bool,
+) -> Result<Packet, NetworkError> {
+    let mut stats = stats.to_vec();
+    stats.sort();
+    stats.dedup();
+
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::StatChange as i16)?;
+    packet.write_byte(item_reaction as u8)?;
+    packet.write_int(stats.iter().fold(0, |mask, &stat| mask | stat as i32))?;
+    for stat in stats {
+        let value = stat.value(character);
+        match stat {
+            Stat::Skin | Stat::Level => {
+                packet.write_byte(value as u8)?;
+            }
+            Stat::Face | Stat::Hair | Stat::Exp | Stat::Meso => {
+                packet.write_int(value)?;
+            }
+            _ => {
+                packet.write_short(value as i16)?;
+            }
+        }
+    }
+    Ok(packet)
+}
+
+/// Update only the stats that changed between `before` and `after`.
+pub fn build_stat_changes(
+    before: &Character,
+    after: &Character,
+    item_reaction: bool,
+) -> Result<Packet, NetworkError> {
+    build_stat_update(after, &changed_stats(before, after), item_reaction)
+}
+
+/// Update the client's meso count. `item_reaction` re-enables the client's
+/// actions, which it locks while waiting for a pickup or similar request.
+pub fn build_meso_update(meso: i32, item_reaction: bool) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::StatChange as i16)?;
+    packet.write_byte(item_reaction as u8)?;
+    packet.write_int(Stat::Meso as i32)?;
+    packet.write_int(meso)?;
+    Ok(packet)
+}
+
+pub fn build_show_meso_gain(gain: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowStatusInfo as i16)?;
+    packet.write_byte(STATUS_INFO_DROP_PICKUP)?;
+    packet.write_byte(DROP_PICKUP_MESO)?;
+    packet.write_byte(0)?;
+    packet.write_int(gain)?;
+    packet.write_short(0)?; // Internet cafe bonus
+    Ok(packet)
+}
+
+pub fn build_show_item_gain(item_id: i32, quantity: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowStatusInfo as i16)?;
+    packet.write_byte(STATUS_INFO_DROP_PICKUP)?;
+    packet.write_byte(DROP_PICKUP_ITEM)?;
+    packet.write_int(item_id)?;
+    packet.write_int(quantity)?;
+    Ok(packet)
+}
+
+pub fn build_show_inventory_full() -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowStatusInfo as i16)?;
+    packet.write_byte(STATUS_INFO_DROP_PICKUP)?;
+    packet.write_byte(DROP_PICKUP_INVENTORY_FULL)?;
+    packet.write_int(0)?;
+    packet.write_int(0)?;
+    Ok(packet)
+}
+
+pub fn build_show_fame_gain(gain: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowStatusInfo as i16)?;
+    packet.write_byte(STATUS_INFO_FAME_GAIN)?;
+    packet.write_int(gain)?;
+    Ok(packet)
+}
+
+/// Build the "You have gained experience" status line. `white` is used for the
+/// player who landed the last hit; other contributors see it in yellow.
+pub fn build_show_exp_gain(gain: i32, white: bool) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::ShowStatusInfo as i16)?;
+    packet.write_byte(STATUS_INFO_EXP_GAIN)?;
+    packet.write_byte(white as u8)?;
+    packet.write_int(gain)?;
+    packet.write_byte(0)?; // In chat
+    packet.write_int(0)?; // Event bonus
+    packet.write_short(0)?;
+    packet.write_int(0)?; // Wedding bonus
+    packet.write_byte(0)?;
+    packet.write_int(0)?; // Party bonus
+    packet.write_int(0)?; // Equip bonus
+    packet.write_int(0)?; // Internet cafe bonus
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    #[test]
+    fn build_show_exp_gain_matches_expected_payload() {
+        let packet = build_show_exp_gain(35, true).expect("build exp gain");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::ShowStatusInfo as i16
+        );
+        assert_eq!(cursor.read_byte().expect("mode"), STATUS_INFO_EXP_GAIN);
+        assert_eq!(cursor.read_byte().expect("white"), 1);
+        assert_eq!(cursor.read_int().expect("gain"), 35);
+        assert_eq!(cursor.read_byte().expect("in chat"), 0);
+        cursor.read_bytes(23).expect("bonuses");
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn build_meso_update_writes_meso_mask() {
+        let packet = build_meso_update(1200, true).expect("build meso update");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::StatChange as i16
+        );
+        assert_eq!(cursor.read_byte().expect("item reaction"), 1);
+        assert_eq!(cursor.read_int().expect("mask"), Stat::Meso as i32);
+        assert_eq!(cursor.read_int().expect("meso"), 1200);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn build_stat_update_writes_stats_in_mask_order() {
+        let mut character = crate::stats::test_character();
+        character.level = 12;
+        character.exp = 345;
+        character.ap = 5;
+        character.skin = 3;
+
+        let packet = build_stat_update(
+            &character,
+            &[Stat::Exp, Stat::Level, Stat::Skin, Stat::Ap],
+            false,
+        )
+        .expect("build stat update");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::StatChange as i16
+        );
+        assert_eq!(cursor.read_byte().expect("item reaction"), 0);
+        assert_eq!(
+            cursor.read_int().expect("mask"),
+            0x1 | 0x10 | 0x4000 | 0x10000
+        );
+        assert_eq!(cursor.read_byte().expect("skin"), 3);
+        assert_eq!(cursor.read_byte().expect("level"), 12);
+        assert_eq!(cursor.read_short().expect("ap"), 5);
+        assert_eq!(cursor.read_int().expect("exp"), 345);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn build_stat_changes_writes_only_changed_stats() {
+        let mut before = crate::stats::test_character();
+        before.ap = 5;
+        let mut after = before.clone();
+        after.dex += 1;
+        after.ap -= 1;
+
+        assert_eq!(changed_stats(&before, &after), vec![Stat::Dex, Stat::Ap]);
+
+        let packet = build_stat_changes(&before, &after, true).expect("build stat changes");
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        cursor.read_short().expect("opcode");
+        assert_eq!(cursor.read_byte().expect("item reaction"), 1);
+        assert_eq!(cursor.read_int().expect("mask"), 0x80 | 0x4000);
+        assert_eq!(cursor.read_short().expect("dex"), after.dex);
+        assert_eq!(cursor.read_short().expect("ap"), after.ap);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+
+    #[test]
+    fn stat_from_mask_only_accepts_single_stats() {
+        assert_eq!(Stat::from_mask(0x800), Some(Stat::MaxHp));
+        assert_eq!(Stat::from_mask(0x40 | 0x80), None);
+        assert_eq!(Stat::from_mask(0x8), None);
+    }
+}
@@ -0,0 +1,155 @@
+use super::inventory::write_item;
+use crate::{error::NetworkError, packet::op::SendOpcode};
+use db::inventory::{InventoryItem, InventoryType};
+use db::storage::AccountStorage;
+use packet::{io::write::PktWrite, Packet};
+
+const STORAGE_TAKE_OUT: u8 = 0x09;
+const STORAGE_STORE: u8 = 0x0D;
+const STORAGE_ARRANGE: u8 = 0x0F;
+const STORAGE_MESO: u8 = 0x13;
+const STORAGE_OPEN: u8 = 0x16;
+
+/// Messages the client shows when the storage refuses an action.
+pub const STORAGE_RESULT_INVENTORY_FULL: u8 = 0x0A;
+pub const STORAGE_RESULT_NOT_ENOUGH_MESO: u8 = 0x0B;
+pub const STORAGE_RESULT_STORAGE_FULL: u8 = 0x11;
+
+/// Which parts of the storage window an update replaces: mesos, or the items
+/// of one tab.
+const STORAGE_FLAG_MESO: i16 = 0x02;
+const STORAGE_FLAG_ALL: i16 = 0x7E;
+
+fn tab_flag(inventory_type: InventoryType) -> i16 {
+    4 << (u8::from(inventory_type) - 1)
+}
+
+/// Open the storage window of `npc_id` showing everything stored.
+pub fn build_open_storage(npc_id: i32, storage: &AccountStorage) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::Storage as i16)?;
+    packet.write_byte(STORAGE_OPEN)?;
+    packet.write_int(npc_id)?;
+    packet.write_byte(storage.slots())?;
+    packet.write_short(STORAGE_FLAG_ALL)?;
+    packet.write_short(0)?;
+    packet.write_int(0)?;
+    packet.write_int(storage.meso())?;
+    packet.write_short(0)?;
+    write_items(&mut packet, storage.items().iter())?;
+    packet.write_short(0)?;
+    packet.write_byte(0)?;
+    Ok(packet)
+}
+
+/// Show the items of one tab after an item was stored.
+pub fn build_storage_stored(
+    storage: &AccountStorage,
+    inventory_type: InventoryType,
+) -> Result<Packet, NetworkError> {
+    build_storage_tab(STORAGE_STORE, storage, inventory_type)
+}
+
+/// Show the items of one tab after an item was taken out.
+pub fn build_storage_taken_out(
+    storage: &AccountStorage,
+    inventory_type: InventoryType,
+) -> Result<Packet, NetworkError> {
+    build_storage_tab(STORAGE_TAKE_OUT, storage, inventory_type)
+}
+
+fn build_storage_tab(
+    mode: u8,
+    storage: &AccountStorage,
+    inventory_type: InventoryType,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::Storage as i16)?;
+    packet.write_byte(mode)?;
+    packet.write_byte(storage.slots())?;
+    packet.write_short(tab_flag(inventory_type))?;
+    packet.write_short(0)?;
+    packet.write_int(0)?;
+    write_items(&mut packet, storage.items_of(inventory_type).into_iter())?;
+    Ok(packet)
+}
+
+/// Show every stored item again after the storage was arranged.
+pub fn build_storage_arranged(storage: &AccountStorage) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::Storage as i16)?;
+    packet.write_byte(STORAGE_ARRANGE)?;
+    packet.write_byte(storage.slots())?;
+    packet.write_byte(124)?;
+    packet.write_bytes(&[0; 10])?;
+    write_items(&mut packet, storage.items().iter())?;
+    packet.write_byte(0)?;
+    Ok(packet)
+}
+
+/// Show the mesos stored after a deposit or withdrawal.
+pub fn build_storage_meso(storage: &AccountStorage) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::Storage as i16)?;
+    packet.write_byte(STORAGE_MESO)?;
+    packet.write_byte(storage.slots())?;
+    packet.write_short(STORAGE_FLAG_MESO)?;
+    packet.write_short(0)?;
+    packet.write_int(0)?;
+    packet.write_int(storage.meso())?;
+    Ok(packet)
+}
+
+pub fn build_storage_result(result: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::Storage as i16)?;
+    packet.write_byte(result)?;
+    Ok(packet)
+}
+
+fn write_items<'a>(
+    packet: &mut Packet,
+    items: impl ExactSizeIterator<Item = &'a InventoryItem>,
+) -> Result<(), NetworkError> {
+    packet.write_byte(items.len() as u8)?;
+    for item in items {
+        write_item(packet, item)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::storage::Storage;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    #[test]
+    fn build_storage_stored_lists_only_the_items_of_the_tab() {
+        let storage = Storage {
+            id: 1,
+            account_id: 1,
+            world: 0,
+            slots: 4,
+            meso: 0,
+        };
+        let mut storage = AccountStorage::from_item_vec(storage, Vec::new());
+        storage.put(InventoryItem::new(4000019, 10));
+        storage.put(InventoryItem::new(2000000, 50));
+
+        let packet = build_storage_stored(&storage, InventoryType::Use).expect("build stored");
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::Storage as i16
+        );
+        assert_eq!(cursor.read_byte().expect("mode"), STORAGE_STORE);
+        assert_eq!(cursor.read_byte().expect("slots"), 4);
+        assert_eq!(cursor.read_short().expect("tab flag"), 0x08);
+        cursor.set_position(cursor.position() + 6);
+        assert_eq!(cursor.read_byte().expect("count"), 1);
+        assert_eq!(cursor.read_byte().expect("item type"), 2);
+        assert_eq!(cursor.read_int().expect("item id"), 2000000);
+    }
+}
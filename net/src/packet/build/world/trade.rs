@@ -0,0 +1,188 @@
+use super::field::{write_look, ForeignCharacter};
+use super::inventory::write_item;
+use crate::{error::NetworkError, packet::op::SendOpcode};
+use db::inventory::InventoryItem;
+use packet::{io::write::PktWrite, Packet};
+
+const INTERACTION_INVITE: u8 = 0x02;
+const INTERACTION_VISIT: u8 = 0x04;
+const INTERACTION_ROOM: u8 = 0x05;
+const INTERACTION_CHAT: u8 = 0x06;
+const INTERACTION_CHAT_MESSAGE: u8 = 0x08;
+const INTERACTION_EXIT: u8 = 0x0A;
+const INTERACTION_SET_ITEMS: u8 = 0x0F;
+const INTERACTION_SET_MESO: u8 = 0x10;
+const INTERACTION_CONFIRM: u8 = 0x11;
+
+const ROOM_TYPE_TRADE: u8 = 3;
+const TRADE_CAPACITY: u8 = 2;
+const ROOM_END: u8 = 0xFF;
+
+/// Why a trade window closed.
+pub const TRADE_RESULT_PARTNER_CANCEL: u8 = 2;
+pub const TRADE_RESULT_SUCCESSFUL: u8 = 7;
+pub const TRADE_RESULT_UNSUCCESSFUL: u8 = 8;
+
+/// Open the trade window. The owner (`number` 0) opens it empty; the visitor
+/// (`number` 1) opens it with the owner already in it.
+pub fn build_trade_room(
+    character: &ForeignCharacter,
+    partner: Option<&ForeignCharacter>,
+) -> Result<Packet, NetworkError> {
+    let number = partner.is_some() as u8;
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PlayerInteraction as i16)?;
+    packet.write_byte(INTERACTION_ROOM)?;
+    packet.write_byte(ROOM_TYPE_TRADE)?;
+    packet.write_byte(TRADE_CAPACITY)?;
+    packet.write_byte(number)?;
+    if let Some(partner) = partner {
+        packet.write_byte(0)?;
+        write_trader(&mut packet, partner)?;
+    }
+    packet.write_byte(number)?;
+    write_trader(&mut packet, character)?;
+    packet.write_byte(ROOM_END)?;
+    Ok(packet)
+}
+
+/// Ask a character to join `room_id`, opened by `inviter`.
+pub fn build_trade_invite(inviter: &str, room_id: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PlayerInteraction as i16)?;
+    packet.write_byte(INTERACTION_INVITE)?;
+    packet.write_byte(ROOM_TYPE_TRADE)?;
+    packet.write_str_with_length(inviter)?;
+    packet.write_int(room_id)?;
+    Ok(packet)
+}
+
+/// Show the owner of a trade window that the invited character joined.
+pub fn build_trade_partner_joined(partner: &ForeignCharacter) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PlayerInteraction as i16)?;
+    packet.write_byte(INTERACTION_VISIT)?;
+    packet.write_byte(1)?;
+    write_trader(&mut packet, partner)?;
+    Ok(packet)
+}
+
+/// Show an offered item in trade slot `slot`, on the receiver's side
+/// (`number` 0) or on the partner's (`number` 1).
+pub fn build_trade_item(
+    number: u8,
+    slot: u8,
+    item: &InventoryItem,
+) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PlayerInteraction as i16)?;
+    packet.write_byte(INTERACTION_SET_ITEMS)?;
+    packet.write_byte(number)?;
+    packet.write_byte(slot)?;
+    write_item(&mut packet, item)?;
+    Ok(packet)
+}
+
+/// Show the mesos offered by the receiver (`number` 0) or the partner
+/// (`number` 1).
+pub fn build_trade_meso(number: u8, meso: i32) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PlayerInteraction as i16)?;
+    packet.write_byte(INTERACTION_SET_MESO)?;
+    packet.write_byte(number)?;
+    packet.write_int(meso)?;
+    Ok(packet)
+}
+
+/// Show that the partner confirmed the trade.
+pub fn build_trade_confirmed() -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PlayerInteraction as i16)?;
+    packet.write_byte(INTERACTION_CONFIRM)?;
+    Ok(packet)
+}
+
+/// Show a line of trade chat said by the receiver (`number` 0) or the
+/// partner (`number` 1).
+pub fn build_trade_chat(number: u8, name: &str, message: &str) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PlayerInteraction as i16)?;
+    packet.write_byte(INTERACTION_CHAT)?;
+    packet.write_byte(INTERACTION_CHAT_MESSAGE)?;
+    packet.write_byte(number)?;
+    packet.write_str_with_length(&format!("{} : {}", name, message))?;
+    Ok(packet)
+}
+
+/// Close the trade window of the receiver, whose place in it is `number`,
+/// with one of the `TRADE_RESULT_*` messages.
+pub fn build_trade_result(number: u8, result: u8) -> Result<Packet, NetworkError> {
+    let mut packet = Packet::new_empty();
+    packet.write_short(SendOpcode::PlayerInteraction as i16)?;
+    packet.write_byte(INTERACTION_EXIT)?;
+    packet.write_byte(number)?;
+    packet.write_byte(result)?;
+    Ok(packet)
+}
+
+fn write_trader(packet: &mut Packet, character: &ForeignCharacter) -> Result<(), NetworkError> {
+    write_look(packet, character)?;
+    packet.write_str_with_length(&character.name)?;
+    packet.write_short(character.job)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packet::io::read::PktRead;
+    use std::io::Cursor;
+
+    fn trader(id: i32, name: &str) -> ForeignCharacter {
+        ForeignCharacter {
+            id,
+            name: name.to_string(),
+            level: 10,
+            job: 100,
+            face: 20000,
+            hair: 30000,
+            skin: 0,
+            gender: 0,
+            equipment: Vec::new(),
+            map_id: 100000000,
+            x: 0,
+            y: 0,
+            stance: 0,
+        }
+    }
+
+    #[test]
+    fn visitor_room_lists_the_owner_before_the_visitor() {
+        let owner = trader(1, "Owner");
+        let visitor = trader(2, "Visitor");
+        let packet = build_trade_room(&visitor, Some(&owner)).expect("build room");
+
+        let mut cursor = Cursor::new(&packet.bytes[..]);
+        assert_eq!(
+            cursor.read_short().expect("opcode"),
+            SendOpcode::PlayerInteraction as i16
+        );
+        assert_eq!(cursor.read_byte().expect("mode"), INTERACTION_ROOM);
+        assert_eq!(cursor.read_byte().expect("type"), ROOM_TYPE_TRADE);
+        assert_eq!(cursor.read_byte().expect("capacity"), TRADE_CAPACITY);
+        assert_eq!(cursor.read_byte().expect("number"), 1);
+        assert_eq!(cursor.read_byte().expect("owner number"), 0);
+
+        let mut look = Packet::new_empty();
+        write_look(&mut look, &owner).expect("write look");
+        cursor.set_position(cursor.position() + look.bytes.len() as u64);
+        assert_eq!(cursor.read_str_with_length().expect("owner"), "Owner");
+        assert_eq!(cursor.read_short().expect("owner job"), 100);
+        assert_eq!(cursor.read_byte().expect("visitor number"), 1);
+        cursor.set_position(cursor.position() + look.bytes.len() as u64);
+        assert_eq!(cursor.read_str_with_length().expect("visitor"), "Visitor");
+        cursor.set_position(cursor.position() + 2);
+        assert_eq!(cursor.read_byte().expect("end"), ROOM_END);
+        assert_eq!(cursor.position() as usize, packet.bytes.len());
+    }
+}
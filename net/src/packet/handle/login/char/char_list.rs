@@ -2,6 +2,7 @@ use crate::error::NetworkError;
 use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
 use crate::packet::build::login::char;
 use db::character;
+use db::inventory::Inventory;
 use packet::{io::read::PktRead, Packet};
 use std::io::BufReader;
 
@@ -34,7 +35,10 @@ impl PacketHandler for CharListHandler {
             .map(|s| s.account_id)
             .ok_or(NetworkError::NotLoggedIn)?;
 
-        let chars = character::get_characters_by_accountid(account_id)?;
+        let chars = character::get_characters_by_accountid(account_id)?
+            .into_iter()
+            .map(|chr| Inventory::from_character(&chr).map(|inventory| (chr, inventory)))
+            .collect::<Result<Vec<_>, _>>()?;
         let char_list_packet = char::build_char_list(chars)?;
         Ok(HandlerResult::reply(char_list_packet).with_update_session_selection(world, channel))
     }
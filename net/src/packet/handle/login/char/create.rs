@@ -1,10 +1,14 @@
 use crate::error::NetworkError;
+use crate::game_data;
 use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
 use crate::packet::build::login;
 use db::character::NewCharacter;
+use db::inventory;
 use packet::{io::read::PktRead, Packet};
 use std::io::BufReader;
 
+const GENDER_FEMALE: i16 = 1;
+
 pub struct CreateCharacterHandler;
 
 impl CreateCharacterHandler {
@@ -36,12 +40,38 @@ impl PacketHandler for CreateCharacterHandler {
         let hair = reader.read_int()?;
         let hair_color = reader.read_int()?;
         let skin = reader.read_int()?;
-        let _top = reader.read_int()?; // Slot 5
-        let _bot = reader.read_int()?; // Slot 6
-        let _shoes = reader.read_int()?; // Slot 7
-        let _weapon = reader.read_int()?; // Special
+        let top = reader.read_int()?; // Slot 5
+        let bot = reader.read_int()?; // Slot 6
+        let shoes = reader.read_int()?; // Slot 7
+        let weapon = reader.read_int()?; // Special
         let gender = reader.read_byte()? as i16;
 
+        // Starter equips have to be ones the creation screen offers this job
+        // and gender. An overall already covers the bottom slot.
+        let choices = game_data::creation()?
+            .choices(job, gender == GENDER_FEMALE)
+            .ok_or(NetworkError::PacketHandlerError(
+                "Unknown creation job type",
+            ))?;
+        let mut starter_equips: Vec<(i32, &[i32])> = vec![
+            (top, &choices.tops),
+            (shoes, &choices.shoes),
+            (weapon, &choices.weapons),
+        ];
+        if !inventory::is_overall(top) {
+            starter_equips.push((bot, &choices.bottoms));
+        }
+        if starter_equips
+            .iter()
+            .any(|&(item_id, allowed)| !allowed.contains(&item_id))
+        {
+            return Err(NetworkError::PacketHandlerError("Invalid starter equip"));
+        }
+        let starter_equips = starter_equips
+            .iter()
+            .map(|&(item_id, _)| game_data::create_item(item_id, 1))
+            .collect::<Result<Vec<_>, _>>()?;
+
         let world = ctx
             .session
             .session
@@ -61,7 +91,7 @@ impl PacketHandler for CreateCharacterHandler {
             gender,
         };
 
-        let char_packet = login::char::build_char_packet(character.create()?.character)?;
+        let char_packet = login::char::build_char_packet(&character.create(starter_equips)?)?;
         Ok(HandlerResult::reply(char_packet))
     }
 }
@@ -0,0 +1,106 @@
+use crate::error::NetworkError;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::stat::{build_stat_changes, Stat};
+use crate::stats;
+use packet::{io::read::PktRead, Packet};
+use rand::thread_rng;
+use std::convert::TryFrom;
+use std::io::Cursor;
+
+/// Most stats the client's auto-assign spreads AP over.
+const MAX_AUTO_ASSIGN_STATS: i32 = 4;
+
+pub struct DistributeApHandler;
+
+impl DistributeApHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for DistributeApHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let _tick = reader.read_int()?;
+        let mask = reader.read_int()?;
+
+        let character = ctx.session.get_character()?;
+        let mut chr = character.lock().unwrap();
+        let before = chr.character.clone();
+        if let Some(stat) = Stat::from_mask(mask) {
+            if stats::assign_ap(&mut chr.character, stat, &mut thread_rng()) {
+                chr.character.save()?;
+            }
+        }
+
+        // An unchanged character still gets an empty update to unlock the
+        // client.
+        Ok(HandlerResult::reply(build_stat_changes(
+            &before,
+            &chr.character,
+            true,
+        )?))
+    }
+}
+
+pub struct AutoDistributeApHandler;
+
+impl AutoDistributeApHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for AutoDistributeApHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let _tick = reader.read_int()?;
+        let count = reader.read_int()?;
+
+        let mut assignments = Vec::new();
+        if (0..=MAX_AUTO_ASSIGN_STATS).contains(&count) {
+            for _ in 0..count {
+                let mask = reader.read_int()?;
+                let amount = reader.read_int()?;
+                match (Stat::from_mask(mask), i16::try_from(amount)) {
+                    (Some(stat), Ok(amount)) => assignments.push((stat, amount)),
+                    _ => {
+                        assignments.clear();
+                        break;
+                    }
+                }
+            }
+        }
+
+        let character = ctx.session.get_character()?;
+        let mut chr = character.lock().unwrap();
+        let before = chr.character.clone();
+        if stats::auto_assign_ap(&mut chr.character, &assignments) {
+            chr.character.save()?;
+        }
+
+        Ok(HandlerResult::reply(build_stat_changes(
+            &before,
+            &chr.character,
+            true,
+        )?))
+    }
+}
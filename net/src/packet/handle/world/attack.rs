@@ -0,0 +1,404 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::attack::{
+    build_close_range_attack, build_magic_attack, build_ranged_attack, AttackInfo, DamageLine,
+};
+use crate::packet::build::world::inventory::build_inventory_take;
+use crate::packet::build::world::skill::build_cooldown;
+use crate::packet::build::world::stat::build_stat_changes;
+use crate::projectiles;
+use crate::skills;
+use ::game_data::SkillLevel;
+use db::character::CharacterWrapper;
+use db::inventory::InventoryType;
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+use std::time::Instant;
+
+/// Skills whose attack packet carries an extra charge time.
+const CHARGE_SKILLS: [i32; 7] = [
+    2121001, 2221001, 2321001, 5101004, 5201002, 14111006, 15101003,
+];
+/// Rapid Fire reports its damage lines without the trailing client tick.
+const SKILL_RAPID_FIRE: i32 = 5221004;
+/// Skills held down to keep firing: Hurricane, Piercing Arrow, Rapid Fire and
+/// the Wind Archer's Hurricane. Their ranged header carries 4 more bytes.
+const KEY_DOWN_SKILLS: [i32; 4] = [3121004, 3221001, SKILL_RAPID_FIRE, 13111002];
+const DAMAGE_LINE_HEADER_LEN: usize = 14;
+
+/// Highest single hit the v83 client can display.
+const MAX_DAMAGE_PER_HIT: i64 = 199_999;
+/// How many times the client's own damage range a hit may reach, leaving
+/// room for criticals, attack buffs and projectile attack it doesn't count.
+const DAMAGE_LEEWAY: i64 = 10;
+/// Highest weapon multiplier for the primary stat, which spears have.
+const PRIMARY_STAT_MULTIPLIER: i64 = 5;
+/// Attack assumed for characters without a weapon.
+const MIN_ATTACK: i64 = 10;
+/// Elemental spells hit mobs weak to their element for up to 150% damage.
+const ELEMENTAL_WEAKNESS_PERCENT: i64 = 150;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AttackType {
+    Close,
+    Ranged,
+    Magic,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum MagicElement {
+    Neutral,
+    Fire,
+    Ice,
+    Lightning,
+    Poison,
+    Holy,
+}
+
+impl MagicElement {
+    fn of_skill(skill_id: i32) -> Self {
+        match skill_id {
+            2101004 | 2111002 | 2111006 | 2121003 | 2121007 => MagicElement::Fire,
+            2101005 | 2111003 | 2121006 => MagicElement::Poison,
+            2201004 | 2211002 | 2211006 | 2221003 | 2221007 => MagicElement::Ice,
+            2201005 | 2211003 | 2221006 => MagicElement::Lightning,
+            2301005 | 2311004 | 2321007 | 2321008 => MagicElement::Holy,
+            _ => MagicElement::Neutral,
+        }
+    }
+}
+
+pub struct CloseRangeAttackHandler;
+
+impl CloseRangeAttackHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for CloseRangeAttackHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        handle_attack(packet, ctx, AttackType::Close)
+    }
+}
+
+pub struct RangedAttackHandler;
+
+impl RangedAttackHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for RangedAttackHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        handle_attack(packet, ctx, AttackType::Ranged)
+    }
+}
+
+pub struct MagicAttackHandler;
+
+impl MagicAttackHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for MagicAttackHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        handle_attack(packet, ctx, AttackType::Magic)
+    }
+}
+
+fn handle_attack(
+    packet: &mut Packet,
+    ctx: &mut HandlerContext,
+    attack_type: AttackType,
+) -> Result<HandlerResult, NetworkError> {
+    if ctx.client_id == 0 {
+        return Ok(HandlerResult::empty());
+    }
+
+    let mut reader = Cursor::new(&packet.bytes[..]);
+    let _op = reader.read_short()?;
+    let mut attack = read_attack(&mut reader, attack_type)?;
+
+    let character = ctx.session.get_character()?;
+    let mut chr = character.lock().unwrap();
+
+    let mut result = HandlerResult::empty();
+    let mut skill = None;
+    if attack.skill_id > 0 {
+        // Skills attack at the level the character learned, once they are off
+        // cooldown and paid for.
+        let now = Instant::now();
+        let level = chr.skills.level(attack.skill_id);
+        let data = game_data::skills()?
+            .skill(attack.skill_id)
+            .and_then(|template| template.level(level))
+            .filter(|_| ctx.cooldowns.is_ready(attack.skill_id, now));
+        let Some(data) = data else {
+            eprintln!(
+                "Character {} attacked with skill {} they can't use",
+                chr.character.id, attack.skill_id
+            );
+            return Ok(result);
+        };
+        let before = chr.character.clone();
+        if !skills::pay_skill_cost(&mut chr.character, data) {
+            return Ok(result);
+        }
+        // Like regen, the cost is saved with the character's next save.
+        result = result.with_reply(build_stat_changes(&before, &chr.character, false)?);
+        if data.cooldown > 0 {
+            ctx.cooldowns.start(attack.skill_id, data.cooldown, now);
+            result = result.with_reply(build_cooldown(attack.skill_id, data.cooldown as i16)?);
+        }
+        attack.skill_level = level as u8;
+        skill = Some(data);
+    }
+    cap_attack_damage(&chr, attack_type, skill, &mut attack);
+
+    if attack_type == AttackType::Ranged {
+        // Each hit fires one projectile, unless a buff fires them for free.
+        let free = ctx
+            .buff_skills
+            .iter()
+            .any(|&skill_id| projectiles::is_projectile_free(skill_id));
+        let shots = if free {
+            0
+        } else {
+            i16::from(attack.hit_count.max(1))
+        };
+        match projectiles::find(&chr.inventory, shots) {
+            Some((position, item_id)) => {
+                attack.projectile = item_id;
+                if shots > 0 {
+                    let remaining = chr
+                        .inventory
+                        .take_at(InventoryType::Use, position, shots)
+                        .ok_or(NetworkError::PacketHandlerError(
+                            "Projectile stack not found",
+                        ))?;
+                    chr.inventory.save()?;
+                    result = result.with_reply(build_inventory_take(
+                        InventoryType::Use,
+                        position,
+                        remaining,
+                    )?);
+                }
+            }
+            None if free => {}
+            None => {
+                eprintln!(
+                    "Character {} attacked without anything to fire",
+                    chr.character.id
+                );
+                return Ok(result);
+            }
+        }
+    }
+
+    let broadcast_packet = match attack_type {
+        AttackType::Close => build_close_range_attack(chr.character.id, &attack)?,
+        AttackType::Ranged => build_ranged_attack(chr.character.id, &attack)?,
+        AttackType::Magic => build_magic_attack(chr.character.id, &attack)?,
+    };
+    Ok(result.with_field_attack(broadcast_packet, attack.damage_lines))
+}
+
+fn read_attack(
+    reader: &mut Cursor<&[u8]>,
+    attack_type: AttackType,
+) -> Result<AttackInfo, NetworkError> {
+    reader.read_byte()?;
+    let targets_and_hits = reader.read_byte()?;
+    let target_count = targets_and_hits >> 4;
+    let hit_count = targets_and_hits & 0x0F;
+    let skill_id = reader.read_int()?;
+    let charge = if CHARGE_SKILLS.contains(&skill_id) {
+        reader.read_int()?
+    } else {
+        0
+    };
+    reader.read_bytes(8)?;
+    let display = reader.read_byte()?;
+    let direction = reader.read_byte()?;
+    let stance = reader.read_byte()?;
+    reader.read_byte()?;
+    let speed = reader.read_byte()?;
+
+    if attack_type == AttackType::Ranged {
+        reader.read_byte()?;
+        let _ranged_direction = reader.read_byte()?;
+        // Projectile slots and shoot range. Not every client fills the slots
+        // in, so the handler finds the projectile in the inventory itself.
+        reader.read_bytes(7)?;
+        if KEY_DOWN_SKILLS.contains(&skill_id) {
+            reader.read_bytes(4)?;
+        }
+    } else {
+        reader.read_bytes(4)?;
+    }
+
+    let mut damage_lines = Vec::with_capacity(target_count as usize);
+    for _ in 0..target_count {
+        let object_id = reader.read_int()?;
+        reader.read_bytes(DAMAGE_LINE_HEADER_LEN)?;
+        let mut damage = Vec::with_capacity(hit_count as usize);
+        for _ in 0..hit_count {
+            damage.push(reader.read_int()?);
+        }
+        if skill_id != SKILL_RAPID_FIRE {
+            reader.read_bytes(4)?;
+        }
+        damage_lines.push(DamageLine { object_id, damage });
+    }
+
+    Ok(AttackInfo {
+        hit_count,
+        skill_id,
+        // Filled in by the handler for skill attacks.
+        skill_level: 0,
+        display,
+        direction,
+        stance,
+        speed,
+        // Filled in by the handler for ranged attacks.
+        projectile: 0,
+        charge,
+        damage_lines,
+    })
+}
+
+/// Upper bound for a single hit from this character, from the client's max
+/// damage formulas, the attack of their equips and the skill's damage. It is
+/// deliberately loose: it only exists to stop edited clients from one-shotting
+/// everything.
+fn max_damage_per_hit(
+    chr: &CharacterWrapper,
+    attack_type: AttackType,
+    skill_id: i32,
+    skill: Option<&SkillLevel>,
+) -> i32 {
+    let character = &chr.character;
+    let (weapon_attack, magic_attack) = chr
+        .inventory
+        .equipped()
+        .filter_map(|(_, item)| item.stats.as_ref())
+        .fold((0, 0), |(watk, matk), stats| {
+            (watk + i64::from(stats.watk), matk + i64::from(stats.matk))
+        });
+
+    let mut cap = if attack_type == AttackType::Magic {
+        let int = i64::from(character.int.max(0));
+        let magic = (magic_attack + int).max(MIN_ATTACK);
+        let spell_attack = skill
+            .map(|level| i64::from(level.matk))
+            .filter(|&matk| matk > 0)
+            .unwrap_or(100);
+        ((magic * magic / 1000 + magic) / 30 + int / 200) * spell_attack
+    } else {
+        let stats = [character.stre, character.dex, character.int, character.luk]
+            .map(|stat| i64::from(stat.max(0)));
+        let primary = stats.iter().copied().max().unwrap_or(0);
+        let secondary = stats.iter().sum::<i64>() - primary;
+        let damage = skill
+            .map(|level| i64::from(level.damage))
+            .filter(|&damage| damage > 0)
+            .unwrap_or(100);
+        (primary * PRIMARY_STAT_MULTIPLIER + secondary) * weapon_attack.max(MIN_ATTACK) * damage
+            / 10_000
+    };
+    cap *= DAMAGE_LEEWAY;
+    if attack_type == AttackType::Magic && MagicElement::of_skill(skill_id) != MagicElement::Neutral
+    {
+        cap = cap * ELEMENTAL_WEAKNESS_PERCENT / 100;
+    }
+    cap.clamp(1, MAX_DAMAGE_PER_HIT) as i32
+}
+
+fn cap_attack_damage(
+    chr: &CharacterWrapper,
+    attack_type: AttackType,
+    skill: Option<&SkillLevel>,
+    attack: &mut AttackInfo,
+) {
+    let cap = max_damage_per_hit(chr, attack_type, attack.skill_id, skill);
+    for line in &mut attack.damage_lines {
+        for damage in &mut line.damage {
+            *damage = (*damage).clamp(0, cap);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::test_character_wrapper;
+    use db::inventory::InventoryItem;
+    use packet::io::write::PktWrite;
+
+    #[test]
+    fn damage_cap_follows_weapon_attack_and_skill_damage() {
+        let mut chr = test_character_wrapper();
+        // Without a weapon the character hits as if with 10 attack.
+        assert_eq!(max_damage_per_hit(&chr, AttackType::Close, 0, None), 70);
+
+        let mut sword = InventoryItem::new(1302000, 1);
+        if let Some(stats) = sword.stats.as_mut() {
+            stats.watk = 17;
+        }
+        chr.inventory.wear(sword);
+        assert_eq!(max_damage_per_hit(&chr, AttackType::Close, 0, None), 120);
+
+        let power_strike = SkillLevel {
+            damage: 200,
+            ..SkillLevel::default()
+        };
+        assert_eq!(
+            max_damage_per_hit(&chr, AttackType::Close, 1001004, Some(&power_strike)),
+            240
+        );
+    }
+
+    #[test]
+    fn key_down_skills_skip_their_extra_ranged_bytes() {
+        let mut packet = Packet::new_empty();
+        packet.write_byte(0).unwrap();
+        packet.write_byte(0x11).unwrap();
+        packet.write_int(3121004).unwrap();
+        packet.write_bytes(&[0; 8]).unwrap();
+        packet.write_bytes(&[1, 0, 2, 0, 6]).unwrap();
+        packet.write_bytes(&[0; 9]).unwrap();
+        packet.write_int(0).unwrap();
+        packet.write_int(1000).unwrap();
+        packet.write_bytes(&[0; DAMAGE_LINE_HEADER_LEN]).unwrap();
+        packet.write_int(350).unwrap();
+        packet.write_int(0).unwrap();
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let attack = read_attack(&mut reader, AttackType::Ranged).expect("read attack");
+        assert_eq!(
+            attack.damage_lines,
+            vec![DamageLine {
+                object_id: 1000,
+                damage: vec![350],
+            }]
+        );
+        assert_eq!(reader.position() as usize, packet.bytes.len());
+    }
+}
@@ -0,0 +1,93 @@
+use crate::buffs;
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::effect::build_show_foreign_skill_effect;
+use crate::packet::build::world::stat::build_stat_changes;
+use crate::skills;
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+
+/// Handles skills cast on the player's own character. Only skills that give
+/// a buff have an effect so far; the rest just unlock the client.
+pub struct UseSkillHandler;
+
+impl UseSkillHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for UseSkillHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let _tick = reader.read_int()?;
+        let skill_id = reader.read_int()?;
+        let level = i16::from(reader.read_byte()?);
+
+        let character = ctx.session.get_character()?;
+        let mut chr = character.lock().unwrap();
+        let before = chr.character.clone();
+        let unlock = build_stat_changes(&before, &chr.character, true)?;
+
+        // The client casts at the level it knows, which has to be the one
+        // the character learned.
+        if level < 1 || chr.skills.level(skill_id) != level {
+            return Ok(HandlerResult::reply(unlock));
+        }
+        let Some(template) = game_data::skills()?.skill(skill_id) else {
+            return Ok(HandlerResult::reply(unlock));
+        };
+        let (Some(data), Some(buff)) = (template.level(level), buffs::skill_buff(template, level))
+        else {
+            return Ok(HandlerResult::reply(unlock));
+        };
+        if !skills::pay_skill_cost(&mut chr.character, data) {
+            return Ok(HandlerResult::reply(unlock));
+        }
+        chr.character.save()?;
+
+        let effect_packet =
+            build_show_foreign_skill_effect(ctx.client_id, skill_id, chr.character.level, level)?;
+        Ok(
+            HandlerResult::reply(build_stat_changes(&before, &chr.character, true)?)
+                .with_give_buff(buff, effect_packet),
+        )
+    }
+}
+
+/// Handles the player ending one of their buffs early.
+pub struct CancelBuffHandler;
+
+impl CancelBuffHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for CancelBuffHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let skill_id = reader.read_int()?;
+
+        Ok(HandlerResult::empty().with_cancel_buff(skill_id))
+    }
+}
@@ -33,11 +33,12 @@ impl PacketHandler for ChangeChannelHandler {
             .ok_or(NetworkError::PacketHandlerError(
                 "No world selected for channel change",
             ))? as u8;
-        let current_channel_id = session
-            .selected_channel_id
-            .ok_or(NetworkError::PacketHandlerError(
-                "No channel selected for channel change",
-            ))? as u8;
+        let current_channel_id =
+            session
+                .selected_channel_id
+                .ok_or(NetworkError::PacketHandlerError(
+                    "No channel selected for channel change",
+                ))? as u8;
 
         if target_channel_id == current_channel_id {
             return Ok(HandlerResult::empty());
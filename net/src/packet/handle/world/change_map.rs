@@ -1,6 +1,9 @@
 use crate::error::NetworkError;
 use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
 use crate::packet::build;
+use crate::portal;
+use crate::stats;
+use db::character::CharacterWrapper;
 use packet::{io::read::PktRead, Packet};
 use std::convert::TryFrom;
 use std::io::BufReader;
@@ -29,10 +32,6 @@ impl PacketHandler for ChangeMapHandler {
         let _wheel_of_destiny = reader.read_short()? > 0;
 
         let character = ctx.session.get_character()?;
-        let mut chr = character.lock().unwrap();
-
-        let mut result = HandlerResult::empty();
-        let old_map_id = chr.character.map_id;
         let channel_id = ctx
             .session
             .session
@@ -40,74 +39,131 @@ impl PacketHandler for ChangeMapHandler {
             .and_then(|session| session.selected_channel_id)
             .unwrap_or(0) as u8;
 
-        if target != -1 {
-            let game_data = crate::game_data::get()?;
-            if !game_data.field_exists(target) {
-                return Err(NetworkError::PacketHandlerError("Target field not found"));
-            }
+        if target == -1 {
+            // Portal scripts lock the character themselves.
+            let result = portal::enter_portal(&character, channel_id, &portal_name)?;
+            let stat_packet = build::world::map::build_empty_stat_update()?;
+            return Ok(result.with_reply(stat_packet));
+        }
 
-            chr.character.map_id = target;
-            chr.character.save()?;
+        let mut chr = character.lock().unwrap();
+        let old_map_id = chr.character.map_id;
 
-            let warp_packet =
-                build::world::map::build_warp_to_map(&chr.character, target, 0, channel_id)?;
-            result = result
-                .with_reply(warp_packet)
-                .with_map_changed(old_map_id, target, None, None, None, None);
-        } else {
+        if chr.character.hp <= 0 {
+            // Dead characters ask to leave with a target; they respawn in the
+            // field's return map.
             let game_data = crate::game_data::get()?;
-            let source_field = game_data
+            let respawn_map = game_data
                 .field(old_map_id)
-                .ok_or(NetworkError::PacketHandlerError("Current field not found"))?;
-            let source_portal = source_field
-                .portal_by_name(portal_name.as_str())
-                .ok_or(NetworkError::PacketHandlerError("Source portal not found"))?;
-            let destination_map = source_portal
-                .to_map
-                .ok_or(NetworkError::PacketHandlerError(
-                    "Portal has no destination",
-                ))?;
-            let destination_field =
-                game_data
-                    .field(destination_map)
-                    .ok_or(NetworkError::PacketHandlerError(
-                        "Destination field not found",
-                    ))?;
-            let spawn_portal = destination_field
-                .resolve_spawn_portal(source_portal.to_name.as_str())
-                .ok_or(NetworkError::PacketHandlerError(
-                    "Destination spawn portal not found",
-                ))?;
-            let spawn_point = u8::try_from(spawn_portal.id).map_err(|_| {
-                NetworkError::PacketHandlerError("Destination spawn portal id out of range")
-            })?;
-            let spawn_x = i16::try_from(spawn_portal.x).map_err(|_| {
-                NetworkError::PacketHandlerError("Destination spawn portal x out of range")
-            })?;
-            let spawn_y = i16::try_from(spawn_portal.y).map_err(|_| {
-                NetworkError::PacketHandlerError("Destination spawn portal y out of range")
-            })?;
-
-            chr.character.map_id = destination_map;
+                .map_or(old_map_id, |field| field.respawn_map_id());
+
+            let before = chr.character.clone();
+            stats::respawn(&mut chr.character);
+            chr.character.map_id = respawn_map;
             chr.character.save()?;
 
-            let warp_packet = build::world::map::build_warp_to_map(
-                &chr.character,
-                destination_map,
-                spawn_point,
-                channel_id,
-            )?;
-            result = result.with_reply(warp_packet).with_map_changed(
-                old_map_id,
-                destination_map,
-                Some(spawn_point),
-                Some(spawn_x),
-                Some(spawn_y),
-                Some(2),
-            );
+            let warp_packet =
+                build::world::map::build_warp_to_map(&chr.character, respawn_map, 0, channel_id)?;
+            let stat_packet =
+                build::world::stat::build_stat_changes(&before, &chr.character, true)?;
+            return Ok(HandlerResult::reply(warp_packet)
+                .with_map_changed(old_map_id, respawn_map, None, None, None, None)
+                .with_reply(stat_packet));
         }
 
+        let result = warp_to_map(&mut chr, target, None, channel_id)?;
         let stat_packet = build::world::map::build_empty_stat_update()?;
         Ok(result.with_reply(stat_packet))
     }
 }
+
+/// Scripted portals the client asks to enter on its own, rather than through
+/// a map change.
+pub struct EnterScriptedPortalHandler;
+
+impl EnterScriptedPortalHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for EnterScriptedPortalHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = BufReader::new(&**packet);
+        let _op = reader.read_short()?;
+        reader.read_byte()?;
+        let portal_name = reader.read_str_with_length()?;
+        let _x = reader.read_short()?;
+
+        let character = ctx.session.get_character()?;
+        let channel_id = ctx
+            .session
+            .session
+            .as_ref()
+            .and_then(|session| session.selected_channel_id)
+            .unwrap_or(0) as u8;
+
+        let result = portal::enter_portal(&character, channel_id, &portal_name)?;
+        let stat_packet = build::world::map::build_empty_stat_update()?;
+        Ok(result.with_reply(stat_packet))
+    }
+}
+
+/// Move the character to `map_id` and tell the client and the runtime. The
+/// character appears at the spawn portal `portal_name` resolves to, or at the
+/// map's first spawn point without one. Scripts warp through this too.
+pub(crate) fn warp_to_map(
+    chr: &mut CharacterWrapper,
+    map_id: i32,
+    portal_name: Option<&str>,
+    channel_id: u8,
+) -> Result<HandlerResult, NetworkError> {
+    let old_map_id = chr.character.map_id;
+    let game_data = crate::game_data::get()?;
+    let Some(destination_field) = game_data.field(map_id) else {
+        return Err(NetworkError::PacketHandlerError("Target field not found"));
+    };
+
+    let Some(portal_name) = portal_name else {
+        chr.character.map_id = map_id;
+        chr.character.save()?;
+
+        let warp_packet =
+            build::world::map::build_warp_to_map(&chr.character, map_id, 0, channel_id)?;
+        return Ok(HandlerResult::reply(warp_packet)
+            .with_map_changed(old_map_id, map_id, None, None, None, None));
+    };
+
+    let spawn_portal = destination_field.resolve_spawn_portal(portal_name).ok_or(
+        NetworkError::PacketHandlerError("Destination spawn portal not found"),
+    )?;
+    let spawn_point = u8::try_from(spawn_portal.id).map_err(|_| {
+        NetworkError::PacketHandlerError("Destination spawn portal id out of range")
+    })?;
+    let spawn_x = i16::try_from(spawn_portal.x)
+        .map_err(|_| NetworkError::PacketHandlerError("Destination spawn portal x out of range"))?;
+    let spawn_y = i16::try_from(spawn_portal.y)
+        .map_err(|_| NetworkError::PacketHandlerError("Destination spawn portal y out of range"))?;
+
+    chr.character.map_id = map_id;
+    chr.character.save()?;
+
+    let warp_packet =
+        build::world::map::build_warp_to_map(&chr.character, map_id, spawn_point, channel_id)?;
+    Ok(HandlerResult::reply(warp_packet).with_map_changed(
+        old_map_id,
+        map_id,
+        Some(spawn_point),
+        Some(spawn_x),
+        Some(spawn_y),
+        Some(2),
+    ))
+}
@@ -0,0 +1,60 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::skill::build_update_skill;
+use crate::packet::build::world::stat::build_stat_changes;
+use crate::skills;
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+
+pub struct DistributeSpHandler;
+
+impl DistributeSpHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for DistributeSpHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let _tick = reader.read_int()?;
+        let skill_id = reader.read_int()?;
+
+        let character = ctx.session.get_character()?;
+        let mut chr = character.lock().unwrap();
+        let before = chr.character.clone();
+        let entry = match game_data::skills()?.skill(skill_id) {
+            Some(template) => {
+                let chr = &mut *chr;
+                skills::assign_sp(&mut chr.character, &mut chr.skills, template)
+            }
+            None => None,
+        };
+
+        // A rejected point still gets an empty update to unlock the client.
+        let Some(entry) = entry else {
+            return Ok(HandlerResult::reply(build_stat_changes(
+                &before,
+                &chr.character,
+                true,
+            )?));
+        };
+        chr.character.save()?;
+        chr.skills.save()?;
+
+        Ok(HandlerResult::replies(vec![
+            build_update_skill(skill_id, entry)?,
+            build_stat_changes(&before, &chr.character, true)?,
+        ]))
+    }
+}
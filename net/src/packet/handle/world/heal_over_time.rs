@@ -0,0 +1,50 @@
+use crate::error::NetworkError;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::stat::build_stat_changes;
+use crate::stats;
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+use std::time::Instant;
+
+/// Handles the client's periodic HP and MP regen ticks.
+pub struct HealOverTimeHandler;
+
+impl HealOverTimeHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for HealOverTimeHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let _tick = reader.read_int()?;
+        let _unknown = reader.read_int()?;
+        let hp = reader.read_short()?;
+        let mp = reader.read_short()?;
+
+        let character = ctx.session.get_character()?;
+        let mut chr = character.lock().unwrap();
+        let before = chr.character.clone();
+        if !stats::regen(&mut chr.character, ctx.regen, hp, mp, Instant::now()) {
+            return Ok(HandlerResult::empty());
+        }
+        // Regen ticks too often to save each one; the restored HP and MP are
+        // saved with the character's next save.
+
+        Ok(HandlerResult::reply(build_stat_changes(
+            &before,
+            &chr.character,
+            false,
+        )?))
+    }
+}
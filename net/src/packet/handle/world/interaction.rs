@@ -0,0 +1,85 @@
+use crate::error::NetworkError;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::map::build_empty_stat_update;
+use crate::trade::TradeAction;
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+
+const INTERACTION_CREATE: u8 = 0x00;
+const INTERACTION_INVITE: u8 = 0x02;
+const INTERACTION_DECLINE: u8 = 0x03;
+const INTERACTION_VISIT: u8 = 0x04;
+const INTERACTION_CHAT: u8 = 0x06;
+const INTERACTION_EXIT: u8 = 0x0A;
+const INTERACTION_SET_ITEMS: u8 = 0x0F;
+const INTERACTION_SET_MESO: u8 = 0x10;
+const INTERACTION_CONFIRM: u8 = 0x11;
+
+const ROOM_TYPE_TRADE: u8 = 3;
+
+/// Handles the trade window. Trades run in the field both characters are in,
+/// so actions are only read here and passed on.
+pub struct PlayerInteractionHandler;
+
+impl PlayerInteractionHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for PlayerInteractionHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let mode = reader.read_byte()?;
+        let action = match mode {
+            INTERACTION_CREATE => {
+                let room_type = reader.read_byte()?;
+                if room_type != ROOM_TYPE_TRADE {
+                    eprintln!(
+                        "Character {} tried to open unsupported room type {}",
+                        ctx.client_id, room_type
+                    );
+                    return Ok(HandlerResult::reply(build_empty_stat_update()?));
+                }
+                TradeAction::Open
+            }
+            INTERACTION_INVITE => TradeAction::Invite {
+                character_id: reader.read_int()?,
+            },
+            INTERACTION_DECLINE => TradeAction::Decline,
+            INTERACTION_VISIT => TradeAction::Accept,
+            INTERACTION_CHAT => TradeAction::Chat {
+                message: reader.read_str_with_length()?,
+            },
+            INTERACTION_EXIT => TradeAction::Cancel,
+            INTERACTION_SET_ITEMS => TradeAction::OfferItem {
+                kind: reader.read_byte()?,
+                position: reader.read_short()?,
+                quantity: reader.read_short()?,
+                slot: reader.read_byte()?,
+            },
+            INTERACTION_SET_MESO => TradeAction::OfferMeso {
+                meso: reader.read_int()?,
+            },
+            INTERACTION_CONFIRM => TradeAction::Confirm,
+            _ => {
+                eprintln!(
+                    "Character {} sent unsupported interaction mode {}",
+                    ctx.client_id, mode
+                );
+                return Ok(HandlerResult::empty());
+            }
+        };
+
+        Ok(HandlerResult::empty().with_trade(action))
+    }
+}
@@ -0,0 +1,167 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::inventory::{build_inventory_move, ItemMovement};
+use crate::packet::build::world::map::build_empty_stat_update;
+use ::game_data::EquipTemplate;
+use db::character::{Character, CharacterWrapper};
+use db::inventory::{self, InventoryType, EQUIP_SLOT_BOTTOM, EQUIP_SLOT_TOP};
+use packet::{io::read::PktRead, Packet};
+use std::convert::TryFrom;
+use std::io::Cursor;
+
+pub struct MoveItemHandler;
+
+impl MoveItemHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for MoveItemHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let _tick = reader.read_int()?;
+        let inventory_type = reader.read_byte()?;
+        let from = reader.read_short()?;
+        let to = reader.read_short()?;
+        let _quantity = reader.read_short()?;
+
+        let character = ctx.session.get_character()?;
+        let mut chr = character.lock().unwrap();
+
+        let moves = match InventoryType::try_from(inventory_type) {
+            // Dropping items on the field is not supported yet.
+            Ok(_) if to == 0 => None,
+            Ok(InventoryType::Equip) if from > 0 && to < 0 => equip(&mut chr, from, to)?,
+            Ok(InventoryType::Equip) if from < 0 && to > 0 => unequip(&mut chr, from, to),
+            Ok(inventory_type) if from > 0 && to > 0 => {
+                if chr.inventory.swap(inventory_type, from, to) {
+                    Some(vec![(inventory_type, from, to, ItemMovement::Internal)])
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        let Some(moves) = moves else {
+            // The client locks its actions until the move is answered.
+            return Ok(HandlerResult::reply(build_empty_stat_update()?));
+        };
+        chr.inventory.save()?;
+
+        let mut result = HandlerResult::empty();
+        let mut look_changed = false;
+        for (inventory_type, from, to, movement) in moves {
+            look_changed |= movement != ItemMovement::Internal;
+            result = result.with_reply(build_inventory_move(inventory_type, from, to, movement)?);
+        }
+        if look_changed {
+            result = result.with_field_update_look(chr.inventory.equipment());
+        }
+        Ok(result)
+    }
+}
+
+type InventoryMove = (InventoryType, i16, i16, ItemMovement);
+
+/// Wear the equip at `from` in equip slot `to`. Whatever was worn there goes
+/// back to `from`; a bottom worn under a new overall, or an overall replaced by
+/// a bottom, is moved to the first free slot.
+fn equip(
+    chr: &mut CharacterWrapper,
+    from: i16,
+    to: i16,
+) -> Result<Option<Vec<InventoryMove>>, NetworkError> {
+    let Some(item) = chr.inventory.get(InventoryType::Equip, from) else {
+        return Ok(None);
+    };
+    let item_id = item.item_id;
+    let template = game_data::items()?
+        .item(item_id)
+        .and_then(|template| template.equip.as_ref());
+    let allowed = match template {
+        Some(template) => meets_requirements(&chr.character, item_id, template),
+        None => false,
+    };
+    if !allowed || !inventory::equip_slots(item_id).contains(&to) {
+        return Ok(None);
+    }
+
+    let covered_slot = if inventory::is_overall(item_id) {
+        Some(EQUIP_SLOT_BOTTOM)
+    } else if to == EQUIP_SLOT_BOTTOM {
+        chr.inventory
+            .get(InventoryType::Equip, EQUIP_SLOT_TOP)
+            .filter(|top| inventory::is_overall(top.item_id))
+            .map(|_| EQUIP_SLOT_TOP)
+    } else {
+        None
+    };
+
+    let mut moves = Vec::new();
+    if let Some(covered_slot) = covered_slot {
+        if chr
+            .inventory
+            .get(InventoryType::Equip, covered_slot)
+            .is_some()
+        {
+            let Some(free) = chr.inventory.first_free_position(InventoryType::Equip) else {
+                return Ok(None);
+            };
+            chr.inventory.swap(InventoryType::Equip, covered_slot, free);
+            moves.push((
+                InventoryType::Equip,
+                covered_slot,
+                free,
+                ItemMovement::Unequip,
+            ));
+        }
+    }
+
+    chr.inventory.swap(InventoryType::Equip, from, to);
+    moves.push((InventoryType::Equip, from, to, ItemMovement::Equip));
+    Ok(Some(moves))
+}
+
+/// Take off the equip in slot `from` and put it in the empty slot `to`.
+fn unequip(chr: &mut CharacterWrapper, from: i16, to: i16) -> Option<Vec<InventoryMove>> {
+    if chr.inventory.get(InventoryType::Equip, to).is_some()
+        || !chr.inventory.swap(InventoryType::Equip, from, to)
+    {
+        return None;
+    }
+    Some(vec![(
+        InventoryType::Equip,
+        from,
+        to,
+        ItemMovement::Unequip,
+    )])
+}
+
+/// Check the equip's level, stat and job requirements. Gender-locked equips
+/// carry the gender in the thousands digit of their id.
+fn meets_requirements(character: &Character, item_id: i32, template: &EquipTemplate) -> bool {
+    let gender_matches = match (item_id / 1000) % 10 {
+        gender @ 0..=1 => i32::from(character.gender) == gender,
+        _ => true,
+    };
+
+    gender_matches
+        && character.level >= template.req_level
+        && character.stre >= template.req_str
+        && character.dex >= template.req_dex
+        && character.int >= template.req_int
+        && character.luk >= template.req_luk
+        && template.allows_job(character.job)
+}
@@ -29,8 +29,7 @@ impl PacketHandler for PlayerLoggedInHandler {
             Ok(character) => {
                 let mut chr = character.lock().unwrap();
                 let keymap_packet = build::world::keymap::build_keymap(&mut chr.key_binds)?;
-                let char_info_packet =
-                    build::world::char::build_char_info(&chr.character, channel_id)?;
+                let char_info_packet = build::world::char::build_char_info(&chr, channel_id)?;
 
                 Ok(HandlerResult::empty()
                     .with_reattach_session(character_id, channel_id)
@@ -1,19 +1,50 @@
+mod ap;
+mod attack;
+mod buff;
 mod change_channel;
 mod change_map;
 mod chat;
+mod distribute_sp;
+mod heal_over_time;
+mod interaction;
+mod inventory;
 mod keybinds;
 mod logged_in;
 mod map_transfer;
+mod move_mob;
 mod move_player;
+mod npc_talk;
+mod party;
 mod party_search;
+mod pickup;
+mod quest;
+mod shop;
+mod storage;
+mod take_damage;
 mod whisper;
 
+pub use self::ap::{AutoDistributeApHandler, DistributeApHandler};
+pub use self::attack::{CloseRangeAttackHandler, MagicAttackHandler, RangedAttackHandler};
+pub use self::buff::{CancelBuffHandler, UseSkillHandler};
 pub use self::change_channel::ChangeChannelHandler;
-pub use self::change_map::ChangeMapHandler;
+pub(crate) use self::change_map::warp_to_map;
+pub use self::change_map::{ChangeMapHandler, EnterScriptedPortalHandler};
 pub use self::chat::AllChatHandler;
+pub use self::distribute_sp::DistributeSpHandler;
+pub use self::heal_over_time::HealOverTimeHandler;
+pub use self::interaction::PlayerInteractionHandler;
+pub use self::inventory::MoveItemHandler;
 pub use self::keybinds::ChangeKeybindsHandler;
 pub use self::logged_in::PlayerLoggedInHandler;
 pub use self::map_transfer::PlayerMapTransferHandler;
+pub use self::move_mob::MobMoveHandler;
 pub use self::move_player::PlayerMoveHandler;
+pub use self::npc_talk::{NpcTalkHandler, NpcTalkMoreHandler};
+pub use self::party::{DenyPartyRequestHandler, MultiChatHandler, PartyOperationHandler};
 pub use self::party_search::PartySearchHandler;
+pub use self::pickup::PickupItemHandler;
+pub use self::quest::QuestActionHandler;
+pub use self::shop::NpcShopHandler;
+pub use self::storage::StorageHandler;
+pub use self::take_damage::TakeDamageHandler;
 pub use self::whisper::WhisperHandler;
@@ -0,0 +1,58 @@
+use crate::error::NetworkError;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::mob::{build_move_mob, build_move_mob_response};
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+
+const MOB_MOVEMENT_UNKNOWN_LEN: usize = 13;
+
+pub struct MobMoveHandler;
+
+impl MobMoveHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for MobMoveHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+
+        let object_id = reader.read_int()?;
+        let move_id = reader.read_short()?;
+        let skill_possible = reader.read_byte()?;
+        let skill = reader.read_bytes(5)?;
+        reader.read_bytes(MOB_MOVEMENT_UNKNOWN_LEN)?;
+        let start_x = reader.read_short()?;
+        let start_y = reader.read_short()?;
+
+        // The controller is always answered so it can send its next move,
+        // even when there is no movement to relay.
+        let movement_bytes = packet.bytes[reader.position() as usize..].to_vec();
+        let move_packet = build_move_mob(
+            object_id,
+            skill_possible,
+            &skill,
+            start_x,
+            start_y,
+            &movement_bytes,
+        )?;
+        let response_packet = build_move_mob_response(object_id, move_id, 0)?;
+
+        Ok(HandlerResult::empty().with_field_mob_move(
+            object_id,
+            move_packet,
+            response_packet,
+            movement_bytes,
+        ))
+    }
+}
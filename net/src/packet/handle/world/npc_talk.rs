@@ -0,0 +1,230 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::npc::{self, Answer, Conversation, NpcContext, NpcScript, Step};
+use crate::packet::build::world::npc::{build_npc_talk, MAP_NPC_OBJECT_ID_BASE};
+use crate::packet::build::world::stat::build_stat_changes;
+use crate::script::NpcFileScript;
+use crate::shops;
+use crate::storage;
+use db::character::CharacterWrapper;
+use packet::{io::read::PktRead, Packet};
+use std::convert::TryFrom;
+use std::io::Cursor;
+use std::sync::Mutex;
+
+pub struct NpcTalkHandler;
+
+impl NpcTalkHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for NpcTalkHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let object_id = reader.read_int()?;
+
+        let character = ctx.session.get_character()?;
+        let map_id = character.lock().unwrap().character.map_id;
+
+        // Only NPCs on the character's own map can be talked to.
+        let npc_id = usize::try_from(object_id.wrapping_sub(MAP_NPC_OBJECT_ID_BASE))
+            .ok()
+            .and_then(|index| {
+                game_data::get()
+                    .ok()?
+                    .field(map_id)?
+                    .map_npcs
+                    .get(index)
+                    .map(|npc| npc.npc_id)
+            });
+        let Some(npc_id) = npc_id else {
+            *ctx.conversation = None;
+            return Ok(HandlerResult::reply(build_unlock(&character)?));
+        };
+
+        // NPCs with a shop or a storage open it, unless a script file says
+        // otherwise.
+        *ctx.shop = None;
+        *ctx.storage = None;
+        if NpcFileScript::load(npc_id).is_none() {
+            if let Some(shop_packet) = shops::open_shop(npc_id)? {
+                *ctx.conversation = None;
+                *ctx.shop = Some(npc_id);
+                return Ok(HandlerResult::reply(shop_packet));
+            }
+            let opened = storage::open_storage(&character.lock().unwrap(), npc_id)?;
+            if let Some((open, storage_packet)) = opened {
+                *ctx.conversation = None;
+                *ctx.storage = Some(open);
+                return Ok(HandlerResult::reply(storage_packet));
+            }
+        }
+
+        // Clicking an NPC drops whatever conversation was still open.
+        let script = npc::npc_script(npc_id);
+        let npc_ctx = NpcContext {
+            npc_id,
+            character,
+            channel_id: channel_id(ctx),
+            result: HandlerResult::empty(),
+        };
+        advance(ctx.conversation, npc_ctx, script, None)
+    }
+}
+
+pub struct NpcTalkMoreHandler;
+
+impl NpcTalkMoreHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for NpcTalkMoreHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let Some(conversation) = ctx.conversation.take() else {
+            return Ok(HandlerResult::empty());
+        };
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let answer = Answer::read(&conversation.dialog, &mut reader)?;
+
+        let character = ctx.session.get_character()?;
+        if answer == Answer::End {
+            return Ok(HandlerResult::reply(build_unlock(&character)?));
+        }
+        let npc_ctx = NpcContext {
+            npc_id: conversation.npc_id,
+            character,
+            channel_id: channel_id(ctx),
+            result: HandlerResult::empty(),
+        };
+        advance(ctx.conversation, npc_ctx, conversation.script, Some(answer))
+    }
+}
+
+/// Start `script`, or pass it the player's answer, and show whatever it says
+/// next. The conversation stays open while the script has a dialog to show.
+pub(super) fn advance(
+    conversation: &mut Option<Conversation>,
+    mut npc_ctx: NpcContext,
+    mut script: Box<dyn NpcScript>,
+    answer: Option<Answer>,
+) -> Result<HandlerResult, NetworkError> {
+    let npc_id = npc_ctx.npc_id;
+    let step = match answer {
+        Some(answer) => script.answer(&mut npc_ctx, answer)?,
+        None => script.start(&mut npc_ctx)?,
+    };
+    let result = npc_ctx.result;
+
+    match step {
+        Step::Show(dialog) => {
+            let result = result.with_reply(build_npc_talk(npc_id, &dialog)?);
+            *conversation = Some(Conversation {
+                npc_id,
+                dialog,
+                script,
+            });
+            Ok(result)
+        }
+        Step::End => {
+            *conversation = None;
+            Ok(result.with_reply(build_unlock(&npc_ctx.character)?))
+        }
+    }
+}
+
+pub(super) fn channel_id(ctx: &HandlerContext) -> u8 {
+    ctx.session
+        .session
+        .as_ref()
+        .and_then(|session| session.selected_channel_id)
+        .unwrap_or(0) as u8
+}
+
+/// An empty stat update unlocks the client once no dialog is left open.
+fn build_unlock(character: &Mutex<CharacterWrapper>) -> Result<Packet, NetworkError> {
+    let chr = character.lock().unwrap();
+    build_stat_changes(&chr.character, &chr.character, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handler::HandlerAction;
+    use crate::npc::Dialog;
+    use crate::stats::test_character_wrapper;
+    use std::sync::Arc;
+
+    /// Says one line, then ends on the next answer.
+    struct OneLiner;
+
+    impl NpcScript for OneLiner {
+        fn start(&mut self, _ctx: &mut NpcContext) -> Result<Step, NetworkError> {
+            Ok(Step::Show(Dialog::say_next("Hello")))
+        }
+
+        fn answer(&mut self, _ctx: &mut NpcContext, answer: Answer) -> Result<Step, NetworkError> {
+            assert_eq!(answer, Answer::Next);
+            Ok(Step::End)
+        }
+    }
+
+    #[test]
+    fn conversation_stays_open_until_the_script_ends() {
+        let character = Arc::new(Mutex::new(test_character_wrapper()));
+        let context = || NpcContext {
+            npc_id: 2100,
+            character: Arc::clone(&character),
+            channel_id: 0,
+            result: HandlerResult::empty(),
+        };
+        let mut conversation = None;
+
+        let result =
+            advance(&mut conversation, context(), Box::new(OneLiner), None).expect("start");
+        let expected = build_npc_talk(2100, &Dialog::say_next("Hello")).expect("dialog");
+        assert!(matches!(
+            &result.actions[..],
+            [HandlerAction::Reply(packet)] if packet.bytes == expected.bytes
+        ));
+        let open = conversation.take().expect("open conversation");
+        assert_eq!((open.npc_id, open.dialog.message_type()), (2100, 0));
+
+        let result = advance(
+            &mut conversation,
+            context(),
+            open.script,
+            Some(Answer::Next),
+        )
+        .expect("answer");
+        assert!(conversation.is_none());
+        let unlock = build_unlock(&character).expect("unlock");
+        assert!(matches!(
+            &result.actions[..],
+            [HandlerAction::Reply(packet)] if packet.bytes == unlock.bytes
+        ));
+    }
+}
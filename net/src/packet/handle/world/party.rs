@@ -0,0 +1,146 @@
+use crate::error::NetworkError;
+use crate::handler::{BroadcastScope, HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::party::build_party_chat;
+use crate::party::PartyAction;
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+
+const PARTY_CREATE: u8 = 1;
+const PARTY_LEAVE: u8 = 2;
+const PARTY_ACCEPT: u8 = 3;
+const PARTY_INVITE: u8 = 4;
+const PARTY_EXPEL: u8 = 5;
+const PARTY_CHANGE_LEADER: u8 = 6;
+
+const MULTI_CHAT_PARTY: u8 = 1;
+
+/// Handles the party window. Parties span the whole world, so actions are
+/// only read here and passed on.
+pub struct PartyOperationHandler;
+
+impl PartyOperationHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for PartyOperationHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let operation = reader.read_byte()?;
+        let action = match operation {
+            PARTY_CREATE => PartyAction::Create,
+            PARTY_LEAVE => PartyAction::Leave,
+            PARTY_ACCEPT => PartyAction::Accept {
+                party_id: reader.read_int()?,
+            },
+            PARTY_INVITE => PartyAction::Invite {
+                name: reader.read_str_with_length()?,
+            },
+            PARTY_EXPEL => PartyAction::Expel {
+                character_id: reader.read_int()?,
+            },
+            PARTY_CHANGE_LEADER => PartyAction::ChangeLeader {
+                character_id: reader.read_int()?,
+            },
+            _ => {
+                eprintln!(
+                    "Character {} sent unsupported party operation {}",
+                    ctx.client_id, operation
+                );
+                return Ok(HandlerResult::empty());
+            }
+        };
+
+        Ok(HandlerResult::empty().with_party(action))
+    }
+}
+
+/// Handles a character turning down a party invite.
+pub struct DenyPartyRequestHandler;
+
+impl DenyPartyRequestHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for DenyPartyRequestHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let _mode = reader.read_byte()?;
+        let inviter = reader.read_str_with_length()?;
+
+        Ok(HandlerResult::empty().with_party(PartyAction::Decline { inviter }))
+    }
+}
+
+/// Handles chat sent to the buddy, party or guild chat. Only party chat is
+/// supported.
+pub struct MultiChatHandler;
+
+impl MultiChatHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for MultiChatHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let chat_type = reader.read_byte()?;
+        let recipients = reader.read_byte()?;
+        for _ in 0..recipients {
+            let _recipient = reader.read_int()?;
+        }
+        let message = reader.read_str_with_length()?;
+
+        if chat_type != MULTI_CHAT_PARTY {
+            eprintln!(
+                "Character {} sent unsupported chat type {}",
+                ctx.client_id, chat_type
+            );
+            return Ok(HandlerResult::empty());
+        }
+        let Some(party_id) = ctx.party_id else {
+            return Ok(HandlerResult::empty());
+        };
+
+        let name = {
+            let character = ctx.session.get_character()?;
+            let character = character.lock().unwrap();
+            character.character.name.clone()
+        };
+        Ok(HandlerResult::empty().with_broadcast(
+            BroadcastScope::PartyExcludeSelf(party_id),
+            build_party_chat(&name, &message)?,
+        ))
+    }
+}
@@ -0,0 +1,64 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::{HandlerContext, HandlerResult, InventorySpace, PacketHandler};
+use db::inventory::InventoryType;
+use packet::{io::read::PktRead, Packet};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+pub struct PickupItemHandler;
+
+impl PickupItemHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for PickupItemHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let _tick = reader.read_int()?;
+        reader.read_byte()?;
+        // The field checks range against the position it tracks, not the one
+        // the client reports.
+        let _x = reader.read_short()?;
+        let _y = reader.read_short()?;
+        let object_id = reader.read_int()?;
+
+        let character = ctx.session.get_character()?;
+        let chr = character.lock().unwrap();
+        // Without item data only free slots count, and the client hands back
+        // whatever it can't store.
+        let mut stack_room = HashMap::new();
+        if let Ok(items) = game_data::items() {
+            // Equips never stack.
+            let tabs = InventoryType::ALL
+                .iter()
+                .copied()
+                .filter(|&tab| tab != InventoryType::Equip);
+            for tab in tabs {
+                for (_, stack) in chr.inventory.items(tab) {
+                    if let Some(template) = items.item(stack.item_id) {
+                        let room = stack_room.entry(stack.item_id).or_insert(0);
+                        *room = template.slot_max.saturating_sub(stack.quantity).max(*room);
+                    }
+                }
+            }
+        }
+        let space = InventorySpace {
+            free_slots: InventoryType::ALL.map(|tab| chr.inventory.free_slots(tab)),
+            stack_room,
+        };
+
+        Ok(HandlerResult::empty().with_field_pickup(object_id, space))
+    }
+}
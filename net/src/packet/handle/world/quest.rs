@@ -0,0 +1,128 @@
+use super::npc_talk::{advance, channel_id};
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::npc::NpcContext;
+use crate::packet::build::world::map::build_empty_stat_update;
+use crate::quests::{self, QuestError};
+use crate::script::NpcFileScript;
+use packet::{io::read::PktRead, Packet};
+use std::convert::TryFrom;
+use std::io::Cursor;
+
+const QUEST_ACTION_RESTORE_ITEM: u8 = 0;
+const QUEST_ACTION_START: u8 = 1;
+const QUEST_ACTION_COMPLETE: u8 = 2;
+const QUEST_ACTION_FORFEIT: u8 = 3;
+const QUEST_ACTION_SCRIPT_START: u8 = 4;
+const QUEST_ACTION_SCRIPT_COMPLETE: u8 = 5;
+
+pub struct QuestActionHandler;
+
+impl QuestActionHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for QuestActionHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let action = reader.read_byte()?;
+        let quest_id = i32::from(reader.read_short()? as u16);
+
+        let character = ctx.session.get_character()?;
+        let outcome = match action {
+            QUEST_ACTION_START | QUEST_ACTION_COMPLETE => {
+                let npc_id = reader.read_int()?;
+                // The NPC's position follows when the client knows it, and a
+                // completion may end with the reward the player picked.
+                let remaining = packet.bytes.len() - reader.position() as usize;
+                if remaining == 4 || remaining >= 6 {
+                    let _position = reader.read_int()?;
+                }
+                let selection = if action == QUEST_ACTION_COMPLETE && remaining != 4 {
+                    read_selection(&mut reader, packet.bytes.len())?
+                } else {
+                    None
+                };
+
+                let mut chr = character.lock().unwrap();
+                if !npc_on_map(chr.character.map_id, npc_id) {
+                    Ok(Err(QuestError::WrongNpc))
+                } else if action == QUEST_ACTION_START {
+                    quests::start_quest(&mut chr, quest_id, Some(npc_id))
+                } else {
+                    quests::complete_quest(&mut chr, quest_id, Some(npc_id), selection)
+                }
+            }
+            QUEST_ACTION_FORFEIT => {
+                let mut chr = character.lock().unwrap();
+                quests::forfeit_quest(&mut chr, quest_id)
+            }
+            QUEST_ACTION_SCRIPT_START | QUEST_ACTION_SCRIPT_COMPLETE => {
+                let npc_id = reader.read_int()?;
+                let complete = action == QUEST_ACTION_SCRIPT_COMPLETE;
+                // Quests without a script start and complete like any other.
+                match NpcFileScript::load_quest(quest_id, complete) {
+                    Some(script) => {
+                        let npc_ctx = NpcContext {
+                            npc_id,
+                            character,
+                            channel_id: channel_id(ctx),
+                            result: HandlerResult::empty(),
+                        };
+                        return advance(ctx.conversation, npc_ctx, Box::new(script), None);
+                    }
+                    None => {
+                        let mut chr = character.lock().unwrap();
+                        if complete {
+                            quests::complete_quest(&mut chr, quest_id, Some(npc_id), None)
+                        } else {
+                            quests::start_quest(&mut chr, quest_id, Some(npc_id))
+                        }
+                    }
+                }
+            }
+            // Restoring lost quest items is not supported.
+            QUEST_ACTION_RESTORE_ITEM => Ok(Err(QuestError::UnknownQuest)),
+            _ => return Err(NetworkError::PacketHandlerError("Unknown quest action")),
+        };
+
+        // A refused quest only needs the client unlocked again.
+        match outcome? {
+            Ok(result) => Ok(result.with_reply(build_empty_stat_update()?)),
+            Err(error) => {
+                eprintln!(
+                    "Quest {} action {} refused for character {}: {}",
+                    quest_id, action, ctx.client_id, error
+                );
+                Ok(HandlerResult::reply(build_empty_stat_update()?))
+            }
+        }
+    }
+}
+
+/// The reward the player picked, sent as a short after everything else.
+fn read_selection(reader: &mut Cursor<&[u8]>, len: usize) -> Result<Option<usize>, NetworkError> {
+    if len - (reader.position() as usize) < 2 {
+        return Ok(None);
+    }
+    Ok(usize::try_from(reader.read_short()?).ok())
+}
+
+fn npc_on_map(map_id: i32, npc_id: i32) -> bool {
+    game_data::get()
+        .ok()
+        .and_then(|data| data.field(map_id))
+        .is_some_and(|field| field.map_npcs.iter().any(|npc| npc.npc_id == npc_id))
+}
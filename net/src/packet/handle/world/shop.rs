@@ -0,0 +1,73 @@
+use crate::error::NetworkError;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::shop::build_shop_result;
+use crate::shops::{self, ShopError};
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+
+const SHOP_ACTION_BUY: u8 = 0;
+const SHOP_ACTION_SELL: u8 = 1;
+const SHOP_ACTION_RECHARGE: u8 = 2;
+const SHOP_ACTION_LEAVE: u8 = 3;
+
+pub struct NpcShopHandler;
+
+impl NpcShopHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for NpcShopHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let action = reader.read_byte()?;
+        if action == SHOP_ACTION_LEAVE {
+            *ctx.shop = None;
+            return Ok(HandlerResult::empty());
+        }
+
+        let character = ctx.session.get_character()?;
+        let mut chr = character.lock().unwrap();
+        let outcome = match (action, *ctx.shop) {
+            (_, None) => Ok(Err(ShopError::NoShop)),
+            (SHOP_ACTION_BUY, Some(npc_id)) => {
+                let slot = reader.read_short()?;
+                let item_id = reader.read_int()?;
+                let quantity = reader.read_short()?;
+                shops::buy(&mut chr, npc_id, slot, item_id, quantity)
+            }
+            (SHOP_ACTION_SELL, Some(_)) => {
+                let position = reader.read_short()?;
+                let item_id = reader.read_int()?;
+                let quantity = reader.read_short()?;
+                shops::sell(&mut chr, position, item_id, quantity)
+            }
+            (SHOP_ACTION_RECHARGE, Some(_)) => {
+                let position = reader.read_short()?;
+                shops::recharge(&mut chr, position)
+            }
+            _ => return Err(NetworkError::PacketHandlerError("Unknown shop action")),
+        };
+
+        match outcome? {
+            Ok(result) => Ok(result),
+            Err(error) => {
+                eprintln!(
+                    "Shop action {} refused for character {}: {}",
+                    action, ctx.client_id, error
+                );
+                Ok(HandlerResult::reply(build_shop_result(error.result())?))
+            }
+        }
+    }
+}
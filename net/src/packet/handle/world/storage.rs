@@ -0,0 +1,78 @@
+use crate::error::NetworkError;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::map::build_empty_stat_update;
+use crate::packet::build::world::storage::build_storage_result;
+use crate::storage::{self, StorageError};
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+
+const STORAGE_ACTION_TAKE_OUT: u8 = 4;
+const STORAGE_ACTION_STORE: u8 = 5;
+const STORAGE_ACTION_ARRANGE: u8 = 6;
+const STORAGE_ACTION_MESO: u8 = 7;
+const STORAGE_ACTION_CLOSE: u8 = 8;
+
+pub struct StorageHandler;
+
+impl StorageHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for StorageHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let action = reader.read_byte()?;
+        if action == STORAGE_ACTION_CLOSE {
+            *ctx.storage = None;
+            return Ok(HandlerResult::empty());
+        }
+
+        let character = ctx.session.get_character()?;
+        let mut chr = character.lock().unwrap();
+        let outcome = match (action, ctx.storage.as_mut()) {
+            (_, None) => Ok(Err(StorageError::NoStorage)),
+            (STORAGE_ACTION_TAKE_OUT, Some(open)) => {
+                let kind = reader.read_byte()?;
+                let index = reader.read_byte()?;
+                storage::take_out(&mut chr, open, kind, index)
+            }
+            (STORAGE_ACTION_STORE, Some(open)) => {
+                let position = reader.read_short()?;
+                let item_id = reader.read_int()?;
+                let quantity = reader.read_short()?;
+                storage::store(&mut chr, open, position, item_id, quantity)
+            }
+            (STORAGE_ACTION_ARRANGE, Some(open)) => storage::arrange(open).map(Ok),
+            (STORAGE_ACTION_MESO, Some(open)) => {
+                let meso = reader.read_int()?;
+                storage::transfer_meso(&mut chr, open, meso)
+            }
+            _ => return Err(NetworkError::PacketHandlerError("Unknown storage action")),
+        };
+
+        match outcome? {
+            Ok(result) => Ok(result),
+            Err(error) => {
+                eprintln!(
+                    "Storage action {} refused for character {}: {}",
+                    action, ctx.client_id, error
+                );
+                match error.result() {
+                    Some(result) => Ok(HandlerResult::reply(build_storage_result(result)?)),
+                    None => Ok(HandlerResult::reply(build_empty_stat_update()?)),
+                }
+            }
+        }
+    }
+}
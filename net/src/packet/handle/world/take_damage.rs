@@ -0,0 +1,58 @@
+use crate::error::NetworkError;
+use crate::handler::{HandlerContext, HandlerResult, PacketHandler};
+use crate::packet::build::world::field::{build_damage_player, DAMAGE_FROM_MAP};
+use crate::packet::build::world::stat::build_stat_changes;
+use crate::stats;
+use packet::{io::read::PktRead, Packet};
+use std::io::Cursor;
+
+pub struct TakeDamageHandler;
+
+impl TakeDamageHandler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PacketHandler for TakeDamageHandler {
+    fn handle(
+        &self,
+        packet: &mut Packet,
+        ctx: &mut HandlerContext,
+    ) -> Result<HandlerResult, NetworkError> {
+        if ctx.client_id == 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let mut reader = Cursor::new(&packet.bytes[..]);
+        let _op = reader.read_short()?;
+        let _tick = reader.read_int()?;
+        let damage_from = reader.read_byte()? as i8;
+        let _element = reader.read_byte()?;
+        let damage = reader.read_int()?.max(0);
+        let (mob_id, direction) = if DAMAGE_FROM_MAP.contains(&damage_from) {
+            (0, 0)
+        } else {
+            let mob_id = reader.read_int()?;
+            let _object_id = reader.read_int()?;
+            (mob_id, reader.read_byte()?)
+        };
+
+        let character = ctx.session.get_character()?;
+        let mut chr = character.lock().unwrap();
+        if chr.character.hp <= 0 {
+            return Ok(HandlerResult::empty());
+        }
+
+        let before = chr.character.clone();
+        stats::take_damage(&mut chr.character, damage);
+        chr.character.save()?;
+
+        let damage_packet =
+            build_damage_player(ctx.client_id, damage_from, damage, mob_id, direction)?;
+        Ok(
+            HandlerResult::reply(build_stat_changes(&before, &chr.character, false)?)
+                .with_field_damage(damage_packet),
+        )
+    }
+}
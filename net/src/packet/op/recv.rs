@@ -30,11 +30,36 @@ pub enum RecvOpcode {
     ChangeMap = 0x26,
 
     PlayerMove = 0x29,
+    CloseRangeAttack = 0x2C,
+    RangedAttack = 0x2D,
+    MagicAttack = 0x2E,
+    TakeDamage = 0x30,
     AllChat = 0x31,
+    TalkToNpc = 0x3A,
+    NpcTalkMore = 0x3C,
+    NpcShopAction = 0x3D,
+    StorageAction = 0x3E,
+    MoveItem = 0x47,
+    DistributeAp = 0x57,
+    AutoDistributeAp = 0x58,
+    HealOverTime = 0x59,
+    DistributeSp = 0x5A,
+    UseSkill = 0x5B,
+    CancelBuff = 0x5C,
+    EnterScriptedPortal = 0x64,
+    QuestAction = 0x6B,
+    MultiChat = 0x77,
     Whisper = 0x78,
+    PlayerInteraction = 0x7B,
+    PartyOperation = 0x7C,
+    DenyPartyRequest = 0x7D,
 
     ChangeKeybinds = 0x87,
 
+    MobMove = 0xBC,
+
+    PickupItem = 0xCA,
+
     PlayerMapTransfer = 0xCF,
     PartySearch = 0xDF,
 
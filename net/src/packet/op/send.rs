@@ -15,18 +15,50 @@ pub enum SendOpcode {
     LastConnectedWorld = 0x1A,
     RecommendedWorlds = 0x1B,
 
+    ModifyInventory = 0x1D,
     StatChange = 0x1F,
+    GiveBuff = 0x20,
+    CancelBuff = 0x21,
+    UpdateSkills = 0x24,
+    ShowStatusInfo = 0x27,
 
+    PartyOperation = 0x3E,
     BuddyList = 0x3F,
     FamilyInfo = 0x5F,
     FamilyList = 0x64,
     SetField = 0x7D,
+    MultiChat = 0x86,
     Whisper = 0x87,
     SpawnPlayer = 0xA0,
     RemovePlayerFromMap = 0xA1,
     ChatText = 0xA2,
     MovePlayer = 0xB9,
+    DamagePlayer = 0xC0,
+    UpdateCharLook = 0xC5,
+    ShowForeignEffect = 0xC6,
+    GiveForeignBuff = 0xC7,
+    CancelForeignBuff = 0xC8,
+    UpdatePartyMemberHp = 0xC9,
+    ShowSelfEffect = 0xCE,
+    UpdateQuestInfo = 0xD3,
+    CloseRangeAttack = 0xBA,
+    RangedAttack = 0xBB,
+    MagicAttack = 0xBC,
+    Cooldown = 0xEA,
+    SpawnMonster = 0xEC,
+    KillMonster = 0xED,
+    SpawnMonsterControl = 0xEE,
+    MoveMonster = 0xEF,
+    MoveMonsterResponse = 0xF0,
+    ShowMonsterHp = 0xFA,
     SpawnNpc = 0x101,
+    DropItemFromMapObject = 0x10C,
+    RemoveItemFromMap = 0x10D,
+    NpcTalk = 0x130,
+    OpenNpcShop = 0x131,
+    ConfirmShopTransaction = 0x132,
+    Storage = 0x135,
+    PlayerInteraction = 0x13A,
 
     KeyMap = 0x14F,
 }
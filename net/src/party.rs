@@ -0,0 +1,146 @@
+/// The most members a party can have.
+pub const MAX_PARTY_SIZE: usize = 6;
+/// How many levels below a mob a party member can be and still share the
+/// EXP of another member's kill without having attacked it.
+pub const PARTY_EXP_LEVEL_RANGE: i32 = 5;
+
+/// Something a character did to form or manage a party. Parties span the
+/// whole world, so these are passed on to the world server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PartyAction {
+    Create,
+    /// Leave the party. A leader leaving disbands it.
+    Leave,
+    /// Join a party the character was invited to.
+    Accept {
+        party_id: i32,
+    },
+    /// Invite a character by name, creating a party first if needed.
+    Invite {
+        name: String,
+    },
+    /// Turn down the invite sent by `inviter`.
+    Decline {
+        inviter: String,
+    },
+    Expel {
+        character_id: i32,
+    },
+    ChangeLeader {
+        character_id: i32,
+    },
+}
+
+/// A party member as the party window shows it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PartyMember {
+    pub id: i32,
+    pub name: String,
+    pub job: i16,
+    pub level: i16,
+    /// The member's channel, or `None` while it is offline.
+    pub channel_id: Option<u8>,
+    pub map_id: i32,
+}
+
+/// A party and its members, in the order they joined.
+#[derive(Clone, Debug)]
+pub struct Party {
+    pub id: i32,
+    pub leader_id: i32,
+    pub members: Vec<PartyMember>,
+}
+
+impl Party {
+    /// Start a party led by its only member.
+    pub fn new(id: i32, leader: PartyMember) -> Self {
+        Self {
+            id,
+            leader_id: leader.id,
+            members: vec![leader],
+        }
+    }
+
+    pub fn member(&self, character_id: i32) -> Option<&PartyMember> {
+        self.members.iter().find(|member| member.id == character_id)
+    }
+
+    pub fn member_mut(&mut self, character_id: i32) -> Option<&mut PartyMember> {
+        self.members
+            .iter_mut()
+            .find(|member| member.id == character_id)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.members.len() >= MAX_PARTY_SIZE
+    }
+
+    /// Ids of the members that are online.
+    pub fn online_member_ids(&self) -> impl Iterator<Item = i32> + '_ {
+        self.members
+            .iter()
+            .filter(|member| member.channel_id.is_some())
+            .map(|member| member.id)
+    }
+
+    /// Add a member. Returns `false`, changing nothing, if the party is full
+    /// or already has the character.
+    pub fn add(&mut self, member: PartyMember) -> bool {
+        if self.is_full() || self.member(member.id).is_some() {
+            return false;
+        }
+        self.members.push(member);
+        true
+    }
+
+    pub fn remove(&mut self, character_id: i32) -> Option<PartyMember> {
+        let index = self
+            .members
+            .iter()
+            .position(|member| member.id == character_id)?;
+        Some(self.members.remove(index))
+    }
+}
+
+/// Whether a party member of `level` shares EXP from a mob of `mob_level`
+/// that it did not attack.
+pub fn in_exp_range(level: i16, mob_level: i32) -> bool {
+    i32::from(level) >= mob_level - PARTY_EXP_LEVEL_RANGE
+}
+
+/// Split `exp` between party members, given as character id and level, in
+/// proportion to their levels.
+pub fn split_exp(exp: i32, members: &[(i32, i16)]) -> Vec<(i32, i32)> {
+    let total_level: i64 = members
+        .iter()
+        .map(|&(_, level)| i64::from(level.max(1)))
+        .sum();
+    if total_level == 0 {
+        return Vec::new();
+    }
+    members
+        .iter()
+        .map(|&(id, level)| {
+            let amount = i64::from(exp) * i64::from(level.max(1)) / total_level;
+            (id, amount as i32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exp_is_split_by_level() {
+        assert_eq!(split_exp(100, &[(1, 30), (2, 10)]), vec![(1, 75), (2, 25)]);
+        assert_eq!(split_exp(100, &[(1, 30)]), vec![(1, 100)]);
+    }
+
+    #[test]
+    fn members_far_below_the_mob_are_out_of_range() {
+        assert!(in_exp_range(25, 30));
+        assert!(!in_exp_range(24, 30));
+        assert!(in_exp_range(50, 30));
+    }
+}
@@ -0,0 +1,259 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::HandlerResult;
+use crate::packet::handle::world::warp_to_map;
+use crate::script::{self, ScriptKind};
+use ::game_data::{PortalTemplate, PortalType};
+use db::character::CharacterWrapper;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+/// What a Rust portal script can reach while it runs.
+pub struct PortalContext {
+    pub character: Arc<Mutex<CharacterWrapper>>,
+    /// The channel the player is on, for warps.
+    pub channel_id: u8,
+    pub result: HandlerResult,
+}
+
+/// Runs when a character enters a scripted portal whose script has no file.
+pub type PortalCallback = Box<dyn Fn(&mut PortalContext) -> Result<(), NetworkError> + Send + Sync>;
+
+/// Where a changeable portal leads instead of its Map.nx destination.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PortalOverride {
+    Closed,
+    Destination { map_id: i32, portal: String },
+}
+
+/// Why a portal could not be changed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PortalError {
+    UnknownPortal,
+    NotChangeable,
+}
+
+impl fmt::Display for PortalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortalError::UnknownPortal => write!(f, "Unknown portal"),
+            PortalError::NotChangeable => write!(f, "Portal is not changeable"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PortalRegistry {
+    callbacks: HashMap<String, PortalCallback>,
+    overrides: HashMap<(i32, String), PortalOverride>,
+}
+
+static PORTAL_REGISTRY: OnceLock<RwLock<PortalRegistry>> = OnceLock::new();
+
+fn registry() -> &'static RwLock<PortalRegistry> {
+    PORTAL_REGISTRY.get_or_init(Default::default)
+}
+
+/// Run `callback` for portals with the script name `script`. A script file of
+/// the same name takes precedence.
+pub fn register_portal(script: &str, callback: PortalCallback) {
+    registry()
+        .write()
+        .unwrap()
+        .callbacks
+        .insert(script.to_string(), callback);
+}
+
+/// Close a changeable portal, send it somewhere else, or with `None` give it
+/// back its Map.nx destination. Applies on every channel.
+pub fn change_portal(
+    map_id: i32,
+    portal_name: &str,
+    portal_override: Option<PortalOverride>,
+) -> Result<(), PortalError> {
+    let portal = game_data::get()
+        .ok()
+        .and_then(|data| data.field(map_id))
+        .and_then(|field| field.portal_by_name(portal_name))
+        .ok_or(PortalError::UnknownPortal)?;
+    if !portal.kind().is_some_and(PortalType::is_changeable) {
+        return Err(PortalError::NotChangeable);
+    }
+
+    let key = (map_id, portal_name.to_string());
+    let mut registry = registry().write().unwrap();
+    match portal_override {
+        Some(portal_override) => {
+            registry.overrides.insert(key, portal_override);
+        }
+        None => {
+            registry.overrides.remove(&key);
+        }
+    }
+    Ok(())
+}
+
+/// Where entering a portal takes the character.
+#[derive(Debug, Eq, PartialEq)]
+enum PortalExit<'a> {
+    Script(&'a str),
+    Warp { map_id: i32, portal: &'a str },
+    Closed,
+}
+
+fn portal_exit<'a>(
+    portal: &'a PortalTemplate,
+    portal_override: Option<&'a PortalOverride>,
+) -> Result<PortalExit<'a>, NetworkError> {
+    let kind = portal
+        .kind()
+        .ok_or(NetworkError::PacketHandlerError("Unknown portal type"))?;
+    if kind.is_spawn_point() {
+        return Err(NetworkError::PacketHandlerError("Portal can't be entered"));
+    }
+    if kind.is_scripted() {
+        let script = portal
+            .script
+            .as_deref()
+            .ok_or(NetworkError::PacketHandlerError(
+                "Scripted portal has no script",
+            ))?;
+        return Ok(PortalExit::Script(script));
+    }
+
+    match portal_override.filter(|_| kind.is_changeable()) {
+        Some(PortalOverride::Closed) => Ok(PortalExit::Closed),
+        Some(PortalOverride::Destination { map_id, portal }) => Ok(PortalExit::Warp {
+            map_id: *map_id,
+            portal,
+        }),
+        None => {
+            let map_id = portal.to_map.ok_or(NetworkError::PacketHandlerError(
+                "Portal has no destination",
+            ))?;
+            Ok(PortalExit::Warp {
+                map_id,
+                portal: &portal.to_name,
+            })
+        }
+    }
+}
+
+/// Enter the portal `portal_name` of the character's current map: warp
+/// through it, or run its script. The result does not unlock the client.
+pub fn enter_portal(
+    character: &Arc<Mutex<CharacterWrapper>>,
+    channel_id: u8,
+    portal_name: &str,
+) -> Result<HandlerResult, NetworkError> {
+    let map_id = character.lock().unwrap().character.map_id;
+    let portal = game_data::get()?
+        .field(map_id)
+        .ok_or(NetworkError::PacketHandlerError("Current field not found"))?
+        .portal_by_name(portal_name)
+        .ok_or(NetworkError::PacketHandlerError("Source portal not found"))?;
+    let portal_override = registry()
+        .read()
+        .unwrap()
+        .overrides
+        .get(&(map_id, portal_name.to_string()))
+        .cloned();
+
+    match portal_exit(portal, portal_override.as_ref())? {
+        PortalExit::Script(script) => run_portal_script(character, channel_id, script),
+        PortalExit::Warp { map_id, portal } => warp_to_map(
+            &mut character.lock().unwrap(),
+            map_id,
+            Some(portal),
+            channel_id,
+        ),
+        PortalExit::Closed => Ok(HandlerResult::empty()),
+    }
+}
+
+fn run_portal_script(
+    character: &Arc<Mutex<CharacterWrapper>>,
+    channel_id: u8,
+    script: &str,
+) -> Result<HandlerResult, NetworkError> {
+    let mut ctx = PortalContext {
+        character: Arc::clone(character),
+        channel_id,
+        result: HandlerResult::empty(),
+    };
+    if let Some(ast) = script::scripts().load(ScriptKind::Portal, script) {
+        script::enter_portal_script(&ast, script, &mut ctx);
+        return Ok(ctx.result);
+    }
+
+    let registry = registry().read().unwrap();
+    match registry.callbacks.get(script) {
+        Some(callback) => callback(&mut ctx)?,
+        None => eprintln!("No script for portal script '{}'", script),
+    }
+    Ok(ctx.result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn portal(portal_type: i32, script: Option<&str>) -> PortalTemplate {
+        PortalTemplate {
+            id: 1,
+            name: "out00".to_string(),
+            portal_type,
+            x: 0,
+            y: 0,
+            to_map: Some(100000000),
+            to_name: "in00".to_string(),
+            script: script.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn portal_exit_follows_the_portal_type() {
+        let warp = PortalExit::Warp {
+            map_id: 100000000,
+            portal: "in00",
+        };
+        assert_eq!(portal_exit(&portal(2, None), None).unwrap(), warp);
+        assert_eq!(portal_exit(&portal(10, None), None).unwrap(), warp);
+        assert_eq!(
+            portal_exit(&portal(9, Some("market00")), None).unwrap(),
+            PortalExit::Script("market00")
+        );
+        assert!(portal_exit(&portal(7, None), None).is_err());
+        assert!(portal_exit(&portal(0, None), None).is_err());
+        assert!(portal_exit(&portal(6, None), None).is_err());
+        assert!(portal_exit(&portal(99, None), None).is_err());
+    }
+
+    #[test]
+    fn only_changeable_portals_follow_their_override() {
+        let closed = PortalOverride::Closed;
+        let moved = PortalOverride::Destination {
+            map_id: 200000000,
+            portal: "sp".to_string(),
+        };
+        assert_eq!(
+            portal_exit(&portal(4, None), Some(&closed)).unwrap(),
+            PortalExit::Closed
+        );
+        assert_eq!(
+            portal_exit(&portal(5, None), Some(&moved)).unwrap(),
+            PortalExit::Warp {
+                map_id: 200000000,
+                portal: "sp"
+            }
+        );
+        assert_eq!(
+            portal_exit(&portal(2, None), Some(&closed)).unwrap(),
+            PortalExit::Warp {
+                map_id: 100000000,
+                portal: "in00"
+            }
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use db::inventory::{Inventory, InventoryType, EQUIP_SLOT_WEAPON};
+
+/// Soul Arrow for bowmen, crossbowmen and Wind Archers. Arrows are fired
+/// without using any up while it runs.
+pub const SOUL_ARROW_SKILLS: [i32; 3] = [3101004, 3201004, 13101003];
+/// Throwing stars are thrown without using any up while Shadow Stars runs.
+pub const SKILL_SHADOW_STARS: i32 = 4121006;
+
+/// Whether a buff from `skill_id` lets ranged attacks skip using up
+/// projectiles.
+pub fn is_projectile_free(skill_id: i32) -> bool {
+    skill_id == SKILL_SHADOW_STARS || SOUL_ARROW_SKILLS.contains(&skill_id)
+}
+
+/// Whether `weapon_id` fires `projectile_id`: bows fire arrows, crossbows
+/// fire crossbow arrows, claws throw stars and guns shoot bullets.
+pub fn fires(weapon_id: i32, projectile_id: i32) -> bool {
+    match weapon_id / 10000 {
+        145 => projectile_id / 1000 == 2060,
+        146 => projectile_id / 1000 == 2061,
+        147 => projectile_id / 10000 == 207,
+        149 => projectile_id / 10000 == 233,
+        _ => false,
+    }
+}
+
+/// The first stack in the Use tab that the equipped weapon can fire `shots`
+/// times from, as its position and item id.
+pub fn find(inventory: &Inventory, shots: i16) -> Option<(i16, i32)> {
+    let weapon = inventory.get(InventoryType::Equip, EQUIP_SLOT_WEAPON)?;
+    inventory
+        .items(InventoryType::Use)
+        .find(|(_, item)| fires(weapon.item_id, item.item_id) && item.quantity >= shots)
+        .map(|(position, item)| (position, item.item_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::test_character_wrapper;
+    use db::inventory::InventoryItem;
+
+    #[test]
+    fn find_skips_stacks_the_weapon_cannot_fire_or_that_are_too_small() {
+        let mut chr = test_character_wrapper();
+        assert_eq!(find(&chr.inventory, 1), None);
+
+        chr.inventory.wear(InventoryItem::new(1472000, 1));
+        chr.inventory.add(InventoryItem::new(2060000, 500), 1000);
+        chr.inventory.add(InventoryItem::new(2070000, 1), 500);
+        chr.inventory.add(InventoryItem::new(2070001, 300), 500);
+
+        assert_eq!(find(&chr.inventory, 1), Some((2, 2070000)));
+        assert_eq!(find(&chr.inventory, 3), Some((3, 2070001)));
+        assert_eq!(find(&chr.inventory, 301), None);
+    }
+}
@@ -0,0 +1,645 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::HandlerResult;
+use crate::packet::build::world::effect::{build_show_self_effect, EFFECT_QUEST_COMPLETE};
+use crate::packet::build::world::quest::{
+    build_complete_quest, build_forfeit_quest, build_quest_result, build_update_quest,
+};
+use crate::rewards;
+use ::game_data::{QuestRequirements, QuestRewards, QuestTemplate};
+use db::character::CharacterWrapper;
+use db::inventory::InventoryType;
+use db::quest::QuestStatus;
+use packet::Packet;
+use rand::{thread_rng, Rng};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::SystemTime;
+
+/// Digits the client reads from a quest's progress for each mob it counts.
+const KILL_COUNT_DIGITS: usize = 3;
+const MAX_KILL_COUNT: i32 = 999;
+
+/// Reward items are given to both genders unless they say otherwise.
+const BOTH_GENDERS: i16 = 2;
+
+/// Why a character can't start or complete a quest.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuestError {
+    UnknownQuest,
+    AlreadyStarted,
+    NotStarted,
+    WrongNpc,
+    LevelTooLow(i16),
+    LevelTooHigh(i16),
+    WrongJob,
+    /// Another quest is not in the state this one needs.
+    QuestState(i32),
+    MissingItem(i32),
+    MissingKills(i32),
+    NotEnoughMeso,
+    InventoryFull,
+}
+
+impl QuestError {
+    pub fn message(self) -> &'static str {
+        match self {
+            QuestError::UnknownQuest => "Unknown quest",
+            QuestError::AlreadyStarted => "Quest already started",
+            QuestError::NotStarted => "Quest not started",
+            QuestError::WrongNpc => "Quest belongs to another NPC",
+            QuestError::LevelTooLow(_) => "Level too low for quest",
+            QuestError::LevelTooHigh(_) => "Level too high for quest",
+            QuestError::WrongJob => "Quest is for another job",
+            QuestError::QuestState(_) => "Quest needs another quest first",
+            QuestError::MissingItem(_) => "Quest items missing",
+            QuestError::MissingKills(_) => "Quest mobs not killed",
+            QuestError::NotEnoughMeso => "Not enough mesos for quest",
+            QuestError::InventoryFull => "No room for quest items",
+        }
+    }
+}
+
+impl fmt::Display for QuestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuestError::LevelTooLow(level) => write!(f, "Requires level {}", level),
+            QuestError::LevelTooHigh(level) => write!(f, "Requires level {} or lower", level),
+            QuestError::QuestState(quest_id) => write!(f, "Requires quest {}", quest_id),
+            QuestError::MissingItem(item_id) => write!(f, "Requires item {}", item_id),
+            QuestError::MissingKills(mob_id) => write!(f, "Requires kills of mob {}", mob_id),
+            error => write!(f, "{}", error.message()),
+        }
+    }
+}
+
+/// The quest state `Check.img` uses: 0 for not started, 1 for started and 2
+/// for completed.
+pub fn quest_state(chr: &CharacterWrapper, quest_id: i32) -> u8 {
+    match chr.quests.status(quest_id) {
+        None => 0,
+        Some(QuestStatus::Started) => 1,
+        Some(QuestStatus::Completed) => 2,
+    }
+}
+
+/// Check everything but mob kills. NPC scripts pass no `npc_id`, so they can
+/// start and complete quests that belong to another NPC.
+fn check_requirements(
+    chr: &CharacterWrapper,
+    requirements: &QuestRequirements,
+    npc_id: Option<i32>,
+) -> Result<(), QuestError> {
+    let character = &chr.character;
+    if let (Some(required), Some(npc_id)) = (requirements.npc, npc_id) {
+        if required != npc_id {
+            return Err(QuestError::WrongNpc);
+        }
+    }
+    if let Some(level) = requirements.min_level {
+        if character.level < level {
+            return Err(QuestError::LevelTooLow(level));
+        }
+    }
+    if let Some(level) = requirements.max_level {
+        if level > 0 && character.level > level {
+            return Err(QuestError::LevelTooHigh(level));
+        }
+    }
+    if !requirements.jobs.is_empty() && !requirements.jobs.contains(&character.job) {
+        return Err(QuestError::WrongJob);
+    }
+    for &(quest_id, state) in &requirements.quests {
+        if quest_state(chr, quest_id) != state {
+            return Err(QuestError::QuestState(quest_id));
+        }
+    }
+    for &(item_id, count) in &requirements.items {
+        if count > 0 && chr.inventory.count(item_id) < count {
+            return Err(QuestError::MissingItem(item_id));
+        }
+    }
+    if character.meso < requirements.meso {
+        return Err(QuestError::NotEnoughMeso);
+    }
+    Ok(())
+}
+
+pub fn check_start(
+    chr: &CharacterWrapper,
+    template: &QuestTemplate,
+    npc_id: Option<i32>,
+) -> Result<(), QuestError> {
+    if chr.quests.status(template.quest_id).is_some() {
+        return Err(QuestError::AlreadyStarted);
+    }
+    check_requirements(chr, &template.start, npc_id)
+}
+
+pub fn check_complete(
+    chr: &CharacterWrapper,
+    template: &QuestTemplate,
+    npc_id: Option<i32>,
+) -> Result<(), QuestError> {
+    let entry = chr
+        .quests
+        .get(template.quest_id)
+        .filter(|entry| entry.status == QuestStatus::Started)
+        .ok_or(QuestError::NotStarted)?;
+    check_requirements(chr, &template.complete, npc_id)?;
+
+    let mobs = &template.complete.mobs;
+    let kills = kill_counts(&entry.progress, mobs.len());
+    for (&(mob_id, needed), &killed) in mobs.iter().zip(&kills) {
+        if killed < needed {
+            return Err(QuestError::MissingKills(mob_id));
+        }
+    }
+    Ok(())
+}
+
+/// The progress of a quest that was just started: no kills of any of the
+/// mobs it counts.
+pub fn initial_progress(template: &QuestTemplate) -> String {
+    "0".repeat(KILL_COUNT_DIGITS * template.complete.mobs.len())
+}
+
+/// Kills of each of `mobs` mobs, read from a quest's progress.
+fn kill_counts(progress: &str, mobs: usize) -> Vec<i32> {
+    (0..mobs)
+        .map(|index| {
+            let start = index * KILL_COUNT_DIGITS;
+            progress
+                .get(start..start + KILL_COUNT_DIGITS)
+                .and_then(|digits| digits.parse().ok())
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// The quest's progress after a kill of `mob_id`, or `None` if the quest does
+/// not count it or already has enough.
+pub fn count_kill(template: &QuestTemplate, progress: &str, mob_id: i32) -> Option<String> {
+    let mobs = &template.complete.mobs;
+    let mut kills = kill_counts(progress, mobs.len());
+    let index = mobs
+        .iter()
+        .zip(&kills)
+        .position(|(&(required, needed), &killed)| required == mob_id && killed < needed)?;
+    kills[index] += 1;
+    Some(
+        kills
+            .iter()
+            .map(|killed| format!("{:03}", killed.min(&MAX_KILL_COUNT)))
+            .collect(),
+    )
+}
+
+/// The items a quest gives or takes, as item ids and counts. Every item for
+/// the character's gender is included, plus one random pick among the items
+/// with a chance and the player's `selection` among the items to choose from.
+pub fn reward_items(
+    rewards: &QuestRewards,
+    gender: i16,
+    selection: Option<usize>,
+    rng: &mut impl Rng,
+) -> Vec<(i32, i32)> {
+    let items: Vec<_> = rewards
+        .items
+        .iter()
+        .filter(|item| item.gender == BOTH_GENDERS || item.gender == gender)
+        .collect();
+
+    let mut picked: Vec<_> = items.iter().filter(|item| item.prop == 0).collect();
+
+    let total: i32 = items.iter().map(|item| item.prop.max(0)).sum();
+    if total > 0 {
+        let mut roll = rng.gen_range(0, total);
+        picked.extend(items.iter().filter(|item| item.prop > 0).find(|item| {
+            roll -= item.prop;
+            roll < 0
+        }));
+    }
+
+    if let Some(selection) = selection {
+        picked.extend(items.iter().filter(|item| item.prop < 0).nth(selection));
+    }
+
+    picked
+        .into_iter()
+        .map(|item| (item.item_id, item.count))
+        .collect()
+}
+
+/// Check the character can afford what a quest takes and has room for what
+/// it gives. Each new item is assumed to need a slot of its own.
+fn check_rewards(
+    chr: &CharacterWrapper,
+    rewards: &QuestRewards,
+    items: &[(i32, i32)],
+) -> Result<(), QuestError> {
+    if chr
+        .character
+        .meso
+        .checked_add(rewards.meso)
+        .filter(|&meso| meso >= 0)
+        .is_none()
+    {
+        return Err(QuestError::NotEnoughMeso);
+    }
+
+    let mut slots: HashMap<InventoryType, u8> = HashMap::new();
+    for &(item_id, count) in items {
+        if count < 0 && chr.inventory.count(item_id) < -count {
+            return Err(QuestError::MissingItem(item_id));
+        }
+        if count > 0 {
+            let tab = InventoryType::of_item(item_id).ok_or(QuestError::InventoryFull)?;
+            *slots.entry(tab).or_default() += 1;
+        }
+    }
+    for (tab, needed) in slots {
+        if chr.inventory.free_slots(tab) < needed {
+            return Err(QuestError::InventoryFull);
+        }
+    }
+    Ok(())
+}
+
+/// Give what a quest gives and take what it takes, without saving.
+/// `check_rewards` must have passed.
+fn give_rewards(
+    chr: &mut CharacterWrapper,
+    rewards: &QuestRewards,
+    items: &[(i32, i32)],
+) -> Result<HandlerResult, NetworkError> {
+    let mut result = HandlerResult::empty();
+    for &(item_id, count) in items {
+        let quantity = count.clamp(i32::from(i16::MIN) + 1, i32::from(i16::MAX)) as i16;
+        let change = if quantity < 0 {
+            rewards::remove_item(chr, item_id, -quantity)?
+        } else {
+            rewards::add_item(chr, item_id, quantity)?
+        };
+        match change {
+            Some(change) => result.actions.extend(change.actions),
+            None => eprintln!("Quest item {} x{} could not be given", item_id, count),
+        }
+    }
+    if rewards.meso != 0 {
+        if let Some(change) = rewards::add_meso(chr, rewards.meso)? {
+            result.actions.extend(change.actions);
+        }
+    }
+    if rewards.exp > 0 {
+        result
+            .actions
+            .extend(rewards::add_exp(chr, rewards.exp)?.actions);
+    }
+    if rewards.fame != 0 {
+        result
+            .actions
+            .extend(rewards::add_fame(chr, rewards.fame)?.actions);
+    }
+    Ok(result)
+}
+
+/// Apply `change` to the character, then save the character, its inventory
+/// and its quest log in one transaction. If either step fails the character
+/// is put back as it was, so a quest is never saved with its rewards half
+/// given and can be tried again.
+fn change_and_save(
+    chr: &mut CharacterWrapper,
+    change: impl FnOnce(&mut CharacterWrapper) -> Result<HandlerResult, NetworkError>,
+) -> Result<HandlerResult, NetworkError> {
+    let character = chr.character.clone();
+    let inventory = chr.inventory.clone();
+    let quests = chr.quests.clone();
+
+    let saved = change(chr).and_then(|result| {
+        db::character::update_character_with_quests(&chr.character, &chr.inventory, &chr.quests)?;
+        Ok(result)
+    });
+    if saved.is_err() {
+        chr.character = character;
+        chr.inventory = inventory;
+        chr.quests = quests;
+    }
+    saved
+}
+
+/// The template of `quest_id`. Unknown quests are refused rather than failing
+/// the handler.
+fn template(quest_id: i32) -> Result<Result<&'static QuestTemplate, QuestError>, NetworkError> {
+    Ok(game_data::quests()?
+        .quest(quest_id)
+        .ok_or(QuestError::UnknownQuest))
+}
+
+/// Start a quest for the character, giving its start rewards, and save it.
+/// The inner error says why the quest was refused.
+pub fn start_quest(
+    chr: &mut CharacterWrapper,
+    quest_id: i32,
+    npc_id: Option<i32>,
+) -> Result<Result<HandlerResult, QuestError>, NetworkError> {
+    let template = match template(quest_id)? {
+        Ok(template) => template,
+        Err(error) => return Ok(Err(error)),
+    };
+    let rewards = &template.start_rewards;
+    let items = reward_items(rewards, chr.character.gender, None, &mut thread_rng());
+    if let Err(error) =
+        check_start(chr, template, npc_id).and_then(|()| check_rewards(chr, rewards, &items))
+    {
+        return Ok(Err(error));
+    }
+
+    let mut result = change_and_save(chr, |chr| {
+        let progress = initial_progress(template);
+        chr.quests.start(quest_id, progress.clone());
+        let mut result = HandlerResult::reply(build_update_quest(quest_id, &progress)?);
+        result
+            .actions
+            .extend(give_rewards(chr, rewards, &items)?.actions);
+        Ok(result)
+    })?;
+    if let Some(npc_id) = npc_id {
+        result = result.with_reply(build_quest_result(quest_id, npc_id, None)?);
+    }
+    Ok(Ok(result))
+}
+
+/// Complete a started quest, giving its rewards, and save the character.
+/// `selection` picks among the reward items the player chooses from.
+pub fn complete_quest(
+    chr: &mut CharacterWrapper,
+    quest_id: i32,
+    npc_id: Option<i32>,
+    selection: Option<usize>,
+) -> Result<Result<HandlerResult, QuestError>, NetworkError> {
+    let template = match template(quest_id)? {
+        Ok(template) => template,
+        Err(error) => return Ok(Err(error)),
+    };
+    let rewards = &template.complete_rewards;
+    let items = reward_items(rewards, chr.character.gender, selection, &mut thread_rng());
+    if let Err(error) =
+        check_complete(chr, template, npc_id).and_then(|()| check_rewards(chr, rewards, &items))
+    {
+        return Ok(Err(error));
+    }
+
+    let mut result = change_and_save(chr, |chr| {
+        let completed_at = SystemTime::now();
+        chr.quests.complete(quest_id, completed_at);
+        let mut result = HandlerResult::reply(build_complete_quest(quest_id, completed_at)?);
+        // Completing takes the mesos the quest asks for.
+        if template.complete.meso > 0 {
+            if let Some(change) = rewards::add_meso(chr, -template.complete.meso)? {
+                result.actions.extend(change.actions);
+            }
+        }
+        result
+            .actions
+            .extend(give_rewards(chr, rewards, &items)?.actions);
+        Ok(result)
+    })?;
+    result = result.with_reply(build_show_self_effect(EFFECT_QUEST_COMPLETE)?);
+    if let Some(npc_id) = npc_id {
+        result = result.with_reply(build_quest_result(quest_id, npc_id, rewards.next_quest)?);
+    }
+    Ok(Ok(result))
+}
+
+/// Drop a started quest and its progress, and save the character.
+pub fn forfeit_quest(
+    chr: &mut CharacterWrapper,
+    quest_id: i32,
+) -> Result<Result<HandlerResult, QuestError>, NetworkError> {
+    if !chr.quests.forfeit(quest_id) {
+        return Ok(Err(QuestError::NotStarted));
+    }
+    chr.quests.save()?;
+    Ok(Ok(HandlerResult::reply(build_forfeit_quest(quest_id)?)))
+}
+
+/// Count a kill of `mob_id` towards the character's started quests, and save
+/// them. Returns the progress updates to show.
+pub fn record_mob_kill(
+    chr: &mut CharacterWrapper,
+    mob_id: i32,
+) -> Result<Vec<Packet>, NetworkError> {
+    // Characters without started quests never need the quest data.
+    if chr.quests.started().next().is_none() {
+        return Ok(Vec::new());
+    }
+    let quest_data = game_data::quests()?;
+    let updates: Vec<(i32, String)> = chr
+        .quests
+        .started()
+        .filter_map(|(quest_id, progress)| {
+            let template = quest_data.quest(quest_id)?;
+            Some((quest_id, count_kill(template, progress, mob_id)?))
+        })
+        .collect();
+    if updates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    for (quest_id, progress) in &updates {
+        chr.quests.set_progress(*quest_id, progress.clone());
+    }
+    chr.quests.save()?;
+    updates
+        .iter()
+        .map(|(quest_id, progress)| build_update_quest(*quest_id, progress))
+        .collect()
+}
+
+/// Progress updates for the started quests that need `item_id`, so the quest
+/// log shows the character's new item count.
+pub fn item_progress(chr: &CharacterWrapper, item_id: i32) -> Result<Vec<Packet>, NetworkError> {
+    if chr.quests.started().next().is_none() {
+        return Ok(Vec::new());
+    }
+    let quest_data = game_data::quests()?;
+    chr.quests
+        .started()
+        .filter(|&(quest_id, _)| {
+            quest_data.quest(quest_id).is_some_and(|template| {
+                template
+                    .complete
+                    .items
+                    .iter()
+                    .any(|&(required, _)| required == item_id)
+            })
+        })
+        .map(|(quest_id, progress)| build_update_quest(quest_id, progress))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::test_character_wrapper;
+    use ::game_data::QuestItem;
+    use db::inventory::InventoryItem;
+    use rand::rngs::mock::StepRng;
+
+    fn snail_quest() -> QuestTemplate {
+        QuestTemplate {
+            quest_id: 1000,
+            start: QuestRequirements {
+                npc: Some(2000),
+                min_level: Some(5),
+                ..QuestRequirements::default()
+            },
+            complete: QuestRequirements {
+                npc: Some(2000),
+                items: vec![(4000019, 3)],
+                mobs: vec![(100100, 2), (100101, 1)],
+                ..QuestRequirements::default()
+            },
+            ..QuestTemplate::default()
+        }
+    }
+
+    fn item(item_id: i32, prop: i32, gender: i16) -> QuestItem {
+        QuestItem {
+            item_id,
+            count: 1,
+            prop,
+            gender,
+        }
+    }
+
+    #[test]
+    fn kills_count_until_each_mob_has_enough() {
+        let template = snail_quest();
+        let progress = initial_progress(&template);
+        assert_eq!(progress, "000000");
+
+        let progress = count_kill(&template, &progress, 100100).expect("first snail");
+        assert_eq!(progress, "001000");
+        let progress = count_kill(&template, &progress, 100100).expect("second snail");
+        assert_eq!(progress, "002000");
+        assert_eq!(count_kill(&template, &progress, 100100), None);
+        assert_eq!(count_kill(&template, &progress, 9300000), None);
+        assert_eq!(
+            count_kill(&template, &progress, 100101).as_deref(),
+            Some("002001")
+        );
+    }
+
+    #[test]
+    fn start_and_complete_requirements_are_checked() {
+        let template = snail_quest();
+        let mut chr = test_character_wrapper();
+
+        chr.character.level = 4;
+        assert_eq!(
+            check_start(&chr, &template, Some(2000)),
+            Err(QuestError::LevelTooLow(5))
+        );
+        chr.character.level = 5;
+        assert_eq!(
+            check_start(&chr, &template, Some(2001)),
+            Err(QuestError::WrongNpc)
+        );
+        assert_eq!(check_start(&chr, &template, None), Ok(()));
+        assert_eq!(
+            check_complete(&chr, &template, Some(2000)),
+            Err(QuestError::NotStarted)
+        );
+
+        chr.quests.start(1000, "002000".to_string());
+        assert_eq!(
+            check_start(&chr, &template, Some(2000)),
+            Err(QuestError::AlreadyStarted)
+        );
+        assert_eq!(
+            check_complete(&chr, &template, Some(2000)),
+            Err(QuestError::MissingItem(4000019))
+        );
+        chr.inventory.add(InventoryItem::new(4000019, 3), 100);
+        assert_eq!(
+            check_complete(&chr, &template, Some(2000)),
+            Err(QuestError::MissingKills(100101))
+        );
+        chr.quests.set_progress(1000, "002001".to_string());
+        assert_eq!(check_complete(&chr, &template, Some(2000)), Ok(()));
+    }
+
+    #[test]
+    fn reward_items_pick_by_gender_chance_and_selection() {
+        let rewards = QuestRewards {
+            items: vec![
+                item(2000000, 0, BOTH_GENDERS),
+                item(1040002, 0, 0),
+                item(1041002, 0, 1),
+                item(2000001, 1, BOTH_GENDERS),
+                item(2000002, 3, BOTH_GENDERS),
+                item(1302000, -1, BOTH_GENDERS),
+                item(1322005, -1, BOTH_GENDERS),
+            ],
+            ..QuestRewards::default()
+        };
+
+        let mut rng = StepRng::new(0, 0);
+        assert_eq!(
+            reward_items(&rewards, 1, Some(1), &mut rng),
+            vec![(2000000, 1), (1041002, 1), (2000001, 1), (1322005, 1)]
+        );
+        let mut rng = StepRng::new(1 << 31, 0);
+        assert_eq!(
+            reward_items(&rewards, 0, None, &mut rng),
+            vec![(2000000, 1), (1040002, 1), (2000002, 1)]
+        );
+    }
+
+    #[test]
+    fn rewards_need_room_and_enough_to_take() {
+        let mut chr = test_character_wrapper();
+        let rewards = QuestRewards {
+            meso: -100,
+            ..QuestRewards::default()
+        };
+
+        chr.character.meso = 99;
+        assert_eq!(
+            check_rewards(&chr, &rewards, &[]),
+            Err(QuestError::NotEnoughMeso)
+        );
+        chr.character.meso = 100;
+        assert_eq!(check_rewards(&chr, &rewards, &[]), Ok(()));
+        assert_eq!(
+            check_rewards(&chr, &rewards, &[(4000019, -1)]),
+            Err(QuestError::MissingItem(4000019))
+        );
+
+        let free = chr.inventory.free_slots(InventoryType::Use);
+        let items: Vec<_> = (0..=i32::from(free)).map(|_| (2000000, 1)).collect();
+        assert_eq!(
+            check_rewards(&chr, &rewards, &items),
+            Err(QuestError::InventoryFull)
+        );
+        assert_eq!(check_rewards(&chr, &rewards, &items[1..]), Ok(()));
+    }
+
+    #[test]
+    fn a_failed_change_puts_the_character_back() {
+        let mut chr = test_character_wrapper();
+        chr.character.meso = 100;
+
+        let result = change_and_save(&mut chr, |chr| {
+            chr.quests.start(1000, String::new());
+            rewards::add_meso(chr, 50)?;
+            Err(NetworkError::PacketHandlerError("Reward failed"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(chr.character.meso, 100);
+        assert_eq!(chr.quests.status(1000), None);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::HandlerResult;
+use crate::packet::build::world::effect::{build_show_self_effect, EFFECT_LEVEL_UP};
+use crate::packet::build::world::inventory::{build_inventory_add, build_inventory_take};
+use crate::packet::build::world::stat::{
+    build_meso_update, build_show_exp_gain, build_show_fame_gain, build_show_item_gain,
+    build_show_meso_gain, build_stat_changes,
+};
+use crate::{quests, stats};
+use db::character::CharacterWrapper;
+use rand::thread_rng;
+
+/// Give the character EXP from a script and save it. Level ups are shown to
+/// the character's field too.
+pub fn gain_exp(chr: &mut CharacterWrapper, amount: i32) -> Result<HandlerResult, NetworkError> {
+    let result = add_exp(chr, amount)?;
+    chr.character.save()?;
+    Ok(result)
+}
+
+/// `gain_exp` without saving, for callers that save the character with
+/// other changes.
+pub(crate) fn add_exp(
+    chr: &mut CharacterWrapper,
+    amount: i32,
+) -> Result<HandlerResult, NetworkError> {
+    let before = chr.character.clone();
+    let levels = stats::gain_exp(&mut chr.character, amount, &mut thread_rng());
+
+    let result = HandlerResult::replies(vec![
+        build_show_exp_gain(amount, true)?,
+        build_stat_changes(&before, &chr.character, false)?,
+    ]);
+    if levels == 0 {
+        return Ok(result);
+    }
+    Ok(result
+        .with_reply(build_show_self_effect(EFFECT_LEVEL_UP)?)
+        .with_field_level_up(chr.character.level))
+}
+
+/// Give or, for a negative amount, take mesos and save the character.
+/// Returns `None`, changing nothing, if the character can't afford it.
+pub fn gain_meso(
+    chr: &mut CharacterWrapper,
+    amount: i32,
+) -> Result<Option<HandlerResult>, NetworkError> {
+    let result = add_meso(chr, amount)?;
+    if result.is_some() {
+        chr.character.save()?;
+    }
+    Ok(result)
+}
+
+/// `gain_meso` without saving, for callers that save the character with
+/// other changes.
+pub(crate) fn add_meso(
+    chr: &mut CharacterWrapper,
+    amount: i32,
+) -> Result<Option<HandlerResult>, NetworkError> {
+    let Some(meso) = chr
+        .character
+        .meso
+        .checked_add(amount)
+        .filter(|&meso| meso >= 0)
+    else {
+        return Ok(None);
+    };
+    chr.character.meso = meso;
+
+    let result = HandlerResult::reply(build_meso_update(meso, false)?);
+    if amount <= 0 {
+        return Ok(Some(result));
+    }
+    Ok(Some(result.with_reply(build_show_meso_gain(amount)?)))
+}
+
+/// Put new items in the character's inventory and save it. Returns `None`,
+/// changing nothing, for unknown items or if there is no room.
+pub fn gain_item(
+    chr: &mut CharacterWrapper,
+    item_id: i32,
+    quantity: i16,
+) -> Result<Option<HandlerResult>, NetworkError> {
+    let result = add_item(chr, item_id, quantity)?;
+    if result.is_some() {
+        chr.inventory.save()?;
+    }
+    Ok(result)
+}
+
+/// `gain_item` without saving, for callers that save the inventory with
+/// other changes.
+pub(crate) fn add_item(
+    chr: &mut CharacterWrapper,
+    item_id: i32,
+    quantity: i16,
+) -> Result<Option<HandlerResult>, NetworkError> {
+    let Some(template) = game_data::items()?.item(item_id) else {
+        return Ok(None);
+    };
+    if quantity <= 0 {
+        return Ok(None);
+    }
+    let item = game_data::item_from_template(template, quantity);
+    let quantity = item.quantity;
+    let Some((tab, position)) = chr.inventory.add(item, template.slot_max) else {
+        return Ok(None);
+    };
+
+    let item = chr
+        .inventory
+        .get(tab, position)
+        .ok_or(NetworkError::PacketHandlerError("Added item not found"))?;
+    let mut packets = vec![
+        build_inventory_add(tab, position, item)?,
+        build_show_item_gain(item_id, i32::from(quantity))?,
+    ];
+    packets.extend(quests::item_progress(chr, item_id)?);
+    Ok(Some(HandlerResult::replies(packets)))
+}
+
+/// Take items out of the character's inventory without saving it. Returns
+/// `None`, changing nothing, if it does not carry enough.
+pub(crate) fn remove_item(
+    chr: &mut CharacterWrapper,
+    item_id: i32,
+    quantity: i16,
+) -> Result<Option<HandlerResult>, NetworkError> {
+    let Some(taken) = chr.inventory.take(item_id, quantity) else {
+        return Ok(None);
+    };
+
+    let mut packets = taken
+        .into_iter()
+        .map(|(tab, position, remaining)| build_inventory_take(tab, position, remaining))
+        .collect::<Result<Vec<_>, _>>()?;
+    packets.push(build_show_item_gain(item_id, -i32::from(quantity))?);
+    Ok(Some(HandlerResult::replies(packets)))
+}
+
+/// Give or, for a negative amount, take fame without saving the character.
+pub(crate) fn add_fame(
+    chr: &mut CharacterWrapper,
+    amount: i32,
+) -> Result<HandlerResult, NetworkError> {
+    let before = chr.character.clone();
+    let fame = i32::from(chr.character.fame).saturating_add(amount);
+    chr.character.fame = fame.clamp(i32::from(i16::MIN), i32::from(i16::MAX)) as i16;
+
+    Ok(HandlerResult::replies(vec![
+        build_stat_changes(&before, &chr.character, false)?,
+        build_show_fame_gain(amount)?,
+    ]))
+}
@@ -0,0 +1,339 @@
+use crate::error::NetworkError;
+use crate::handler::HandlerResult;
+use crate::jobs;
+use crate::npc::Dialog;
+use crate::packet::handle::world::warp_to_map;
+use crate::portal::{self, PortalOverride};
+use crate::quests;
+use crate::rewards;
+use crate::storage;
+use db::character::CharacterWrapper;
+use rhai::{Array, Engine, EvalAltResult};
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type ApiResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// The handle scripts get as their first argument. It reads and changes the
+/// player's character and collects what the call asks to send back. Clones
+/// share the same state.
+#[derive(Clone)]
+pub(crate) struct ScriptApi(Arc<Mutex<ApiState>>);
+
+struct ApiState {
+    character: Arc<Mutex<CharacterWrapper>>,
+    channel_id: u8,
+    status: i64,
+    result: HandlerResult,
+    dialog: Option<Dialog>,
+    /// Portal scripts run without a conversation, so they can't show dialogs.
+    dialogs: bool,
+}
+
+impl ScriptApi {
+    pub(crate) fn new(
+        character: Arc<Mutex<CharacterWrapper>>,
+        channel_id: u8,
+        status: i64,
+    ) -> Self {
+        ScriptApi(Arc::new(Mutex::new(ApiState {
+            character,
+            channel_id,
+            status,
+            result: HandlerResult::empty(),
+            dialog: None,
+            dialogs: true,
+        })))
+    }
+
+    pub(crate) fn for_portal(character: Arc<Mutex<CharacterWrapper>>, channel_id: u8) -> Self {
+        let api = Self::new(character, channel_id, 0);
+        api.state().dialogs = false;
+        api
+    }
+
+    /// What the call asked for: its actions, the dialog to show next, and the
+    /// status to start the next call with.
+    pub(crate) fn finish(self) -> (HandlerResult, Option<Dialog>, i64) {
+        let mut state = self.state();
+        let result = std::mem::take(&mut state.result);
+        (result, state.dialog.take(), state.status)
+    }
+
+    fn state(&self) -> MutexGuard<'_, ApiState> {
+        self.0.lock().unwrap()
+    }
+
+    fn with_character<T>(&self, f: impl FnOnce(&mut CharacterWrapper) -> T) -> T {
+        let character = Arc::clone(&self.state().character);
+        let mut chr = character.lock().unwrap();
+        f(&mut chr)
+    }
+
+    fn extend(&self, result: HandlerResult) {
+        self.state().result.actions.extend(result.actions);
+    }
+
+    fn show(&mut self, dialog: Dialog) -> ApiResult<()> {
+        let mut state = self.state();
+        if !state.dialogs {
+            return Err("portal scripts can't show dialogs".into());
+        }
+        if state.dialog.is_some() {
+            return Err("a script call can only show one dialog".into());
+        }
+        state.dialog = Some(dialog);
+        Ok(())
+    }
+
+    fn warp(&mut self, map_id: i64, portal: Option<&str>) -> ApiResult<()> {
+        let map_id = int(map_id, "map id")?;
+        let channel_id = self.state().channel_id;
+        let result = self
+            .with_character(|chr| warp_to_map(chr, map_id, portal, channel_id))
+            .map_err(script_error)?;
+        self.extend(result);
+        Ok(())
+    }
+
+    /// Run a change that may be refused, keeping its actions if it went ahead.
+    fn try_change(
+        &mut self,
+        change: impl FnOnce(&mut CharacterWrapper) -> Result<Option<HandlerResult>, NetworkError>,
+    ) -> ApiResult<bool> {
+        match self.with_character(change).map_err(script_error)? {
+            Some(result) => {
+                self.extend(result);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+fn int<T: TryFrom<i64>>(value: i64, what: &str) -> ApiResult<T> {
+    T::try_from(value).map_err(|_| format!("{} {} is out of range", what, value).into())
+}
+
+/// Change a portal, returning whether it was changeable.
+fn change_portal(
+    map_id: i64,
+    name: &str,
+    portal_override: Option<PortalOverride>,
+) -> ApiResult<bool> {
+    let map_id = int(map_id, "map id")?;
+    Ok(portal::change_portal(map_id, name, portal_override).is_ok())
+}
+
+fn script_error(error: NetworkError) -> Box<EvalAltResult> {
+    error.to_string().into()
+}
+
+/// Register the script API on `engine`.
+pub(crate) fn register(engine: &mut Engine) {
+    engine.register_type_with_name::<ScriptApi>("Script");
+
+    // State the script keeps between calls of one conversation.
+    engine.register_get_set(
+        "status",
+        |api: &mut ScriptApi| api.state().status,
+        |api: &mut ScriptApi, status: i64| api.state().status = status,
+    );
+
+    // Dialogs. A call shows at most one; returning without one ends the
+    // conversation.
+    engine.register_fn("say", |api: &mut ScriptApi, text: &str| {
+        api.show(Dialog::say(text))
+    });
+    engine.register_fn("say_next", |api: &mut ScriptApi, text: &str| {
+        api.show(Dialog::say_next(text))
+    });
+    engine.register_fn("say_prev", |api: &mut ScriptApi, text: &str| {
+        api.show(Dialog::Say {
+            text: text.to_string(),
+            prev: true,
+            next: false,
+        })
+    });
+    engine.register_fn("say_prev_next", |api: &mut ScriptApi, text: &str| {
+        api.show(Dialog::Say {
+            text: text.to_string(),
+            prev: true,
+            next: true,
+        })
+    });
+    engine.register_fn("yes_no", |api: &mut ScriptApi, text: &str| {
+        api.show(Dialog::YesNo {
+            text: text.to_string(),
+        })
+    });
+    engine.register_fn("accept_decline", |api: &mut ScriptApi, text: &str| {
+        api.show(Dialog::AcceptDecline {
+            text: text.to_string(),
+        })
+    });
+    engine.register_fn("menu", |api: &mut ScriptApi, text: &str| {
+        api.show(Dialog::Menu {
+            text: text.to_string(),
+        })
+    });
+    engine.register_fn(
+        "get_number",
+        |api: &mut ScriptApi, text: &str, default: i64, min: i64, max: i64| {
+            api.show(Dialog::GetNumber {
+                text: text.to_string(),
+                default: int(default, "default")?,
+                min: int(min, "minimum")?,
+                max: int(max, "maximum")?,
+            })
+        },
+    );
+    engine.register_fn(
+        "get_text",
+        |api: &mut ScriptApi, text: &str, default: &str| {
+            api.show(Dialog::GetText {
+                text: text.to_string(),
+                default: default.to_string(),
+            })
+        },
+    );
+    engine.register_fn("style", |api: &mut ScriptApi, text: &str, styles: Array| {
+        let styles = styles
+            .into_iter()
+            .map(|style| {
+                let style = style.as_int().map_err(|_| "styles must be numbers")?;
+                int(style, "style")
+            })
+            .collect::<ApiResult<Vec<i32>>>()?;
+        api.show(Dialog::Style {
+            text: text.to_string(),
+            styles,
+        })
+    });
+
+    // The character.
+    engine.register_get("name", |api: &mut ScriptApi| {
+        api.with_character(|chr| chr.character.name.clone())
+    });
+    engine.register_get("level", |api: &mut ScriptApi| {
+        api.with_character(|chr| i64::from(chr.character.level))
+    });
+    engine.register_get("job", |api: &mut ScriptApi| {
+        api.with_character(|chr| i64::from(chr.character.job))
+    });
+    engine.register_get("gender", |api: &mut ScriptApi| {
+        api.with_character(|chr| i64::from(chr.character.gender))
+    });
+    engine.register_get("meso", |api: &mut ScriptApi| {
+        api.with_character(|chr| i64::from(chr.character.meso))
+    });
+    engine.register_get("map", |api: &mut ScriptApi| {
+        api.with_character(|chr| i64::from(chr.character.map_id))
+    });
+    engine.register_fn(
+        "item_count",
+        |api: &mut ScriptApi, item_id: i64| -> ApiResult<i64> {
+            let item_id = int(item_id, "item id")?;
+            Ok(api.with_character(|chr| i64::from(chr.inventory.count(item_id))))
+        },
+    );
+    engine.register_fn(
+        "has_item",
+        |api: &mut ScriptApi, item_id: i64, quantity: i64| -> ApiResult<bool> {
+            let item_id = int(item_id, "item id")?;
+            Ok(api.with_character(|chr| i64::from(chr.inventory.count(item_id))) >= quantity)
+        },
+    );
+    engine.register_fn(
+        "quest_status",
+        |api: &mut ScriptApi, quest_id: i64| -> ApiResult<i64> {
+            let quest_id = int(quest_id, "quest id")?;
+            Ok(api.with_character(|chr| i64::from(quests::quest_state(chr, quest_id))))
+        },
+    );
+
+    // Changes. Each one is saved and shown to the player straight away.
+    engine.register_fn("warp", |api: &mut ScriptApi, map_id: i64| {
+        api.warp(map_id, None)
+    });
+    engine.register_fn("warp", |api: &mut ScriptApi, map_id: i64, portal: &str| {
+        api.warp(map_id, Some(portal))
+    });
+    engine.register_fn(
+        "gain_exp",
+        |api: &mut ScriptApi, amount: i64| -> ApiResult<()> {
+            let amount: i32 = int(amount, "EXP")?;
+            if amount <= 0 {
+                return Err("EXP must be positive".into());
+            }
+            let result = api
+                .with_character(|chr| rewards::gain_exp(chr, amount))
+                .map_err(script_error)?;
+            api.extend(result);
+            Ok(())
+        },
+    );
+    engine.register_fn("gain_meso", |api: &mut ScriptApi, amount: i64| {
+        let amount = int(amount, "meso amount")?;
+        api.try_change(|chr| rewards::gain_meso(chr, amount))
+    });
+    engine.register_fn(
+        "gain_item",
+        |api: &mut ScriptApi, item_id: i64, quantity: i64| {
+            let item_id = int(item_id, "item id")?;
+            let quantity = int(quantity, "item quantity")?;
+            api.try_change(|chr| rewards::gain_item(chr, item_id, quantity))
+        },
+    );
+    // Quests, checked against their requirements but not their NPC.
+    engine.register_fn("start_quest", |api: &mut ScriptApi, quest_id: i64| {
+        let quest_id = int(quest_id, "quest id")?;
+        api.try_change(|chr| Ok(quests::start_quest(chr, quest_id, None)?.ok()))
+    });
+    engine.register_fn("complete_quest", |api: &mut ScriptApi, quest_id: i64| {
+        let quest_id = int(quest_id, "quest id")?;
+        api.try_change(|chr| Ok(quests::complete_quest(chr, quest_id, None, None)?.ok()))
+    });
+    engine.register_fn(
+        "can_change_job",
+        |api: &mut ScriptApi, job: i64| -> ApiResult<bool> {
+            let job = int(job, "job")?;
+            Ok(api.with_character(|chr| jobs::check_advancement(&chr.character, job).is_ok()))
+        },
+    );
+    engine.register_fn("change_job", |api: &mut ScriptApi, job: i64| {
+        let job = int(job, "job")?;
+        api.try_change(|chr| {
+            if jobs::check_advancement(&chr.character, job).is_err() {
+                return Ok(None);
+            }
+            jobs::change_job(chr, job).map(Some)
+        })
+    });
+    engine.register_fn("expand_storage", |api: &mut ScriptApi, slots: i64| {
+        let slots = int(slots, "storage slots")?;
+        api.try_change(|chr| Ok(storage::expand_storage(chr, slots)?.then(HandlerResult::empty)))
+    });
+
+    // Changeable portals, shared by every player on every channel.
+    engine.register_fn(
+        "close_portal",
+        |_api: &mut ScriptApi, map_id: i64, name: &str| {
+            change_portal(map_id, name, Some(PortalOverride::Closed))
+        },
+    );
+    engine.register_fn(
+        "open_portal",
+        |_api: &mut ScriptApi, map_id: i64, name: &str| change_portal(map_id, name, None),
+    );
+    engine.register_fn(
+        "redirect_portal",
+        |_api: &mut ScriptApi, map_id: i64, name: &str, to_map: i64, to_portal: &str| {
+            let destination = PortalOverride::Destination {
+                map_id: int(to_map, "map id")?,
+                portal: to_portal.to_string(),
+            };
+            change_portal(map_id, name, Some(destination))
+        },
+    );
+}
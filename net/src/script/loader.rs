@@ -0,0 +1,147 @@
+use rhai::{Engine, AST};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// What a script is attached to. Each kind has its own directory under the
+/// scripts root.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ScriptKind {
+    /// `npc/<npc id>.rhai`
+    Npc,
+    /// `portal/<portal script name>.rhai`
+    Portal,
+    /// `quest/<quest id>.rhai`
+    Quest,
+}
+
+impl ScriptKind {
+    pub fn directory(self) -> &'static str {
+        match self {
+            ScriptKind::Npc => "npc",
+            ScriptKind::Portal => "portal",
+            ScriptKind::Quest => "quest",
+        }
+    }
+}
+
+struct CachedScript {
+    modified: SystemTime,
+    ast: Arc<AST>,
+}
+
+/// Compiles scripts the first time they are used, and again whenever their
+/// file changes, so edits take effect without a restart.
+pub struct ScriptLoader {
+    root: PathBuf,
+    cache: Mutex<HashMap<PathBuf, CachedScript>>,
+}
+
+impl ScriptLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Where the script for `name` lives, or `None` for names that are not a
+    /// plain file name.
+    pub fn path(&self, kind: ScriptKind, name: &str) -> Option<PathBuf> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        valid.then(|| {
+            self.root
+                .join(kind.directory())
+                .join(format!("{}.rhai", name))
+        })
+    }
+
+    /// The compiled script for `name`, or `None` if there is none. Scripts
+    /// that fail to compile are logged and treated as missing.
+    pub fn load(&self, engine: &Engine, kind: ScriptKind, name: &str) -> Option<Arc<AST>> {
+        let path = self.path(kind, name)?;
+        let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(cached) = cache.get(&path) {
+            if cached.modified == modified {
+                return Some(Arc::clone(&cached.ast));
+            }
+        }
+
+        match engine.compile_file(path.clone()) {
+            Ok(ast) => {
+                let ast = Arc::new(ast);
+                cache.insert(
+                    path,
+                    CachedScript {
+                        modified,
+                        ast: Arc::clone(&ast),
+                    },
+                );
+                Some(ast)
+            }
+            Err(error) => {
+                eprintln!("failed to compile script '{}': {}", path.display(), error);
+                cache.remove(&path);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn write_script(path: &PathBuf, source: &str, modified: SystemTime) {
+        fs::write(path, source).expect("write script");
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(modified))
+            .expect("set modified");
+    }
+
+    #[test]
+    fn load_recompiles_scripts_when_their_file_changes() {
+        let root = std::env::temp_dir().join(format!("rustms-scripts-{}", std::process::id()));
+        fs::create_dir_all(root.join("npc")).expect("create script dir");
+        let loader = ScriptLoader::new(&root);
+        let engine = Engine::new();
+        let path = loader.path(ScriptKind::Npc, "2100").expect("path");
+        let then = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+        write_script(&path, "fn start() { 1 }", then);
+        let first = loader
+            .load(&engine, ScriptKind::Npc, "2100")
+            .expect("script");
+        let again = loader
+            .load(&engine, ScriptKind::Npc, "2100")
+            .expect("script");
+        assert!(Arc::ptr_eq(&first, &again));
+
+        write_script(&path, "fn start() { 2 }", then + Duration::from_secs(1));
+        let edited = loader
+            .load(&engine, ScriptKind::Npc, "2100")
+            .expect("script");
+        assert!(!Arc::ptr_eq(&first, &edited));
+        let value: i64 = engine
+            .call_fn(&mut rhai::Scope::new(), &edited, "start", ())
+            .expect("call");
+        assert_eq!(value, 2);
+
+        write_script(&path, "fn start( {", then + Duration::from_secs(2));
+        assert!(loader.load(&engine, ScriptKind::Npc, "2100").is_none());
+        assert!(loader.load(&engine, ScriptKind::Npc, "9999").is_none());
+        assert!(loader.path(ScriptKind::Npc, "../secret").is_none());
+
+        fs::remove_dir_all(&root).expect("remove script dir");
+    }
+}
@@ -0,0 +1,105 @@
+mod api;
+mod loader;
+mod npc;
+mod portal;
+
+pub use self::loader::{ScriptKind, ScriptLoader};
+pub use self::npc::NpcFileScript;
+pub(crate) use self::portal::enter_portal_script;
+
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, ParseError, Scope, AST};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+static SCRIPTS: OnceLock<Scripts> = OnceLock::new();
+
+/// The scripting engine and the content scripts it runs. Scripts are Rhai
+/// files under `scripts/`, or under `RUSTMS_SCRIPTS_PATH` if it is set.
+pub struct Scripts {
+    engine: Engine,
+    loader: ScriptLoader,
+}
+
+impl Scripts {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let mut engine = Engine::new();
+        // Scripts only get the game API, and limits that stop a runaway
+        // script from holding up the server.
+        engine.disable_symbol("eval");
+        engine.set_max_operations(100_000);
+        engine.set_max_call_levels(32);
+        engine.set_max_string_size(16_384);
+        engine.set_max_array_size(1_024);
+        engine.set_max_map_size(1_024);
+        engine.on_print(|text| println!("[script] {}", text));
+        api::register(&mut engine);
+
+        Self {
+            engine,
+            loader: ScriptLoader::new(root),
+        }
+    }
+
+    pub fn load(&self, kind: ScriptKind, name: &str) -> Option<Arc<AST>> {
+        self.loader.load(&self.engine, kind, name)
+    }
+
+    pub fn compile(&self, source: &str) -> Result<AST, ParseError> {
+        self.engine.compile(source)
+    }
+
+    /// Call `function` in a script. Scripts that don't define it do nothing.
+    pub fn call(
+        &self,
+        ast: &AST,
+        function: &str,
+        args: Vec<Dynamic>,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let defined = ast
+            .iter_functions()
+            .any(|f| f.name == function && f.params.len() == args.len());
+        if !defined {
+            return Ok(());
+        }
+        // Only the function runs, not the script's top-level statements.
+        let options = CallFnOptions::new().eval_ast(false);
+        self.engine
+            .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), ast, function, args)
+            .map(|_| ())
+    }
+}
+
+pub fn scripts() -> &'static Scripts {
+    SCRIPTS.get_or_init(|| {
+        let root = std::env::var("RUSTMS_SCRIPTS_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("scripts"));
+        Scripts::new(root)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn shipped_scripts_compile() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../scripts");
+        let scripts = Scripts::new(&root);
+        for kind in [ScriptKind::Npc, ScriptKind::Portal, ScriptKind::Quest].iter() {
+            let Ok(entries) = fs::read_dir(root.join(kind.directory())) else {
+                continue;
+            };
+            for entry in entries {
+                let path = entry.expect("script entry").path();
+                let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap();
+                assert!(
+                    scripts.load(*kind, name).is_some(),
+                    "{} does not compile",
+                    path.display()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use super::api::ScriptApi;
+use super::{scripts, ScriptKind};
+use crate::error::NetworkError;
+use crate::npc::{Answer, NpcContext, NpcScript, Step};
+use rhai::{Dynamic, AST};
+use std::sync::Arc;
+
+/// An NPC run by `npc/<npc id>.rhai`. The script defines `start(npc)`, called
+/// when the player clicks the NPC, and `action(npc, answer)`, called with each
+/// answer. The script keeps the version it started with for the rest of the
+/// conversation.
+pub struct NpcFileScript {
+    ast: Arc<AST>,
+    status: i64,
+    /// The functions called to start the conversation and with each answer.
+    entry: &'static str,
+    action: &'static str,
+}
+
+impl NpcFileScript {
+    pub fn new(ast: Arc<AST>) -> Self {
+        Self {
+            ast,
+            status: 0,
+            entry: "start",
+            action: "action",
+        }
+    }
+
+    /// The script for `npc_id`, if the scripts directory has one.
+    pub fn load(npc_id: i32) -> Option<Self> {
+        scripts()
+            .load(ScriptKind::Npc, &npc_id.to_string())
+            .map(Self::new)
+    }
+
+    /// The conversation in `quest/<quest id>.rhai` that starts the quest, run
+    /// by `start(npc)` and `start_action(npc, answer)`, or the one that
+    /// completes it, run by `end(npc)` and `end_action(npc, answer)`.
+    pub fn load_quest(quest_id: i32, complete: bool) -> Option<Self> {
+        let ast = scripts().load(ScriptKind::Quest, &quest_id.to_string())?;
+        let (entry, action) = if complete {
+            ("end", "end_action")
+        } else {
+            ("start", "start_action")
+        };
+        Some(Self {
+            entry,
+            action,
+            ..Self::new(ast)
+        })
+    }
+
+    fn call(
+        &mut self,
+        ctx: &mut NpcContext,
+        function: &str,
+        answer: Option<Dynamic>,
+    ) -> Result<Step, NetworkError> {
+        let api = ScriptApi::new(Arc::clone(&ctx.character), ctx.channel_id, self.status);
+        let mut args = vec![Dynamic::from(api.clone())];
+        args.extend(answer);
+        let outcome = scripts().call(&self.ast, function, args);
+
+        let (result, dialog, status) = api.finish();
+        self.status = status;
+        ctx.extend(result);
+        match outcome {
+            Ok(()) => Ok(dialog.map_or(Step::End, Step::Show)),
+            Err(error) => {
+                eprintln!(
+                    "NPC {} script failed in {}: {}",
+                    ctx.npc_id, function, error
+                );
+                Ok(Step::End)
+            }
+        }
+    }
+}
+
+impl NpcScript for NpcFileScript {
+    fn start(&mut self, ctx: &mut NpcContext) -> Result<Step, NetworkError> {
+        self.call(ctx, self.entry, None)
+    }
+
+    fn answer(&mut self, ctx: &mut NpcContext, answer: Answer) -> Result<Step, NetworkError> {
+        // Scripts see true or false for OK, next and yes/no buttons, and the
+        // value for everything else.
+        let answer = match answer {
+            Answer::End => return Ok(Step::End),
+            Answer::Next | Answer::Yes => Dynamic::from(true),
+            Answer::Prev | Answer::No => Dynamic::from(false),
+            Answer::Selection(value) | Answer::Number(value) => Dynamic::from(i64::from(value)),
+            Answer::Text(text) => Dynamic::from(text),
+        };
+        self.call(ctx, self.action, Some(answer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handler::HandlerResult;
+    use crate::npc::Dialog;
+    use crate::stats::test_character_wrapper;
+    use std::sync::Mutex;
+
+    fn script(source: &str) -> NpcFileScript {
+        NpcFileScript::new(Arc::new(scripts().compile(source).expect("compile")))
+    }
+
+    fn context() -> NpcContext {
+        NpcContext {
+            npc_id: 2100,
+            character: Arc::new(Mutex::new(test_character_wrapper())),
+            channel_id: 0,
+            result: HandlerResult::empty(),
+        }
+    }
+
+    fn shown(step: Step) -> Dialog {
+        match step {
+            Step::Show(dialog) => dialog,
+            Step::End => panic!("expected a dialog"),
+        }
+    }
+
+    #[test]
+    fn scripts_show_dialogs_and_keep_their_status() {
+        let mut npc = script(
+            r##"
+            fn start(npc) {
+                npc.menu("Hello " + npc.name + "#L0#Level#l");
+            }
+
+            fn action(npc, answer) {
+                if npc.status == 0 {
+                    npc.status = 1;
+                    npc.yes_no("You picked " + answer + ". You are level " + npc.level + "?");
+                } else if npc.status == 1 && answer {
+                    npc.status = 2;
+                    npc.say("Good.");
+                }
+            }
+            "##,
+        );
+        let mut ctx = context();
+
+        let dialog = shown(npc.start(&mut ctx).expect("start"));
+        assert_eq!(dialog.text(), "Hello tester#L0#Level#l");
+        let dialog = shown(npc.answer(&mut ctx, Answer::Selection(0)).expect("answer"));
+        assert_eq!(
+            dialog,
+            Dialog::YesNo {
+                text: "You picked 0. You are level 1?".to_string()
+            }
+        );
+        let dialog = shown(npc.answer(&mut ctx, Answer::Yes).expect("answer"));
+        assert_eq!(dialog, Dialog::say("Good."));
+        assert!(matches!(
+            npc.answer(&mut ctx, Answer::Next).expect("answer"),
+            Step::End
+        ));
+    }
+
+    #[test]
+    fn failing_scripts_end_the_conversation() {
+        let mut ctx = context();
+        let mut npc = script(r#"fn start(npc) { npc.say("One"); npc.say("Two"); }"#);
+        assert!(matches!(npc.start(&mut ctx).expect("start"), Step::End));
+
+        let mut npc = script("fn start(npc) { loop {} }");
+        assert!(matches!(npc.start(&mut ctx).expect("start"), Step::End));
+
+        // No `action` function: the first answer ends the conversation.
+        let mut npc = script(r#"fn start(npc) { npc.say_next("Hi"); }"#);
+        shown(npc.start(&mut ctx).expect("start"));
+        assert!(matches!(
+            npc.answer(&mut ctx, Answer::Next).expect("answer"),
+            Step::End
+        ));
+    }
+}
@@ -0,0 +1,46 @@
+use super::api::ScriptApi;
+use super::scripts;
+use crate::portal::PortalContext;
+use rhai::{Dynamic, AST};
+use std::sync::Arc;
+
+/// Run `portal/<script>.rhai` for a character entering a scripted portal. The
+/// script defines `enter(portal)`; it warps the character or does nothing.
+/// Failures are logged and leave the character where they are.
+pub(crate) fn enter_portal_script(ast: &AST, script: &str, ctx: &mut PortalContext) {
+    let api = ScriptApi::for_portal(Arc::clone(&ctx.character), ctx.channel_id);
+    let outcome = scripts().call(ast, "enter", vec![Dynamic::from(api.clone())]);
+
+    let (result, _, _) = api.finish();
+    ctx.result.actions.extend(result.actions);
+    if let Err(error) = outcome {
+        eprintln!("Portal script {} failed: {}", script, error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handler::HandlerResult;
+    use crate::stats::test_character_wrapper;
+    use std::sync::Mutex;
+
+    #[test]
+    fn portal_scripts_cannot_show_dialogs() {
+        let ast = scripts()
+            .compile(r#"fn enter(portal) { portal.say("Hi"); }"#)
+            .expect("compile");
+        let api = ScriptApi::for_portal(Arc::new(Mutex::new(test_character_wrapper())), 0);
+        let outcome = scripts().call(&ast, "enter", vec![Dynamic::from(api.clone())]);
+        assert!(outcome.is_err());
+        assert!(api.finish().1.is_none());
+
+        let mut ctx = PortalContext {
+            character: Arc::new(Mutex::new(test_character_wrapper())),
+            channel_id: 0,
+            result: HandlerResult::empty(),
+        };
+        enter_portal_script(&ast, "test", &mut ctx);
+        assert!(ctx.result.actions.is_empty());
+    }
+}
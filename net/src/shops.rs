@@ -0,0 +1,314 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::HandlerResult;
+use crate::packet::build::world::inventory::{build_inventory_add, build_inventory_take};
+use crate::packet::build::world::shop::{
+    build_open_shop, build_shop_result, SHOP_RESULT_BOUGHT, SHOP_RESULT_INVENTORY_FULL,
+    SHOP_RESULT_NOT_ENOUGH_MESO, SHOP_RESULT_SOLD,
+};
+use crate::packet::build::world::stat::build_meso_update;
+use crate::quests;
+use ::game_data::ItemTemplate;
+use db::character::CharacterWrapper;
+use db::inventory::{is_rechargeable, InventoryType};
+use packet::Packet;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Why a shop refused to buy, sell or recharge.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShopError {
+    NoShop,
+    NotSold,
+    InvalidQuantity,
+    ItemNotFound,
+    NotRechargeable,
+    NotEnoughMeso,
+    /// Selling would take the character past the most mesos it can hold.
+    MesoLimit,
+    InventoryFull,
+}
+
+impl ShopError {
+    pub fn message(self) -> &'static str {
+        match self {
+            ShopError::NoShop => "No shop open",
+            ShopError::NotSold => "Item is not sold here",
+            ShopError::InvalidQuantity => "Invalid quantity",
+            ShopError::ItemNotFound => "Item not in inventory",
+            ShopError::NotRechargeable => "Item can't be recharged",
+            ShopError::NotEnoughMeso => "Not enough mesos",
+            ShopError::MesoLimit => "Too many mesos",
+            ShopError::InventoryFull => "Inventory full",
+        }
+    }
+
+    /// The shop result the client shows for the refusal. Anything the
+    /// client has no message for only unlocks the shop.
+    pub fn result(self) -> u8 {
+        match self {
+            ShopError::NotEnoughMeso => SHOP_RESULT_NOT_ENOUGH_MESO,
+            ShopError::InventoryFull => SHOP_RESULT_INVENTORY_FULL,
+            _ => SHOP_RESULT_SOLD,
+        }
+    }
+}
+
+impl fmt::Display for ShopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+/// How many times what the shop pays for an item it charges for one.
+const BUY_PRICE_MARKUP: i32 = 2;
+
+/// The templates of the items `npc_id` sells, in shop order, or `None` if it
+/// has no shop. Items without a template or a price are left out, so the
+/// client's shop slots index into this list.
+fn shop_items(npc_id: i32) -> Result<Option<Vec<&'static ItemTemplate>>, NetworkError> {
+    let Some(item_ids) = game_data::shops()?.shop(npc_id) else {
+        return Ok(None);
+    };
+    let items = game_data::items()?;
+    Ok(Some(
+        item_ids
+            .iter()
+            .filter_map(|&item_id| items.item(item_id))
+            .filter(|template| template.price > 0 && shop_price(template).is_some())
+            .collect(),
+    ))
+}
+
+/// The packet that opens `npc_id`'s shop, or `None` if it has no shop.
+pub fn open_shop(npc_id: i32) -> Result<Option<Packet>, NetworkError> {
+    match shop_items(npc_id)? {
+        Some(items) => {
+            let items: Vec<_> = items
+                .into_iter()
+                .filter_map(|template| Some((template, shop_price(template)?)))
+                .collect();
+            Ok(Some(build_open_shop(npc_id, &items)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The price a shop lists an item at. Item.nx prices are what a shop pays
+/// for an item, so shops charge `BUY_PRICE_MARKUP` times that, for a full
+/// stack of throwing stars or bullets.
+pub fn shop_price(template: &ItemTemplate) -> Option<i32> {
+    let quantity = if is_rechargeable(template.item_id) {
+        template.slot_max
+    } else {
+        1
+    };
+    sell_price(template, quantity)?.checked_mul(BUY_PRICE_MARKUP)
+}
+
+/// What `quantity` of an item costs. Throwing stars and bullets are sold a
+/// full stack at a time for their listed price.
+pub fn buy_price(template: &ItemTemplate, quantity: i16) -> Option<i32> {
+    if is_rechargeable(template.item_id) {
+        return shop_price(template);
+    }
+    shop_price(template)?.checked_mul(i32::from(quantity))
+}
+
+/// What the shop pays for `quantity` of an item. Throwing stars and bullets
+/// also fetch their unit price for each one left in the stack.
+pub fn sell_price(template: &ItemTemplate, quantity: i16) -> Option<i32> {
+    if is_rechargeable(template.item_id) {
+        let units = (template.unit_price * f64::from(quantity)) as i32;
+        return template.price.checked_add(units);
+    }
+    template.price.checked_mul(i32::from(quantity))
+}
+
+/// What refilling `missing` throwing stars or bullets costs, rounded up.
+pub fn recharge_price(template: &ItemTemplate, missing: i16) -> i32 {
+    (template.unit_price * f64::from(missing)).ceil() as i32
+}
+
+/// Buy the item in `slot` of `npc_id`'s shop and save the character.
+pub fn buy(
+    chr: &mut CharacterWrapper,
+    npc_id: i32,
+    slot: i16,
+    item_id: i32,
+    quantity: i16,
+) -> Result<Result<HandlerResult, ShopError>, NetworkError> {
+    let Some(items) = shop_items(npc_id)? else {
+        return Ok(Err(ShopError::NoShop));
+    };
+    let Some(template) = usize::try_from(slot)
+        .ok()
+        .and_then(|slot| items.get(slot))
+        .filter(|template| template.item_id == item_id)
+    else {
+        return Ok(Err(ShopError::NotSold));
+    };
+
+    let quantity = if is_rechargeable(item_id) {
+        template.slot_max
+    } else {
+        quantity
+    };
+    if quantity <= 0 || quantity > template.slot_max {
+        return Ok(Err(ShopError::InvalidQuantity));
+    }
+    let Some(price) = buy_price(template, quantity) else {
+        return Ok(Err(ShopError::InvalidQuantity));
+    };
+    if chr.character.meso < price {
+        return Ok(Err(ShopError::NotEnoughMeso));
+    }
+
+    let item = game_data::item_from_template(template, quantity);
+    let Some((tab, position)) = chr.inventory.add(item, template.slot_max) else {
+        return Ok(Err(ShopError::InventoryFull));
+    };
+    chr.character.meso -= price;
+    db::character::update_characters_with_inventories(&[(&chr.character, &chr.inventory)])?;
+
+    let item = chr
+        .inventory
+        .get(tab, position)
+        .ok_or(NetworkError::PacketHandlerError("Bought item not found"))?;
+    let mut packets = vec![
+        build_inventory_add(tab, position, item)?,
+        build_meso_update(chr.character.meso, false)?,
+        build_shop_result(SHOP_RESULT_BOUGHT)?,
+    ];
+    packets.extend(quests::item_progress(chr, item_id)?);
+    Ok(Ok(HandlerResult::replies(packets)))
+}
+
+/// Sell `quantity` of the stack at `position` and save the character.
+/// Throwing stars and bullets are always sold as a whole stack.
+pub fn sell(
+    chr: &mut CharacterWrapper,
+    position: i16,
+    item_id: i32,
+    quantity: i16,
+) -> Result<Result<HandlerResult, ShopError>, NetworkError> {
+    let Some(tab) = InventoryType::of_item(item_id) else {
+        return Ok(Err(ShopError::ItemNotFound));
+    };
+    let Some(stack) = chr
+        .inventory
+        .get(tab, position)
+        .filter(|stack| position > 0 && stack.item_id == item_id)
+    else {
+        return Ok(Err(ShopError::ItemNotFound));
+    };
+    let quantity = if is_rechargeable(item_id) {
+        stack.quantity
+    } else {
+        quantity
+    };
+    if quantity <= 0 || quantity > stack.quantity {
+        return Ok(Err(ShopError::InvalidQuantity));
+    }
+
+    let Some(template) = game_data::items()?
+        .item(item_id)
+        .filter(|template| !template.cash)
+    else {
+        return Ok(Err(ShopError::NotSold));
+    };
+    let Some(meso) =
+        sell_price(template, quantity).and_then(|price| chr.character.meso.checked_add(price))
+    else {
+        return Ok(Err(ShopError::MesoLimit));
+    };
+
+    let Some(remaining) = chr.inventory.take_at(tab, position, quantity) else {
+        return Ok(Err(ShopError::InvalidQuantity));
+    };
+    chr.character.meso = meso;
+    db::character::update_characters_with_inventories(&[(&chr.character, &chr.inventory)])?;
+
+    Ok(Ok(HandlerResult::replies(vec![
+        build_inventory_take(tab, position, remaining)?,
+        build_meso_update(meso, false)?,
+        build_shop_result(SHOP_RESULT_SOLD)?,
+    ])))
+}
+
+/// Refill the throwing stars or bullets at `position` in the use tab and
+/// save the character.
+pub fn recharge(
+    chr: &mut CharacterWrapper,
+    position: i16,
+) -> Result<Result<HandlerResult, ShopError>, NetworkError> {
+    let Some(stack) = chr
+        .inventory
+        .get(InventoryType::Use, position)
+        .filter(|_| position > 0)
+    else {
+        return Ok(Err(ShopError::ItemNotFound));
+    };
+    if !is_rechargeable(stack.item_id) {
+        return Ok(Err(ShopError::NotRechargeable));
+    }
+    let Some(template) = game_data::items()?.item(stack.item_id) else {
+        return Ok(Err(ShopError::NotRechargeable));
+    };
+    let missing = template.slot_max - stack.quantity;
+    if missing <= 0 {
+        return Ok(Err(ShopError::InvalidQuantity));
+    }
+    let price = recharge_price(template, missing);
+    if chr.character.meso < price {
+        return Ok(Err(ShopError::NotEnoughMeso));
+    }
+
+    chr.inventory
+        .set_quantity(InventoryType::Use, position, template.slot_max);
+    chr.character.meso -= price;
+    db::character::update_characters_with_inventories(&[(&chr.character, &chr.inventory)])?;
+
+    let item = chr
+        .inventory
+        .get(InventoryType::Use, position)
+        .ok_or(NetworkError::PacketHandlerError("Recharged item not found"))?;
+    Ok(Ok(HandlerResult::replies(vec![
+        build_inventory_add(InventoryType::Use, position, item)?,
+        build_meso_update(chr.character.meso, false)?,
+        build_shop_result(SHOP_RESULT_SOLD)?,
+    ])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(item_id: i32, price: i32, unit_price: f64) -> ItemTemplate {
+        ItemTemplate {
+            item_id,
+            price,
+            unit_price,
+            slot_max: 100,
+            cash: false,
+            equip: None,
+        }
+    }
+
+    #[test]
+    fn prices_come_from_the_template() {
+        let potion = template(2000000, 25, 0.0);
+        assert_eq!(shop_price(&potion), Some(50));
+        assert_eq!(buy_price(&potion, 3), Some(150));
+        assert_eq!(sell_price(&potion, 3), Some(75));
+        assert_eq!(buy_price(&template(2000000, i32::MAX, 0.0), 1), None);
+
+        let stars = template(2070000, 500, 0.5);
+        assert_eq!(shop_price(&stars), Some(1100));
+        assert_eq!(buy_price(&stars, 100), Some(1100));
+        assert_eq!(sell_price(&stars, 100), Some(550));
+        assert_eq!(sell_price(&stars, 99), Some(549));
+        assert_eq!(recharge_price(&stars, 99), 50);
+        assert_eq!(recharge_price(&stars, 1), 1);
+    }
+}
@@ -0,0 +1,183 @@
+use db::character::Character;
+use db::skill::{SkillBook, SkillEntry};
+use game_data::{SkillLevel, SkillTemplate};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Jobs whose skills a character with `job` may learn: beginner, then every
+/// advancement up to `job`. A hero (112) learns from 0, 100, 110, 111 and
+/// 112.
+pub fn job_path(job: i16) -> Vec<i16> {
+    let mut path = vec![0];
+    if job >= 100 {
+        path.push(job / 100 * 100);
+    }
+    if job % 100 >= 10 {
+        path.extend(job / 10 * 10..=job);
+    }
+    path
+}
+
+/// Spend one SP on the skill. Returns the skill's new entry, or None, leaving
+/// the character untouched, if there is no SP left, the skill is outside the
+/// character's job path, is maxed, or its required skills are too low.
+pub fn assign_sp(
+    character: &mut Character,
+    skills: &mut SkillBook,
+    template: &SkillTemplate,
+) -> Option<SkillEntry> {
+    if character.sp < 1 || !job_path(character.job).contains(&template.job()) {
+        return None;
+    }
+
+    let mut entry = skills.get(template.skill_id).unwrap_or_default();
+    let cap = if template.is_fourth_job() {
+        entry.master_level.min(template.max_level())
+    } else {
+        template.max_level()
+    };
+    if entry.level >= cap {
+        return None;
+    }
+    if template
+        .requirements
+        .iter()
+        .any(|&(skill_id, level)| skills.level(skill_id) < level)
+    {
+        return None;
+    }
+
+    entry.level += 1;
+    skills.set(template.skill_id, entry);
+    character.sp -= 1;
+    Some(entry)
+}
+
+/// Take a skill's HP and MP cost from the character. Returns false, leaving
+/// the character untouched, if it can't pay without dying or running out of
+/// MP.
+pub fn pay_skill_cost(character: &mut Character, level: &SkillLevel) -> bool {
+    let hp = i32::from(character.hp) - level.hp_cost;
+    let mp = i32::from(character.mp) - level.mp_cost;
+    if hp <= 0 || mp < 0 {
+        return false;
+    }
+    character.hp = hp as i16;
+    character.mp = mp as i16;
+    true
+}
+
+/// When the skills a character used can be used again.
+#[derive(Clone, Debug, Default)]
+pub struct Cooldowns {
+    ready_at: HashMap<i32, Instant>,
+}
+
+impl Cooldowns {
+    /// Whether `skill_id` is off cooldown at `now`.
+    pub fn is_ready(&self, skill_id: i32, now: Instant) -> bool {
+        self.ready_at
+            .get(&skill_id)
+            .is_none_or(|&ready_at| now >= ready_at)
+    }
+
+    /// Put `skill_id` on cooldown for `seconds` from `now`. Skills without a
+    /// cooldown are left alone.
+    pub fn start(&mut self, skill_id: i32, seconds: i32, now: Instant) {
+        if seconds > 0 {
+            self.ready_at
+                .insert(skill_id, now + Duration::from_secs(seconds as u64));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(skill_id: i32, max_level: usize) -> SkillTemplate {
+        SkillTemplate {
+            skill_id,
+            levels: vec![SkillLevel::default(); max_level],
+            ..SkillTemplate::default()
+        }
+    }
+
+    #[test]
+    fn job_path_covers_every_advancement() {
+        assert_eq!(job_path(0), vec![0]);
+        assert_eq!(job_path(100), vec![0, 100]);
+        assert_eq!(job_path(110), vec![0, 100, 110]);
+        assert_eq!(job_path(112), vec![0, 100, 110, 111, 112]);
+        assert_eq!(job_path(231), vec![0, 200, 230, 231]);
+    }
+
+    #[test]
+    fn assign_sp_checks_job_cap_and_requirements() {
+        let mut character = crate::stats::test_character();
+        character.job = 100;
+        character.sp = 3;
+        let mut skills = SkillBook::from_skill_vec(character.id, Vec::new());
+
+        let power_strike = template(1001004, 1);
+        let mut slash_blast = template(1001005, 20);
+        slash_blast.requirements = vec![(1001004, 1)];
+
+        assert!(assign_sp(&mut character, &mut skills, &slash_blast).is_none());
+        assert!(assign_sp(&mut character, &mut skills, &template(2001002, 20)).is_none());
+        assert_eq!(
+            assign_sp(&mut character, &mut skills, &power_strike).map(|entry| entry.level),
+            Some(1)
+        );
+        assert!(assign_sp(&mut character, &mut skills, &power_strike).is_none());
+        assert_eq!(
+            assign_sp(&mut character, &mut skills, &slash_blast).map(|entry| entry.level),
+            Some(1)
+        );
+        assert_eq!(character.sp, 1);
+        assert_eq!(skills.level(1001005), 1);
+    }
+
+    #[test]
+    fn fourth_job_skills_stop_at_the_master_level() {
+        let mut character = crate::stats::test_character();
+        character.job = 112;
+        character.sp = 5;
+        let mut skills = SkillBook::from_skill_vec(character.id, Vec::new());
+        let brandish = template(1121008, 30);
+
+        assert!(assign_sp(&mut character, &mut skills, &brandish).is_none());
+
+        skills.set(
+            1121008,
+            SkillEntry {
+                level: 0,
+                master_level: 1,
+            },
+        );
+        assert!(assign_sp(&mut character, &mut skills, &brandish).is_some());
+        assert!(assign_sp(&mut character, &mut skills, &brandish).is_none());
+        assert_eq!(character.sp, 4);
+    }
+
+    #[test]
+    fn pay_skill_cost_never_kills_or_overdraws() {
+        let mut character = crate::stats::test_character();
+        character.hp = 50;
+        character.mp = 20;
+        let level = SkillLevel {
+            hp_cost: 10,
+            mp_cost: 20,
+            ..SkillLevel::default()
+        };
+
+        assert!(pay_skill_cost(&mut character, &level));
+        assert_eq!((character.hp, character.mp), (40, 0));
+        assert!(!pay_skill_cost(&mut character, &level));
+
+        character.mp = 20;
+        character.hp = 10;
+        assert!(!pay_skill_cost(&mut character, &level));
+        assert_eq!((character.hp, character.mp), (10, 20));
+    }
+}
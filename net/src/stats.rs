@@ -0,0 +1,480 @@
+use crate::packet::build::world::stat::Stat;
+use db::character::Character;
+use game_data::{exp_to_next_level, MAX_LEVEL};
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+/// AP granted on every level up.
+pub const AP_PER_LEVEL: i16 = 5;
+/// SP granted on every level up once a character has a job.
+pub const SP_PER_LEVEL: i16 = 3;
+/// Beginners earn one SP per level until this level, enough for the
+/// beginner skills.
+const BEGINNER_SP_MAX_LEVEL: i16 = 10;
+/// Highest max HP or MP a character can have.
+pub const MAX_HP_MP: i16 = 30_000;
+/// Highest STR, DEX, INT or LUK that AP can raise a stat to.
+pub const MAX_STAT: i16 = 999;
+/// Most HP a single regen tick from the client may restore.
+pub const MAX_REGEN_HP: i16 = 140;
+/// Most MP a single regen tick from the client may restore.
+pub const MAX_REGEN_MP: i16 = 1_000;
+/// The client regenerates every 10 seconds. Ticks closer together than this,
+/// which leaves room for network jitter, are dropped.
+pub const MIN_REGEN_INTERVAL: Duration = Duration::from_secs(8);
+/// HP a character has after respawning.
+pub const RESPAWN_HP: i16 = 50;
+/// Share of the EXP needed for the next level that a character loses on
+/// death, in percent.
+const DEATH_EXP_PENALTY_PERCENT: i32 = 10;
+
+/// Add `amount` EXP to the character, levelling up as often as the EXP
+/// covers. Returns the number of levels gained.
+pub fn gain_exp(character: &mut Character, amount: i32, rng: &mut impl Rng) -> i16 {
+    character.exp = character.exp.saturating_add(amount.max(0));
+
+    let mut levels = 0;
+    while let Some(needed) = exp_to_next_level(character.level) {
+        if character.exp < needed {
+            break;
+        }
+        character.exp -= needed;
+        level_up(character, rng);
+        levels += 1;
+    }
+
+    // There is nothing left to earn EXP towards at the cap.
+    if character.level >= MAX_LEVEL {
+        character.exp = 0;
+    }
+    levels
+}
+
+/// Raise the character one level: grant AP and SP, raise max HP and MP by an
+/// amount that depends on the job branch, and refill HP and MP.
+fn level_up(character: &mut Character, rng: &mut impl Rng) {
+    character.level += 1;
+    character.ap = character.ap.saturating_add(AP_PER_LEVEL);
+    if character.job != 0 {
+        character.sp = character.sp.saturating_add(SP_PER_LEVEL);
+    } else if character.level <= BEGINNER_SP_MAX_LEVEL {
+        character.sp = character.sp.saturating_add(1);
+    }
+
+    let (hp_gain, mp_gain) = hp_mp_gain(character.job, rng);
+    character.maxhp = character.maxhp.saturating_add(hp_gain).min(MAX_HP_MP);
+    character.maxmp = character
+        .maxmp
+        .saturating_add(mp_gain + character.int / 10)
+        .min(MAX_HP_MP);
+    character.hp = character.maxhp;
+    character.mp = character.maxmp;
+}
+
+/// Max HP and MP gained on a level up, before the INT bonus to MP.
+fn hp_mp_gain(job: i16, rng: &mut impl Rng) -> (i16, i16) {
+    let (hp, mp) = match job / 100 % 10 {
+        // Warrior
+        1 => ((24, 28), (4, 6)),
+        // Magician
+        2 => ((10, 14), (22, 24)),
+        // Bowman and thief
+        3 | 4 => ((20, 24), (14, 16)),
+        // Pirate
+        5 => ((22, 28), (18, 23)),
+        // Beginner
+        _ => ((12, 16), (10, 12)),
+    };
+
+    (rng.gen_range(hp.0, hp.1 + 1), rng.gen_range(mp.0, mp.1 + 1))
+}
+
+/// Take `damage` off the character's HP. Returns true if it killed the
+/// character, who then loses part of their EXP. Dead characters take no
+/// further damage.
+pub fn take_damage(character: &mut Character, damage: i32) -> bool {
+    if character.hp <= 0 {
+        return false;
+    }
+
+    let damage = damage.clamp(0, i32::from(character.hp)) as i16;
+    character.hp -= damage;
+    if character.hp > 0 {
+        return false;
+    }
+
+    // Beginners die without losing EXP.
+    if character.job != 0 {
+        if let Some(needed) = exp_to_next_level(character.level) {
+            let penalty = needed / 100 * DEATH_EXP_PENALTY_PERCENT;
+            character.exp = (character.exp - penalty).max(0);
+        }
+    }
+    true
+}
+
+/// When a character last regenerated HP and MP. The client times the two
+/// separately and sends a tick for each.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RegenClock {
+    hp: Option<Instant>,
+    mp: Option<Instant>,
+}
+
+impl RegenClock {
+    /// Record a tick restoring `hp` and `mp` at `now`. Returns false, leaving
+    /// the clock untouched, if either comes sooner than the client regenerates.
+    fn tick(&mut self, hp: i16, mp: i16, now: Instant) -> bool {
+        let too_soon = |last: Option<Instant>| {
+            last.is_some_and(|last| now.saturating_duration_since(last) < MIN_REGEN_INTERVAL)
+        };
+        if (hp > 0 && too_soon(self.hp)) || (mp > 0 && too_soon(self.mp)) {
+            return false;
+        }
+
+        if hp > 0 {
+            self.hp = Some(now);
+        }
+        if mp > 0 {
+            self.mp = Some(now);
+        }
+        true
+    }
+}
+
+/// Restore HP and MP from a regen tick at `now`. Returns false, leaving the
+/// character untouched, for dead characters and ticks that restore more or
+/// sooner than the client can.
+pub fn regen(
+    character: &mut Character,
+    clock: &mut RegenClock,
+    hp: i16,
+    mp: i16,
+    now: Instant,
+) -> bool {
+    if character.hp <= 0 || !(0..=MAX_REGEN_HP).contains(&hp) || !(0..=MAX_REGEN_MP).contains(&mp) {
+        return false;
+    }
+    if !clock.tick(hp, mp, now) {
+        return false;
+    }
+
+    character.hp = character.hp.saturating_add(hp).min(character.maxhp);
+    character.mp = character.mp.saturating_add(mp).min(character.maxmp);
+    true
+}
+
+/// Bring a dead character back with `RESPAWN_HP`.
+pub fn respawn(character: &mut Character) {
+    character.hp = RESPAWN_HP.min(character.maxhp);
+}
+
+/// Spend one AP on `stat`. Returns false, leaving the character untouched,
+/// if there is no AP left or the stat can't take it.
+pub fn assign_ap(character: &mut Character, stat: Stat, rng: &mut impl Rng) -> bool {
+    if character.ap < 1 {
+        return false;
+    }
+
+    match stat {
+        Stat::MaxHp | Stat::MaxMp => {
+            let (hp_gain, mp_gain) = ap_hp_mp_gain(character.job, rng);
+            let (max, gain) = if stat == Stat::MaxHp {
+                (&mut character.maxhp, hp_gain)
+            } else {
+                (&mut character.maxmp, mp_gain)
+            };
+            if *max >= MAX_HP_MP {
+                return false;
+            }
+            *max = max.saturating_add(gain).min(MAX_HP_MP);
+        }
+        _ => {
+            let Some(value) = primary_stat_mut(character, stat) else {
+                return false;
+            };
+            if *value >= MAX_STAT {
+                return false;
+            }
+            *value += 1;
+        }
+    }
+
+    character.ap -= 1;
+    true
+}
+
+/// Spend AP on several primary stats at once, as the client's auto-assign
+/// asks. Nothing is spent unless the whole request fits the AP pool and the
+/// stat cap.
+pub fn auto_assign_ap(character: &mut Character, assignments: &[(Stat, i16)]) -> bool {
+    let mut assigned = character.clone();
+    let mut total: i16 = 0;
+    for &(stat, amount) in assignments {
+        let Some(value) = primary_stat_mut(&mut assigned, stat) else {
+            return false;
+        };
+        if amount < 0 || amount > MAX_STAT - *value {
+            return false;
+        }
+        *value += amount;
+        total = total.saturating_add(amount);
+    }
+    if total == 0 || total > character.ap {
+        return false;
+    }
+
+    assigned.ap -= total;
+    *character = assigned;
+    true
+}
+
+fn primary_stat_mut(character: &mut Character, stat: Stat) -> Option<&mut i16> {
+    match stat {
+        Stat::Str => Some(&mut character.stre),
+        Stat::Dex => Some(&mut character.dex),
+        Stat::Int => Some(&mut character.int),
+        Stat::Luk => Some(&mut character.luk),
+        _ => None,
+    }
+}
+
+/// Max HP and MP gained by spending one AP on them.
+fn ap_hp_mp_gain(job: i16, rng: &mut impl Rng) -> (i16, i16) {
+    let (hp, mp) = match job / 100 % 10 {
+        // Warrior
+        1 => ((20, 24), (2, 4)),
+        // Magician
+        2 => ((6, 10), (18, 20)),
+        // Bowman and thief
+        3 | 4 => ((16, 20), (10, 12)),
+        // Pirate
+        5 => ((18, 22), (14, 16)),
+        // Beginner
+        _ => ((8, 12), (6, 8)),
+    };
+
+    (rng.gen_range(hp.0, hp.1 + 1), rng.gen_range(mp.0, mp.1 + 1))
+}
+
+#[cfg(test)]
+pub(crate) fn test_character() -> Character {
+    Character {
+        id: 1,
+        accountid: 1,
+        world: 0,
+        name: "tester".to_string(),
+        level: 1,
+        exp: 0,
+        stre: 12,
+        dex: 5,
+        luk: 4,
+        int: 4,
+        hp: 50,
+        mp: 5,
+        maxhp: 50,
+        maxmp: 5,
+        ap: 0,
+        fame: 0,
+        meso: 0,
+        job: 0,
+        face: 20000,
+        hair: 30000,
+        hair_color: 0,
+        skin: 0,
+        gender: 0,
+        created_at: std::time::SystemTime::UNIX_EPOCH,
+        map_id: 10000,
+        equip_slots: 24,
+        use_slots: 24,
+        setup_slots: 24,
+        etc_slots: 24,
+        cash_slots: 96,
+        sp: 0,
+    }
+}
+
+/// `test_character` with empty keybinds, inventory, skills and quests.
+#[cfg(test)]
+pub(crate) fn test_character_wrapper() -> db::character::CharacterWrapper {
+    let character = test_character();
+    db::character::CharacterWrapper {
+        key_binds: db::keybinding::KeybindSet::from_bind_vec(character.id, Vec::new()),
+        inventory: db::inventory::Inventory::from_item_vec(
+            character.id,
+            character.inventory_slot_limits(),
+            Vec::new(),
+        ),
+        skills: db::skill::SkillBook::from_skill_vec(character.id, Vec::new()),
+        quests: db::quest::QuestLog::from_quest_vec(character.id, Vec::new()),
+        character,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn gain_exp_levels_up_through_the_table() {
+        let mut character = test_character();
+        let mut rng = StepRng::new(0, 0);
+
+        assert_eq!(gain_exp(&mut character, 10, &mut rng), 0);
+        assert_eq!((character.level, character.exp), (1, 10));
+
+        // 15 to reach level 2, then 34 to reach level 3, with 1 left over.
+        assert_eq!(gain_exp(&mut character, 40, &mut rng), 2);
+        assert_eq!((character.level, character.exp), (3, 1));
+        assert_eq!(character.ap, 2 * AP_PER_LEVEL);
+        assert_eq!(character.sp, 2);
+        assert!(character.maxhp >= 50 + 2 * 12);
+        assert!(character.maxmp >= 5 + 2 * 10);
+        assert_eq!(
+            (character.hp, character.mp),
+            (character.maxhp, character.maxmp)
+        );
+    }
+
+    #[test]
+    fn gain_exp_stops_at_the_level_cap() {
+        let mut character = test_character();
+        character.level = MAX_LEVEL - 1;
+        character.job = 112;
+        let mut rng = StepRng::new(0, 0);
+
+        assert_eq!(gain_exp(&mut character, i32::MAX, &mut rng), 1);
+        assert_eq!((character.level, character.exp), (MAX_LEVEL, 0));
+        assert_eq!(character.sp, SP_PER_LEVEL);
+        assert_eq!(gain_exp(&mut character, 100, &mut rng), 0);
+        assert_eq!(character.exp, 0);
+    }
+
+    #[test]
+    fn assign_ap_spends_one_point() {
+        let mut character = test_character();
+        character.ap = 2;
+        character.luk = MAX_STAT;
+        let mut rng = StepRng::new(0, 0);
+
+        assert!(assign_ap(&mut character, Stat::Dex, &mut rng));
+        assert_eq!((character.dex, character.ap), (6, 1));
+        assert!(!assign_ap(&mut character, Stat::Luk, &mut rng));
+        assert!(!assign_ap(&mut character, Stat::Level, &mut rng));
+        assert_eq!(character.ap, 1);
+
+        assert!(assign_ap(&mut character, Stat::MaxHp, &mut rng));
+        assert_eq!((character.maxhp, character.hp, character.ap), (58, 50, 0));
+        assert!(!assign_ap(&mut character, Stat::Str, &mut rng));
+        assert_eq!(character.stre, 12);
+    }
+
+    #[test]
+    fn auto_assign_ap_is_all_or_nothing() {
+        let mut character = test_character();
+        character.ap = 5;
+
+        assert!(!auto_assign_ap(
+            &mut character,
+            &[(Stat::Str, 4), (Stat::Dex, 2)]
+        ));
+        assert!(!auto_assign_ap(
+            &mut character,
+            &[(Stat::Str, 2), (Stat::MaxHp, 1)]
+        ));
+        assert!(!auto_assign_ap(
+            &mut character,
+            &[(Stat::Str, -1), (Stat::Dex, 2)]
+        ));
+        assert_eq!((character.stre, character.dex, character.ap), (12, 5, 5));
+
+        assert!(auto_assign_ap(
+            &mut character,
+            &[(Stat::Str, 3), (Stat::Dex, 2)]
+        ));
+        assert_eq!((character.stre, character.dex, character.ap), (15, 7, 0));
+    }
+
+    #[test]
+    fn take_damage_kills_and_costs_exp() {
+        let mut character = test_character();
+        character.level = 10;
+        character.job = 100;
+        character.exp = 200;
+
+        assert!(!take_damage(&mut character, 20));
+        assert_eq!(character.hp, 30);
+        assert!(!take_damage(&mut character, -5));
+        assert_eq!(character.hp, 30);
+
+        // Level 10 needs 1144 EXP, so death costs 110.
+        assert!(take_damage(&mut character, 500));
+        assert_eq!((character.hp, character.exp), (0, 90));
+        assert!(!take_damage(&mut character, 10));
+        assert_eq!(character.exp, 90);
+
+        respawn(&mut character);
+        assert_eq!(character.hp, RESPAWN_HP);
+    }
+
+    #[test]
+    fn beginners_die_without_losing_exp() {
+        let mut character = test_character();
+        character.exp = 10;
+
+        assert!(take_damage(&mut character, 50));
+        assert_eq!(character.exp, 10);
+    }
+
+    #[test]
+    fn regen_is_capped_and_skips_the_dead() {
+        let mut character = test_character();
+        character.hp = 10;
+        character.mp = 1;
+        let tick = |character: &mut Character, hp, mp| {
+            regen(
+                character,
+                &mut RegenClock::default(),
+                hp,
+                mp,
+                Instant::now(),
+            )
+        };
+
+        assert!(tick(&mut character, 20, 10));
+        assert_eq!((character.hp, character.mp), (30, 5));
+        assert!(!tick(&mut character, MAX_REGEN_HP + 1, 0));
+        assert!(!tick(&mut character, -1, 0));
+        assert_eq!(character.hp, 30);
+
+        character.hp = 0;
+        assert!(!tick(&mut character, 10, 0));
+        assert_eq!(character.hp, 0);
+    }
+
+    #[test]
+    fn regen_drops_ticks_faster_than_the_client_regenerates() {
+        let mut character = test_character();
+        character.hp = 10;
+        character.mp = 1;
+        let mut clock = RegenClock::default();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(regen(&mut character, &mut clock, 10, 0, at(0)));
+        // MP is timed on its own.
+        assert!(regen(&mut character, &mut clock, 0, 1, at(1)));
+        assert!(!regen(&mut character, &mut clock, 10, 0, at(2)));
+        assert_eq!((character.hp, character.mp), (20, 2));
+
+        assert!(regen(
+            &mut character,
+            &mut clock,
+            10,
+            0,
+            start + MIN_REGEN_INTERVAL
+        ));
+        assert_eq!(character.hp, 30);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::handler::HandlerResult;
+use crate::packet::build::world::inventory::{build_inventory_add, build_inventory_take};
+use crate::packet::build::world::stat::build_meso_update;
+use crate::packet::build::world::storage::{
+    build_open_storage, build_storage_arranged, build_storage_meso, build_storage_stored,
+    build_storage_taken_out, STORAGE_RESULT_INVENTORY_FULL, STORAGE_RESULT_NOT_ENOUGH_MESO,
+    STORAGE_RESULT_STORAGE_FULL,
+};
+use crate::quests;
+use ::game_data::DEFAULT_SLOT_MAX;
+use db::character::CharacterWrapper;
+use db::inventory::{is_rechargeable, InventoryType};
+use db::storage::AccountStorage;
+use packet::Packet;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Why the storage refused to store or take out an item or mesos.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StorageError {
+    NoStorage,
+    InvalidQuantity,
+    ItemNotFound,
+    StorageFull,
+    InventoryFull,
+    NotEnoughMeso,
+    /// The character or the storage would end up with more mesos than it can
+    /// hold.
+    MesoLimit,
+}
+
+impl StorageError {
+    pub fn message(self) -> &'static str {
+        match self {
+            StorageError::NoStorage => "No storage open",
+            StorageError::InvalidQuantity => "Invalid quantity",
+            StorageError::ItemNotFound => "Item not found",
+            StorageError::StorageFull => "Storage full",
+            StorageError::InventoryFull => "Inventory full",
+            StorageError::NotEnoughMeso => "Not enough mesos",
+            StorageError::MesoLimit => "Too many mesos",
+        }
+    }
+
+    /// The storage result the client shows for the refusal, if it has a
+    /// message for it.
+    pub fn result(self) -> Option<u8> {
+        match self {
+            StorageError::StorageFull => Some(STORAGE_RESULT_STORAGE_FULL),
+            StorageError::InventoryFull => Some(STORAGE_RESULT_INVENTORY_FULL),
+            StorageError::NotEnoughMeso => Some(STORAGE_RESULT_NOT_ENOUGH_MESO),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+/// An account storage a client has open, with what the storage keeper it was
+/// opened through charges.
+pub struct OpenStorage {
+    pub npc_id: i32,
+    pub store_fee: i32,
+    pub take_out_fee: i32,
+    pub storage: AccountStorage,
+}
+
+/// Open the account storage through `npc_id`, or `None` if the NPC is not a
+/// storage keeper. Returns the storage with the packet that shows it.
+pub fn open_storage(
+    chr: &CharacterWrapper,
+    npc_id: i32,
+) -> Result<Option<(OpenStorage, Packet)>, NetworkError> {
+    let Some(npc) = game_data::npcs()?
+        .npc(npc_id)
+        .filter(|npc| npc.is_storage_keeper())
+    else {
+        return Ok(None);
+    };
+
+    let storage = AccountStorage::from_account(chr.character.accountid, chr.character.world)?;
+    let packet = build_open_storage(npc_id, &storage)?;
+    let open = OpenStorage {
+        npc_id,
+        store_fee: npc.trunk_put.unwrap_or(0).max(0),
+        take_out_fee: npc.trunk_get.unwrap_or(0).max(0),
+        storage,
+    };
+    Ok(Some((open, packet)))
+}
+
+/// Store `quantity` of the stack at `position` and save the character with
+/// the storage. Throwing stars and bullets are always stored as a whole
+/// stack.
+pub fn store(
+    chr: &mut CharacterWrapper,
+    open: &mut OpenStorage,
+    position: i16,
+    item_id: i32,
+    quantity: i16,
+) -> Result<Result<HandlerResult, StorageError>, NetworkError> {
+    let Some(tab) = InventoryType::of_item(item_id) else {
+        return Ok(Err(StorageError::ItemNotFound));
+    };
+    let Some(stack) = chr
+        .inventory
+        .get(tab, position)
+        .filter(|stack| position > 0 && stack.item_id == item_id)
+    else {
+        return Ok(Err(StorageError::ItemNotFound));
+    };
+    let quantity = if is_rechargeable(item_id) {
+        stack.quantity
+    } else {
+        quantity
+    };
+    if quantity <= 0 || quantity > stack.quantity {
+        return Ok(Err(StorageError::InvalidQuantity));
+    }
+    if open.storage.is_full() {
+        return Ok(Err(StorageError::StorageFull));
+    }
+    if chr.character.meso < open.store_fee {
+        return Ok(Err(StorageError::NotEnoughMeso));
+    }
+
+    let mut item = stack.clone();
+    item.quantity = quantity;
+    let Some(remaining) = chr.inventory.take_at(tab, position, quantity) else {
+        return Ok(Err(StorageError::InvalidQuantity));
+    };
+    open.storage.put(item);
+    chr.character.meso -= open.store_fee;
+    open.storage.save_with(chr)?;
+
+    Ok(Ok(HandlerResult::replies(vec![
+        build_inventory_take(tab, position, remaining)?,
+        build_meso_update(chr.character.meso, false)?,
+        build_storage_stored(&open.storage, tab)?,
+    ])))
+}
+
+/// Take out the item at `index` among the stored items of tab `kind` and
+/// save the character with the storage.
+pub fn take_out(
+    chr: &mut CharacterWrapper,
+    open: &mut OpenStorage,
+    kind: u8,
+    index: u8,
+) -> Result<Result<HandlerResult, StorageError>, NetworkError> {
+    let Ok(tab) = InventoryType::try_from(kind) else {
+        return Ok(Err(StorageError::ItemNotFound));
+    };
+    let index = usize::from(index);
+    let Some(item) = open
+        .storage
+        .items_of(tab)
+        .get(index)
+        .map(|&item| item.clone())
+    else {
+        return Ok(Err(StorageError::ItemNotFound));
+    };
+    if chr.character.meso < open.take_out_fee {
+        return Ok(Err(StorageError::NotEnoughMeso));
+    }
+
+    let slot_max = game_data::items()?
+        .item(item.item_id)
+        .map_or(DEFAULT_SLOT_MAX, |template| template.slot_max);
+    let Some((_, position)) = chr.inventory.add(item, slot_max) else {
+        return Ok(Err(StorageError::InventoryFull));
+    };
+    open.storage.take(tab, index);
+    chr.character.meso -= open.take_out_fee;
+    open.storage.save_with(chr)?;
+
+    let item = chr
+        .inventory
+        .get(tab, position)
+        .ok_or(NetworkError::PacketHandlerError("Taken out item not found"))?;
+    let mut packets = vec![
+        build_inventory_add(tab, position, item)?,
+        build_meso_update(chr.character.meso, false)?,
+        build_storage_taken_out(&open.storage, tab)?,
+    ];
+    packets.extend(quests::item_progress(chr, item.item_id)?);
+    Ok(Ok(HandlerResult::replies(packets)))
+}
+
+/// Move mesos between the character and the storage and save both. A
+/// positive `meso` is taken out of the storage, a negative one put in.
+pub fn transfer_meso(
+    chr: &mut CharacterWrapper,
+    open: &mut OpenStorage,
+    meso: i32,
+) -> Result<Result<HandlerResult, StorageError>, NetworkError> {
+    if meso > 0 {
+        if meso > open.storage.meso() {
+            return Ok(Err(StorageError::NotEnoughMeso));
+        }
+        let Some(total) = chr.character.meso.checked_add(meso) else {
+            return Ok(Err(StorageError::MesoLimit));
+        };
+        open.storage.withdraw_meso(meso);
+        chr.character.meso = total;
+    } else {
+        let Some(deposit) = meso.checked_neg().filter(|&deposit| deposit > 0) else {
+            return Ok(Err(StorageError::InvalidQuantity));
+        };
+        if deposit > chr.character.meso {
+            return Ok(Err(StorageError::NotEnoughMeso));
+        }
+        if !open.storage.deposit_meso(deposit) {
+            return Ok(Err(StorageError::MesoLimit));
+        }
+        chr.character.meso -= deposit;
+    }
+    open.storage.save_with(chr)?;
+
+    Ok(Ok(HandlerResult::replies(vec![
+        build_meso_update(chr.character.meso, false)?,
+        build_storage_meso(&open.storage)?,
+    ])))
+}
+
+/// Sort the stored items and save the storage.
+pub fn arrange(open: &mut OpenStorage) -> Result<HandlerResult, NetworkError> {
+    open.storage.arrange();
+    open.storage.save()?;
+    Ok(HandlerResult::reply(build_storage_arranged(&open.storage)?))
+}
+
+/// Add `slots` slots to the character's account storage in its world and
+/// save it. Returns `false`, changing nothing, if the storage can't grow that
+/// much.
+pub fn expand_storage(chr: &CharacterWrapper, slots: i16) -> Result<bool, NetworkError> {
+    let mut storage = AccountStorage::from_account(chr.character.accountid, chr.character.world)?;
+    if !storage.expand(slots) {
+        return Ok(false);
+    }
+    storage.save()?;
+    Ok(true)
+}
@@ -0,0 +1,387 @@
+use crate::error::NetworkError;
+use crate::game_data;
+use crate::packet::build::world::inventory::{build_inventory_add, build_inventory_take};
+use crate::packet::build::world::stat::build_meso_update;
+use crate::quests;
+use ::game_data::DEFAULT_SLOT_MAX;
+use db::character::{Character, CharacterWrapper};
+use db::inventory::{is_rechargeable, Inventory, InventoryItem, InventoryType};
+use packet::Packet;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// How many items each side of a trade can offer.
+pub const TRADE_SLOTS: u8 = 9;
+
+/// Something a character did in a trade window. Trades are run by the field
+/// both characters are in, so these are passed on to it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TradeAction {
+    /// Open an empty trade window.
+    Open,
+    /// Invite a character on the same map into the open window.
+    Invite {
+        character_id: i32,
+    },
+    /// Turn down an invite.
+    Decline,
+    /// Join the window the character was invited to.
+    Accept,
+    Chat {
+        message: String,
+    },
+    /// Close the window, cancelling the trade.
+    Cancel,
+    /// Offer `quantity` of the stack at `position` of tab `kind`, shown in
+    /// trade slot `slot`.
+    OfferItem {
+        kind: u8,
+        position: i16,
+        quantity: i16,
+        slot: u8,
+    },
+    /// Offer `meso` more mesos.
+    OfferMeso {
+        meso: i32,
+    },
+    Confirm,
+}
+
+/// Why an offer or a trade was refused.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TradeError {
+    InvalidSlot,
+    ItemNotFound,
+    InvalidQuantity,
+    NotEnoughMeso,
+    /// A character would end up with more mesos than it can hold.
+    MesoLimit,
+    InventoryFull,
+    /// An offered item was moved, used or dropped before the trade completed.
+    ItemChanged,
+}
+
+impl TradeError {
+    pub fn message(self) -> &'static str {
+        match self {
+            TradeError::InvalidSlot => "Invalid trade slot",
+            TradeError::ItemNotFound => "Item not found",
+            TradeError::InvalidQuantity => "Invalid quantity",
+            TradeError::NotEnoughMeso => "Not enough mesos",
+            TradeError::MesoLimit => "Too many mesos",
+            TradeError::InventoryFull => "Inventory full",
+            TradeError::ItemChanged => "Offered item changed",
+        }
+    }
+}
+
+impl fmt::Display for TradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+/// An item offered in a trade, with where it is kept in the inventory.
+#[derive(Clone, Debug)]
+pub struct OfferedItem {
+    pub slot: u8,
+    pub tab: InventoryType,
+    pub position: i16,
+    /// A copy of the stack holding only the offered quantity.
+    pub item: InventoryItem,
+}
+
+/// What one side of a trade offers. Offered items and mesos stay with the
+/// character until the trade completes, so a cancelled trade has nothing to
+/// give back; the client is only shown them as taken.
+#[derive(Clone, Debug, Default)]
+pub struct TradeOffer {
+    items: Vec<OfferedItem>,
+    meso: i32,
+}
+
+impl TradeOffer {
+    pub fn items(&self) -> &[OfferedItem] {
+        &self.items
+    }
+
+    pub fn meso(&self) -> i32 {
+        self.meso
+    }
+
+    /// Offer `quantity` of the stack at `position` in trade slot `slot`.
+    /// Throwing stars and bullets are always offered as a whole stack.
+    /// Returns the offered item with how much of the stack the client should
+    /// now be shown.
+    pub fn add_item(
+        &mut self,
+        chr: &CharacterWrapper,
+        kind: u8,
+        position: i16,
+        quantity: i16,
+        slot: u8,
+    ) -> Result<(&OfferedItem, i16), TradeError> {
+        if !(1..=TRADE_SLOTS).contains(&slot) || self.items.iter().any(|item| item.slot == slot) {
+            return Err(TradeError::InvalidSlot);
+        }
+        let tab = InventoryType::try_from(kind).map_err(|_| TradeError::ItemNotFound)?;
+        let stack = chr
+            .inventory
+            .get(tab, position)
+            .filter(|_| position > 0)
+            .ok_or(TradeError::ItemNotFound)?;
+        let reserved = self.reserved(tab, position);
+        let quantity = if is_rechargeable(stack.item_id) && reserved == 0 {
+            stack.quantity
+        } else {
+            quantity
+        };
+        if quantity <= 0 || i32::from(quantity) + i32::from(reserved) > i32::from(stack.quantity) {
+            return Err(TradeError::InvalidQuantity);
+        }
+
+        let mut item = stack.clone();
+        item.quantity = quantity;
+        let shown = stack.quantity - reserved - quantity;
+        self.items.push(OfferedItem {
+            slot,
+            tab,
+            position,
+            item,
+        });
+        Ok((&self.items[self.items.len() - 1], shown))
+    }
+
+    /// Offer `meso` more mesos. Returns the total offered.
+    pub fn add_meso(&mut self, chr: &CharacterWrapper, meso: i32) -> Result<i32, TradeError> {
+        if meso <= 0 {
+            return Err(TradeError::InvalidQuantity);
+        }
+        match self.meso.checked_add(meso) {
+            Some(total) if total <= chr.character.meso => {
+                self.meso = total;
+                Ok(total)
+            }
+            _ => Err(TradeError::NotEnoughMeso),
+        }
+    }
+
+    /// Packets that show the character its real stacks and mesos again,
+    /// undoing what the client was shown as offered.
+    pub fn restore_view(&self, chr: &CharacterWrapper) -> Result<Vec<Packet>, NetworkError> {
+        let positions = self.items.iter().map(|item| (item.tab, item.position));
+        let mut packets = inventory_view(&chr.inventory, positions)?;
+        if self.meso > 0 {
+            packets.push(build_meso_update(chr.character.meso, false)?);
+        }
+        Ok(packets)
+    }
+
+    fn reserved(&self, tab: InventoryType, position: i16) -> i16 {
+        self.items
+            .iter()
+            .filter(|item| item.tab == tab && item.position == position)
+            .map(|item| item.item.quantity)
+            .sum()
+    }
+}
+
+/// The packets that show the two traders the outcome of an exchange, in the
+/// order the traders were given.
+pub type ExchangeViews = (Vec<Packet>, Vec<Packet>);
+
+/// A character's inventory and mesos after a trade, before they are saved.
+struct Settlement {
+    character: Character,
+    inventory: Inventory,
+    touched: Vec<(InventoryType, i16)>,
+}
+
+/// Complete a trade: give each character what the other offered and save
+/// both in one transaction. Offers are checked against the inventories again,
+/// since the items stayed with their owners while the window was open.
+/// Returns the packets that show each character the outcome; on a refusal,
+/// or if saving fails, neither character changes.
+pub fn exchange(
+    a: &mut CharacterWrapper,
+    a_offer: &TradeOffer,
+    b: &mut CharacterWrapper,
+    b_offer: &TradeOffer,
+) -> Result<Result<ExchangeViews, TradeError>, NetworkError> {
+    let items = game_data::items()?;
+    let slot_max = |item_id| {
+        items
+            .item(item_id)
+            .map_or(DEFAULT_SLOT_MAX, |template| template.slot_max)
+    };
+    let a_settled = match settle(a, a_offer, b_offer, slot_max) {
+        Ok(settled) => settled,
+        Err(error) => return Ok(Err(error)),
+    };
+    let b_settled = match settle(b, b_offer, a_offer, slot_max) {
+        Ok(settled) => settled,
+        Err(error) => return Ok(Err(error)),
+    };
+
+    db::character::update_characters_with_inventories(&[
+        (&a_settled.character, &a_settled.inventory),
+        (&b_settled.character, &b_settled.inventory),
+    ])?;
+
+    let mut a_packets = settled_view(&a_settled)?;
+    let mut b_packets = settled_view(&b_settled)?;
+    a.character = a_settled.character;
+    a.inventory = a_settled.inventory;
+    b.character = b_settled.character;
+    b.inventory = b_settled.inventory;
+    for offered in &b_offer.items {
+        a_packets.extend(quests::item_progress(a, offered.item.item_id)?);
+    }
+    for offered in &a_offer.items {
+        b_packets.extend(quests::item_progress(b, offered.item.item_id)?);
+    }
+    Ok(Ok((a_packets, b_packets)))
+}
+
+/// Work out what a character is left with after giving `given` and receiving
+/// `received`, without changing the character.
+fn settle(
+    chr: &CharacterWrapper,
+    given: &TradeOffer,
+    received: &TradeOffer,
+    slot_max: impl Fn(i32) -> i16,
+) -> Result<Settlement, TradeError> {
+    let mut inventory = chr.inventory.clone();
+    let mut touched = Vec::new();
+    for offered in &given.items {
+        let unchanged = inventory
+            .get(offered.tab, offered.position)
+            .is_some_and(|stack| is_same_item(stack, &offered.item));
+        if !unchanged {
+            return Err(TradeError::ItemChanged);
+        }
+        inventory
+            .take_at(offered.tab, offered.position, offered.item.quantity)
+            .ok_or(TradeError::ItemChanged)?;
+        touched.push((offered.tab, offered.position));
+    }
+
+    if given.meso > chr.character.meso {
+        return Err(TradeError::NotEnoughMeso);
+    }
+    let meso = (chr.character.meso - given.meso)
+        .checked_add(received.meso)
+        .ok_or(TradeError::MesoLimit)?;
+
+    for offered in &received.items {
+        let item_id = offered.item.item_id;
+        let position = inventory
+            .add(offered.item.clone(), slot_max(item_id))
+            .ok_or(TradeError::InventoryFull)?;
+        touched.push(position);
+    }
+
+    let mut character = chr.character.clone();
+    character.meso = meso;
+    Ok(Settlement {
+        character,
+        inventory,
+        touched,
+    })
+}
+
+/// Whether a stack still holds the item that was offered out of it, apart
+/// from its size.
+fn is_same_item(stack: &InventoryItem, offered: &InventoryItem) -> bool {
+    stack.item_id == offered.item_id
+        && stack.stats == offered.stats
+        && stack.owner == offered.owner
+        && stack.flag == offered.flag
+}
+
+fn settled_view(settled: &Settlement) -> Result<Vec<Packet>, NetworkError> {
+    let mut packets = inventory_view(&settled.inventory, settled.touched.iter().copied())?;
+    packets.push(build_meso_update(settled.character.meso, false)?);
+    Ok(packets)
+}
+
+/// Show the client what each of the given positions holds.
+fn inventory_view(
+    inventory: &Inventory,
+    positions: impl Iterator<Item = (InventoryType, i16)>,
+) -> Result<Vec<Packet>, NetworkError> {
+    let mut seen = Vec::new();
+    let mut packets = Vec::new();
+    for (tab, position) in positions {
+        if seen.contains(&(tab, position)) {
+            continue;
+        }
+        seen.push((tab, position));
+        packets.push(match inventory.get(tab, position) {
+            Some(item) => build_inventory_add(tab, position, item)?,
+            None => build_inventory_take(tab, position, 0)?,
+        });
+    }
+    Ok(packets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::test_character_wrapper;
+
+    #[test]
+    fn offers_cannot_reserve_more_than_the_stack() {
+        let mut chr = test_character_wrapper();
+        chr.character.meso = 1000;
+        chr.inventory.add(InventoryItem::new(4000019, 10), 100);
+
+        let mut offer = TradeOffer::default();
+        let (offered, shown) = offer
+            .add_item(&chr, 4, 1, 6, 1)
+            .expect("first part of the stack");
+        assert_eq!((offered.item.quantity, shown), (6, 4));
+        assert_eq!(
+            offer.add_item(&chr, 4, 1, 1, 1).unwrap_err(),
+            TradeError::InvalidSlot
+        );
+        assert_eq!(
+            offer.add_item(&chr, 4, 1, 5, 2).unwrap_err(),
+            TradeError::InvalidQuantity
+        );
+        assert_eq!(offer.add_item(&chr, 4, 1, 4, 2).expect("rest").1, 0);
+
+        assert_eq!(offer.add_meso(&chr, 600), Ok(600));
+        assert_eq!(offer.add_meso(&chr, 401), Err(TradeError::NotEnoughMeso));
+        // Nothing left the inventory.
+        assert_eq!(chr.inventory.count(4000019), 10);
+    }
+
+    #[test]
+    fn settling_swaps_items_and_mesos() {
+        let mut a = test_character_wrapper();
+        a.character.meso = 500;
+        a.inventory.add(InventoryItem::new(4000019, 10), 100);
+        let mut b = test_character_wrapper();
+        b.character.id = 2;
+        b.inventory.add(InventoryItem::new(2000000, 5), 100);
+
+        let mut a_offer = TradeOffer::default();
+        a_offer.add_item(&a, 4, 1, 10, 1).expect("a offers");
+        a_offer.add_meso(&a, 200).expect("a offers mesos");
+        let mut b_offer = TradeOffer::default();
+        b_offer.add_item(&b, 2, 1, 2, 3).expect("b offers");
+
+        let settled = settle(&a, &a_offer, &b_offer, |_| 100).expect("a settles");
+        assert_eq!(settled.character.meso, 300);
+        assert_eq!(settled.inventory.count(4000019), 0);
+        assert_eq!(settled.inventory.count(2000000), 2);
+
+        // The offered stack shrank after it was offered.
+        b.inventory.take_at(InventoryType::Use, 1, 4);
+        assert_eq!(
+            settle(&b, &b_offer, &a_offer, |_| 100).err(),
+            Some(TradeError::ItemChanged)
+        );
+    }
+}
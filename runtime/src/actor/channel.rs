@@ -1,11 +1,14 @@
-use crate::actor::field::FieldMapEntityNpc;
+use crate::actor::field::{FieldMapEntityMob, FieldMapEntityNpc};
 use crate::actor::FieldActor;
 use crate::message::{ChannelMessage, FieldKey, FieldMessage, RuntimeLocation};
+use game_data::{ItemData, MobDrop};
+use net::packet::build::world::npc::MAP_NPC_OBJECT_ID_BASE;
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{info, warn};
 
-const MAP_NPC_OBJECT_ID_BASE: i32 = 1_000_000_000;
+const BASE_MOB_RESPAWN_DELAY: Duration = Duration::from_secs(7);
 
 struct FieldHandle {
     sender: mpsc::Sender<FieldMessage>,
@@ -69,8 +72,15 @@ impl ChannelActor {
                 location,
                 packet,
             } => {
-                self.send_to_field(location, FieldMessage::Chat { from: client_id, packet }, client_id)
-                    .await;
+                self.send_to_field(
+                    location,
+                    FieldMessage::Chat {
+                        from: client_id,
+                        packet,
+                    },
+                    client_id,
+                )
+                .await;
             }
             ChannelMessage::Move {
                 client_id,
@@ -89,6 +99,185 @@ impl ChannelActor {
                 )
                 .await;
             }
+            ChannelMessage::MobMove {
+                client_id,
+                location,
+                object_id,
+                packet,
+                response_packet,
+                movement_bytes,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::MoveMob {
+                        from: client_id,
+                        object_id,
+                        packet,
+                        response_packet,
+                        movement_bytes,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::Attack {
+                client_id,
+                location,
+                packet,
+                damage_lines,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::Attack {
+                        from: client_id,
+                        packet,
+                        damage_lines,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::Pickup {
+                client_id,
+                location,
+                object_id,
+                space,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::Pickup {
+                        from: client_id,
+                        object_id,
+                        space,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::ReturnItem {
+                client_id,
+                location,
+                item_id,
+                quantity,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::ReturnItem {
+                        from: client_id,
+                        item_id,
+                        quantity,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::UpdateLook {
+                client_id,
+                location,
+                equipment,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::UpdateLook {
+                        from: client_id,
+                        equipment,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::LevelUp {
+                client_id,
+                location,
+                level,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::LevelUp {
+                        from: client_id,
+                        level,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::JobChange {
+                client_id,
+                location,
+                job,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::JobChange {
+                        from: client_id,
+                        job,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::Damage {
+                client_id,
+                location,
+                packet,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::Damage {
+                        from: client_id,
+                        packet,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::Buff {
+                client_id,
+                location,
+                packet,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::Buff {
+                        from: client_id,
+                        packet,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::Trade {
+                client_id,
+                location,
+                character,
+                action,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::Trade {
+                        from: client_id,
+                        character,
+                        action,
+                    },
+                    client_id,
+                )
+                .await;
+            }
+            ChannelMessage::PartyChanged {
+                client_id,
+                location,
+                party_id,
+            } => {
+                self.send_to_field(
+                    location,
+                    FieldMessage::PartyChanged {
+                        from: client_id,
+                        party_id,
+                    },
+                    client_id,
+                )
+                .await;
+            }
             ChannelMessage::TransferWithinChannel {
                 client_id,
                 sender,
@@ -121,10 +310,24 @@ impl ChannelActor {
         }
 
         let (field_tx, field_rx) = mpsc::channel(64);
-        let map_npcs = load_field_map_npcs(field_key);
-        let actor = FieldActor::new(field_key, field_rx, map_npcs);
         tokio::spawn(async move {
-            actor.run().await;
+            // The first fields to start read Map.nx, Mob.nx, Item.nx and
+            // Character.nx, so keep them off the workers. Messages wait in
+            // the field's queue until it starts.
+            let (map_npcs, map_mobs) = tokio::task::spawn_blocking(move || {
+                (
+                    load_field_map_npcs(field_key),
+                    load_field_map_mobs(field_key),
+                )
+            })
+            .await
+            .unwrap_or_else(|error| {
+                warn!(field = ?field_key, %error, "Failed to load field NPCs and mobs");
+                (Vec::new(), Vec::new())
+            });
+            FieldActor::new(field_key, field_rx, map_npcs, map_mobs)
+                .run()
+                .await;
         });
 
         self.fields.insert(
@@ -137,7 +340,12 @@ impl ChannelActor {
         field_tx
     }
 
-    async fn send_to_field(&self, location: RuntimeLocation, message: FieldMessage, client_id: i32) {
+    async fn send_to_field(
+        &self,
+        location: RuntimeLocation,
+        message: FieldMessage,
+        client_id: i32,
+    ) {
         let field_key = location.field_key();
         let Some(field) = self.fields.get(&field_key) else {
             warn!(client_id, field = ?field_key, "No field actor for client location");
@@ -179,6 +387,73 @@ fn load_field_map_npcs(field_key: FieldKey) -> Vec<FieldMapEntityNpc> {
         .collect()
 }
 
+fn load_field_map_mobs(field_key: FieldKey) -> Vec<FieldMapEntityMob> {
+    let Ok(game_data) = net::get_game_data() else {
+        warn!(field = ?field_key, "Failed to load game data for field mobs");
+        return Vec::new();
+    };
+
+    let Some(field) = game_data.field(field_key.map_id) else {
+        return Vec::new();
+    };
+    let Ok(mob_data) = net::get_mob_data() else {
+        warn!(field = ?field_key, "Failed to load mob templates for field mobs");
+        return Vec::new();
+    };
+
+    let drop_data = net::get_drop_data()
+        .inspect_err(|_| warn!(field = ?field_key, "Failed to load mob drop tables"))
+        .ok();
+    let item_data = net::get_item_data()
+        .inspect_err(|_| warn!(field = ?field_key, "Failed to load item data for mob drops"))
+        .ok();
+
+    field
+        .map_mobs
+        .iter()
+        .filter_map(|mob| {
+            let template = mob_data.mob(mob.mob_id);
+            if template.is_none() {
+                warn!(field = ?field_key, mob_id = mob.mob_id, "Skipping spawn of unknown mob");
+            }
+            Some((mob, template?))
+        })
+        .map(|(mob, template)| FieldMapEntityMob {
+            mob_id: mob.mob_id,
+            x: mob.x,
+            y: mob.y,
+            foothold: mob.foothold,
+            level: template.level,
+            max_hp: template.max_hp,
+            exp: template.exp,
+            drops: match (drop_data, item_data) {
+                (Some(drop_data), Some(item_data)) => {
+                    valid_mob_drops(field_key, drop_data.mob_drops(mob.mob_id), item_data)
+                }
+                _ => Vec::new(),
+            },
+            respawn_delay: BASE_MOB_RESPAWN_DELAY
+                + Duration::from_secs(u64::try_from(mob.mob_time).unwrap_or(0)),
+        })
+        .collect()
+}
+
+/// Drop table entries for items that have no template are logged and left
+/// out, so pickups only ever hand out real items.
+fn valid_mob_drops(field_key: FieldKey, drops: &[MobDrop], item_data: &ItemData) -> Vec<MobDrop> {
+    drops
+        .iter()
+        .filter(|drop| {
+            let valid = drop.is_meso() || item_data.item_exists(drop.item_id);
+            if !valid {
+                warn!(field = ?field_key, item_id = drop.item_id, "Skipping drop of unknown item");
+            }
+            valid
+        })
+        .cloned()
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,11 +470,13 @@ mod tests {
             hair: 30000,
             skin: 0,
             gender: 0,
+            equipment: Vec::new(),
             channel_id: 0,
             map_id,
             x,
             y,
             stance: 2,
+            party_id: None,
         }
     }
 
@@ -252,4 +529,41 @@ mod tests {
             .await
             .is_err());
     }
+
+    #[test]
+    fn drops_of_unknown_items_are_left_out() {
+        let field_key = FieldKey {
+            channel_id: 0,
+            map_id: 100000000,
+            instance_id: 0,
+        };
+        let item_data = ItemData::from_templates([game_data::ItemTemplate {
+            item_id: 4000019,
+            price: 1,
+            unit_price: 0.0,
+            slot_max: 200,
+            cash: false,
+            equip: None,
+        }]);
+        let drop = |item_id| MobDrop {
+            item_id,
+            min_quantity: 1,
+            max_quantity: 1,
+            chance: 1,
+        };
+
+        let drops = valid_mob_drops(
+            field_key,
+            &[
+                drop(game_data::MESO_DROP_ITEM_ID),
+                drop(4000019),
+                drop(4999999),
+            ],
+            &item_data,
+        );
+        assert_eq!(
+            drops,
+            vec![drop(game_data::MESO_DROP_ITEM_ID), drop(4000019)]
+        );
+    }
 }
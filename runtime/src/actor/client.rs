@@ -1,17 +1,28 @@
+use super::field::sleep_until_deadline;
+use crate::buffs::{ActiveBuff, BuffRegistry};
 use crate::error::RuntimeError;
 use crate::handler::{ClientId, HandlerAction, HandlerContext, HandlerResult};
 use crate::io::{PacketReader, PacketWriter};
-use crate::message::{ClientEvent, FieldCharacter, RuntimeLocation, ServerMessage};
+use crate::message::{
+    ClientEvent, FieldCharacter, RuntimeLocation, ServerMessage, SharedCharacter,
+};
+use db::character::CharacterWrapper;
 use db::session::{SessionState, SessionWrapper};
+use net::buffs::{Buff, BuffStat};
 use net::get_handler;
 use net::listener::ServerType;
 use net::login_world::resolve_login_channel;
+use net::npc::Conversation;
 use net::packet::build;
+use net::skills::Cooldowns;
+use net::stats::RegenClock;
+use net::storage::OpenStorage;
 use packet::Packet;
 use rand::{thread_rng, Rng};
 use std::net::SocketAddr;
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
+use tokio::time::Instant;
 use tracing::{error, info, warn};
 
 /// Actor handling a single world server client connection.
@@ -27,6 +38,22 @@ pub struct ClientActor {
     /// Our sender (given to world server on connect)
     server_tx: mpsc::Sender<ServerMessage>,
     peer_addr: SocketAddr,
+    /// Buffs running on the session character
+    buffs: BuffRegistry,
+    /// The NPC conversation waiting for an answer
+    conversation: Option<Conversation>,
+    /// The NPC whose shop is open
+    shop: Option<i32>,
+    /// The account storage that is open
+    storage: Option<OpenStorage>,
+    /// The party the character is in
+    party_id: Option<i32>,
+    /// The HP and max HP last shown to the party
+    party_hp: Option<(i32, i32)>,
+    /// When the character last regenerated HP and MP
+    regen: RegenClock,
+    /// When the character's skills come off cooldown
+    cooldowns: Cooldowns,
 }
 
 impl ClientActor {
@@ -69,6 +96,14 @@ impl ClientActor {
             server_rx,
             server_tx,
             peer_addr,
+            buffs: BuffRegistry::default(),
+            conversation: None,
+            shop: None,
+            storage: None,
+            party_id: None,
+            party_hp: None,
+            regen: RegenClock::default(),
+            cooldowns: Cooldowns::default(),
         })
     }
 
@@ -113,6 +148,14 @@ impl ClientActor {
                         break;
                     }
                 }
+
+                _ = sleep_until_deadline(self.buffs.next_expiry()) => {
+                    let expired = self.buffs.take_expired(Instant::now());
+                    if let Err(e) = self.end_buffs(expired).await {
+                        error!(self.client_id, error = %e, "Error ending expired buffs");
+                        break;
+                    }
+                }
             }
         }
 
@@ -129,25 +172,67 @@ impl ClientActor {
         // Execute handler in blocking context for DB calls
         // Move session out temporarily to satisfy borrow checker
         let mut session = std::mem::replace(&mut self.session, SessionWrapper::new_empty());
+        let mut conversation = self.conversation.take();
+        let mut shop = self.shop.take();
+        let mut storage = self.storage.take();
+        let mut regen = self.regen;
+        let mut cooldowns = std::mem::take(&mut self.cooldowns);
         let client_id = self.client_id;
-
-        let (result, returned_session) = tokio::task::spawn_blocking(move || {
+        let party_id = self.party_id;
+        let buff_skills = self
+            .buffs
+            .iter()
+            .map(|active| active.buff.skill_id)
+            .collect();
+
+        let (
+            result,
+            returned_session,
+            returned_conversation,
+            returned_shop,
+            returned_storage,
+            returned_regen,
+            returned_cooldowns,
+        ) = tokio::task::spawn_blocking(move || {
             let mut ctx = HandlerContext {
                 client_id,
                 session: &mut session,
+                conversation: &mut conversation,
+                shop: &mut shop,
+                storage: &mut storage,
+                party_id,
+                buff_skills,
+                regen: &mut regen,
+                cooldowns: &mut cooldowns,
             };
             let result = handler.handle(&mut packet, &mut ctx);
-            (result, session)
+            (
+                result,
+                session,
+                conversation,
+                shop,
+                storage,
+                regen,
+                cooldowns,
+            )
         })
         .await
         .map_err(|e| RuntimeError::Handler(format!("Task join error: {}", e)))?;
 
         // Restore session
         self.session = returned_session;
+        self.conversation = returned_conversation;
+        self.shop = returned_shop;
+        self.storage = returned_storage;
+        self.regen = returned_regen;
+        self.cooldowns = returned_cooldowns;
 
         // Process handler result
         match result {
-            Ok(result) => self.process_actions(result).await,
+            Ok(result) => {
+                self.process_actions(result).await?;
+                self.report_party_hp().await
+            }
             Err(e) => {
                 // Log handler error but don't disconnect for unsupported opcodes
                 if matches!(e, net::error::NetworkError::UnsupportedOpcodeError(_)) {
@@ -220,6 +305,117 @@ impl ClientActor {
                         .await
                         .map_err(|_| RuntimeError::ChannelSend)?;
                 }
+                HandlerAction::FieldMobMove {
+                    object_id,
+                    packet,
+                    response_packet,
+                    movement_bytes,
+                } => {
+                    let event = ClientEvent::FieldMobMove {
+                        from: self.client_id,
+                        object_id,
+                        packet,
+                        response_packet,
+                        movement_bytes,
+                    };
+                    self.world_tx
+                        .send(event)
+                        .await
+                        .map_err(|_| RuntimeError::ChannelSend)?;
+                }
+                HandlerAction::FieldAttack {
+                    packet,
+                    damage_lines,
+                } => {
+                    let event = ClientEvent::FieldAttack {
+                        from: self.client_id,
+                        packet,
+                        damage_lines,
+                    };
+                    self.world_tx
+                        .send(event)
+                        .await
+                        .map_err(|_| RuntimeError::ChannelSend)?;
+                }
+                HandlerAction::FieldPickup { object_id, space } => {
+                    let event = ClientEvent::FieldPickup {
+                        from: self.client_id,
+                        object_id,
+                        space,
+                    };
+                    self.world_tx
+                        .send(event)
+                        .await
+                        .map_err(|_| RuntimeError::ChannelSend)?;
+                }
+                HandlerAction::FieldUpdateLook { equipment } => {
+                    let event = ClientEvent::FieldUpdateLook {
+                        from: self.client_id,
+                        equipment,
+                    };
+                    self.world_tx
+                        .send(event)
+                        .await
+                        .map_err(|_| RuntimeError::ChannelSend)?;
+                }
+                HandlerAction::FieldDamage { packet } => {
+                    let event = ClientEvent::FieldDamage {
+                        from: self.client_id,
+                        packet,
+                    };
+                    self.world_tx
+                        .send(event)
+                        .await
+                        .map_err(|_| RuntimeError::ChannelSend)?;
+                }
+                HandlerAction::FieldLevelUp { level } => {
+                    self.world_tx
+                        .send(ClientEvent::FieldLevelUp {
+                            from: self.client_id,
+                            level,
+                        })
+                        .await
+                        .map_err(|_| RuntimeError::ChannelSend)?;
+                }
+                HandlerAction::FieldJobChange { job } => {
+                    self.world_tx
+                        .send(ClientEvent::FieldJobChange {
+                            from: self.client_id,
+                            job,
+                        })
+                        .await
+                        .map_err(|_| RuntimeError::ChannelSend)?;
+                }
+                HandlerAction::GiveBuff {
+                    buff,
+                    effect_packet,
+                } => {
+                    self.give_buff(buff, effect_packet).await?;
+                }
+                HandlerAction::CancelBuff { skill_id } => {
+                    let cancelled = self.buffs.cancel(skill_id).into_iter().collect();
+                    self.end_buffs(cancelled).await?;
+                }
+                HandlerAction::Trade(action) => {
+                    let character = SharedCharacter(self.session.get_character()?);
+                    self.world_tx
+                        .send(ClientEvent::FieldTrade {
+                            from: self.client_id,
+                            character,
+                            action,
+                        })
+                        .await
+                        .map_err(|_| RuntimeError::ChannelSend)?;
+                }
+                HandlerAction::Party(action) => {
+                    self.world_tx
+                        .send(ClientEvent::Party {
+                            from: self.client_id,
+                            action,
+                        })
+                        .await
+                        .map_err(|_| RuntimeError::ChannelSend)?;
+                }
                 HandlerAction::MapChanged {
                     old_map_id,
                     new_map_id,
@@ -286,49 +482,52 @@ impl ClientActor {
 
                     // Load session from database by character_id
                     // Build packets synchronously, then release all locks before await
-                    let reattach_result: Option<(FieldCharacter, RuntimeLocation, Packet, Packet)> = (|| {
-                        let mut session = db::session::get_transition_session_by_character_channel_ip(
-                            character_id,
-                            i16::from(channel_id),
-                            self.peer_addr.ip().into(),
-                        )
-                        .ok()?;
-                        session.state = SessionState::InGame;
-                        let session = db::session::update_session(&session).ok()?;
-                        let wrapper = SessionWrapper::from(session).ok()?;
-                        self.session = wrapper;
-                        let chr_ref = self.session.get_character().ok()?;
-                        let mut chr = chr_ref.lock().ok()?;
-                        let character = FieldCharacter {
-                            id: chr.character.id,
-                            name: chr.character.name.clone(),
-                            level: chr.character.level,
-                            job: chr.character.job,
-                            face: chr.character.face,
-                            hair: chr.character.hair,
-                            skin: chr.character.skin,
-                            gender: chr.character.gender,
-                            channel_id,
-                            map_id: chr.character.map_id,
-                            x: 240,
-                            y: 190,
-                            stance: 2,
-                        };
-                        let location = RuntimeLocation {
-                            channel_id,
-                            map_id: chr.character.map_id,
-                            instance_id: 0,
-                        };
-
-                        // Build the character data packets
-                        let keymap_packet =
-                            build::world::keymap::build_keymap(&mut chr.key_binds).ok()?;
-                        let char_info_packet =
-                            build::world::char::build_char_info(&chr.character, channel_id).ok()?;
-
-                        Some((character, location, keymap_packet, char_info_packet))
-                    })(
-                    );
+                    let reattach_result: Option<(FieldCharacter, RuntimeLocation, Packet, Packet)> =
+                        (|| {
+                            let mut session =
+                                db::session::get_transition_session_by_character_channel_ip(
+                                    character_id,
+                                    i16::from(channel_id),
+                                    self.peer_addr.ip().into(),
+                                )
+                                .ok()?;
+                            session.state = SessionState::InGame;
+                            let session = db::session::update_session(&session).ok()?;
+                            let wrapper = SessionWrapper::from(session).ok()?;
+                            self.session = wrapper;
+                            let chr_ref = self.session.get_character().ok()?;
+                            let mut chr = chr_ref.lock().ok()?;
+                            let character = FieldCharacter {
+                                id: chr.character.id,
+                                name: chr.character.name.clone(),
+                                level: chr.character.level,
+                                job: chr.character.job,
+                                face: chr.character.face,
+                                hair: chr.character.hair,
+                                skin: chr.character.skin,
+                                gender: chr.character.gender,
+                                equipment: chr.inventory.equipment(),
+                                channel_id,
+                                map_id: chr.character.map_id,
+                                x: 240,
+                                y: 190,
+                                stance: 2,
+                                party_id: None,
+                            };
+                            let location = RuntimeLocation {
+                                channel_id,
+                                map_id: chr.character.map_id,
+                                instance_id: 0,
+                            };
+
+                            // Build the character data packets
+                            let keymap_packet =
+                                build::world::keymap::build_keymap(&mut chr.key_binds).ok()?;
+                            let char_info_packet =
+                                build::world::char::build_char_info(&chr, channel_id).ok()?;
+
+                            Some((character, location, keymap_packet, char_info_packet))
+                        })();
 
                     if let Some((character, location, mut keymap_packet, mut char_info_packet)) =
                         reattach_result
@@ -375,9 +574,18 @@ impl ClientActor {
 
                     let mut redirect_packet =
                         build::world::channel::build_channel_change(channel.host, channel.port)
-                    .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+                            .map_err(|e| RuntimeError::Handler(e.to_string()))?;
                     self.writer.send_packet(&mut redirect_packet).await?;
 
+                    if !self.buffs.is_empty() {
+                        self.world_tx
+                            .send(ClientEvent::KeepBuffs {
+                                client_id: self.client_id,
+                                buffs: std::mem::take(&mut self.buffs),
+                            })
+                            .await
+                            .map_err(|_| RuntimeError::ChannelSend)?;
+                    }
                     self.world_tx
                         .send(ClientEvent::Disconnected {
                             client_id: self.client_id,
@@ -397,6 +605,30 @@ impl ClientActor {
             ServerMessage::SendPacket(mut packet) => {
                 self.writer.send_packet(&mut packet).await?;
             }
+            ServerMessage::GainExp { amount, last_hit } => {
+                let result = self.gain_exp(amount, last_hit).await;
+                self.log_award_failure(result, "exp")?;
+            }
+            ServerMessage::MobKilled { mob_id } => {
+                let result = self.record_mob_kill(mob_id).await;
+                self.log_award_failure(result, "quest progress")?;
+            }
+            ServerMessage::GainMeso { amount } => {
+                let result = self.gain_meso(amount).await;
+                self.log_award_failure(result, "meso")?;
+            }
+            ServerMessage::GainItem { item_id, quantity } => {
+                let result = self.gain_item(item_id, quantity).await;
+                self.log_award_failure(result, "item")?;
+            }
+            ServerMessage::RestoreBuffs { buffs } => {
+                self.restore_buffs(buffs).await?;
+            }
+            ServerMessage::PartyChanged { party_id } => {
+                self.party_id = party_id;
+                // Show the new party the character's HP.
+                self.party_hp = None;
+            }
             ServerMessage::Kick(reason) => {
                 warn!(self.client_id, reason, "Client kicked");
                 return Err(RuntimeError::ClientDisconnected);
@@ -406,9 +638,314 @@ impl ClientActor {
                 return Err(RuntimeError::ClientDisconnected);
             }
         }
+        self.report_party_hp().await
+    }
+
+    /// Show the character's party its HP, if it changed since it was last
+    /// shown.
+    async fn report_party_hp(&mut self) -> Result<(), RuntimeError> {
+        if self.party_id.is_none() || self.client_id == 0 {
+            return Ok(());
+        }
+        let hp = {
+            let character = self.session.get_character()?;
+            let chr = character
+                .lock()
+                .map_err(|_| RuntimeError::Handler("Character lock poisoned".to_string()))?;
+            (i32::from(chr.character.hp), i32::from(chr.character.maxhp))
+        };
+        if self.party_hp == Some(hp) {
+            return Ok(());
+        }
+
+        self.party_hp = Some(hp);
+        self.world_tx
+            .send(ClientEvent::PartyMemberHp {
+                from: self.client_id,
+                hp: hp.0,
+                max_hp: hp.1,
+            })
+            .await
+            .map_err(|_| RuntimeError::ChannelSend)
+    }
+
+    /// Run `change` on the session character in a blocking task, since it
+    /// saves to the database.
+    async fn change_character<T, F>(&mut self, change: F) -> Result<T, RuntimeError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut CharacterWrapper) -> Result<T, RuntimeError> + Send + 'static,
+    {
+        let character = self.session.get_character()?;
+        tokio::task::spawn_blocking(move || {
+            let mut chr = character
+                .lock()
+                .map_err(|_| RuntimeError::Handler("Character lock poisoned".to_string()))?;
+            change(&mut chr)
+        })
+        .await
+        .map_err(|e| RuntimeError::Handler(format!("Task join error: {}", e)))?
+    }
+
+    /// Log a failure to give the character something its field awarded. The
+    /// session only ends if the client itself is gone.
+    fn log_award_failure(
+        &self,
+        result: Result<(), RuntimeError>,
+        award: &str,
+    ) -> Result<(), RuntimeError> {
+        match result {
+            Err(e @ (RuntimeError::Io(_) | RuntimeError::ClientDisconnected)) => Err(e),
+            Err(e) => {
+                error!(self.client_id, award, error = %e, "Failed to give award");
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// Add field-earned EXP to the session character, level it up if the EXP
+    /// covers it, and show the gain. Level ups are shown to the field too.
+    async fn gain_exp(&mut self, amount: i32, last_hit: bool) -> Result<(), RuntimeError> {
+        let client_id = self.client_id;
+        let (levels, level, mut stat_packet) = self
+            .change_character(move |chr| {
+                let before = chr.character.clone();
+                let levels = net::stats::gain_exp(&mut chr.character, amount, &mut thread_rng());
+                if let Err(e) = chr.character.save() {
+                    error!(client_id, error = %e, "Failed to save EXP gain");
+                }
+
+                let packet = build::world::stat::build_stat_changes(&before, &chr.character, false)
+                    .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+                Ok((levels, chr.character.level, packet))
+            })
+            .await?;
+
+        let mut packet = build::world::stat::build_show_exp_gain(amount, last_hit)
+            .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+        self.writer.send_packet(&mut packet).await?;
+        self.writer.send_packet(&mut stat_packet).await?;
+        if levels == 0 {
+            return Ok(());
+        }
+
+        info!(self.client_id, level, "Character levelled up");
+        let mut effect_packet =
+            build::world::effect::build_show_self_effect(build::world::effect::EFFECT_LEVEL_UP)
+                .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+        self.writer.send_packet(&mut effect_packet).await?;
+        self.world_tx
+            .send(ClientEvent::FieldLevelUp {
+                from: self.client_id,
+                level,
+            })
+            .await
+            .map_err(|_| RuntimeError::ChannelSend)
+    }
+
+    /// Add picked up mesos to the session character and show the gain.
+    async fn gain_meso(&mut self, amount: i32) -> Result<(), RuntimeError> {
+        let client_id = self.client_id;
+        let meso = self
+            .change_character(move |chr| {
+                chr.character.meso = chr.character.meso.saturating_add(amount);
+                if let Err(e) = chr.character.save() {
+                    error!(client_id, error = %e, "Failed to save meso pickup");
+                }
+                Ok(chr.character.meso)
+            })
+            .await?;
+
+        let mut update_packet = build::world::stat::build_meso_update(meso, true)
+            .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+        self.writer.send_packet(&mut update_packet).await?;
+        let mut gain_packet = build::world::stat::build_show_meso_gain(amount)
+            .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+        self.writer.send_packet(&mut gain_packet).await
+    }
+
+    /// Store a picked up item in the session character's inventory and show
+    /// the gain. An item that can't be stored goes back to the field.
+    async fn gain_item(&mut self, item_id: i32, quantity: i16) -> Result<(), RuntimeError> {
+        let Some(template) = net::get_item_data()
+            .ok()
+            .and_then(|items| items.item(item_id))
+        else {
+            warn!(self.client_id, item_id, "No template for picked up item");
+            return self.return_item(item_id, quantity).await;
+        };
+        let client_id = self.client_id;
+        let added = self
+            .change_character(move |chr| {
+                let Some((tab, position)) = chr.inventory.add(
+                    net::item_from_template(template, quantity),
+                    template.slot_max,
+                ) else {
+                    return Ok(None);
+                };
+                if let Err(e) = chr.inventory.save() {
+                    error!(client_id, error = %e, "Failed to save item pickup");
+                }
+                let item = chr
+                    .inventory
+                    .get(tab, position)
+                    .cloned()
+                    .ok_or_else(|| RuntimeError::Handler("Added item not found".to_string()))?;
+                let quest_packets = net::quests::item_progress(chr, item_id)
+                    .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+                Ok(Some((tab, position, item, quest_packets)))
+            })
+            .await?;
+
+        let Some((tab, position, item, quest_packets)) = added else {
+            // The field checked for room when the pickup was requested, so
+            // this only happens if the inventory filled up in the meantime.
+            warn!(
+                self.client_id,
+                item_id, quantity, "No room for picked up item"
+            );
+            let mut packet = build::world::stat::build_show_inventory_full()
+                .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+            self.writer.send_packet(&mut packet).await?;
+            let mut packet = build::world::map::build_empty_stat_update()
+                .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+            self.writer.send_packet(&mut packet).await?;
+            return self.return_item(item_id, quantity).await;
+        };
+
+        let mut update_packet = build::world::inventory::build_inventory_add(tab, position, &item)
+            .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+        self.writer.send_packet(&mut update_packet).await?;
+        let mut gain_packet = build::world::stat::build_show_item_gain(
+            item_id,
+            i32::from(quantity.min(template.slot_max)),
+        )
+        .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+        self.writer.send_packet(&mut gain_packet).await?;
+        for mut packet in quest_packets {
+            self.writer.send_packet(&mut packet).await?;
+        }
         Ok(())
     }
 
+    /// Hand a picked up item the inventory could not take back to the field,
+    /// which drops it again.
+    async fn return_item(&mut self, item_id: i32, quantity: i16) -> Result<(), RuntimeError> {
+        self.world_tx
+            .send(ClientEvent::FieldReturnItem {
+                from: self.client_id,
+                item_id,
+                quantity,
+            })
+            .await
+            .map_err(|_| RuntimeError::ChannelSend)
+    }
+
+    /// Count a kill towards the session character's started quests and show
+    /// the new progress.
+    async fn record_mob_kill(&mut self, mob_id: i32) -> Result<(), RuntimeError> {
+        let packets = self
+            .change_character(move |chr| {
+                net::quests::record_mob_kill(chr, mob_id)
+                    .map_err(|e| RuntimeError::Handler(e.to_string()))
+            })
+            .await?;
+        for mut packet in packets {
+            self.writer.send_packet(&mut packet).await?;
+        }
+        Ok(())
+    }
+
+    /// Start a buff on the session character and show it to its field.
+    async fn give_buff(&mut self, buff: Buff, effect_packet: Packet) -> Result<(), RuntimeError> {
+        let now = Instant::now();
+        let active = self.buffs.give(buff, now).clone();
+        self.send_field_buff(effect_packet).await?;
+        self.show_buff(&active, now).await
+    }
+
+    /// Take over the buffs the character had on its previous channel.
+    async fn restore_buffs(&mut self, buffs: BuffRegistry) -> Result<(), RuntimeError> {
+        let now = Instant::now();
+        self.buffs = buffs;
+        self.buffs.take_expired(now);
+        let active: Vec<_> = self.buffs.iter().cloned().collect();
+        for active in &active {
+            self.show_buff(active, now).await?;
+        }
+        Ok(())
+    }
+
+    /// End buffs on the client and its field. Buffs still running on any of
+    /// the same stats are given again, since the client ends the stat, not
+    /// the buff.
+    async fn end_buffs(&mut self, ended: Vec<ActiveBuff>) -> Result<(), RuntimeError> {
+        if ended.is_empty() {
+            return Ok(());
+        }
+
+        let mut stats: Vec<BuffStat> = ended
+            .iter()
+            .flat_map(|active| active.buff.stats.iter().map(|&(stat, _)| stat))
+            .collect();
+        stats.sort();
+        stats.dedup();
+        let mut packet = build::world::buff::build_cancel_buff(&stats)
+            .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+        self.writer.send_packet(&mut packet).await?;
+
+        let foreign: Vec<BuffStat> = stats
+            .iter()
+            .copied()
+            .filter(|stat| stat.is_foreign())
+            .collect();
+        if !foreign.is_empty() {
+            let packet = build::world::buff::build_cancel_foreign_buff(self.client_id, &foreign)
+                .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+            self.send_field_buff(packet).await?;
+        }
+
+        let mut overlapping: Vec<ActiveBuff> = ended
+            .iter()
+            .flat_map(|active| self.buffs.overlapping(&active.buff))
+            .cloned()
+            .collect();
+        overlapping.sort_by_key(|active| active.buff.skill_id);
+        overlapping.dedup_by_key(|active| active.buff.skill_id);
+        let now = Instant::now();
+        for active in &overlapping {
+            self.show_buff(active, now).await?;
+        }
+        Ok(())
+    }
+
+    /// Send a running buff to the client, and its visible stats to its field.
+    async fn show_buff(&mut self, active: &ActiveBuff, now: Instant) -> Result<(), RuntimeError> {
+        let mut packet =
+            build::world::buff::build_give_buff(&active.buff, active.remaining_ms(now))
+                .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+        self.writer.send_packet(&mut packet).await?;
+
+        let foreign = active.buff.foreign_stats();
+        if foreign.is_empty() {
+            return Ok(());
+        }
+        let packet = build::world::buff::build_give_foreign_buff(self.client_id, &foreign)
+            .map_err(|e| RuntimeError::Handler(e.to_string()))?;
+        self.send_field_buff(packet).await
+    }
+
+    async fn send_field_buff(&self, packet: Packet) -> Result<(), RuntimeError> {
+        self.world_tx
+            .send(ClientEvent::FieldBuff {
+                from: self.client_id,
+                packet,
+            })
+            .await
+            .map_err(|_| RuntimeError::ChannelSend)
+    }
+
     /// Set the client ID (character ID) after login.
     pub fn set_client_id(&mut self, id: ClientId) {
         self.client_id = id;
@@ -436,11 +973,13 @@ impl ClientActor {
                 hair: 30000,
                 skin: 0,
                 gender: 0,
+                equipment: Vec::new(),
                 channel_id,
                 map_id,
                 x: 240,
                 y: 190,
                 stance: 2,
+                party_id: None,
             },
             location: RuntimeLocation {
                 channel_id,
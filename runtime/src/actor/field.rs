@@ -1,13 +1,45 @@
 use crate::message::{FieldCharacter, FieldKey, FieldMessage, ServerMessage};
+use crate::room::{Exchange, InteractionRooms, Outgoing};
+use game_data::{MobDrop, DROP_CHANCE_SCALE};
+use net::error::NetworkError;
+use net::packet::build::world::attack::DamageLine;
+use net::packet::build::world::drop::{
+    build_drop_item_from_map_object, build_pick_up_drop, build_remove_drop, DropContent,
+    ForeignDrop, DROP_PICKUP_FREE_FOR_ALL, DROP_PICKUP_OWNER, REMOVE_DROP_EXPIRED,
+};
+use net::packet::build::world::effect::{
+    build_show_foreign_effect, EFFECT_JOB_CHANGE, EFFECT_LEVEL_UP,
+};
 use net::packet::build::world::field::{
-    build_player_enter_field, build_player_leave_field, parse_movement_state, ForeignCharacter,
+    build_player_enter_field, build_player_leave_field, build_update_char_look,
+    parse_movement_state, ForeignCharacter,
+};
+use net::packet::build::world::map::build_empty_stat_update;
+use net::packet::build::world::mob::{
+    build_kill_mob, build_mob_control, build_show_mob_hp, build_spawn_mob, ForeignMob,
+    MOB_DEATH_ANIMATION_NORMAL,
 };
 use net::packet::build::world::npc::{build_spawn_npc, ForeignNpc};
+use net::packet::build::world::stat::build_show_inventory_full;
+use net::party;
+use net::{ClientId, InventorySpace};
 use packet::Packet;
+use rand::{thread_rng, Rng};
 use std::collections::HashMap;
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::time::{sleep_until, Instant};
 use tracing::{info, warn};
 
+const FIELD_OBJECT_ID_BASE: i32 = 1;
+const MOB_SPAWN_STANCE: u8 = 5;
+/// How long only the drop's owner may pick it up.
+const DROP_OWNERSHIP_WINDOW: Duration = Duration::from_secs(15);
+const DROP_EXPIRY: Duration = Duration::from_secs(180);
+/// Horizontal gap between drops from the same mob.
+const DROP_SPACING: i16 = 25;
+const MAX_PICKUP_DISTANCE: i32 = 200;
+
 struct Occupant {
     sender: mpsc::Sender<ServerMessage>,
     character: FieldCharacter,
@@ -25,11 +57,82 @@ pub struct FieldMapEntityNpc {
     pub rx1: i16,
 }
 
+/// A mob spawn point authored in the map's life data.
+#[derive(Clone, Debug)]
+pub struct FieldMapEntityMob {
+    pub mob_id: i32,
+    pub x: i16,
+    pub y: i16,
+    pub foothold: i16,
+    pub level: i32,
+    pub max_hp: i32,
+    pub exp: i32,
+    pub drops: Vec<MobDrop>,
+    pub respawn_delay: Duration,
+}
+
+struct MobSpawnPoint {
+    spawn: FieldMapEntityMob,
+    /// Object id of the mob currently alive at this spawn point.
+    live_object_id: Option<i32>,
+    /// When the next mob should appear, if the spawn point is empty.
+    respawn_at: Option<Instant>,
+}
+
+struct FieldMob {
+    object_id: i32,
+    spawn_index: usize,
+    mob_id: i32,
+    x: i16,
+    y: i16,
+    stance: u8,
+    foothold: i16,
+    level: i32,
+    hp: i32,
+    max_hp: i32,
+    exp: i32,
+    /// Damage dealt by each attacker, used to split EXP on death.
+    damage_by: HashMap<i32, i64>,
+    /// Occupant whose client simulates this mob's movement.
+    controller: Option<i32>,
+}
+
+struct FieldDrop {
+    object_id: i32,
+    content: DropContent,
+    owner: i32,
+    x: i16,
+    y: i16,
+    source_object_id: i32,
+    owned_until: Instant,
+    expires_at: Instant,
+}
+
+impl FieldDrop {
+    fn can_be_picked_up_by(&self, client_id: i32, now: Instant) -> bool {
+        self.owner == client_id || now >= self.owned_until
+    }
+}
+
+/// The packets of a trade exchange that ran off the field's task, for the
+/// room `owner` opened.
+struct TradeExchanged {
+    owner: ClientId,
+    outgoing: Result<Outgoing, NetworkError>,
+}
+
 pub struct FieldActor {
     key: FieldKey,
     event_rx: mpsc::Receiver<FieldMessage>,
+    exchange_tx: mpsc::Sender<TradeExchanged>,
+    exchange_rx: mpsc::Receiver<TradeExchanged>,
     occupants: HashMap<i32, Occupant>,
     map_npcs: Vec<FieldMapEntityNpc>,
+    mob_spawns: Vec<MobSpawnPoint>,
+    mobs: HashMap<i32, FieldMob>,
+    drops: HashMap<i32, FieldDrop>,
+    rooms: InteractionRooms,
+    next_object_id: i32,
 }
 
 impl FieldActor {
@@ -37,20 +140,70 @@ impl FieldActor {
         key: FieldKey,
         event_rx: mpsc::Receiver<FieldMessage>,
         map_npcs: Vec<FieldMapEntityNpc>,
+        map_mobs: Vec<FieldMapEntityMob>,
     ) -> Self {
+        let now = Instant::now();
+        let (exchange_tx, exchange_rx) = mpsc::channel(8);
         Self {
             key,
             event_rx,
+            exchange_tx,
+            exchange_rx,
             occupants: HashMap::new(),
             map_npcs,
+            mob_spawns: map_mobs
+                .into_iter()
+                .map(|spawn| MobSpawnPoint {
+                    spawn,
+                    live_object_id: None,
+                    respawn_at: Some(now),
+                })
+                .collect(),
+            mobs: HashMap::new(),
+            drops: HashMap::new(),
+            rooms: InteractionRooms::default(),
+            next_object_id: FIELD_OBJECT_ID_BASE,
         }
     }
 
     pub async fn run(mut self) {
-        info!(field = ?self.key, "FieldActor started");
+        // The first field to start loads String.nx, so keep it off the worker.
+        let map_id = self.key.map_id;
+        let map_name = tokio::task::spawn_blocking(move || {
+            net::get_string_data()
+                .ok()
+                .and_then(|strings| strings.map_name(map_id))
+        })
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+        info!(field = ?self.key, map_name, "FieldActor started");
+
+        self.respawn_due_mobs().await;
 
-        while let Some(message) = self.event_rx.recv().await {
-            self.handle_message(message).await;
+        loop {
+            let next_deadline = self
+                .next_respawn_at()
+                .into_iter()
+                .chain(self.next_drop_expiry())
+                .min();
+            tokio::select! {
+                message = self.event_rx.recv() => {
+                    let Some(message) = message else {
+                        break;
+                    };
+                    self.handle_message(message).await;
+                }
+                Some(exchanged) = self.exchange_rx.recv() => {
+                    self.rooms.finish_exchange(exchanged.owner);
+                    self.send_room_packets(exchanged.outgoing).await;
+                }
+                _ = sleep_until_deadline(next_deadline) => {
+                    self.respawn_due_mobs().await;
+                    self.expire_drops().await;
+                }
+            }
         }
 
         info!(field = ?self.key, "FieldActor shutting down");
@@ -92,6 +245,105 @@ impl FieldActor {
                 }
                 self.broadcast_to_others(from, packet).await;
             }
+            FieldMessage::UpdateLook { from, equipment } => {
+                let Some(occupant) = self.occupants.get_mut(&from) else {
+                    return;
+                };
+                occupant.character.equipment = equipment;
+                match build_update_char_look(&to_foreign_character(&occupant.character)) {
+                    Ok(packet) => self.broadcast_to_others(from, packet).await,
+                    Err(error) => warn!(from, error = %error, "Failed to build look update packet"),
+                }
+            }
+            FieldMessage::LevelUp { from, level } => {
+                let Some(occupant) = self.occupants.get_mut(&from) else {
+                    return;
+                };
+                occupant.character.level = level;
+                match build_show_foreign_effect(from, EFFECT_LEVEL_UP) {
+                    Ok(packet) => self.broadcast_to_others(from, packet).await,
+                    Err(error) => {
+                        warn!(from, error = %error, "Failed to build level-up effect packet")
+                    }
+                }
+            }
+            FieldMessage::JobChange { from, job } => {
+                let Some(occupant) = self.occupants.get_mut(&from) else {
+                    return;
+                };
+                occupant.character.job = job;
+                match build_show_foreign_effect(from, EFFECT_JOB_CHANGE) {
+                    Ok(packet) => self.broadcast_to_others(from, packet).await,
+                    Err(error) => {
+                        warn!(from, error = %error, "Failed to build job change effect packet")
+                    }
+                }
+            }
+            FieldMessage::Damage { from, packet } | FieldMessage::Buff { from, packet } => {
+                if self.occupants.contains_key(&from) {
+                    self.broadcast_to_others(from, packet).await;
+                }
+            }
+            FieldMessage::MoveMob {
+                from,
+                object_id,
+                packet,
+                response_packet,
+                movement_bytes,
+            } => {
+                self.handle_mob_move(from, object_id, packet, response_packet, movement_bytes)
+                    .await;
+            }
+            FieldMessage::Attack {
+                from,
+                packet,
+                damage_lines,
+            } => {
+                self.handle_attack(from, packet, damage_lines).await;
+            }
+            FieldMessage::Pickup {
+                from,
+                object_id,
+                space,
+            } => {
+                self.handle_pickup(from, object_id, space).await;
+            }
+            FieldMessage::ReturnItem {
+                from,
+                item_id,
+                quantity,
+            } => {
+                self.return_item(from, item_id, quantity).await;
+            }
+            FieldMessage::Trade {
+                from,
+                character,
+                action,
+            } => {
+                let occupants = &self.occupants;
+                let outgoing = self
+                    .rooms
+                    .handle_trade(from, character, action, |client_id| {
+                        occupants
+                            .get(&client_id)
+                            .map(|occupant| to_foreign_character(&occupant.character))
+                    })
+                    .map(|(outgoing, exchange)| {
+                        if let Some(exchange) = exchange {
+                            self.start_exchange(exchange);
+                        }
+                        outgoing
+                    });
+                self.send_room_packets(outgoing).await;
+            }
+            FieldMessage::PartyChanged { from, party_id } => {
+                if let Some(occupant) = self.occupants.get_mut(&from) {
+                    occupant.character.party_id = party_id;
+                }
+            }
+            FieldMessage::KillMob { object_id } => {
+                self.kill_mob(object_id, MOB_DEATH_ANIMATION_NORMAL).await;
+            }
         }
     }
 
@@ -115,6 +367,35 @@ impl FieldActor {
             }
         }
 
+        for mob in self.mobs.values() {
+            match build_spawn_mob(&to_foreign_mob(mob), false) {
+                Ok(packet) => self.send_packet(&sender, packet, client_id).await,
+                Err(error) => {
+                    warn!(
+                        client_id,
+                        mob_id = mob.mob_id,
+                        error = %error,
+                        "Failed to build existing mob replay packet"
+                    );
+                }
+            }
+        }
+
+        let now = Instant::now();
+        for drop in self.drops.values() {
+            match build_drop_item_from_map_object(&to_foreign_drop(drop, now), None) {
+                Ok(packet) => self.send_packet(&sender, packet, client_id).await,
+                Err(error) => {
+                    warn!(
+                        client_id,
+                        object_id = drop.object_id,
+                        error = %error,
+                        "Failed to build existing drop replay packet"
+                    );
+                }
+            }
+        }
+
         for occupant in self.occupants.values() {
             match build_player_enter_field(&to_foreign_character(&occupant.character)) {
                 Ok(packet) => {
@@ -148,9 +429,15 @@ impl FieldActor {
 
         self.occupants
             .insert(client_id, Occupant { sender, character });
+
+        self.assign_uncontrolled_mobs().await;
     }
 
     async fn handle_leave(&mut self, client_id: i32) {
+        // Cancel the client's trade while it can still be shown its inventory.
+        let outgoing = self.rooms.leave(client_id);
+        self.send_room_packets(outgoing).await;
+
         let Some(occupant) = self.occupants.remove(&client_id) else {
             return;
         };
@@ -159,6 +446,466 @@ impl FieldActor {
             Ok(packet) => self.broadcast_to_all(packet).await,
             Err(error) => warn!(client_id, error = %error, "Failed to build leave packet"),
         }
+
+        for mob in self.mobs.values_mut() {
+            if mob.controller == Some(client_id) {
+                mob.controller = None;
+            }
+        }
+        self.assign_uncontrolled_mobs().await;
+    }
+
+    async fn handle_mob_move(
+        &mut self,
+        from: i32,
+        object_id: i32,
+        packet: Packet,
+        response_packet: Packet,
+        movement_bytes: Vec<u8>,
+    ) {
+        let Some(mob) = self.mobs.get_mut(&object_id) else {
+            return;
+        };
+
+        if mob.controller != Some(from) {
+            warn!(from, object_id, "Ignoring mob movement from non-controller");
+            return;
+        }
+
+        if let Some((x, y, stance)) =
+            parse_movement_state(&movement_bytes, (mob.x, mob.y, mob.stance))
+        {
+            mob.x = x;
+            mob.y = y;
+            mob.stance = stance;
+        }
+
+        if let Some(occupant) = self.occupants.get(&from) {
+            self.send_packet(&occupant.sender, response_packet, from)
+                .await;
+        }
+        if movement_bytes.first().is_some_and(|&count| count > 0) {
+            self.broadcast_to_others(from, packet).await;
+        }
+    }
+
+    async fn handle_attack(&mut self, from: i32, packet: Packet, damage_lines: Vec<DamageLine>) {
+        let Some(attacker) = self.occupants.get(&from) else {
+            return;
+        };
+        let attacker_sender = attacker.sender.clone();
+        self.broadcast_to_others(from, packet).await;
+
+        for line in damage_lines {
+            let Some(mob) = self.mobs.get_mut(&line.object_id) else {
+                continue;
+            };
+
+            let damage = line.total().min(i64::from(mob.hp));
+            mob.hp -= damage as i32;
+            *mob.damage_by.entry(from).or_insert(0) += damage;
+
+            if mob.hp > 0 {
+                let hp_percent = mob_hp_percent(mob);
+                match build_show_mob_hp(line.object_id, hp_percent) {
+                    Ok(packet) => self.send_packet(&attacker_sender, packet, from).await,
+                    Err(error) => warn!(
+                        object_id = line.object_id,
+                        error = %error,
+                        "Failed to build mob HP packet"
+                    ),
+                }
+                continue;
+            }
+
+            if let Some(mob) = self
+                .kill_mob(line.object_id, MOB_DEATH_ANIMATION_NORMAL)
+                .await
+            {
+                self.distribute_mob_exp(&mob, from).await;
+                self.credit_mob_kill(&mob).await;
+                self.spawn_mob_drops(&mob).await;
+            }
+        }
+    }
+
+    /// Split a dead mob's EXP between the occupants that damaged it, in
+    /// proportion to the damage each of them dealt. An attacker in a party
+    /// shares its part with the party members in the field that also
+    /// attacked the mob or are in level range of it.
+    async fn distribute_mob_exp(&self, mob: &FieldMob, last_hit: i32) {
+        let total_damage: i64 = mob.damage_by.values().sum();
+        if total_damage <= 0 || mob.exp <= 0 {
+            return;
+        }
+
+        let mut amounts: HashMap<i32, i32> = HashMap::new();
+        for (&client_id, &damage) in &mob.damage_by {
+            let Some(attacker) = self.occupants.get(&client_id) else {
+                continue;
+            };
+            let share = (i64::from(mob.exp) * damage / total_damage) as i32;
+            let sharers: Vec<(i32, i16)> = match attacker.character.party_id {
+                Some(party_id) => self
+                    .occupants
+                    .iter()
+                    .filter(|&(&id, occupant)| {
+                        occupant.character.party_id == Some(party_id)
+                            && (mob.damage_by.contains_key(&id)
+                                || party::in_exp_range(occupant.character.level, mob.level))
+                    })
+                    .map(|(&id, occupant)| (id, occupant.character.level))
+                    .collect(),
+                None => vec![(client_id, attacker.character.level)],
+            };
+            for (id, amount) in party::split_exp(share, &sharers) {
+                *amounts.entry(id).or_insert(0) += amount;
+            }
+        }
+
+        for (client_id, amount) in amounts {
+            let Some(occupant) = self.occupants.get(&client_id) else {
+                continue;
+            };
+            if amount <= 0 {
+                continue;
+            }
+            if occupant
+                .sender
+                .send(ServerMessage::GainExp {
+                    amount,
+                    last_hit: client_id == last_hit,
+                })
+                .await
+                .is_err()
+            {
+                warn!(client_id, "Failed to credit mob EXP to client");
+            }
+        }
+    }
+
+    /// Count the kill towards the quests of every occupant that damaged the
+    /// mob.
+    async fn credit_mob_kill(&self, mob: &FieldMob) {
+        for &client_id in mob.damage_by.keys() {
+            let Some(occupant) = self.occupants.get(&client_id) else {
+                continue;
+            };
+            if occupant
+                .sender
+                .send(ServerMessage::MobKilled { mob_id: mob.mob_id })
+                .await
+                .is_err()
+            {
+                warn!(client_id, "Failed to credit mob kill to client");
+            }
+        }
+    }
+
+    /// Roll the dead mob's drop table and scatter the results around where it
+    /// died. Drops belong to whoever dealt the most damage.
+    async fn spawn_mob_drops(&mut self, mob: &FieldMob) {
+        let Some(owner) = mob
+            .damage_by
+            .iter()
+            .max_by_key(|&(&client_id, &damage)| (damage, std::cmp::Reverse(client_id)))
+            .map(|(&client_id, _)| client_id)
+        else {
+            return;
+        };
+        let Some(spawn_point) = self.mob_spawns.get(mob.spawn_index) else {
+            return;
+        };
+
+        let mut contents = Vec::new();
+        {
+            let mut rng = thread_rng();
+            for drop in &spawn_point.spawn.drops {
+                if rng.gen_range(0, DROP_CHANCE_SCALE) >= drop.chance {
+                    continue;
+                }
+                let quantity = rng.gen_range(drop.min_quantity, drop.max_quantity + 1);
+                contents.push(if drop.is_meso() {
+                    DropContent::Meso(quantity)
+                } else {
+                    DropContent::Item {
+                        item_id: drop.item_id,
+                        quantity: i16::try_from(quantity).unwrap_or(i16::MAX),
+                    }
+                });
+            }
+        }
+
+        let now = Instant::now();
+        let count = contents.len() as i16;
+        for (index, content) in contents.into_iter().enumerate() {
+            let offset = (index as i16 * 2 - (count - 1)) * DROP_SPACING / 2;
+            let drop = FieldDrop {
+                object_id: self.allocate_object_id(),
+                content,
+                owner,
+                x: mob.x.saturating_add(offset),
+                y: mob.y,
+                source_object_id: mob.object_id,
+                owned_until: now + DROP_OWNERSHIP_WINDOW,
+                expires_at: now + DROP_EXPIRY,
+            };
+            self.add_drop(drop, (mob.x, mob.y), now).await;
+        }
+    }
+
+    async fn handle_pickup(&mut self, from: i32, object_id: i32, space: InventorySpace) {
+        let Some(occupant) = self.occupants.get(&from) else {
+            return;
+        };
+        let sender = occupant.sender.clone();
+        let (x, y) = (occupant.character.x, occupant.character.y);
+
+        let now = Instant::now();
+        let rejection = match self.drops.get(&object_id) {
+            None => Some(None),
+            Some(drop) if !drop.can_be_picked_up_by(from, now) => Some(None),
+            Some(drop)
+                if distance_squared((x, y), (drop.x, drop.y)) > MAX_PICKUP_DISTANCE.pow(2) =>
+            {
+                warn!(from, object_id, "Ignoring pickup of out-of-range drop");
+                Some(None)
+            }
+            Some(FieldDrop {
+                content: DropContent::Item { item_id, quantity },
+                ..
+            }) if !space.has_room_for(*item_id, *quantity) => {
+                Some(Some(build_show_inventory_full()))
+            }
+            Some(_) => None,
+        };
+
+        if let Some(notice) = rejection {
+            if let Some(Ok(packet)) = notice {
+                self.send_packet(&sender, packet, from).await;
+            }
+            // The client locks its actions until the pickup is answered.
+            if let Ok(packet) = build_empty_stat_update() {
+                self.send_packet(&sender, packet, from).await;
+            }
+            return;
+        }
+
+        // The drop stays in the field unless it can be handed to the client.
+        let Ok(permit) = sender.reserve().await else {
+            warn!(from, object_id, "Failed to hand picked up drop to client");
+            return;
+        };
+        let Some(drop) = self.drops.remove(&object_id) else {
+            return;
+        };
+        match build_pick_up_drop(object_id, from) {
+            Ok(packet) => self.broadcast_to_all(packet).await,
+            Err(error) => warn!(object_id, error = %error, "Failed to build pickup packet"),
+        }
+
+        permit.send(match drop.content {
+            DropContent::Meso(amount) => ServerMessage::GainMeso { amount },
+            DropContent::Item { item_id, quantity } => {
+                ServerMessage::GainItem { item_id, quantity }
+            }
+        });
+    }
+
+    /// Drop an item again at the feet of the occupant who picked it up but
+    /// had no room for it, so it is not lost.
+    async fn return_item(&mut self, from: i32, item_id: i32, quantity: i16) {
+        let Some(occupant) = self.occupants.get(&from) else {
+            warn!(
+                from,
+                item_id, quantity, "Lost item returned by a client not in the field"
+            );
+            return;
+        };
+        let (x, y) = (occupant.character.x, occupant.character.y);
+
+        let now = Instant::now();
+        let drop = FieldDrop {
+            object_id: self.allocate_object_id(),
+            content: DropContent::Item { item_id, quantity },
+            owner: from,
+            x,
+            y,
+            source_object_id: from,
+            owned_until: now + DROP_OWNERSHIP_WINDOW,
+            expires_at: now + DROP_EXPIRY,
+        };
+        self.add_drop(drop, (x, y), now).await;
+    }
+
+    /// Show a new drop falling from `dropped_from` and keep it in the field.
+    async fn add_drop(&mut self, drop: FieldDrop, dropped_from: (i16, i16), now: Instant) {
+        match build_drop_item_from_map_object(&to_foreign_drop(&drop, now), Some(dropped_from)) {
+            Ok(packet) => self.broadcast_to_all(packet).await,
+            Err(error) => warn!(
+                object_id = drop.object_id,
+                error = %error,
+                "Failed to build drop packet"
+            ),
+        }
+        self.drops.insert(drop.object_id, drop);
+    }
+
+    fn next_drop_expiry(&self) -> Option<Instant> {
+        self.drops.values().map(|drop| drop.expires_at).min()
+    }
+
+    async fn expire_drops(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<i32> = self
+            .drops
+            .values()
+            .filter(|drop| drop.expires_at <= now)
+            .map(|drop| drop.object_id)
+            .collect();
+
+        for object_id in expired {
+            self.drops.remove(&object_id);
+            match build_remove_drop(object_id, REMOVE_DROP_EXPIRED) {
+                Ok(packet) => self.broadcast_to_all(packet).await,
+                Err(error) => {
+                    warn!(object_id, error = %error, "Failed to build drop expiry packet")
+                }
+            }
+        }
+    }
+
+    fn next_respawn_at(&self) -> Option<Instant> {
+        self.mob_spawns
+            .iter()
+            .filter_map(|spawn_point| spawn_point.respawn_at)
+            .min()
+    }
+
+    async fn respawn_due_mobs(&mut self) {
+        let now = Instant::now();
+        let due: Vec<usize> = self
+            .mob_spawns
+            .iter()
+            .enumerate()
+            .filter(|(_, spawn_point)| {
+                spawn_point.live_object_id.is_none()
+                    && spawn_point.respawn_at.is_some_and(|at| at <= now)
+            })
+            .map(|(spawn_index, _)| spawn_index)
+            .collect();
+
+        for spawn_index in due {
+            self.spawn_mob(spawn_index).await;
+        }
+    }
+
+    async fn spawn_mob(&mut self, spawn_index: usize) {
+        let object_id = self.allocate_object_id();
+        let spawn_point = &mut self.mob_spawns[spawn_index];
+        spawn_point.live_object_id = Some(object_id);
+        spawn_point.respawn_at = None;
+
+        let spawn = &spawn_point.spawn;
+        let mob = FieldMob {
+            object_id,
+            spawn_index,
+            mob_id: spawn.mob_id,
+            x: spawn.x,
+            y: spawn.y,
+            stance: MOB_SPAWN_STANCE,
+            foothold: spawn.foothold,
+            level: spawn.level,
+            hp: spawn.max_hp,
+            max_hp: spawn.max_hp,
+            exp: spawn.exp,
+            damage_by: HashMap::new(),
+            controller: None,
+        };
+
+        match build_spawn_mob(&to_foreign_mob(&mob), true) {
+            Ok(packet) => self.broadcast_to_all(packet).await,
+            Err(error) => warn!(
+                mob_id = mob.mob_id,
+                error = %error,
+                "Failed to build mob spawn packet"
+            ),
+        }
+
+        self.mobs.insert(object_id, mob);
+        self.assign_mob_controller(object_id).await;
+    }
+
+    async fn assign_uncontrolled_mobs(&mut self) {
+        let mut uncontrolled: Vec<i32> = self
+            .mobs
+            .values()
+            .filter(|mob| mob.controller.is_none())
+            .map(|mob| mob.object_id)
+            .collect();
+        uncontrolled.sort_unstable();
+
+        for object_id in uncontrolled {
+            self.assign_mob_controller(object_id).await;
+        }
+    }
+
+    async fn assign_mob_controller(&mut self, object_id: i32) {
+        let Some(controller) = self.elect_mob_controller() else {
+            return;
+        };
+        let Some(mob) = self.mobs.get_mut(&object_id) else {
+            return;
+        };
+        mob.controller = Some(controller);
+        let foreign_mob = to_foreign_mob(mob);
+
+        let Some(occupant) = self.occupants.get(&controller) else {
+            return;
+        };
+        match build_mob_control(&foreign_mob) {
+            Ok(packet) => self.send_packet(&occupant.sender, packet, controller).await,
+            Err(error) => warn!(
+                object_id,
+                controller,
+                error = %error,
+                "Failed to build mob control packet"
+            ),
+        }
+    }
+
+    /// Pick the occupant controlling the fewest mobs, so control is spread
+    /// across everyone in the field.
+    fn elect_mob_controller(&self) -> Option<i32> {
+        self.occupants.keys().copied().min_by_key(|&client_id| {
+            let controlled = self
+                .mobs
+                .values()
+                .filter(|mob| mob.controller == Some(client_id))
+                .count();
+            (controlled, client_id)
+        })
+    }
+
+    async fn kill_mob(&mut self, object_id: i32, animation: u8) -> Option<FieldMob> {
+        let mob = self.mobs.remove(&object_id)?;
+
+        if let Some(spawn_point) = self.mob_spawns.get_mut(mob.spawn_index) {
+            spawn_point.live_object_id = None;
+            spawn_point.respawn_at = Some(Instant::now() + spawn_point.spawn.respawn_delay);
+        }
+
+        match build_kill_mob(object_id, animation) {
+            Ok(packet) => self.broadcast_to_all(packet).await,
+            Err(error) => warn!(object_id, error = %error, "Failed to build mob kill packet"),
+        }
+        Some(mob)
+    }
+
+    fn allocate_object_id(&mut self) -> i32 {
+        let object_id = self.next_object_id;
+        self.next_object_id += 1;
+        object_id
     }
 
     async fn broadcast_to_all(&self, packet: Packet) {
@@ -178,6 +925,37 @@ impl FieldActor {
         }
     }
 
+    /// Run a confirmed trade in a blocking task, since it locks both
+    /// characters and saves them. Its room stays reserved until the packets
+    /// come back.
+    fn start_exchange(&self, exchange: Exchange) {
+        let owner = exchange.owner();
+        let exchange_tx = self.exchange_tx.clone();
+        tokio::spawn(async move {
+            let outgoing = tokio::task::spawn_blocking(move || exchange.run())
+                .await
+                .unwrap_or(Err(NetworkError::PacketHandlerError(
+                    "Trade exchange panicked",
+                )));
+            let _ = exchange_tx.send(TradeExchanged { owner, outgoing }).await;
+        });
+    }
+
+    async fn send_room_packets(&self, outgoing: Result<Outgoing, NetworkError>) {
+        let outgoing = match outgoing {
+            Ok(outgoing) => outgoing,
+            Err(error) => {
+                warn!(field = ?self.key, error = %error, "Failed to run interaction room");
+                return;
+            }
+        };
+        for (client_id, packet) in outgoing {
+            if let Some(occupant) = self.occupants.get(&client_id) {
+                self.send_packet(&occupant.sender, packet, client_id).await;
+            }
+        }
+    }
+
     async fn send_packet(
         &self,
         sender: &mpsc::Sender<ServerMessage>,
@@ -204,6 +982,7 @@ fn to_foreign_character(character: &FieldCharacter) -> ForeignCharacter {
         hair: character.hair,
         skin: character.skin,
         gender: character.gender,
+        equipment: character.equipment.clone(),
         map_id: character.map_id,
         x: character.x,
         y: character.y,
@@ -211,6 +990,51 @@ fn to_foreign_character(character: &FieldCharacter) -> ForeignCharacter {
     }
 }
 
+fn to_foreign_mob(mob: &FieldMob) -> ForeignMob {
+    ForeignMob {
+        object_id: mob.object_id,
+        mob_id: mob.mob_id,
+        x: mob.x,
+        y: mob.y,
+        stance: mob.stance,
+        foothold: mob.foothold,
+    }
+}
+
+fn to_foreign_drop(drop: &FieldDrop, now: Instant) -> ForeignDrop {
+    ForeignDrop {
+        object_id: drop.object_id,
+        content: drop.content,
+        owner: drop.owner,
+        pickup_type: if now < drop.owned_until {
+            DROP_PICKUP_OWNER
+        } else {
+            DROP_PICKUP_FREE_FOR_ALL
+        },
+        x: drop.x,
+        y: drop.y,
+        source_object_id: drop.source_object_id,
+    }
+}
+
+fn distance_squared(a: (i16, i16), b: (i16, i16)) -> i32 {
+    let dx = i32::from(a.0) - i32::from(b.0);
+    let dy = i32::from(a.1) - i32::from(b.1);
+    dx * dx + dy * dy
+}
+
+fn mob_hp_percent(mob: &FieldMob) -> u8 {
+    let max_hp = i64::from(mob.max_hp.max(1));
+    (i64::from(mob.hp.max(0)) * 100 / max_hp) as u8
+}
+
+pub(super) async fn sleep_until_deadline(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
 fn to_foreign_npc(npc: &FieldMapEntityNpc) -> ForeignNpc {
     ForeignNpc {
         object_id: npc.object_id,
@@ -228,11 +1052,16 @@ fn to_foreign_npc(npc: &FieldMapEntityNpc) -> ForeignNpc {
 mod tests {
     use super::*;
     use crate::message::ServerMessage;
-    use net::packet::build::world::field::{build_local_chat, build_player_move};
+    use net::packet::build::world::attack::{build_close_range_attack, AttackInfo};
+    use net::packet::build::world::field::{
+        build_damage_player, build_local_chat, build_player_move,
+    };
+    use net::packet::build::world::mob::{build_move_mob, build_move_mob_response};
     use net::packet::op::SendOpcode;
     use packet::io::read::PktRead;
     use std::io::Cursor;
     use tokio::sync::mpsc;
+    use tokio::time::timeout;
 
     fn test_character(id: i32, name: &str) -> FieldCharacter {
         FieldCharacter {
@@ -244,11 +1073,13 @@ mod tests {
             hair: 30000,
             skin: 0,
             gender: 0,
+            equipment: Vec::new(),
             channel_id: 0,
             map_id: 1_000_000,
             x: 240,
             y: 190,
             stance: 2,
+            party_id: None,
         }
     }
 
@@ -259,9 +1090,24 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn joining_second_player_replays_existing_occupant() {
-        let (field_tx, field_rx) = mpsc::channel(8);
+    /// Receive spawn packets until the one for `character_id`. Occupants are
+    /// replayed in no particular order.
+    async fn recv_spawn_of(rx: &mut mpsc::Receiver<ServerMessage>, character_id: i32) -> Packet {
+        loop {
+            match rx.recv().await.unwrap() {
+                ServerMessage::SendPacket(packet) => {
+                    if packet.bytes[2..6] == character_id.to_le_bytes() {
+                        return packet;
+                    }
+                }
+                other => panic!("expected packet, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn joining_second_player_replays_existing_occupant() {
+        let (field_tx, field_rx) = mpsc::channel(8);
         let field = FieldActor::new(
             FieldKey {
                 channel_id: 0,
@@ -270,6 +1116,7 @@ mod tests {
             },
             field_rx,
             Vec::new(),
+            Vec::new(),
         );
         tokio::spawn(field.run());
 
@@ -297,6 +1144,238 @@ mod tests {
         assert_opcode(second_rx.recv().await.unwrap(), SendOpcode::SpawnPlayer);
     }
 
+    #[tokio::test]
+    async fn look_updates_reach_other_occupants_and_later_joiners() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 1_000_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            Vec::new(),
+        );
+        tokio::spawn(field.run());
+
+        let (first_tx, mut first_rx) = mpsc::channel(8);
+        let (second_tx, mut second_rx) = mpsc::channel(8);
+        let (third_tx, mut third_rx) = mpsc::channel(8);
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: first_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 2,
+                sender: second_tx,
+                character: test_character(2, "second"),
+            })
+            .await
+            .unwrap();
+        let _ = first_rx.recv().await;
+        let _ = second_rx.recv().await;
+
+        field_tx
+            .send(FieldMessage::UpdateLook {
+                from: 1,
+                equipment: vec![(-11, 1302000)],
+            })
+            .await
+            .unwrap();
+        assert_opcode(second_rx.recv().await.unwrap(), SendOpcode::UpdateCharLook);
+        assert!(timeout(Duration::from_millis(50), first_rx.recv())
+            .await
+            .is_err());
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 3,
+                sender: third_tx,
+                character: test_character(3, "third"),
+            })
+            .await
+            .unwrap();
+        // The weapon shows up in the replayed look.
+        let packet = recv_spawn_of(&mut third_rx, 1).await;
+        assert!(packet
+            .bytes
+            .windows(5)
+            .any(|window| window == [11, 0xF0, 0xDD, 0x13, 0x00]));
+    }
+
+    #[tokio::test]
+    async fn level_ups_show_the_effect_and_update_the_spawned_level() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 1_000_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            Vec::new(),
+        );
+        tokio::spawn(field.run());
+
+        let (first_tx, mut first_rx) = mpsc::channel(8);
+        let (second_tx, mut second_rx) = mpsc::channel(8);
+        let (third_tx, mut third_rx) = mpsc::channel(8);
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: first_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 2,
+                sender: second_tx,
+                character: test_character(2, "second"),
+            })
+            .await
+            .unwrap();
+        let _ = first_rx.recv().await;
+        let _ = second_rx.recv().await;
+
+        field_tx
+            .send(FieldMessage::LevelUp { from: 1, level: 30 })
+            .await
+            .unwrap();
+        assert_opcode(
+            second_rx.recv().await.unwrap(),
+            SendOpcode::ShowForeignEffect,
+        );
+        assert!(timeout(Duration::from_millis(50), first_rx.recv())
+            .await
+            .is_err());
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 3,
+                sender: third_tx,
+                character: test_character(3, "third"),
+            })
+            .await
+            .unwrap();
+        // Opcode, then the character id, then the level.
+        let packet = recv_spawn_of(&mut third_rx, 1).await;
+        assert_eq!(packet.bytes[6], 30);
+    }
+
+    #[tokio::test]
+    async fn job_change_is_shown_to_other_occupants_only() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 1_000_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            Vec::new(),
+        );
+        tokio::spawn(field.run());
+
+        let (first_tx, mut first_rx) = mpsc::channel(8);
+        let (second_tx, mut second_rx) = mpsc::channel(8);
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: first_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 2,
+                sender: second_tx,
+                character: test_character(2, "second"),
+            })
+            .await
+            .unwrap();
+        let _ = first_rx.recv().await;
+        let _ = second_rx.recv().await;
+
+        field_tx
+            .send(FieldMessage::JobChange { from: 1, job: 100 })
+            .await
+            .unwrap();
+        match second_rx.recv().await.unwrap() {
+            // Opcode, then the character id, then the effect.
+            ServerMessage::SendPacket(packet) => {
+                assert_eq!(packet.bytes[6], EFFECT_JOB_CHANGE);
+            }
+            other => panic!("expected job change effect, got {other:?}"),
+        }
+        assert!(timeout(Duration::from_millis(50), first_rx.recv())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn damage_is_shown_to_other_occupants_only() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 1_000_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            Vec::new(),
+        );
+        tokio::spawn(field.run());
+
+        let (first_tx, mut first_rx) = mpsc::channel(8);
+        let (second_tx, mut second_rx) = mpsc::channel(8);
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: first_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 2,
+                sender: second_tx,
+                character: test_character(2, "second"),
+            })
+            .await
+            .unwrap();
+        let _ = first_rx.recv().await;
+        let _ = second_rx.recv().await;
+
+        field_tx
+            .send(FieldMessage::Damage {
+                from: 1,
+                packet: build_damage_player(1, -1, 25, 100100, 0).unwrap(),
+            })
+            .await
+            .unwrap();
+        assert_opcode(second_rx.recv().await.unwrap(), SendOpcode::DamagePlayer);
+        assert!(timeout(Duration::from_millis(50), first_rx.recv())
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn leave_broadcasts_remove_packet() {
         let (field_tx, field_rx) = mpsc::channel(8);
@@ -308,6 +1387,7 @@ mod tests {
             },
             field_rx,
             Vec::new(),
+            Vec::new(),
         );
         tokio::spawn(field.run());
 
@@ -354,6 +1434,7 @@ mod tests {
             },
             field_rx,
             Vec::new(),
+            Vec::new(),
         );
         tokio::spawn(field.run());
 
@@ -424,6 +1505,7 @@ mod tests {
                 rx0: 80,
                 rx1: 180,
             }],
+            Vec::new(),
         );
         tokio::spawn(field.run());
 
@@ -465,4 +1547,648 @@ mod tests {
 
         assert_opcode(second_msg, SendOpcode::SpawnPlayer);
     }
+
+    fn test_mob_spawn(respawn_delay: Duration) -> FieldMapEntityMob {
+        FieldMapEntityMob {
+            mob_id: 100100,
+            x: -120,
+            y: 275,
+            foothold: 12,
+            level: 1,
+            max_hp: 100,
+            exp: 10,
+            drops: Vec::new(),
+            respawn_delay,
+        }
+    }
+
+    fn read_spawned_mob(msg: ServerMessage) -> (i32, i32) {
+        match msg {
+            ServerMessage::SendPacket(packet) => {
+                let mut cursor = Cursor::new(&packet.bytes[..]);
+                assert_eq!(
+                    cursor.read_short().expect("opcode"),
+                    SendOpcode::SpawnMonster as i16
+                );
+                let object_id = cursor.read_int().expect("object id");
+                cursor.read_byte().expect("control");
+                (object_id, cursor.read_int().expect("mob id"))
+            }
+            other => panic!("expected packet, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn joining_player_receives_live_mobs() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 104_040_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            vec![test_mob_spawn(Duration::from_secs(7))],
+        );
+        tokio::spawn(field.run());
+
+        let (client_tx, mut client_rx) = mpsc::channel(8);
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: client_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+
+        let (object_id, mob_id) = read_spawned_mob(client_rx.recv().await.unwrap());
+        assert_eq!(object_id, FIELD_OBJECT_ID_BASE);
+        assert_eq!(mob_id, 100100);
+    }
+
+    #[tokio::test]
+    async fn killed_mob_is_removed_and_respawns_after_delay() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 104_040_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            vec![test_mob_spawn(Duration::from_millis(50))],
+        );
+        tokio::spawn(field.run());
+
+        let (client_tx, mut client_rx) = mpsc::channel(8);
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: client_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        let (object_id, _) = read_spawned_mob(client_rx.recv().await.unwrap());
+        assert_opcode(
+            client_rx.recv().await.unwrap(),
+            SendOpcode::SpawnMonsterControl,
+        );
+
+        field_tx
+            .send(FieldMessage::KillMob { object_id })
+            .await
+            .unwrap();
+
+        match client_rx.recv().await.unwrap() {
+            ServerMessage::SendPacket(packet) => {
+                let mut cursor = Cursor::new(&packet.bytes[..]);
+                assert_eq!(
+                    cursor.read_short().expect("opcode"),
+                    SendOpcode::KillMonster as i16
+                );
+                assert_eq!(cursor.read_int().expect("object id"), object_id);
+            }
+            other => panic!("expected packet, got {other:?}"),
+        }
+
+        let (respawned_id, mob_id) = read_spawned_mob(client_rx.recv().await.unwrap());
+        assert_ne!(respawned_id, object_id);
+        assert_eq!(mob_id, 100100);
+    }
+
+    fn read_controlled_mob(msg: ServerMessage) -> i32 {
+        match msg {
+            ServerMessage::SendPacket(packet) => {
+                let mut cursor = Cursor::new(&packet.bytes[..]);
+                assert_eq!(
+                    cursor.read_short().expect("opcode"),
+                    SendOpcode::SpawnMonsterControl as i16
+                );
+                assert_eq!(cursor.read_byte().expect("control mode"), 1);
+                cursor.read_int().expect("object id")
+            }
+            other => panic!("expected packet, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn mob_control_moves_to_remaining_occupant_when_controller_leaves() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 104_040_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            vec![test_mob_spawn(Duration::from_secs(7))],
+        );
+        tokio::spawn(field.run());
+
+        let (first_tx, mut first_rx) = mpsc::channel(8);
+        let (second_tx, mut second_rx) = mpsc::channel(8);
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: first_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        let (object_id, _) = read_spawned_mob(first_rx.recv().await.unwrap());
+        assert_eq!(
+            read_controlled_mob(first_rx.recv().await.unwrap()),
+            object_id
+        );
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 2,
+                sender: second_tx,
+                character: test_character(2, "second"),
+            })
+            .await
+            .unwrap();
+        read_spawned_mob(second_rx.recv().await.unwrap());
+        assert_opcode(second_rx.recv().await.unwrap(), SendOpcode::SpawnPlayer);
+
+        field_tx
+            .send(FieldMessage::Leave { client_id: 1 })
+            .await
+            .unwrap();
+
+        assert_opcode(
+            second_rx.recv().await.unwrap(),
+            SendOpcode::RemovePlayerFromMap,
+        );
+        assert_eq!(
+            read_controlled_mob(second_rx.recv().await.unwrap()),
+            object_id
+        );
+    }
+
+    #[tokio::test]
+    async fn mob_movement_is_relayed_only_from_controller() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 104_040_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            vec![test_mob_spawn(Duration::from_secs(7))],
+        );
+        tokio::spawn(field.run());
+
+        let (first_tx, mut first_rx) = mpsc::channel(8);
+        let (second_tx, mut second_rx) = mpsc::channel(8);
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: first_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        let (object_id, _) = read_spawned_mob(first_rx.recv().await.unwrap());
+        read_controlled_mob(first_rx.recv().await.unwrap());
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 2,
+                sender: second_tx,
+                character: test_character(2, "second"),
+            })
+            .await
+            .unwrap();
+        let _ = first_rx.recv().await;
+        let _ = second_rx.recv().await;
+        let _ = second_rx.recv().await;
+
+        let movement_bytes = vec![1, 0, 10, 0, 20, 0];
+        field_tx
+            .send(FieldMessage::MoveMob {
+                from: 2,
+                object_id,
+                packet: build_move_mob(object_id, 0, &[0; 5], 10, 20, &movement_bytes).unwrap(),
+                response_packet: build_move_mob_response(object_id, 1, 0).unwrap(),
+                movement_bytes: movement_bytes.clone(),
+            })
+            .await
+            .unwrap();
+        field_tx
+            .send(FieldMessage::MoveMob {
+                from: 1,
+                object_id,
+                packet: build_move_mob(object_id, 0, &[0; 5], 10, 20, &movement_bytes).unwrap(),
+                response_packet: build_move_mob_response(object_id, 2, 0).unwrap(),
+                movement_bytes,
+            })
+            .await
+            .unwrap();
+
+        assert_opcode(
+            first_rx.recv().await.unwrap(),
+            SendOpcode::MoveMonsterResponse,
+        );
+        assert_opcode(second_rx.recv().await.unwrap(), SendOpcode::MoveMonster);
+        assert!(timeout(Duration::from_millis(50), first_rx.recv())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn empty_mob_movement_is_answered_but_not_relayed() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 104_040_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            vec![test_mob_spawn(Duration::from_secs(7))],
+        );
+        tokio::spawn(field.run());
+
+        let (first_tx, mut first_rx) = mpsc::channel(8);
+        let (second_tx, mut second_rx) = mpsc::channel(8);
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: first_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        let (object_id, _) = read_spawned_mob(first_rx.recv().await.unwrap());
+        read_controlled_mob(first_rx.recv().await.unwrap());
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 2,
+                sender: second_tx,
+                character: test_character(2, "second"),
+            })
+            .await
+            .unwrap();
+        let _ = first_rx.recv().await;
+        let _ = second_rx.recv().await;
+        let _ = second_rx.recv().await;
+
+        field_tx
+            .send(FieldMessage::MoveMob {
+                from: 1,
+                object_id,
+                packet: build_move_mob(object_id, 0, &[0; 5], 10, 20, &[]).unwrap(),
+                response_packet: build_move_mob_response(object_id, 1, 0).unwrap(),
+                movement_bytes: Vec::new(),
+            })
+            .await
+            .unwrap();
+
+        assert_opcode(
+            first_rx.recv().await.unwrap(),
+            SendOpcode::MoveMonsterResponse,
+        );
+        assert!(timeout(Duration::from_millis(50), second_rx.recv())
+            .await
+            .is_err());
+    }
+
+    fn attack_message(from: i32, object_id: i32, damage: i32) -> FieldMessage {
+        let damage_lines = vec![DamageLine {
+            object_id,
+            damage: vec![damage],
+        }];
+        let packet = build_close_range_attack(
+            from,
+            &AttackInfo {
+                hit_count: 1,
+                skill_id: 0,
+                skill_level: 0,
+                display: 0,
+                direction: 0,
+                stance: 0,
+                speed: 0,
+                projectile: 0,
+                charge: 0,
+                damage_lines: damage_lines.clone(),
+            },
+        )
+        .unwrap();
+        FieldMessage::Attack {
+            from,
+            packet,
+            damage_lines,
+        }
+    }
+
+    fn assert_gain_exp(msg: ServerMessage, expected_amount: i32, expected_last_hit: bool) {
+        match msg {
+            ServerMessage::GainExp { amount, last_hit } => {
+                assert_eq!(amount, expected_amount);
+                assert_eq!(last_hit, expected_last_hit);
+            }
+            other => panic!("expected EXP credit, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn attacks_damage_mobs_and_split_exp_on_death() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 104_040_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            vec![test_mob_spawn(Duration::from_secs(7))],
+        );
+        tokio::spawn(field.run());
+
+        let (first_tx, mut first_rx) = mpsc::channel(8);
+        let (second_tx, mut second_rx) = mpsc::channel(8);
+
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: first_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        let (object_id, _) = read_spawned_mob(first_rx.recv().await.unwrap());
+        let _ = first_rx.recv().await;
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 2,
+                sender: second_tx,
+                character: test_character(2, "second"),
+            })
+            .await
+            .unwrap();
+        let _ = first_rx.recv().await;
+        let _ = second_rx.recv().await;
+        let _ = second_rx.recv().await;
+
+        field_tx
+            .send(attack_message(1, object_id, 40))
+            .await
+            .unwrap();
+        assert_opcode(
+            second_rx.recv().await.unwrap(),
+            SendOpcode::CloseRangeAttack,
+        );
+        match first_rx.recv().await.unwrap() {
+            ServerMessage::SendPacket(packet) => {
+                let mut cursor = Cursor::new(&packet.bytes[..]);
+                assert_eq!(
+                    cursor.read_short().expect("opcode"),
+                    SendOpcode::ShowMonsterHp as i16
+                );
+                assert_eq!(cursor.read_int().expect("object id"), object_id);
+                assert_eq!(cursor.read_byte().expect("hp percent"), 60);
+            }
+            other => panic!("expected packet, got {other:?}"),
+        }
+
+        // Overkill damage only counts up to the mob's remaining HP.
+        field_tx
+            .send(attack_message(2, object_id, 500))
+            .await
+            .unwrap();
+        assert_opcode(first_rx.recv().await.unwrap(), SendOpcode::CloseRangeAttack);
+        assert_opcode(first_rx.recv().await.unwrap(), SendOpcode::KillMonster);
+        assert_opcode(second_rx.recv().await.unwrap(), SendOpcode::KillMonster);
+        assert_gain_exp(first_rx.recv().await.unwrap(), 4, false);
+        assert_gain_exp(second_rx.recv().await.unwrap(), 6, true);
+    }
+
+    /// The next EXP credit sent to a client, skipping packets.
+    async fn next_gain_exp(rx: &mut mpsc::Receiver<ServerMessage>) -> Option<(i32, bool)> {
+        timeout(Duration::from_millis(100), async {
+            loop {
+                match rx.recv().await? {
+                    ServerMessage::GainExp { amount, last_hit } => return Some((amount, last_hit)),
+                    _ => continue,
+                }
+            }
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    #[tokio::test]
+    async fn party_members_in_level_range_share_the_killers_exp() {
+        let (field_tx, field_rx) = mpsc::channel(16);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 104_040_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            vec![FieldMapEntityMob {
+                level: 12,
+                exp: 40,
+                ..test_mob_spawn(Duration::from_secs(7))
+            }],
+        );
+        tokio::spawn(field.run());
+
+        let mut receivers = Vec::new();
+        for (id, level) in [(1, 30), (2, 10), (3, 1)] {
+            let (tx, rx) = mpsc::channel(16);
+            let mut character = test_character(id, &format!("member{}", id));
+            character.level = level;
+            field_tx
+                .send(FieldMessage::Join {
+                    client_id: id,
+                    sender: tx,
+                    character,
+                })
+                .await
+                .unwrap();
+            field_tx
+                .send(FieldMessage::PartyChanged {
+                    from: id,
+                    party_id: Some(7),
+                })
+                .await
+                .unwrap();
+            receivers.push(rx);
+        }
+        let (object_id, _) = read_spawned_mob(receivers[0].recv().await.unwrap());
+
+        field_tx
+            .send(attack_message(1, object_id, 100))
+            .await
+            .unwrap();
+        assert_eq!(next_gain_exp(&mut receivers[0]).await, Some((30, true)));
+        assert_eq!(next_gain_exp(&mut receivers[1]).await, Some((10, false)));
+        // Too far below the mob, and it never attacked.
+        assert_eq!(next_gain_exp(&mut receivers[2]).await, None);
+    }
+
+    #[tokio::test]
+    async fn killed_mobs_drop_loot_for_the_top_damager() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 104_040_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            vec![FieldMapEntityMob {
+                x: 240,
+                y: 190,
+                drops: vec![MobDrop {
+                    item_id: game_data::MESO_DROP_ITEM_ID,
+                    min_quantity: 5,
+                    max_quantity: 5,
+                    chance: DROP_CHANCE_SCALE,
+                }],
+                ..test_mob_spawn(Duration::from_secs(7))
+            }],
+        );
+        tokio::spawn(field.run());
+
+        let (client_tx, mut client_rx) = mpsc::channel(8);
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: client_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        let (object_id, mob_id) = read_spawned_mob(client_rx.recv().await.unwrap());
+        let _ = client_rx.recv().await;
+
+        field_tx
+            .send(attack_message(1, object_id, 100))
+            .await
+            .unwrap();
+        assert_opcode(client_rx.recv().await.unwrap(), SendOpcode::KillMonster);
+        assert_gain_exp(client_rx.recv().await.unwrap(), 10, true);
+        match client_rx.recv().await.unwrap() {
+            ServerMessage::MobKilled { mob_id: killed } => assert_eq!(killed, mob_id),
+            other => panic!("expected kill credit, got {other:?}"),
+        }
+        let drop_id = match client_rx.recv().await.unwrap() {
+            ServerMessage::SendPacket(packet) => {
+                let mut cursor = Cursor::new(&packet.bytes[..]);
+                assert_eq!(
+                    cursor.read_short().expect("opcode"),
+                    SendOpcode::DropItemFromMapObject as i16
+                );
+                cursor.read_byte().expect("mode");
+                let drop_id = cursor.read_int().expect("object id");
+                assert_eq!(cursor.read_byte().expect("meso"), 1);
+                assert_eq!(cursor.read_int().expect("amount"), 5);
+                assert_eq!(cursor.read_int().expect("owner"), 1);
+                drop_id
+            }
+            other => panic!("expected packet, got {other:?}"),
+        };
+
+        field_tx
+            .send(FieldMessage::Pickup {
+                from: 1,
+                object_id: drop_id,
+                space: InventorySpace::default(),
+            })
+            .await
+            .unwrap();
+        assert_opcode(
+            client_rx.recv().await.unwrap(),
+            SendOpcode::RemoveItemFromMap,
+        );
+        match client_rx.recv().await.unwrap() {
+            ServerMessage::GainMeso { amount } => assert_eq!(amount, 5),
+            other => panic!("expected meso credit, got {other:?}"),
+        }
+
+        // A second pickup of the same drop is only answered with an unlock.
+        field_tx
+            .send(FieldMessage::Pickup {
+                from: 1,
+                object_id: drop_id,
+                space: InventorySpace::default(),
+            })
+            .await
+            .unwrap();
+        assert_opcode(client_rx.recv().await.unwrap(), SendOpcode::StatChange);
+    }
+
+    #[tokio::test]
+    async fn returned_items_drop_again_at_the_occupant() {
+        let (field_tx, field_rx) = mpsc::channel(8);
+        let field = FieldActor::new(
+            FieldKey {
+                channel_id: 0,
+                map_id: 1_000_000,
+                instance_id: 0,
+            },
+            field_rx,
+            Vec::new(),
+            Vec::new(),
+        );
+        tokio::spawn(field.run());
+
+        let (client_tx, mut client_rx) = mpsc::channel(8);
+        field_tx
+            .send(FieldMessage::Join {
+                client_id: 1,
+                sender: client_tx,
+                character: test_character(1, "first"),
+            })
+            .await
+            .unwrap();
+        field_tx
+            .send(FieldMessage::ReturnItem {
+                from: 1,
+                item_id: 2000000,
+                quantity: 3,
+            })
+            .await
+            .unwrap();
+
+        match client_rx.recv().await.unwrap() {
+            ServerMessage::SendPacket(packet) => {
+                let mut cursor = Cursor::new(&packet.bytes[..]);
+                assert_eq!(
+                    cursor.read_short().expect("opcode"),
+                    SendOpcode::DropItemFromMapObject as i16
+                );
+                cursor.read_byte().expect("mode");
+                cursor.read_int().expect("object id");
+                assert_eq!(cursor.read_byte().expect("meso"), 0);
+                assert_eq!(cursor.read_int().expect("item id"), 2000000);
+                assert_eq!(cursor.read_int().expect("owner"), 1);
+                cursor.read_byte().expect("pickup type");
+                assert_eq!(cursor.read_short().expect("x"), 240);
+                assert_eq!(cursor.read_short().expect("y"), 190);
+            }
+            other => panic!("expected packet, got {other:?}"),
+        }
+    }
 }
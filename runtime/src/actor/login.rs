@@ -5,6 +5,8 @@ use db::session::{NewSession, SessionState, SessionWrapper};
 use net::get_handler;
 use net::listener::ServerType;
 use net::packet::build;
+use net::skills::Cooldowns;
+use net::stats::RegenClock;
 use packet::Packet;
 use rand::{thread_rng, Rng};
 use std::net::SocketAddr;
@@ -100,6 +102,13 @@ impl LoginClientActor {
             let mut ctx = HandlerContext {
                 client_id: 0, // Login server doesn't use client_id
                 session: &mut session,
+                conversation: &mut None,
+                shop: &mut None,
+                storage: &mut None,
+                party_id: None,
+                buff_skills: Vec::new(),
+                regen: &mut RegenClock::default(),
+                cooldowns: &mut Cooldowns::default(),
             };
             let result = handler.handle(&mut packet, &mut ctx);
             (result, session)
@@ -205,7 +214,21 @@ impl LoginClientActor {
                     // Login server never handles in-world whispers.
                     warn!("Whisper action ignored in login server");
                 }
-                HandlerAction::FieldChat { .. } | HandlerAction::FieldMove { .. } => {
+                HandlerAction::Party(_) => {
+                    warn!("Party action ignored in login server");
+                }
+                HandlerAction::FieldChat { .. }
+                | HandlerAction::FieldMove { .. }
+                | HandlerAction::FieldMobMove { .. }
+                | HandlerAction::FieldAttack { .. }
+                | HandlerAction::FieldPickup { .. }
+                | HandlerAction::FieldUpdateLook { .. }
+                | HandlerAction::FieldDamage { .. }
+                | HandlerAction::FieldLevelUp { .. }
+                | HandlerAction::FieldJobChange { .. }
+                | HandlerAction::GiveBuff { .. }
+                | HandlerAction::CancelBuff { .. }
+                | HandlerAction::Trade(_) => {
                     warn!("Field action ignored in login server");
                 }
                 HandlerAction::MapChanged { .. } => {
@@ -1,16 +1,40 @@
 use crate::actor::ChannelActor;
+use crate::buffs::BuffRegistry;
 use crate::handler::{BroadcastScope, ClientId};
 use crate::message::{ChannelMessage, ClientEvent, RuntimeLocation, ServerMessage};
+use crate::party::{PartyOutcome, PartyRegistry};
+use net::packet::build::world::party::build_party_member_hp;
+use net::party::{PartyAction, PartyMember};
 use std::collections::HashMap;
 use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
 use tracing::{info, warn};
 
+/// How long buffs are kept for a character changing channel. A client that
+/// hasn't reconnected by then isn't coming back for them.
+const MIGRATION_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Registry entry for a connected client.
 struct ClientEntry {
     sender: mpsc::Sender<ServerMessage>,
     location: RuntimeLocation,
     name: String,
     character: crate::message::FieldCharacter,
+    /// The last HP and max HP reported for party HP bars
+    hp: Option<(i32, i32)>,
+}
+
+impl ClientEntry {
+    fn party_member(&self) -> PartyMember {
+        PartyMember {
+            id: self.character.id,
+            name: self.name.clone(),
+            job: self.character.job,
+            level: self.character.level,
+            channel_id: Some(self.location.channel_id),
+            map_id: self.location.map_id,
+        }
+    }
 }
 
 struct ChannelHandle {
@@ -27,6 +51,11 @@ pub struct WorldServerActor {
     channels: HashMap<u8, ChannelHandle>,
     /// Character name to client ID for directed routing
     names: HashMap<String, ClientId>,
+    /// Buffs of characters that are changing channel, until they reconnect
+    /// or the deadline with them passes
+    migrating_buffs: HashMap<ClientId, (Instant, BuffRegistry)>,
+    /// Parties of the world, spanning all of its channels
+    parties: PartyRegistry,
 }
 
 impl WorldServerActor {
@@ -36,6 +65,8 @@ impl WorldServerActor {
             clients: HashMap::new(),
             channels: HashMap::new(),
             names: HashMap::new(),
+            migrating_buffs: HashMap::new(),
+            parties: PartyRegistry::default(),
         }
     }
 
@@ -58,11 +89,15 @@ impl WorldServerActor {
                 character,
                 location,
             } => {
-                self.register_client(client_id, sender, character, location).await;
+                self.register_client(client_id, sender, character, location)
+                    .await;
             }
             ClientEvent::Disconnected { client_id } => {
                 self.unregister_client(client_id).await;
             }
+            ClientEvent::KeepBuffs { client_id, buffs } => {
+                self.keep_buffs(client_id, buffs, Instant::now());
+            }
             ClientEvent::LocationChanged {
                 client_id,
                 old,
@@ -91,14 +126,11 @@ impl WorldServerActor {
                 self.handle_broadcast(from, scope, packet).await;
             }
             ClientEvent::FieldChat { from, packet } => {
-                self.forward_to_channel(
-                    from,
-                    |location| ChannelMessage::Chat {
-                        client_id: from,
-                        location,
-                        packet,
-                    },
-                )
+                self.forward_to_channel(from, |location| ChannelMessage::Chat {
+                    client_id: from,
+                    location,
+                    packet,
+                })
                 .await;
             }
             ClientEvent::FieldMove {
@@ -106,17 +138,141 @@ impl WorldServerActor {
                 packet,
                 movement_bytes,
             } => {
-                self.forward_to_channel(
-                    from,
-                    |location| ChannelMessage::Move {
-                        client_id: from,
-                        location,
-                        packet,
-                        movement_bytes,
-                    },
-                )
+                self.forward_to_channel(from, |location| ChannelMessage::Move {
+                    client_id: from,
+                    location,
+                    packet,
+                    movement_bytes,
+                })
+                .await;
+            }
+            ClientEvent::FieldMobMove {
+                from,
+                object_id,
+                packet,
+                response_packet,
+                movement_bytes,
+            } => {
+                self.forward_to_channel(from, |location| ChannelMessage::MobMove {
+                    client_id: from,
+                    location,
+                    object_id,
+                    packet,
+                    response_packet,
+                    movement_bytes,
+                })
                 .await;
             }
+            ClientEvent::FieldAttack {
+                from,
+                packet,
+                damage_lines,
+            } => {
+                self.forward_to_channel(from, |location| ChannelMessage::Attack {
+                    client_id: from,
+                    location,
+                    packet,
+                    damage_lines,
+                })
+                .await;
+            }
+            ClientEvent::FieldPickup {
+                from,
+                object_id,
+                space,
+            } => {
+                self.forward_to_channel(from, |location| ChannelMessage::Pickup {
+                    client_id: from,
+                    location,
+                    object_id,
+                    space,
+                })
+                .await;
+            }
+            ClientEvent::FieldReturnItem {
+                from,
+                item_id,
+                quantity,
+            } => {
+                self.forward_to_channel(from, |location| ChannelMessage::ReturnItem {
+                    client_id: from,
+                    location,
+                    item_id,
+                    quantity,
+                })
+                .await;
+            }
+            ClientEvent::FieldUpdateLook { from, equipment } => {
+                // Later map changes join with this copy of the character.
+                if let Some(entry) = self.clients.get_mut(&from) {
+                    entry.character.equipment = equipment.clone();
+                }
+                self.forward_to_channel(from, |location| ChannelMessage::UpdateLook {
+                    client_id: from,
+                    location,
+                    equipment,
+                })
+                .await;
+            }
+            ClientEvent::FieldLevelUp { from, level } => {
+                if let Some(entry) = self.clients.get_mut(&from) {
+                    entry.character.level = level;
+                }
+                self.update_party_member(from, false).await;
+                self.forward_to_channel(from, |location| ChannelMessage::LevelUp {
+                    client_id: from,
+                    location,
+                    level,
+                })
+                .await;
+            }
+            ClientEvent::FieldJobChange { from, job } => {
+                if let Some(entry) = self.clients.get_mut(&from) {
+                    entry.character.job = job;
+                }
+                self.update_party_member(from, false).await;
+                self.forward_to_channel(from, |location| ChannelMessage::JobChange {
+                    client_id: from,
+                    location,
+                    job,
+                })
+                .await;
+            }
+            ClientEvent::FieldDamage { from, packet } => {
+                self.forward_to_channel(from, |location| ChannelMessage::Damage {
+                    client_id: from,
+                    location,
+                    packet,
+                })
+                .await;
+            }
+            ClientEvent::FieldBuff { from, packet } => {
+                self.forward_to_channel(from, |location| ChannelMessage::Buff {
+                    client_id: from,
+                    location,
+                    packet,
+                })
+                .await;
+            }
+            ClientEvent::FieldTrade {
+                from,
+                character,
+                action,
+            } => {
+                self.forward_to_channel(from, |location| ChannelMessage::Trade {
+                    client_id: from,
+                    location,
+                    character,
+                    action,
+                })
+                .await;
+            }
+            ClientEvent::Party { from, action } => {
+                self.handle_party_action(from, action).await;
+            }
+            ClientEvent::PartyMemberHp { from, hp, max_hp } => {
+                self.handle_party_member_hp(from, hp, max_hp).await;
+            }
             ClientEvent::Whisper {
                 from,
                 target_name,
@@ -136,14 +292,33 @@ impl WorldServerActor {
         }
     }
 
+    /// Hold on to the buffs of a character changing channel. Buffs whose
+    /// character never reconnected are dropped.
+    fn keep_buffs(&mut self, client_id: ClientId, buffs: BuffRegistry, now: Instant) {
+        self.migrating_buffs
+            .retain(|_, (deadline, _)| *deadline > now);
+        self.migrating_buffs
+            .insert(client_id, (now + MIGRATION_TIMEOUT, buffs));
+    }
+
+    /// The buffs a character brought over a channel change, unless it took
+    /// too long to reconnect.
+    fn take_migrating_buffs(&mut self, client_id: ClientId, now: Instant) -> Option<BuffRegistry> {
+        self.migrating_buffs
+            .remove(&client_id)
+            .filter(|(deadline, _)| *deadline > now)
+            .map(|(_, buffs)| buffs)
+    }
+
     async fn register_client(
         &mut self,
         client_id: ClientId,
         sender: mpsc::Sender<ServerMessage>,
-        character: crate::message::FieldCharacter,
+        mut character: crate::message::FieldCharacter,
         location: RuntimeLocation,
     ) {
         let character_name = character.name.clone();
+        character.party_id = self.parties.party_of(client_id).map(|party| party.id);
         info!(client_id, location = ?location, character_name, "Client connected");
 
         self.names.insert(character_name.clone(), client_id);
@@ -154,9 +329,22 @@ impl WorldServerActor {
                 location,
                 name: character_name,
                 character: character.clone(),
+                hp: None,
             },
         );
 
+        // Buffs carried over a channel change. The client shows them to its
+        // new field, which it has joined by the time it reports back.
+        if let Some(buffs) = self.take_migrating_buffs(client_id, Instant::now()) {
+            if sender
+                .send(ServerMessage::RestoreBuffs { buffs })
+                .await
+                .is_err()
+            {
+                warn!(client_id, "Failed to restore buffs after channel change");
+            }
+        }
+
         let channel_sender = self.get_or_create_channel(location.channel_id);
         if channel_sender
             .send(ChannelMessage::JoinClient {
@@ -170,12 +358,17 @@ impl WorldServerActor {
         {
             warn!(client_id, location = ?location, "Failed to join client to channel");
         }
+        self.update_party_member(client_id, true).await;
     }
 
     async fn unregister_client(&mut self, client_id: ClientId) {
         if let Some(entry) = self.clients.remove(&client_id) {
             info!(client_id, "Client disconnected");
             self.names.remove(&entry.name);
+            match self.parties.member_offline(client_id) {
+                Ok(outcome) => self.send_party_outcome(outcome).await,
+                Err(e) => warn!(client_id, error = %e, "Failed to show party member going offline"),
+            }
             self.send_to_channel(
                 entry.location.channel_id,
                 ChannelMessage::LeaveClient {
@@ -255,6 +448,8 @@ impl WorldServerActor {
             .await;
         }
 
+        self.update_party_member(client_id, false).await;
+        self.exchange_party_hp(client_id).await;
         info!(client_id, old = ?old, new = ?new, "Client changed location");
     }
 
@@ -270,7 +465,10 @@ impl WorldServerActor {
             if let Some(entry) = self.clients.get(&client_id) {
                 let msg = ServerMessage::SendPacket(packet.clone());
                 if entry.sender.send(msg).await.is_err() {
-                    warn!(client_id, "Failed to send broadcast, client may have disconnected");
+                    warn!(
+                        client_id,
+                        "Failed to send broadcast, client may have disconnected"
+                    );
                 }
             }
         }
@@ -285,7 +483,8 @@ impl WorldServerActor {
         sender_failure_packet: packet::Packet,
     ) {
         let Some(&target_id) = self.names.get(&target_name) else {
-            self.send_packet_to_client(from, sender_failure_packet).await;
+            self.send_packet_to_client(from, sender_failure_packet)
+                .await;
             return;
         };
 
@@ -300,10 +499,147 @@ impl WorldServerActor {
         };
 
         if delivered {
-            self.send_packet_to_client(from, sender_success_packet).await;
+            self.send_packet_to_client(from, sender_success_packet)
+                .await;
         } else {
             warn!(from, target_name, "Failed to deliver whisper to target");
-            self.send_packet_to_client(from, sender_failure_packet).await;
+            self.send_packet_to_client(from, sender_failure_packet)
+                .await;
+        }
+    }
+
+    async fn handle_party_action(&mut self, from: ClientId, action: PartyAction) {
+        let Some(entry) = self.clients.get(&from) else {
+            warn!(from, "Ignoring party action from unregistered client");
+            return;
+        };
+
+        let member = entry.party_member();
+        let (clients, names) = (&self.clients, &self.names);
+        let find = |name: &str| {
+            names
+                .get(name)
+                .and_then(|client_id| clients.get(client_id))
+                .map(ClientEntry::party_member)
+        };
+        match self.parties.handle(member, action, find) {
+            Ok(outcome) => self.send_party_outcome(outcome).await,
+            Err(e) => warn!(from, error = %e, "Failed to run party action"),
+        }
+    }
+
+    /// Show a client's new HP to the members of its party on its map.
+    async fn handle_party_member_hp(&mut self, from: ClientId, hp: i32, max_hp: i32) {
+        let Some(entry) = self.clients.get_mut(&from) else {
+            return;
+        };
+        entry.hp = Some((hp, max_hp));
+
+        let packet = match build_party_member_hp(from, hp, max_hp) {
+            Ok(packet) => packet,
+            Err(e) => {
+                warn!(from, error = %e, "Failed to build party member HP");
+                return;
+            }
+        };
+        for client_id in self.party_members_on_map(from) {
+            self.send_packet_to_client(client_id, packet.clone()).await;
+        }
+    }
+
+    /// Show a client that just arrived on a map, or joined a party, the HP of
+    /// its party members there, and show them its own.
+    async fn exchange_party_hp(&self, client_id: ClientId) {
+        let Some(entry) = self.clients.get(&client_id) else {
+            return;
+        };
+
+        for member_id in self.party_members_on_map(client_id) {
+            let Some(member) = self.clients.get(&member_id) else {
+                continue;
+            };
+            let shown = [
+                (member_id, member.hp, client_id),
+                (client_id, entry.hp, member_id),
+            ];
+            for (shown_id, hp, to) in shown {
+                let Some((hp, max_hp)) = hp else {
+                    continue;
+                };
+                match build_party_member_hp(shown_id, hp, max_hp) {
+                    Ok(packet) => self.send_packet_to_client(to, packet).await,
+                    Err(e) => warn!(client_id, error = %e, "Failed to build party member HP"),
+                }
+            }
+        }
+    }
+
+    /// The other online members of a client's party that are on its map.
+    fn party_members_on_map(&self, client_id: ClientId) -> Vec<ClientId> {
+        let (Some(entry), Some(party)) = (
+            self.clients.get(&client_id),
+            self.parties.party_of(client_id),
+        ) else {
+            return Vec::new();
+        };
+
+        party
+            .online_member_ids()
+            .filter(|&id| id != client_id)
+            .filter(|id| {
+                self.clients
+                    .get(id)
+                    .is_some_and(|member| member.location == entry.location)
+            })
+            .collect()
+    }
+
+    /// Show a client's party its new channel, map, level or job.
+    async fn update_party_member(&mut self, client_id: ClientId, came_online: bool) {
+        let Some(entry) = self.clients.get(&client_id) else {
+            return;
+        };
+
+        match self
+            .parties
+            .update_member(entry.party_member(), came_online)
+        {
+            Ok(outcome) => self.send_party_outcome(outcome).await,
+            Err(e) => warn!(client_id, error = %e, "Failed to update party member"),
+        }
+    }
+
+    /// Send the packets of a party change, and tell members that joined or
+    /// left, and their fields, which party they are in now. Members that
+    /// joined swap HP bars with the party members on their map.
+    async fn send_party_outcome(&mut self, outcome: PartyOutcome) {
+        for (client_id, packet) in outcome.packets {
+            self.send_packet_to_client(client_id, packet).await;
+        }
+
+        for (client_id, party_id) in outcome.memberships {
+            let Some(entry) = self.clients.get_mut(&client_id) else {
+                continue;
+            };
+            entry.character.party_id = party_id;
+            if entry
+                .sender
+                .send(ServerMessage::PartyChanged { party_id })
+                .await
+                .is_err()
+            {
+                warn!(client_id, "Failed to tell client about its party");
+            }
+            self.forward_to_channel(client_id, |location| ChannelMessage::PartyChanged {
+                client_id,
+                location,
+                party_id,
+            })
+            .await;
+
+            if party_id.is_some() {
+                self.exchange_party_hp(client_id).await;
+            }
         }
     }
 
@@ -321,7 +657,10 @@ impl WorldServerActor {
     }
 
     fn get_broadcast_targets(&self, from: ClientId, scope: &BroadcastScope) -> Vec<ClientId> {
-        let sender_channel_id = self.clients.get(&from).map(|entry| entry.location.channel_id);
+        let sender_channel_id = self
+            .clients
+            .get(&from)
+            .map(|entry| entry.location.channel_id);
 
         match scope {
             BroadcastScope::Map(map_id) => self
@@ -354,6 +693,16 @@ impl WorldServerActor {
                 .filter(|&&id| id != from)
                 .copied()
                 .collect(),
+            BroadcastScope::Party(party_id) => self
+                .parties
+                .get(*party_id)
+                .map(|party| party.online_member_ids().collect())
+                .unwrap_or_default(),
+            BroadcastScope::PartyExcludeSelf(party_id) => self
+                .parties
+                .get(*party_id)
+                .map(|party| party.online_member_ids().filter(|&id| id != from).collect())
+                .unwrap_or_default(),
         }
     }
 
@@ -399,8 +748,12 @@ impl WorldServerActor {
             return;
         };
 
-        self.send_to_channel(entry.location.channel_id, build_message(entry.location), client_id)
-            .await;
+        self.send_to_channel(
+            entry.location.channel_id,
+            build_message(entry.location),
+            client_id,
+        )
+        .await;
     }
 }
 
@@ -410,8 +763,9 @@ mod tests {
     use crate::message::FieldCharacter;
     use net::packet::op::SendOpcode;
     use packet::io::read::PktRead;
+    use packet::io::write::PktWrite;
     use std::io::Cursor;
-    use tokio::time::{timeout, Duration};
+    use tokio::time::timeout;
 
     fn test_character(id: i32, name: &str, map_id: i32, x: i16, y: i16) -> FieldCharacter {
         FieldCharacter {
@@ -423,11 +777,13 @@ mod tests {
             hair: 30000,
             skin: 0,
             gender: 0,
+            equipment: Vec::new(),
             channel_id: 0,
             map_id,
             x,
             y,
             stance: 2,
+            party_id: None,
         }
     }
 
@@ -450,7 +806,8 @@ mod tests {
             .read_bytes(2 + 2 + 1 + 2 + 1 + 8 + 4 + 4 + 4 + 43 + 4 + 61 + 2)
             .expect("spawn pre-position payload");
         cursor
-            .read_bytes(1 + 1 + 4 + 1 + 4 + 1 + 4 + 1 + 4 + 1 + 1 + 4 + 4 + 4 + 4 + 4 + 4 + 4)
+            // Look of a character without equipment, then the trailing ints.
+            .read_bytes(1 + 1 + 4 + 1 + 4 + 1 + 1 + 4 + 4 + 4 + 4 + 4 + 4 + 4)
             .expect("spawn look payload");
         let x = cursor.read_short().expect("spawn x");
         let y = cursor.read_short().expect("spawn y");
@@ -506,6 +863,306 @@ mod tests {
         assert_eq!((x, y, stance), (202, 124, 2));
     }
 
+    #[tokio::test]
+    async fn map_change_join_keeps_level_ups() {
+        let (world_tx, world_rx) = mpsc::channel(16);
+        let world = WorldServerActor::new(world_rx);
+        tokio::spawn(world.run());
+
+        let (mover_tx, _mover_rx) = mpsc::channel(16);
+        let (observer_tx, mut observer_rx) = mpsc::channel(16);
+
+        world_tx
+            .send(ClientEvent::Connected {
+                client_id: 1,
+                sender: mover_tx,
+                character: test_character(1, "mover", 100000000, 240, 190),
+                location: location(0, 100000000),
+            })
+            .await
+            .unwrap();
+        world_tx
+            .send(ClientEvent::Connected {
+                client_id: 2,
+                sender: observer_tx,
+                character: test_character(2, "observer", 100000001, 10, 20),
+                location: location(0, 100000001),
+            })
+            .await
+            .unwrap();
+        world_tx
+            .send(ClientEvent::FieldLevelUp { from: 1, level: 30 })
+            .await
+            .unwrap();
+        world_tx
+            .send(ClientEvent::LocationChanged {
+                client_id: 1,
+                old: location(0, 100000000),
+                new: location(0, 100000001),
+                spawn_portal_id: None,
+                spawn_x: None,
+                spawn_y: None,
+                spawn_stance: None,
+            })
+            .await
+            .unwrap();
+
+        match observer_rx.recv().await.expect("observer spawn packet") {
+            // Opcode, then the character id, then the level.
+            ServerMessage::SendPacket(packet) => assert_eq!(packet.bytes[6], 30),
+            other => panic!("expected spawn packet, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn channel_change_hands_buffs_to_the_next_connection() {
+        let (world_tx, world_rx) = mpsc::channel(16);
+        let world = WorldServerActor::new(world_rx);
+        tokio::spawn(world.run());
+
+        let mut buffs = BuffRegistry::default();
+        buffs.give(
+            net::buffs::Buff {
+                skill_id: 2001002,
+                level: 1,
+                duration_ms: 60_000,
+                stats: vec![(net::buffs::BuffStat::Speed, 10)],
+            },
+            tokio::time::Instant::now(),
+        );
+
+        let (old_tx, _old_rx) = mpsc::channel(16);
+        world_tx
+            .send(ClientEvent::Connected {
+                client_id: 1,
+                sender: old_tx,
+                character: test_character(1, "hopper", 100000000, 240, 190),
+                location: location(0, 100000000),
+            })
+            .await
+            .unwrap();
+        world_tx
+            .send(ClientEvent::KeepBuffs {
+                client_id: 1,
+                buffs,
+            })
+            .await
+            .unwrap();
+        world_tx
+            .send(ClientEvent::Disconnected { client_id: 1 })
+            .await
+            .unwrap();
+
+        let (new_tx, mut new_rx) = mpsc::channel(16);
+        let mut character = test_character(1, "hopper", 100000000, 240, 190);
+        character.channel_id = 1;
+        world_tx
+            .send(ClientEvent::Connected {
+                client_id: 1,
+                sender: new_tx,
+                character,
+                location: location(1, 100000000),
+            })
+            .await
+            .unwrap();
+
+        match new_rx.recv().await.expect("restored buffs") {
+            ServerMessage::RestoreBuffs { buffs } => {
+                let skill_ids: Vec<_> = buffs.iter().map(|active| active.buff.skill_id).collect();
+                assert_eq!(skill_ids, vec![2001002]);
+            }
+            other => panic!("expected restored buffs, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn migrating_buffs_are_dropped_after_the_deadline() {
+        let (_world_tx, world_rx) = mpsc::channel(16);
+        let mut world = WorldServerActor::new(world_rx);
+        let now = Instant::now();
+
+        world.keep_buffs(1, BuffRegistry::default(), now);
+        assert!(world
+            .take_migrating_buffs(1, now + MIGRATION_TIMEOUT)
+            .is_none());
+
+        world.keep_buffs(1, BuffRegistry::default(), now);
+        world.keep_buffs(2, BuffRegistry::default(), now + MIGRATION_TIMEOUT);
+        assert!(!world.migrating_buffs.contains_key(&1));
+        assert!(world
+            .take_migrating_buffs(2, now + MIGRATION_TIMEOUT)
+            .is_some());
+    }
+
+    /// Have client 1 invite client 2 into a new party, and wait until both
+    /// are told about it.
+    async fn form_party(
+        world_tx: &mpsc::Sender<ClientEvent>,
+        leader_rx: &mut mpsc::Receiver<ServerMessage>,
+        member_rx: &mut mpsc::Receiver<ServerMessage>,
+    ) -> i32 {
+        world_tx
+            .send(ClientEvent::Party {
+                from: 1,
+                action: PartyAction::Invite {
+                    name: "member".to_string(),
+                },
+            })
+            .await
+            .unwrap();
+        let party_id = loop {
+            if let ServerMessage::PartyChanged { party_id } =
+                leader_rx.recv().await.expect("leader party")
+            {
+                break party_id.expect("party id");
+            }
+        };
+        world_tx
+            .send(ClientEvent::Party {
+                from: 2,
+                action: PartyAction::Accept { party_id },
+            })
+            .await
+            .unwrap();
+        loop {
+            if let ServerMessage::PartyChanged { party_id: joined } =
+                member_rx.recv().await.expect("member party")
+            {
+                assert_eq!(joined, Some(party_id));
+                break party_id;
+            }
+        }
+    }
+
+    /// Wait for a packet with `opcode`, skipping anything else.
+    async fn recv_opcode(
+        rx: &mut mpsc::Receiver<ServerMessage>,
+        opcode: SendOpcode,
+    ) -> Option<packet::Packet> {
+        let opcode = opcode as i16;
+        timeout(Duration::from_millis(200), async {
+            loop {
+                match rx.recv().await? {
+                    ServerMessage::SendPacket(packet) if packet.opcode() == opcode => {
+                        return Some(packet)
+                    }
+                    _ => continue,
+                }
+            }
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    #[tokio::test]
+    async fn party_chat_reaches_members_on_other_channels() {
+        let (world_tx, world_rx) = mpsc::channel(16);
+        let world = WorldServerActor::new(world_rx);
+        tokio::spawn(world.run());
+
+        let (leader_tx, mut leader_rx) = mpsc::channel(16);
+        let (member_tx, mut member_rx) = mpsc::channel(16);
+        let mut member_character = test_character(2, "member", 100000000, 240, 190);
+        member_character.channel_id = 1;
+
+        world_tx
+            .send(ClientEvent::Connected {
+                client_id: 1,
+                sender: leader_tx,
+                character: test_character(1, "leader", 100000000, 240, 190),
+                location: location(0, 100000000),
+            })
+            .await
+            .unwrap();
+        world_tx
+            .send(ClientEvent::Connected {
+                client_id: 2,
+                sender: member_tx,
+                character: member_character,
+                location: location(1, 100000000),
+            })
+            .await
+            .unwrap();
+        let party_id = form_party(&world_tx, &mut leader_rx, &mut member_rx).await;
+
+        let mut chat = packet::Packet::new_empty();
+        chat.write_short(SendOpcode::MultiChat as i16).unwrap();
+        world_tx
+            .send(ClientEvent::Broadcast {
+                from: 1,
+                scope: BroadcastScope::PartyExcludeSelf(party_id),
+                packet: chat,
+            })
+            .await
+            .unwrap();
+        assert!(recv_opcode(&mut member_rx, SendOpcode::MultiChat)
+            .await
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn party_hp_bars_are_shown_on_the_same_map_only() {
+        let (world_tx, world_rx) = mpsc::channel(16);
+        let world = WorldServerActor::new(world_rx);
+        tokio::spawn(world.run());
+
+        let (leader_tx, mut leader_rx) = mpsc::channel(16);
+        let (member_tx, mut member_rx) = mpsc::channel(16);
+        world_tx
+            .send(ClientEvent::Connected {
+                client_id: 1,
+                sender: leader_tx,
+                character: test_character(1, "leader", 100000000, 240, 190),
+                location: location(0, 100000000),
+            })
+            .await
+            .unwrap();
+        world_tx
+            .send(ClientEvent::Connected {
+                client_id: 2,
+                sender: member_tx,
+                character: test_character(2, "member", 100000001, 240, 190),
+                location: location(0, 100000001),
+            })
+            .await
+            .unwrap();
+        form_party(&world_tx, &mut leader_rx, &mut member_rx).await;
+
+        world_tx
+            .send(ClientEvent::PartyMemberHp {
+                from: 2,
+                hp: 40,
+                max_hp: 50,
+            })
+            .await
+            .unwrap();
+        assert!(recv_opcode(&mut leader_rx, SendOpcode::UpdatePartyMemberHp)
+            .await
+            .is_none());
+
+        // Arriving on the leader's map shows the HP last reported.
+        world_tx
+            .send(ClientEvent::LocationChanged {
+                client_id: 2,
+                old: location(0, 100000001),
+                new: location(0, 100000000),
+                spawn_portal_id: None,
+                spawn_x: None,
+                spawn_y: None,
+                spawn_stance: None,
+            })
+            .await
+            .unwrap();
+        let packet = recv_opcode(&mut leader_rx, SendOpcode::UpdatePartyMemberHp)
+            .await
+            .expect("member HP bar");
+        let mut cursor = Cursor::new(&packet.bytes[2..]);
+        assert_eq!(cursor.read_int().expect("character id"), 2);
+        assert_eq!(cursor.read_int().expect("hp"), 40);
+        assert_eq!(cursor.read_int().expect("max hp"), 50);
+    }
+
     #[tokio::test]
     async fn same_map_different_channels_do_not_share_presence() {
         let (world_tx, world_rx) = mpsc::channel(16);
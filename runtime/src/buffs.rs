@@ -0,0 +1,147 @@
+use net::buffs::Buff;
+use tokio::time::{Duration, Instant};
+
+/// A buff running on a character.
+#[derive(Clone, Debug)]
+pub struct ActiveBuff {
+    pub buff: Buff,
+    pub expires_at: Instant,
+}
+
+impl ActiveBuff {
+    pub fn remaining_ms(&self, now: Instant) -> i32 {
+        self.expires_at.saturating_duration_since(now).as_millis() as i32
+    }
+}
+
+/// The buffs running on one character. Giving a buff from a skill that is
+/// already running restarts it.
+#[derive(Clone, Debug, Default)]
+pub struct BuffRegistry {
+    active: Vec<ActiveBuff>,
+}
+
+impl BuffRegistry {
+    pub fn give(&mut self, buff: Buff, now: Instant) -> &ActiveBuff {
+        self.active
+            .retain(|active| active.buff.skill_id != buff.skill_id);
+        let duration = Duration::from_millis(buff.duration_ms.max(0) as u64);
+        self.active.push(ActiveBuff {
+            buff,
+            expires_at: now + duration,
+        });
+        self.active.last().unwrap()
+    }
+
+    pub fn cancel(&mut self, skill_id: i32) -> Option<ActiveBuff> {
+        let index = self
+            .active
+            .iter()
+            .position(|active| active.buff.skill_id == skill_id)?;
+        Some(self.active.remove(index))
+    }
+
+    /// Remove and return the buffs that have run out by `now`.
+    pub fn take_expired(&mut self, now: Instant) -> Vec<ActiveBuff> {
+        let (expired, active) = std::mem::take(&mut self.active)
+            .into_iter()
+            .partition(|active| active.expires_at <= now);
+        self.active = active;
+        expired
+    }
+
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.active.iter().map(|active| active.expires_at).min()
+    }
+
+    /// Running buffs that share a stat with `buff`. Ending `buff` ends the
+    /// stat on the client, so these have to be given again.
+    pub fn overlapping(&self, buff: &Buff) -> Vec<&ActiveBuff> {
+        self.active
+            .iter()
+            .filter(|active| {
+                active
+                    .buff
+                    .stats
+                    .iter()
+                    .any(|(stat, _)| buff.stats.iter().any(|(other, _)| other == stat))
+            })
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ActiveBuff> {
+        self.active.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use net::buffs::BuffStat;
+
+    fn buff(skill_id: i32, duration_ms: i32, stats: Vec<(BuffStat, i16)>) -> Buff {
+        Buff {
+            skill_id,
+            level: 1,
+            duration_ms,
+            stats,
+        }
+    }
+
+    #[test]
+    fn giving_a_running_buff_restarts_it() {
+        let now = Instant::now();
+        let mut buffs = BuffRegistry::default();
+        buffs.give(buff(1, 1_000, vec![(BuffStat::Watk, 5)]), now);
+        buffs.give(buff(1, 5_000, vec![(BuffStat::Watk, 10)]), now);
+
+        assert_eq!(buffs.iter().count(), 1);
+        assert_eq!(buffs.next_expiry(), Some(now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn take_expired_only_removes_buffs_that_ran_out() {
+        let now = Instant::now();
+        let mut buffs = BuffRegistry::default();
+        buffs.give(buff(1, 1_000, vec![(BuffStat::Watk, 5)]), now);
+        buffs.give(buff(2, 3_000, vec![(BuffStat::Speed, 10)]), now);
+
+        let expired = buffs.take_expired(now + Duration::from_secs(2));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].buff.skill_id, 1);
+        assert_eq!(buffs.next_expiry(), Some(now + Duration::from_secs(3)));
+        assert_eq!(
+            buffs
+                .iter()
+                .next()
+                .unwrap()
+                .remaining_ms(now + Duration::from_secs(2)),
+            1_000
+        );
+    }
+
+    #[test]
+    fn cancel_leaves_overlapping_buffs_running() {
+        let now = Instant::now();
+        let mut buffs = BuffRegistry::default();
+        buffs.give(buff(1, 1_000, vec![(BuffStat::Watk, 5)]), now);
+        buffs.give(
+            buff(2, 1_000, vec![(BuffStat::Watk, 10), (BuffStat::Wdef, 10)]),
+            now,
+        );
+        buffs.give(buff(3, 1_000, vec![(BuffStat::Jump, 10)]), now);
+
+        let cancelled = buffs.cancel(1).expect("cancelled buff");
+        assert!(buffs.cancel(1).is_none());
+        let overlapping: Vec<_> = buffs
+            .overlapping(&cancelled.buff)
+            .iter()
+            .map(|active| active.buff.skill_id)
+            .collect();
+        assert_eq!(overlapping, vec![2]);
+    }
+}
@@ -1,9 +1,12 @@
 pub mod actor;
+pub mod buffs;
 pub mod db;
 pub mod error;
 pub mod handler;
 pub mod io;
 pub mod message;
+pub mod party;
+pub mod room;
 
 pub use actor::{ClientActor, LoginServerActor, WorldServerActor};
 pub use db::spawn_db;
@@ -1,5 +1,12 @@
-use net::{BroadcastScope, ClientId};
+use crate::buffs::BuffRegistry;
+use db::character::CharacterWrapper;
+use net::packet::build::world::attack::DamageLine;
+use net::party::PartyAction;
+use net::trade::TradeAction;
+use net::{BroadcastScope, ClientId, InventorySpace};
 use packet::Packet;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct FieldKey {
@@ -35,11 +42,15 @@ pub struct FieldCharacter {
     pub hair: i32,
     pub skin: i32,
     pub gender: i16,
+    /// Worn item ids by equip position.
+    pub equipment: Vec<(i16, i32)>,
     pub channel_id: u8,
     pub map_id: i32,
     pub x: i16,
     pub y: i16,
     pub stance: u8,
+    /// The party the character is in, for sharing EXP in its field.
+    pub party_id: Option<i32>,
 }
 
 impl FieldCharacter {
@@ -52,11 +63,36 @@ impl FieldCharacter {
     }
 }
 
+/// A client's loaded character, shared with its field so a trade can change
+/// both traders at once.
+#[derive(Clone)]
+pub struct SharedCharacter(pub Arc<Mutex<CharacterWrapper>>);
+
+impl fmt::Debug for SharedCharacter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedCharacter").finish_non_exhaustive()
+    }
+}
+
 /// Messages sent TO a client from the server or other clients.
 #[derive(Debug)]
 pub enum ServerMessage {
     /// Send a packet to this client
     SendPacket(Packet),
+    /// Credit EXP earned in the field to this client's character.
+    /// `last_hit` marks the player who landed the killing blow.
+    GainExp { amount: i32, last_hit: bool },
+    /// Count a mob this client's character helped kill towards its quests.
+    MobKilled { mob_id: i32 },
+    /// Credit mesos picked up in the field.
+    GainMeso { amount: i32 },
+    /// Hand an item picked up in the field to this client's inventory.
+    GainItem { item_id: i32, quantity: i16 },
+    /// Carry on the buffs this client's character had before it changed
+    /// channel.
+    RestoreBuffs { buffs: BuffRegistry },
+    /// This client's character joined or left a party.
+    PartyChanged { party_id: Option<i32> },
     /// Forcibly disconnect with reason
     Kick(String),
     /// Server is shutting down
@@ -75,6 +111,11 @@ pub enum ClientEvent {
     },
     /// Client has disconnected
     Disconnected { client_id: ClientId },
+    /// Client is changing channel; hand its buffs to its next connection.
+    KeepBuffs {
+        client_id: ClientId,
+        buffs: BuffRegistry,
+    },
     /// Client changed runtime location
     LocationChanged {
         client_id: ClientId,
@@ -99,6 +140,61 @@ pub enum ClientEvent {
         packet: Packet,
         movement_bytes: Vec<u8>,
     },
+    /// Request to relay movement of a controlled mob.
+    FieldMobMove {
+        from: ClientId,
+        object_id: i32,
+        packet: Packet,
+        response_packet: Packet,
+        movement_bytes: Vec<u8>,
+    },
+    /// Request to broadcast an attack and apply its damage.
+    FieldAttack {
+        from: ClientId,
+        packet: Packet,
+        damage_lines: Vec<DamageLine>,
+    },
+    /// Request to pick up a drop in the client's field.
+    FieldPickup {
+        from: ClientId,
+        object_id: i32,
+        space: InventorySpace,
+    },
+    /// An item picked up in the client's field that its inventory could not
+    /// take, to drop again.
+    FieldReturnItem {
+        from: ClientId,
+        item_id: i32,
+        quantity: i16,
+    },
+    /// Request to show the client's new equipment to its field.
+    FieldUpdateLook {
+        from: ClientId,
+        equipment: Vec<(i16, i32)>,
+    },
+    /// The client's character levelled up; its field should see the effect.
+    FieldLevelUp { from: ClientId, level: i16 },
+    /// The client's character advanced to a new job; its field should see the
+    /// effect.
+    FieldJobChange { from: ClientId, job: i16 },
+    /// Request to show the client taking damage to its field.
+    FieldDamage { from: ClientId, packet: Packet },
+    /// Request to show the start or end of the client's buff to its field.
+    FieldBuff { from: ClientId, packet: Packet },
+    /// A trade window action, run by the client's field.
+    FieldTrade {
+        from: ClientId,
+        character: SharedCharacter,
+        action: TradeAction,
+    },
+    /// A party action, run by the world server.
+    Party { from: ClientId, action: PartyAction },
+    /// The client's character, which is in a party, has new HP.
+    PartyMemberHp {
+        from: ClientId,
+        hp: i32,
+        max_hp: i32,
+    },
     /// Request to deliver a whisper to a named online player.
     Whisper {
         from: ClientId,
@@ -132,6 +228,68 @@ pub enum ChannelMessage {
         packet: Packet,
         movement_bytes: Vec<u8>,
     },
+    MobMove {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        object_id: i32,
+        packet: Packet,
+        response_packet: Packet,
+        movement_bytes: Vec<u8>,
+    },
+    Attack {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        packet: Packet,
+        damage_lines: Vec<DamageLine>,
+    },
+    Pickup {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        object_id: i32,
+        space: InventorySpace,
+    },
+    ReturnItem {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        item_id: i32,
+        quantity: i16,
+    },
+    UpdateLook {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        equipment: Vec<(i16, i32)>,
+    },
+    LevelUp {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        level: i16,
+    },
+    JobChange {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        job: i16,
+    },
+    Damage {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        packet: Packet,
+    },
+    Buff {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        packet: Packet,
+    },
+    Trade {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        character: SharedCharacter,
+        action: TradeAction,
+    },
+    PartyChanged {
+        client_id: ClientId,
+        location: RuntimeLocation,
+        party_id: Option<i32>,
+    },
     TransferWithinChannel {
         client_id: ClientId,
         sender: tokio::sync::mpsc::Sender<ServerMessage>,
@@ -160,4 +318,70 @@ pub enum FieldMessage {
         packet: Packet,
         movement_bytes: Vec<u8>,
     },
+    /// Movement reported by the client controlling a mob.
+    MoveMob {
+        from: ClientId,
+        object_id: i32,
+        packet: Packet,
+        response_packet: Packet,
+        movement_bytes: Vec<u8>,
+    },
+    /// Attack by an occupant; damage is applied to the targeted mobs.
+    Attack {
+        from: ClientId,
+        packet: Packet,
+        damage_lines: Vec<DamageLine>,
+    },
+    /// Pickup request for a drop; `space` is the picker's free inventory.
+    Pickup {
+        from: ClientId,
+        object_id: i32,
+        space: InventorySpace,
+    },
+    /// A picked up item the occupant had no room for; drop it again.
+    ReturnItem {
+        from: ClientId,
+        item_id: i32,
+        quantity: i16,
+    },
+    /// An occupant changed equipment; others should see the new look.
+    UpdateLook {
+        from: ClientId,
+        equipment: Vec<(i16, i32)>,
+    },
+    /// An occupant levelled up; others should see the level-up effect.
+    LevelUp {
+        from: ClientId,
+        level: i16,
+    },
+    /// An occupant advanced to a new job; others should see the effect.
+    JobChange {
+        from: ClientId,
+        job: i16,
+    },
+    /// An occupant took damage; others should see it.
+    Damage {
+        from: ClientId,
+        packet: Packet,
+    },
+    /// An occupant's buff started or ended; others should see it.
+    Buff {
+        from: ClientId,
+        packet: Packet,
+    },
+    /// A trade window action by an occupant.
+    Trade {
+        from: ClientId,
+        character: SharedCharacter,
+        action: TradeAction,
+    },
+    /// An occupant joined or left a party.
+    PartyChanged {
+        from: ClientId,
+        party_id: Option<i32>,
+    },
+    /// Remove a live mob from the field and schedule its respawn.
+    KillMob {
+        object_id: i32,
+    },
 }
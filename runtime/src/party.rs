@@ -0,0 +1,445 @@
+use crate::room::Outgoing;
+use net::error::NetworkError;
+use net::packet::build::world::party::{
+    build_party_created, build_party_disbanded, build_party_invite, build_party_joined,
+    build_party_leader_changed, build_party_left, build_party_message, build_party_message_about,
+    build_party_update, PARTY_MESSAGE_ALREADY_JOINED, PARTY_MESSAGE_BUSY, PARTY_MESSAGE_DENIED,
+    PARTY_MESSAGE_FULL, PARTY_MESSAGE_NOT_FOUND,
+};
+use net::party::{Party, PartyAction, PartyMember};
+use net::ClientId;
+use packet::Packet;
+use std::collections::HashMap;
+
+/// What a change to the parties means for their members.
+#[derive(Debug, Default)]
+pub struct PartyOutcome {
+    pub packets: Outgoing,
+    /// Characters that joined or left a party, with the party they are in now.
+    pub memberships: Vec<(ClientId, Option<i32>)>,
+}
+
+/// The parties of a world. Members keep their place while offline, and a
+/// party only ends when its leader leaves it.
+#[derive(Default)]
+pub struct PartyRegistry {
+    parties: HashMap<i32, Party>,
+    /// The party of every member, online or not.
+    member_of: HashMap<i32, i32>,
+    /// The party each invited character was last invited to.
+    invites: HashMap<i32, i32>,
+    next_id: i32,
+}
+
+impl PartyRegistry {
+    pub fn get(&self, party_id: i32) -> Option<&Party> {
+        self.parties.get(&party_id)
+    }
+
+    pub fn party_of(&self, character_id: i32) -> Option<&Party> {
+        self.member_of
+            .get(&character_id)
+            .and_then(|party_id| self.parties.get(party_id))
+    }
+
+    /// Run a party action by the online character `from`. `find` looks up an
+    /// online character by name.
+    pub fn handle(
+        &mut self,
+        from: PartyMember,
+        action: PartyAction,
+        find: impl Fn(&str) -> Option<PartyMember>,
+    ) -> Result<PartyOutcome, NetworkError> {
+        let mut out = PartyOutcome::default();
+        match action {
+            PartyAction::Create => {
+                if self.member_of.contains_key(&from.id) {
+                    out.packets
+                        .push((from.id, build_party_message(PARTY_MESSAGE_ALREADY_JOINED)?));
+                } else {
+                    self.create(from, &mut out)?;
+                }
+            }
+            PartyAction::Leave => {
+                let Some(party) = self.party_of(from.id) else {
+                    return Ok(out);
+                };
+                let (party_id, leader_id) = (party.id, party.leader_id);
+                if leader_id == from.id {
+                    self.disband(party_id, &mut out)?;
+                } else {
+                    self.remove_member(party_id, from.id, false, &mut out)?;
+                }
+            }
+            PartyAction::Accept { party_id } => {
+                if self.invites.get(&from.id) != Some(&party_id) {
+                    return Ok(out);
+                }
+                self.invites.remove(&from.id);
+                if self.member_of.contains_key(&from.id) {
+                    out.packets
+                        .push((from.id, build_party_message(PARTY_MESSAGE_ALREADY_JOINED)?));
+                    return Ok(out);
+                }
+                let Some(party) = self.parties.get_mut(&party_id) else {
+                    out.packets
+                        .push((from.id, build_party_message(PARTY_MESSAGE_NOT_FOUND)?));
+                    return Ok(out);
+                };
+                let (id, name) = (from.id, from.name.clone());
+                if !party.add(from) {
+                    out.packets
+                        .push((id, build_party_message(PARTY_MESSAGE_FULL)?));
+                    return Ok(out);
+                }
+                self.member_of.insert(id, party_id);
+                out.memberships.push((id, Some(party_id)));
+                let party = &self.parties[&party_id];
+                for_online(party, &mut out.packets, |channel_id| {
+                    build_party_joined(party, &name, channel_id)
+                })?;
+            }
+            PartyAction::Invite { name } => {
+                let Some(target) = find(&name).filter(|target| target.id != from.id) else {
+                    out.packets
+                        .push((from.id, build_party_message(PARTY_MESSAGE_NOT_FOUND)?));
+                    return Ok(out);
+                };
+                let party_id = match self.member_of.get(&from.id) {
+                    Some(&party_id) => party_id,
+                    None => self.create(from.clone(), &mut out)?,
+                };
+                let party = &self.parties[&party_id];
+                if party.leader_id != from.id {
+                    return Ok(out);
+                }
+                let refusal = if party.is_full() {
+                    Some(build_party_message(PARTY_MESSAGE_FULL)?)
+                } else if self.member_of.contains_key(&target.id) {
+                    Some(build_party_message(PARTY_MESSAGE_ALREADY_JOINED)?)
+                } else if self.invites.contains_key(&target.id) {
+                    Some(build_party_message_about(PARTY_MESSAGE_BUSY, &target.name)?)
+                } else {
+                    None
+                };
+                if let Some(packet) = refusal {
+                    out.packets.push((from.id, packet));
+                    return Ok(out);
+                }
+                self.invites.insert(target.id, party_id);
+                out.packets
+                    .push((target.id, build_party_invite(party_id, &from.name)?));
+            }
+            PartyAction::Decline { inviter } => {
+                let Some(party_id) = self.invites.remove(&from.id) else {
+                    return Ok(out);
+                };
+                let leader = find(&inviter)
+                    .filter(|inviter| self.member_of.get(&inviter.id) == Some(&party_id));
+                if let Some(leader) = leader {
+                    out.packets.push((
+                        leader.id,
+                        build_party_message_about(PARTY_MESSAGE_DENIED, &from.name)?,
+                    ));
+                }
+            }
+            PartyAction::Expel { character_id } => {
+                let Some(party) = self.party_of(from.id) else {
+                    return Ok(out);
+                };
+                if party.leader_id != from.id
+                    || character_id == from.id
+                    || party.member(character_id).is_none()
+                {
+                    return Ok(out);
+                }
+                let party_id = party.id;
+                self.remove_member(party_id, character_id, true, &mut out)?;
+            }
+            PartyAction::ChangeLeader { character_id } => {
+                let Some(&party_id) = self.member_of.get(&from.id) else {
+                    return Ok(out);
+                };
+                let party = self
+                    .parties
+                    .get_mut(&party_id)
+                    .expect("member's party exists");
+                let online = party
+                    .member(character_id)
+                    .is_some_and(|member| member.channel_id.is_some());
+                if party.leader_id != from.id || character_id == from.id || !online {
+                    return Ok(out);
+                }
+                party.leader_id = character_id;
+                for_online(party, &mut out.packets, |_| {
+                    build_party_leader_changed(character_id)
+                })?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Show a member's new channel, map, level or job to its party. A member
+    /// coming online is also told which party it is in.
+    pub fn update_member(
+        &mut self,
+        member: PartyMember,
+        came_online: bool,
+    ) -> Result<PartyOutcome, NetworkError> {
+        let mut out = PartyOutcome::default();
+        let Some(&party_id) = self.member_of.get(&member.id) else {
+            return Ok(out);
+        };
+        let party = self
+            .parties
+            .get_mut(&party_id)
+            .expect("member's party exists");
+        if came_online {
+            out.memberships.push((member.id, Some(party_id)));
+        }
+        if let Some(slot) = party.member_mut(member.id) {
+            *slot = member;
+        }
+        let party = &self.parties[&party_id];
+        for_online(party, &mut out.packets, |channel_id| {
+            build_party_update(party, channel_id)
+        })?;
+        Ok(out)
+    }
+
+    /// Keep a member that logged out in its party, shown as offline.
+    pub fn member_offline(&mut self, character_id: i32) -> Result<PartyOutcome, NetworkError> {
+        let mut out = PartyOutcome::default();
+        self.invites.remove(&character_id);
+        let Some(&party_id) = self.member_of.get(&character_id) else {
+            return Ok(out);
+        };
+        let party = self
+            .parties
+            .get_mut(&party_id)
+            .expect("member's party exists");
+        if let Some(member) = party.member_mut(character_id) {
+            member.channel_id = None;
+        }
+        let party = &self.parties[&party_id];
+        for_online(party, &mut out.packets, |channel_id| {
+            build_party_update(party, channel_id)
+        })?;
+        Ok(out)
+    }
+
+    fn create(&mut self, leader: PartyMember, out: &mut PartyOutcome) -> Result<i32, NetworkError> {
+        self.next_id += 1;
+        let party_id = self.next_id;
+        let leader_id = leader.id;
+        self.parties.insert(party_id, Party::new(party_id, leader));
+        self.member_of.insert(leader_id, party_id);
+        self.invites.remove(&leader_id);
+        out.packets
+            .push((leader_id, build_party_created(party_id)?));
+        out.memberships.push((leader_id, Some(party_id)));
+        Ok(party_id)
+    }
+
+    fn disband(&mut self, party_id: i32, out: &mut PartyOutcome) -> Result<(), NetworkError> {
+        let party = self.parties.remove(&party_id).expect("party exists");
+        self.invites.retain(|_, invited_to| *invited_to != party_id);
+        for_online(&party, &mut out.packets, |_| {
+            build_party_disbanded(party_id, party.leader_id)
+        })?;
+        for member in &party.members {
+            self.member_of.remove(&member.id);
+            if member.channel_id.is_some() {
+                out.memberships.push((member.id, None));
+            }
+        }
+        Ok(())
+    }
+
+    /// Take a member out of its party and show everyone in it, the member
+    /// included, that it left or was expelled.
+    fn remove_member(
+        &mut self,
+        party_id: i32,
+        character_id: i32,
+        expelled: bool,
+        out: &mut PartyOutcome,
+    ) -> Result<(), NetworkError> {
+        let party = self.parties.get_mut(&party_id).expect("party exists");
+        let member = party.remove(character_id).expect("member is in the party");
+        self.member_of.remove(&character_id);
+        let party = &self.parties[&party_id];
+        for_online(party, &mut out.packets, |channel_id| {
+            build_party_left(party, member.id, &member.name, expelled, channel_id)
+        })?;
+        if let Some(channel_id) = member.channel_id {
+            out.packets.push((
+                member.id,
+                build_party_left(party, member.id, &member.name, expelled, channel_id)?,
+            ));
+            out.memberships.push((member.id, None));
+        }
+        Ok(())
+    }
+}
+
+/// Build a packet for every online member of `party`, for the channel it is
+/// on.
+fn for_online(
+    party: &Party,
+    out: &mut Outgoing,
+    build: impl Fn(u8) -> Result<Packet, NetworkError>,
+) -> Result<(), NetworkError> {
+    for member in &party.members {
+        if let Some(channel_id) = member.channel_id {
+            out.push((member.id, build(channel_id)?));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use net::packet::op::SendOpcode;
+
+    fn member(id: i32) -> PartyMember {
+        PartyMember {
+            id,
+            name: format!("member{}", id),
+            job: 0,
+            level: 10,
+            channel_id: Some(0),
+            map_id: 100000000,
+        }
+    }
+
+    fn find(name: &str) -> Option<PartyMember> {
+        (1..=7).map(member).find(|member| member.name == name)
+    }
+
+    fn recipients(out: &PartyOutcome) -> Vec<ClientId> {
+        let mut recipients: Vec<_> = out.packets.iter().map(|(to, _)| *to).collect();
+        recipients.sort();
+        recipients
+    }
+
+    /// A party led by 1 with `others` accepted into it.
+    fn party_with(registry: &mut PartyRegistry, others: &[i32]) -> i32 {
+        for &id in others {
+            let invite = PartyAction::Invite {
+                name: format!("member{}", id),
+            };
+            registry.handle(member(1), invite, find).expect("invite");
+            let party_id = registry.party_of(1).expect("party").id;
+            registry
+                .handle(member(id), PartyAction::Accept { party_id }, find)
+                .expect("accept");
+        }
+        registry.party_of(1).expect("party").id
+    }
+
+    #[test]
+    fn inviting_creates_a_party_and_accepting_joins_it() {
+        let mut registry = PartyRegistry::default();
+        let out = registry
+            .handle(
+                member(1),
+                PartyAction::Invite {
+                    name: "member2".to_string(),
+                },
+                find,
+            )
+            .expect("invite");
+        let party_id = registry.party_of(1).expect("party").id;
+        assert_eq!(out.memberships, vec![(1, Some(party_id))]);
+        assert_eq!(recipients(&out), vec![1, 2]);
+
+        let out = registry
+            .handle(member(2), PartyAction::Accept { party_id }, find)
+            .expect("accept");
+        assert_eq!(out.memberships, vec![(2, Some(party_id))]);
+        assert_eq!(recipients(&out), vec![1, 2]);
+        assert_eq!(registry.party_of(2).map(|party| party.id), Some(party_id));
+
+        // Accepting a party it was never invited to does nothing.
+        let out = registry
+            .handle(member(3), PartyAction::Accept { party_id }, find)
+            .expect("uninvited accept");
+        assert!(out.packets.is_empty());
+        assert!(registry.party_of(3).is_none());
+    }
+
+    #[test]
+    fn full_parties_turn_down_invites() {
+        let mut registry = PartyRegistry::default();
+        party_with(&mut registry, &[2, 3, 4, 5, 6]);
+
+        let out = registry
+            .handle(
+                member(1),
+                PartyAction::Invite {
+                    name: "member7".to_string(),
+                },
+                find,
+            )
+            .expect("invite");
+        assert_eq!(recipients(&out), vec![1]);
+        assert_eq!(out.packets[0].1.bytes[2], PARTY_MESSAGE_FULL);
+    }
+
+    #[test]
+    fn leader_leaving_disbands_the_party() {
+        let mut registry = PartyRegistry::default();
+        let party_id = party_with(&mut registry, &[2, 3]);
+        registry.member_offline(3).expect("offline");
+
+        let out = registry
+            .handle(member(1), PartyAction::Leave, find)
+            .expect("leave");
+        assert_eq!(recipients(&out), vec![1, 2]);
+        assert_eq!(out.memberships, vec![(1, None), (2, None)]);
+        assert!(registry.get(party_id).is_none());
+        assert!(registry.party_of(3).is_none());
+    }
+
+    #[test]
+    fn only_the_leader_expels() {
+        let mut registry = PartyRegistry::default();
+        party_with(&mut registry, &[2, 3]);
+
+        let out = registry
+            .handle(member(2), PartyAction::Expel { character_id: 3 }, find)
+            .expect("member expel");
+        assert!(out.packets.is_empty());
+
+        let out = registry
+            .handle(member(1), PartyAction::Expel { character_id: 3 }, find)
+            .expect("leader expel");
+        assert_eq!(recipients(&out), vec![1, 2, 3]);
+        assert_eq!(out.memberships, vec![(3, None)]);
+        assert!(registry.party_of(3).is_none());
+    }
+
+    #[test]
+    fn members_coming_online_are_told_their_party() {
+        let mut registry = PartyRegistry::default();
+        let party_id = party_with(&mut registry, &[2]);
+        registry.member_offline(2).expect("offline");
+
+        let mut back = member(2);
+        back.channel_id = Some(1);
+        let out = registry.update_member(back, true).expect("online");
+        assert_eq!(out.memberships, vec![(2, Some(party_id))]);
+        assert_eq!(recipients(&out), vec![1, 2]);
+        assert!(out
+            .packets
+            .iter()
+            .all(|(_, packet)| packet.opcode() == SendOpcode::PartyOperation as i16));
+        let party = registry.get(party_id).expect("party");
+        assert_eq!(
+            party.member(2).and_then(|member| member.channel_id),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,597 @@
+use crate::message::SharedCharacter;
+use net::error::NetworkError;
+use net::packet::build::world::field::ForeignCharacter;
+use net::packet::build::world::inventory::build_inventory_take;
+use net::packet::build::world::map::build_empty_stat_update;
+use net::packet::build::world::stat::build_meso_update;
+use net::packet::build::world::trade::{
+    build_trade_chat, build_trade_confirmed, build_trade_invite, build_trade_item,
+    build_trade_meso, build_trade_partner_joined, build_trade_result, build_trade_room,
+    TRADE_RESULT_PARTNER_CANCEL, TRADE_RESULT_SUCCESSFUL, TRADE_RESULT_UNSUCCESSFUL,
+};
+use net::trade::{self, TradeAction, TradeOffer};
+use net::ClientId;
+use packet::Packet;
+use tracing::warn;
+
+/// Packets to send, and who to send them to.
+pub type Outgoing = Vec<(ClientId, Packet)>;
+
+#[derive(Clone)]
+struct Trader {
+    client_id: ClientId,
+    character: SharedCharacter,
+    offer: TradeOffer,
+    confirmed: bool,
+}
+
+impl Trader {
+    fn new(client_id: ClientId, character: SharedCharacter) -> Self {
+        Self {
+            client_id,
+            character,
+            offer: TradeOffer::default(),
+            confirmed: false,
+        }
+    }
+
+    /// Close the trader's window, showing its real stacks and mesos again.
+    fn close(&self, number: u8, result: u8, out: &mut Outgoing) -> Result<(), NetworkError> {
+        let chr = self.character.0.lock().unwrap();
+        for packet in self.offer.restore_view(&chr)? {
+            out.push((self.client_id, packet));
+        }
+        out.push((self.client_id, build_trade_result(number, result)?));
+        Ok(())
+    }
+}
+
+/// A trade window. The owner opened it and is number 0 in it; the visitor
+/// joined through an invite and is number 1.
+struct TradeRoom {
+    owner: Trader,
+    visitor: Option<Trader>,
+    invited: Option<ClientId>,
+    /// Both sides confirmed and their exchange is running. The room can't be
+    /// cancelled any more and closes when the exchange is done.
+    exchanging: bool,
+}
+
+impl TradeRoom {
+    fn has(&self, client_id: ClientId) -> bool {
+        self.owner.client_id == client_id
+            || self
+                .visitor
+                .as_ref()
+                .is_some_and(|visitor| visitor.client_id == client_id)
+    }
+
+    /// The trader `client_id` and its partner, if one has joined.
+    fn sides_mut(&mut self, client_id: ClientId) -> (&mut Trader, Option<&mut Trader>) {
+        if self.owner.client_id == client_id {
+            (&mut self.owner, self.visitor.as_mut())
+        } else {
+            let visitor = self.visitor.as_mut().expect("visitor is in the room");
+            (visitor, Some(&mut self.owner))
+        }
+    }
+}
+
+/// The interaction rooms open in one field. Both characters in a room must
+/// stay in the field; leaving it cancels whatever room they were in.
+///
+/// Offered items and mesos are not taken from their owners until both sides
+/// confirm, so a trade that is cancelled, or whose trader disconnects, only
+/// has to show the owners what they still hold. Once both confirm, the room
+/// hands back an `Exchange`, which changes and saves both characters
+/// together, and stays reserved until `finish_exchange`.
+#[derive(Default)]
+pub struct InteractionRooms {
+    trades: Vec<TradeRoom>,
+}
+
+impl InteractionRooms {
+    /// Run a trade window action by `from`. `look` finds the characters in
+    /// the field, to show them in the window. Returns the exchange to run if
+    /// the action completed a trade.
+    pub fn handle_trade(
+        &mut self,
+        from: ClientId,
+        character: SharedCharacter,
+        action: TradeAction,
+        look: impl Fn(ClientId) -> Option<ForeignCharacter>,
+    ) -> Result<(Outgoing, Option<Exchange>), NetworkError> {
+        let mut out = Vec::new();
+        let mut exchange = None;
+        match action {
+            TradeAction::Open => {
+                let Some(me) = look(from) else {
+                    return Ok((out, exchange));
+                };
+                if self.room_of(from).is_some() {
+                    out.push((from, build_empty_stat_update()?));
+                    return Ok((out, exchange));
+                }
+                self.trades.push(TradeRoom {
+                    owner: Trader::new(from, character),
+                    visitor: None,
+                    invited: None,
+                    exchanging: false,
+                });
+                out.push((from, build_trade_room(&me, None)?));
+            }
+            TradeAction::Invite { character_id } => {
+                let available = character_id != from
+                    && self.room_of(character_id).is_none()
+                    && self.invite_to(character_id).is_none()
+                    && look(character_id).is_some();
+                let room = self.trades.iter_mut().find(|room| {
+                    room.owner.client_id == from && room.visitor.is_none() && room.invited.is_none()
+                });
+                match (room, look(from)) {
+                    (Some(room), Some(me)) if available => {
+                        room.invited = Some(character_id);
+                        out.push((character_id, build_trade_invite(&me.name, from)?));
+                    }
+                    _ => warn!(from, character_id, "Trade invite refused"),
+                }
+            }
+            TradeAction::Decline => {
+                if let Some(index) = self.invite_to(from) {
+                    let room = self.trades.remove(index);
+                    room.owner.close(0, TRADE_RESULT_PARTNER_CANCEL, &mut out)?;
+                }
+            }
+            TradeAction::Accept => {
+                let Some(index) = self.invite_to(from) else {
+                    return Ok((out, exchange));
+                };
+                if self.room_of(from).is_some() {
+                    return Ok((out, exchange));
+                }
+                let room = &mut self.trades[index];
+                let (Some(me), Some(owner)) = (look(from), look(room.owner.client_id)) else {
+                    return Ok((out, exchange));
+                };
+                room.invited = None;
+                room.visitor = Some(Trader::new(from, character));
+                out.push((from, build_trade_room(&me, Some(&owner))?));
+                out.push((room.owner.client_id, build_trade_partner_joined(&me)?));
+            }
+            TradeAction::Chat { message } => {
+                let (Some(index), Some(me)) = (self.room_of(from), look(from)) else {
+                    return Ok((out, exchange));
+                };
+                let (_, partner) = self.trades[index].sides_mut(from);
+                out.push((from, build_trade_chat(0, &me.name, &message)?));
+                if let Some(partner) = partner {
+                    out.push((partner.client_id, build_trade_chat(1, &me.name, &message)?));
+                }
+            }
+            TradeAction::Cancel => {
+                if let Some(index) = self.room_of(from) {
+                    self.close_room(index, &mut out)?;
+                }
+            }
+            TradeAction::OfferItem {
+                kind,
+                position,
+                quantity,
+                slot,
+            } => {
+                let Some((trader, partner)) = self.open_sides(from) else {
+                    out.push((from, build_empty_stat_update()?));
+                    return Ok((out, exchange));
+                };
+                let chr = character.0.lock().unwrap();
+                match trader.offer.add_item(&chr, kind, position, quantity, slot) {
+                    Ok((offered, shown)) => {
+                        out.push((from, build_inventory_take(offered.tab, position, shown)?));
+                        out.push((from, build_trade_item(0, slot, &offered.item)?));
+                        out.push((partner, build_trade_item(1, slot, &offered.item)?));
+                    }
+                    Err(error) => {
+                        warn!(from, error = %error, "Trade item offer refused");
+                        out.push((from, build_empty_stat_update()?));
+                    }
+                }
+            }
+            TradeAction::OfferMeso { meso } => {
+                let Some((trader, partner)) = self.open_sides(from) else {
+                    out.push((from, build_empty_stat_update()?));
+                    return Ok((out, exchange));
+                };
+                let chr = character.0.lock().unwrap();
+                match trader.offer.add_meso(&chr, meso) {
+                    Ok(total) => {
+                        out.push((from, build_meso_update(chr.character.meso - total, false)?));
+                        out.push((from, build_trade_meso(0, total)?));
+                        out.push((partner, build_trade_meso(1, total)?));
+                    }
+                    Err(error) => {
+                        warn!(from, error = %error, "Trade meso offer refused");
+                        out.push((from, build_empty_stat_update()?));
+                    }
+                }
+            }
+            TradeAction::Confirm => {
+                let Some(index) = self.room_of(from) else {
+                    return Ok((out, exchange));
+                };
+                let (trader, partner) = self.trades[index].sides_mut(from);
+                let Some(partner) = partner.filter(|_| !trader.confirmed) else {
+                    return Ok((out, exchange));
+                };
+                trader.confirmed = true;
+                out.push((partner.client_id, build_trade_confirmed()?));
+
+                if partner.confirmed {
+                    let room = &mut self.trades[index];
+                    room.exchanging = true;
+                    exchange = Some(Exchange {
+                        owner: room.owner.clone(),
+                        visitor: room.visitor.clone().expect("confirmed room has a visitor"),
+                    });
+                }
+            }
+        }
+        Ok((out, exchange))
+    }
+
+    /// Close the room of the exchange `owner` started, now that it is done.
+    pub fn finish_exchange(&mut self, owner: ClientId) {
+        self.trades
+            .retain(|room| !(room.exchanging && room.owner.client_id == owner));
+    }
+
+    /// Cancel the room `client_id` is in, or the invite it has, as when it
+    /// leaves the field.
+    pub fn leave(&mut self, client_id: ClientId) -> Result<Outgoing, NetworkError> {
+        let mut out = Vec::new();
+        if let Some(index) = self.room_of(client_id) {
+            self.close_room(index, &mut out)?;
+        }
+        for room in &mut self.trades {
+            if room.invited == Some(client_id) {
+                room.invited = None;
+            }
+        }
+        Ok(out)
+    }
+
+    fn room_of(&self, client_id: ClientId) -> Option<usize> {
+        self.trades.iter().position(|room| room.has(client_id))
+    }
+
+    fn invite_to(&self, client_id: ClientId) -> Option<usize> {
+        self.trades
+            .iter()
+            .position(|room| room.invited == Some(client_id))
+    }
+
+    /// The trader `client_id` and its partner's client id, if both are in a
+    /// room and neither has confirmed yet. Offers are final once either side
+    /// confirms.
+    fn open_sides(&mut self, client_id: ClientId) -> Option<(&mut Trader, ClientId)> {
+        let index = self.room_of(client_id)?;
+        let (trader, partner) = self.trades[index].sides_mut(client_id);
+        let partner = partner?;
+        if trader.confirmed || partner.confirmed {
+            return None;
+        }
+        Some((trader, partner.client_id))
+    }
+
+    fn close_room(&mut self, index: usize, out: &mut Outgoing) -> Result<(), NetworkError> {
+        if self.trades[index].exchanging {
+            return Ok(());
+        }
+        let room = self.trades.remove(index);
+        room.owner.close(0, TRADE_RESULT_PARTNER_CANCEL, out)?;
+        if let Some(visitor) = &room.visitor {
+            visitor.close(1, TRADE_RESULT_PARTNER_CANCEL, out)?;
+        }
+        Ok(())
+    }
+}
+
+/// The offers of a room both sides confirmed, to exchange off the field's
+/// task: it locks both characters and saves them.
+pub struct Exchange {
+    owner: Trader,
+    visitor: Trader,
+}
+
+impl Exchange {
+    /// The owner of the room, to hand to `InteractionRooms::finish_exchange`.
+    pub fn owner(&self) -> ClientId {
+        self.owner.client_id
+    }
+
+    /// Exchange the offers. If the exchange is refused or can't be saved,
+    /// nothing changes hands and both windows close as unsuccessful.
+    pub fn run(self) -> Result<Outgoing, NetworkError> {
+        let mut out = Vec::new();
+        complete(self.owner, self.visitor, &mut out)?;
+        Ok(out)
+    }
+}
+
+fn complete(owner: Trader, visitor: Trader, out: &mut Outgoing) -> Result<(), NetworkError> {
+    let outcome = {
+        // Lock in a fixed order so two trades can never wait on each other.
+        let (mut owner_chr, mut visitor_chr) = if owner.client_id < visitor.client_id {
+            let owner_chr = owner.character.0.lock().unwrap();
+            (owner_chr, visitor.character.0.lock().unwrap())
+        } else {
+            let visitor_chr = visitor.character.0.lock().unwrap();
+            (owner.character.0.lock().unwrap(), visitor_chr)
+        };
+        trade::exchange(
+            &mut owner_chr,
+            &owner.offer,
+            &mut visitor_chr,
+            &visitor.offer,
+        )
+    };
+
+    match outcome {
+        Ok(Ok((owner_packets, visitor_packets))) => {
+            out.extend(
+                owner_packets
+                    .into_iter()
+                    .map(|packet| (owner.client_id, packet)),
+            );
+            out.push((
+                owner.client_id,
+                build_trade_result(0, TRADE_RESULT_SUCCESSFUL)?,
+            ));
+            out.extend(
+                visitor_packets
+                    .into_iter()
+                    .map(|packet| (visitor.client_id, packet)),
+            );
+            out.push((
+                visitor.client_id,
+                build_trade_result(1, TRADE_RESULT_SUCCESSFUL)?,
+            ));
+            return Ok(());
+        }
+        Ok(Err(error)) => {
+            warn!(owner = owner.client_id, visitor = visitor.client_id, error = %error, "Trade refused");
+        }
+        Err(error) => {
+            warn!(owner = owner.client_id, visitor = visitor.client_id, error = %error, "Trade failed");
+        }
+    }
+    owner.close(0, TRADE_RESULT_UNSUCCESSFUL, out)?;
+    visitor.close(1, TRADE_RESULT_UNSUCCESSFUL, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::character::{Character, CharacterWrapper};
+    use db::inventory::{Inventory, InventoryItem};
+    use db::keybinding::KeybindSet;
+    use db::quest::QuestLog;
+    use db::skill::SkillBook;
+    use net::packet::op::SendOpcode;
+    use std::sync::{Arc, Mutex};
+
+    const TRADE_OWNER: ClientId = 1;
+    const TRADE_VISITOR: ClientId = 2;
+
+    fn shared_character(id: i32) -> SharedCharacter {
+        let character = Character {
+            id,
+            accountid: id,
+            world: 0,
+            name: format!("trader{}", id),
+            level: 30,
+            exp: 0,
+            stre: 4,
+            dex: 4,
+            luk: 4,
+            int: 4,
+            hp: 50,
+            mp: 5,
+            maxhp: 50,
+            maxmp: 5,
+            ap: 0,
+            fame: 0,
+            meso: 1000,
+            job: 0,
+            face: 20000,
+            hair: 30000,
+            hair_color: 0,
+            skin: 0,
+            gender: 0,
+            created_at: std::time::SystemTime::UNIX_EPOCH,
+            map_id: 100000000,
+            equip_slots: 24,
+            use_slots: 24,
+            setup_slots: 24,
+            etc_slots: 24,
+            cash_slots: 96,
+            sp: 0,
+        };
+        let mut inventory =
+            Inventory::from_item_vec(id, character.inventory_slot_limits(), Vec::new());
+        inventory.add(InventoryItem::new(4000019, 10), 100);
+        SharedCharacter(Arc::new(Mutex::new(CharacterWrapper {
+            key_binds: KeybindSet::from_bind_vec(id, Vec::new()),
+            inventory,
+            skills: SkillBook::from_skill_vec(id, Vec::new()),
+            quests: QuestLog::from_quest_vec(id, Vec::new()),
+            character,
+        })))
+    }
+
+    fn look(client_id: ClientId) -> Option<ForeignCharacter> {
+        (1..=3).contains(&client_id).then(|| ForeignCharacter {
+            id: client_id,
+            name: format!("trader{}", client_id),
+            level: 30,
+            job: 0,
+            face: 20000,
+            hair: 30000,
+            skin: 0,
+            gender: 0,
+            equipment: Vec::new(),
+            map_id: 100000000,
+            x: 0,
+            y: 0,
+            stance: 0,
+        })
+    }
+
+    fn opcodes_to(out: &Outgoing, client_id: ClientId) -> Vec<i16> {
+        out.iter()
+            .filter(|(to, _)| *to == client_id)
+            .map(|(_, packet)| packet.opcode())
+            .collect()
+    }
+
+    /// Open a room between the owner and the visitor.
+    fn open_trade(rooms: &mut InteractionRooms) -> (SharedCharacter, SharedCharacter) {
+        let owner = shared_character(TRADE_OWNER);
+        let visitor = shared_character(TRADE_VISITOR);
+        let actions = [
+            (TRADE_OWNER, &owner, TradeAction::Open),
+            (
+                TRADE_OWNER,
+                &owner,
+                TradeAction::Invite {
+                    character_id: TRADE_VISITOR,
+                },
+            ),
+            (TRADE_VISITOR, &visitor, TradeAction::Accept),
+        ];
+        for (from, character, action) in actions {
+            rooms
+                .handle_trade(from, character.clone(), action, look)
+                .expect("trade action");
+        }
+        (owner, visitor)
+    }
+
+    #[test]
+    fn cancelled_trades_only_restore_the_view_of_offers() {
+        let mut rooms = InteractionRooms::default();
+        let (owner, _visitor) = open_trade(&mut rooms);
+
+        let offer = TradeAction::OfferItem {
+            kind: 4,
+            position: 1,
+            quantity: 4,
+            slot: 1,
+        };
+        let (out, _) = rooms
+            .handle_trade(TRADE_OWNER, owner.clone(), offer, look)
+            .expect("offer item");
+        assert_eq!(
+            opcodes_to(&out, TRADE_OWNER),
+            vec![
+                SendOpcode::ModifyInventory as i16,
+                SendOpcode::PlayerInteraction as i16
+            ]
+        );
+        assert_eq!(
+            opcodes_to(&out, TRADE_VISITOR),
+            vec![SendOpcode::PlayerInteraction as i16]
+        );
+
+        let out = rooms.leave(TRADE_VISITOR).expect("visitor leaves");
+        assert_eq!(
+            opcodes_to(&out, TRADE_OWNER),
+            vec![
+                SendOpcode::ModifyInventory as i16,
+                SendOpcode::PlayerInteraction as i16
+            ]
+        );
+        assert_eq!(
+            owner.0.lock().unwrap().inventory.count(4000019),
+            10,
+            "offered items never left the inventory"
+        );
+        assert!(rooms.trades.is_empty());
+    }
+
+    #[test]
+    fn offers_are_refused_once_either_side_confirmed() {
+        let mut rooms = InteractionRooms::default();
+        let (owner, visitor) = open_trade(&mut rooms);
+
+        let (out, _) = rooms
+            .handle_trade(TRADE_VISITOR, visitor, TradeAction::Confirm, look)
+            .expect("confirm");
+        assert_eq!(
+            opcodes_to(&out, TRADE_OWNER),
+            vec![SendOpcode::PlayerInteraction as i16]
+        );
+
+        let (out, _) = rooms
+            .handle_trade(
+                TRADE_OWNER,
+                owner,
+                TradeAction::OfferMeso { meso: 100 },
+                look,
+            )
+            .expect("offer meso");
+        assert_eq!(
+            opcodes_to(&out, TRADE_OWNER),
+            vec![SendOpcode::StatChange as i16]
+        );
+        assert!(opcodes_to(&out, TRADE_VISITOR).is_empty());
+    }
+
+    #[test]
+    fn confirmed_trades_stay_reserved_until_their_exchange_is_done() {
+        let mut rooms = InteractionRooms::default();
+        let (owner, visitor) = open_trade(&mut rooms);
+
+        let (_, exchange) = rooms
+            .handle_trade(TRADE_OWNER, owner.clone(), TradeAction::Confirm, look)
+            .expect("owner confirms");
+        assert!(exchange.is_none());
+        let (_, exchange) = rooms
+            .handle_trade(TRADE_VISITOR, visitor.clone(), TradeAction::Confirm, look)
+            .expect("visitor confirms");
+        let exchange = exchange.expect("both confirmed");
+        assert_eq!(exchange.owner(), TRADE_OWNER);
+
+        let (out, _) = rooms
+            .handle_trade(TRADE_VISITOR, visitor, TradeAction::Cancel, look)
+            .expect("cancel");
+        assert!(out.is_empty());
+        assert!(rooms.leave(TRADE_OWNER).expect("owner leaves").is_empty());
+        let (out, _) = rooms
+            .handle_trade(TRADE_OWNER, owner, TradeAction::Open, look)
+            .expect("open");
+        assert_eq!(
+            opcodes_to(&out, TRADE_OWNER),
+            vec![SendOpcode::StatChange as i16]
+        );
+
+        rooms.finish_exchange(TRADE_OWNER);
+        assert!(rooms.trades.is_empty());
+    }
+
+    #[test]
+    fn invites_need_a_free_character_in_the_field() {
+        let mut rooms = InteractionRooms::default();
+        let (_owner, _visitor) = open_trade(&mut rooms);
+
+        let third = shared_character(3);
+        rooms
+            .handle_trade(3, third.clone(), TradeAction::Open, look)
+            .expect("open");
+        for character_id in [TRADE_OWNER, 3, 4] {
+            let (out, _) = rooms
+                .handle_trade(3, third.clone(), TradeAction::Invite { character_id }, look)
+                .expect("invite");
+            assert!(out.is_empty(), "invite to {} sent", character_id);
+        }
+    }
+}
@@ -191,6 +191,7 @@ pub fn compare_maps(
 
         maps.push(MapComparison {
             map_id,
+            map_name: None,
             status,
             diffs,
             hard_count: map_hard,
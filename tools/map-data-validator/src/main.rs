@@ -7,7 +7,8 @@ mod report;
 use anyhow::{Context, Result};
 use clap::Parser;
 use compare::CompareOptions;
-use model::ValidationReport;
+use game_data::StringData;
+use model::{MapStatus, ValidationReport};
 use std::collections::BTreeSet;
 use std::path::PathBuf;
 
@@ -33,6 +34,10 @@ struct Args {
     #[arg(long, default_value_t = false)]
     strict: bool,
 
+    /// Optional path to String.nx, used to show map names in the report
+    #[arg(long)]
+    nx_string: Option<PathBuf>,
+
     /// Optional path to write JSON report
     #[arg(long)]
     report: Option<PathBuf>,
@@ -72,7 +77,7 @@ fn run(args: Args) -> Result<ValidationReport> {
             )
         })?;
 
-    let report = compare::compare_maps(
+    let mut report = compare::compare_maps(
         &nx_maps,
         &cosmic_maps,
         &CompareOptions {
@@ -81,6 +86,14 @@ fn run(args: Args) -> Result<ValidationReport> {
         },
     );
 
+    if let Some(path) = args.nx_string.as_ref() {
+        let strings = StringData::load_from_nx(path)
+            .with_context(|| format!("failed to read map names from {}", path.display()))?;
+        for map in &mut report.maps {
+            map.map_name = strings.map_name(map.map_id).map(str::to_string);
+        }
+    }
+
     if let Some(path) = args.report.as_ref() {
         report::write_json(path, &report)
             .with_context(|| format!("failed to write report to {}", path.display()))?;
@@ -109,4 +122,13 @@ fn print_summary(report: &ValidationReport) {
     println!("  soft mismatch count: {}", report.summary.soft_count);
     println!("  warning count: {}", report.summary.warning_count);
     println!("  should fail: {}", report.summary.should_fail);
+
+    for map in &report.maps {
+        if map.status == MapStatus::HardMismatch {
+            match map.map_name.as_deref() {
+                Some(name) => println!("  hard mismatch: {} ({name})", map.map_id),
+                None => println!("  hard mismatch: {}", map.map_id),
+            }
+        }
+    }
 }
@@ -62,6 +62,8 @@ pub enum MapStatus {
 #[derive(Clone, Debug, Serialize)]
 pub struct MapComparison {
     pub map_id: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub map_name: Option<String>,
     pub status: MapStatus,
     pub diffs: Vec<Diff>,
     pub hard_count: usize,